@@ -1,20 +1,26 @@
-use syn::{Attribute, Error, Ident, MetaNameValue, Result};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, Error, Member, MetaNameValue, Result};
 
 use crate::diagnostic::Severity;
+use crate::suggest::unknown_value;
+
+/// Defines the names of all arguments of the `#[diagnostic(...)]` attribute.
+const DIAGNOSTIC_ARGUMENTS: &[&str] = &["message", "code", "help", "severity"];
 
-#[derive(Debug)]
 pub enum DiagnosticArg {
     Message(String),
-    Code(String),
+    Code(TokenStream),
     Help(String),
+    HelpField(Member),
     Severity(Severity),
-    Related(Ident, bool),
-    Cause(Ident, bool),
-    Span(Ident),
+    Related(Member),
+    Cause(Member),
+    Span(Member),
     Label {
         severity: Option<Severity>,
         label: String,
-        ident: Ident,
+        ident: Member,
         has_source: bool,
     },
 }
@@ -49,9 +55,11 @@ impl DiagnosticArg {
 
                     args.push(arg);
                 } else {
+                    let name = arg_meta.path().to_token_stream().to_string().replace(' ', "");
+
                     return Err(Error::new_spanned(
-                        meta,
-                        "expected name-value attribute, such as `#[attr = \"...\"]`",
+                        arg_meta,
+                        format!("expected name-value argument, such as `{name} = ...`"),
                     ));
                 }
             }
@@ -73,7 +81,10 @@ impl DiagnosticArg {
             "message" => Self::parse_message(name_value),
             "help" => Self::parse_help(name_value),
             "severity" => Self::parse_severity(name_value),
-            _ => Err(Error::new_spanned(ident, "Invalid diagnostic attribute")),
+            name => Err(Error::new_spanned(
+                ident,
+                unknown_value("diagnostic argument", name, DIAGNOSTIC_ARGUMENTS),
+            )),
         }
     }
 
@@ -85,19 +96,39 @@ impl DiagnosticArg {
         {
             Ok(DiagnosticArg::Message(lit_str.value()))
         } else {
-            Err(Error::new_spanned(meta, "Expected string literal"))
+            Err(Error::new_spanned(
+                &meta.value,
+                "expected string literal, such as `message = \"...\"`",
+            ))
         }
     }
 
     fn parse_code(meta: &MetaNameValue) -> Result<Self> {
-        if let syn::Expr::Lit(syn::ExprLit {
-            lit: syn::Lit::Str(lit_str),
-            ..
-        }) = meta.value.clone()
-        {
-            Ok(DiagnosticArg::Code(lit_str.value()))
-        } else {
-            Err(Error::new_spanned(meta, "Expected string literal"))
+        match &meta.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(_), ..
+            })
+            | syn::Expr::Path(_) => Ok(DiagnosticArg::Code(meta.value.to_token_stream())),
+
+            // Fields are borrowed, so they don't need to be moved out of `self`.
+            syn::Expr::Field(syn::ExprField { base, .. }) if Self::is_self_expr(base) => {
+                let value = &meta.value;
+
+                Ok(DiagnosticArg::Code(quote! { &#value }))
+            }
+            _ => Err(Error::new_spanned(
+                &meta.value,
+                "expected string literal, field or path, such as `code = \"E0001\"` or `code = self.code`",
+            )),
+        }
+    }
+
+    /// Determines whether the given expression refers to `self` or one of it's fields.
+    fn is_self_expr(expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::Path(syn::ExprPath { path, .. }) => path.is_ident("self"),
+            syn::Expr::Field(syn::ExprField { base, .. }) => Self::is_self_expr(base),
+            _ => false,
         }
     }
 
@@ -109,20 +140,41 @@ impl DiagnosticArg {
         {
             Ok(DiagnosticArg::Help(lit_str.value()))
         } else {
-            Err(Error::new_spanned(meta, "Expected string literal"))
+            Err(Error::new_spanned(
+                &meta.value,
+                "expected string literal, such as `help = \"...\"`",
+            ))
         }
     }
 
     fn parse_severity(meta: &MetaNameValue) -> Result<Self> {
-        if let syn::Expr::Path(syn::ExprPath { path, .. }) = meta.value.clone() {
-            let ident = match path.get_ident() {
-                Some(ident) => ident,
-                None => return Err(Error::new_spanned(path, "Expected ident for path")),
-            };
+        match &meta.value {
+            syn::Expr::Path(syn::ExprPath { path, .. }) => {
+                let ident = match path.get_ident() {
+                    Some(ident) => ident,
+                    None => {
+                        return Err(Error::new_spanned(
+                            path,
+                            "expected identifier, such as `severity = Warning`",
+                        ))
+                    }
+                };
+
+                Ok(DiagnosticArg::Severity(Severity(ident.clone())))
+            }
 
-            Ok(DiagnosticArg::Severity(Severity(ident.clone())))
-        } else {
-            Err(Error::new_spanned(meta, "Expected path literal"))
+            // Severities are commonly quoted by mistake, so suggest the unquoted variant instead.
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit_str),
+                ..
+            }) => Err(Error::new_spanned(
+                &meta.value,
+                format!("expected identifier, did you mean `severity = {}`?", lit_str.value()),
+            )),
+            _ => Err(Error::new_spanned(
+                &meta.value,
+                "expected identifier, such as `severity = Warning`",
+            )),
         }
     }
 }
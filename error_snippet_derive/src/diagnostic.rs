@@ -7,6 +7,7 @@ pub struct Severity(pub Ident);
 
 pub struct AttrDiagnostic {
     pub ident: Ident,
+    pub attr: Option<syn::Attribute>,
     pub args: Vec<DiagnosticArg>,
     pub generics: syn::Generics,
     pub fields: syn::Fields,
@@ -16,20 +17,36 @@ impl AttrDiagnostic {
     pub fn from(input: syn::DeriveInput) -> syn::Result<Self> {
         if let syn::Data::Struct(syn::DataStruct { fields, .. }) = input.data {
             let args = DiagnosticArg::parse_attributes(&input.attrs)?;
+            let attr = input
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("diagnostic"))
+                .cloned();
 
             let mut diagnostic = AttrDiagnostic {
                 ident: input.ident,
+                attr,
                 args,
                 generics: input.generics,
                 fields,
             };
 
-            for field in &diagnostic.fields {
-                let field_attr_arg = match DiagnosticArg::parse_field(field)? {
+            for (index, field) in diagnostic.fields.iter().enumerate() {
+                let field_attr_arg = match DiagnosticArg::parse_field(index, field)? {
                     Some(attr) => attr,
                     None => continue,
                 };
 
+                // Only a single source can be returned, so multiple `#[span]` fields would be ambiguous.
+                if matches!(field_attr_arg, DiagnosticArg::Span(_))
+                    && diagnostic.args.iter().any(|arg| matches!(arg, DiagnosticArg::Span(_)))
+                {
+                    return Err(syn::Error::new_spanned(
+                        field.attrs[0].path(),
+                        "only a single field can be marked with `#[span]`",
+                    ));
+                }
+
                 diagnostic.args.push(field_attr_arg);
             }
 
@@ -52,7 +69,12 @@ impl AttrDiagnostic {
         Ok(())
     }
 
+    /// Creates an error with the given message, which is spanned on the `#[diagnostic(...)]`
+    /// attribute, or the name of the struct if the attribute is missing.
     pub(crate) fn err(&self, message: &'static str) -> syn::Error {
-        syn::Error::new_spanned(&self.ident, message)
+        match &self.attr {
+            Some(attr) => syn::Error::new_spanned(attr, message),
+            None => syn::Error::new_spanned(&self.ident, message),
+        }
     }
 }
@@ -1,19 +1,52 @@
-use syn::{Error, Field, Result};
+use syn::{Error, Field, Member, Result};
 
 use crate::args::DiagnosticArg;
 use crate::diagnostic::Severity;
+use crate::suggest::unknown_value;
+
+/// Defines the names of all diagnostic attributes on fields.
+const FIELD_ATTRIBUTES: &[&str] = &["span", "related", "cause", "causes", "help", "label"];
+
+/// Defines the options of `#[related(...)]` and `#[cause(...)]` attributes.
+const COLLECTION_OPTIONS: &[&str] = &["collection"];
+
+/// Defines the options of `#[label(...)]` attributes, other than the message.
+const LABEL_OPTIONS: &[&str] = &["source", "note", "help", "info", "warning", "error"];
 
 impl DiagnosticArg {
-    pub fn parse_field(field: &Field) -> Result<Option<Self>> {
+    /// Parses the attribute of the given field, if any.
+    ///
+    /// Named fields are referred to by their name, while unnamed fields of tuple
+    /// structs are referred to by their index, such as `self.0`.
+    pub fn parse_field(index: usize, field: &Field) -> Result<Option<Self>> {
         let attr = match field.attrs.first() {
             Some(attr) => attr,
             None => return Ok(None),
         };
 
+        // Only a single attribute is read from each field, so any further diagnostic
+        // attributes are rejected, instead of being silently ignored.
+        if let Some(extra) = field.attrs.iter().skip(1).find(|attr| Self::is_field_attribute(attr)) {
+            return Err(Error::new_spanned(
+                extra.path(),
+                "a field can only have a single diagnostic attribute",
+            ));
+        }
+
         let attr_path = attr.path();
 
-        let field_ident = field.ident.as_ref().unwrap();
-        let attr_ident = attr_path.get_ident().unwrap();
+        let field_ident = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(syn::Index {
+                index: index as u32,
+                span: syn::spanned::Spanned::span(&field.ty),
+            }),
+        };
+
+        let attr_ident = match attr_path.get_ident() {
+            Some(ident) => ident,
+            None => return Err(Error::new_spanned(attr_path, "expected identifier")),
+        };
 
         let arg =
             match attr_ident.to_string().as_str() {
@@ -28,36 +61,54 @@ impl DiagnosticArg {
                     }
                 }
                 "related" => match &attr.meta {
-                    syn::Meta::Path(_) => DiagnosticArg::Related(field_ident.clone(), false),
-                    syn::Meta::List(meta) => Self::parse_related(field_ident, meta)?,
+                    syn::Meta::Path(_) => DiagnosticArg::Related(field_ident.clone()),
+                    syn::Meta::List(meta) => {
+                        Self::parse_collection_options(meta)?;
+
+                        DiagnosticArg::Related(field_ident.clone())
+                    }
                     _ => return Err(Error::new_spanned(
                         attr_path,
                         "expected zero-or-one arguments; should be formatted `#[related]` or `#[related(collection)]`",
                     )),
                 },
                 "cause" => {
+                    match &attr.meta {
+                        syn::Meta::Path(_) => DiagnosticArg::Cause(field_ident.clone()),
+                        syn::Meta::List(meta) => {
+                            Self::parse_collection_options(meta)?;
+
+                            DiagnosticArg::Cause(field_ident.clone())
+                        }
+                        _ => return Err(Error::new_spanned(
+                            attr_path,
+                            "expected zero-or-one arguments; should be formatted `#[cause]` or `#[cause(collection)]`",
+                        )),
+                    }
+                }
+                "causes" => {
                     if let syn::Meta::Path(_) = &attr.meta {
-                        DiagnosticArg::Cause(field_ident.clone(), false)
+                        DiagnosticArg::Cause(field_ident.clone())
                     } else {
                         return Err(Error::new_spanned(
                             attr_path,
-                            "expected no arguments; should be formatted `#[cause]`",
+                            "expected no arguments; should be formatted `#[causes]`",
                         ));
                     }
                 }
-                "causes" => {
+                "help" => {
                     if let syn::Meta::Path(_) = &attr.meta {
-                        DiagnosticArg::Cause(field_ident.clone(), true)
+                        DiagnosticArg::HelpField(field_ident.clone())
                     } else {
                         return Err(Error::new_spanned(
                             attr_path,
-                            "expected no arguments; should be formatted `#[causes]`",
+                            "expected no arguments; should be formatted `#[help]`",
                         ));
                     }
                 }
                 "label" => {
                     if let syn::Meta::List(meta) = &attr.meta {
-                        Self::parse_label(field_ident, meta)?
+                        Self::parse_label(&field_ident, &field.ty, meta)?
                     } else {
                         return Err(Error::new_spanned(
                             attr_path,
@@ -68,7 +119,7 @@ impl DiagnosticArg {
                 unk => {
                     return Err(Error::new_spanned(
                         attr_path,
-                        format!("unknown property attribute: {unk}"),
+                        unknown_value("field attribute", unk, FIELD_ATTRIBUTES),
                     ))
                 }
             };
@@ -76,37 +127,73 @@ impl DiagnosticArg {
         Ok(Some(arg))
     }
 
-    fn parse_related(ident: &syn::Ident, list: &syn::MetaList) -> Result<Self> {
-        let parser = syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated;
+    /// Determines whether the given attribute is one of the diagnostic field attributes.
+    fn is_field_attribute(attr: &syn::Attribute) -> bool {
+        attr.path()
+            .get_ident()
+            .is_some_and(|ident| FIELD_ATTRIBUTES.contains(&ident.to_string().as_str()))
+    }
+
+    /// Determines whether the given type carries it's own source, which is the case
+    /// for `SourceRange` and `SourceLocation`, or any path ending with those names.
+    fn carries_source(ty: &syn::Type) -> bool {
+        let syn::Type::Path(path) = ty else {
+            return false;
+        };
+
+        path.path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "SourceRange" || segment.ident == "SourceLocation")
+    }
 
-        let mut collection = false;
+    /// Parses the options of `#[related(...)]` and `#[cause(...)]` attributes.
+    ///
+    /// The `collection` option is accepted for backwards compatibility, but has no effect,
+    /// since single diagnostics, optional diagnostics and collections are all supported.
+    fn parse_collection_options(list: &syn::MetaList) -> Result<()> {
+        let parser = syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated;
 
         for arg in list.parse_args_with(parser)? {
-            match arg.get_ident().unwrap().to_string().as_str() {
-                "collection" => collection = true,
-                value => return Err(Error::new_spanned(arg, format!("invalid option, {value}"))),
+            match arg.get_ident().map(|i| i.to_string()).as_deref() {
+                Some("collection") => {}
+                _ => {
+                    let value = quote::ToTokens::to_token_stream(&arg).to_string().replace(' ', "");
+
+                    return Err(Error::new_spanned(
+                        arg,
+                        unknown_value("option", &value, COLLECTION_OPTIONS),
+                    ));
+                }
             }
         }
 
-        Ok(DiagnosticArg::Related(ident.clone(), collection))
+        Ok(())
     }
 
-    fn parse_label(ident: &syn::Ident, list: &syn::MetaList) -> Result<Self> {
+    /// Parses the arguments of a `#[label(...)]` attribute.
+    ///
+    /// Fields of type `SourceRange` or `SourceLocation` already carry their own source,
+    /// so the `source` option is implied for them.
+    fn parse_label(ident: &Member, ty: &syn::Type, list: &syn::MetaList) -> Result<Self> {
         let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
 
-        let mut has_source = false;
+        let mut has_source = Self::carries_source(ty);
         let mut severity = None;
         let mut label_str = None;
 
         for label_arg in list.parse_args_with(parser)? {
             match label_arg {
                 syn::Expr::Path(syn::ExprPath { path, .. }) => {
-                    let ident = path.get_ident().unwrap();
+                    let ident = match path.get_ident() {
+                        Some(ident) => ident,
+                        None => return Err(Error::new_spanned(path, "expected identifier")),
+                    };
 
                     match ident.to_string().as_str() {
                         "source" => has_source = true,
                         "note" | "help" | "info" | "warning" | "error" => severity = Some(Severity(ident.clone())),
-                        value => return Err(Error::new_spanned(path, format!("invalid option, {value}"))),
+                        value => return Err(Error::new_spanned(path, unknown_value("option", value, LABEL_OPTIONS))),
                     }
                 }
                 syn::Expr::Lit(syn::ExprLit {
@@ -115,7 +202,10 @@ impl DiagnosticArg {
                 }) => {
                     label_str = Some(lit_str.value());
                 }
-                _ => (),
+                other => return Err(Error::new_spanned(
+                    other,
+                    "expected string literal or option, such as `#[label(\"...\")]` or `#[label(warning, \"...\")]`",
+                )),
             }
         }
 
@@ -127,7 +217,10 @@ impl DiagnosticArg {
                 has_source,
             })
         } else {
-            Err(Error::new_spanned(list, "expected attribute argument"))
+            Err(Error::new_spanned(
+                list,
+                "expected label message, such as `#[label(\"...\")]`",
+            ))
         }
     }
 }
@@ -39,9 +39,22 @@ impl FormattedMessage {
                 _ => continue,
             };
 
-            args.push(quote! {
-                #ident = self.#ident
-            });
+            // Fields formatted with `Debug` are passed as-is, while fields formatted
+            // with `Display` are wrapped, so paths and `Debug`-only fields can be displayed too.
+            if Self::is_debug_spec(read) {
+                args.push(quote! {
+                    #ident = self.#ident
+                });
+            } else {
+                args.push(quote! {
+                    #ident = {
+                        #[allow(unused_imports)]
+                        use ::error_snippet::__private::{DebugArg as _, DisplayArg as _, PathArg as _};
+
+                        (&&&::error_snippet::__private::FormatArg(&self.#ident)).as_display_arg()
+                    }
+                });
+            }
         }
 
         let fmt_lit = LitStr::new(&fmt, span);
@@ -51,6 +64,15 @@ impl FormattedMessage {
         }
     }
 
+    /// Determines whether the format spec at the start of `read` uses the `Debug` trait.
+    fn is_debug_spec(read: &str) -> bool {
+        let Some(spec) = read.strip_prefix(':') else {
+            return false;
+        };
+
+        spec.split('}').next().is_some_and(|spec| spec.ends_with('?'))
+    }
+
     fn read_ident(read: &mut &str) -> Ident {
         let mut ident = String::new();
 
@@ -6,9 +6,10 @@ mod args;
 mod diagnostic;
 mod fields;
 mod fmt;
+mod suggest;
 mod tokens;
 
-#[proc_macro_derive(Diagnostic, attributes(diagnostic, span, label, related, cause, causes))]
+#[proc_macro_derive(Diagnostic, attributes(diagnostic, span, label, related, cause, causes, help))]
 pub fn derive_diagnostic(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let cmd = match AttrDiagnostic::from(input) {
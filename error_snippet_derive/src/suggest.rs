@@ -0,0 +1,57 @@
+/// Formats an error message for an unknown value, such as an unknown option, followed by
+/// the most similar of the expected values, or all of them if none are similar enough.
+///
+/// ```text
+/// unknown option `colection`, did you mean `collection`?
+/// unknown option `foo`, expected one of `source`, `note` or `error`
+/// ```
+pub(crate) fn unknown_value(kind: &str, value: &str, expected: &[&str]) -> String {
+    match did_you_mean(value, expected) {
+        Some(candidate) => format!("unknown {kind} `{value}`, did you mean `{candidate}`?"),
+        None => format!("unknown {kind} `{value}`, expected {}", one_of(expected)),
+    }
+}
+
+/// Gets the candidate which is most similar to the given value, if any is similar enough
+/// to likely be a typo, such as `colection` for `collection`.
+pub(crate) fn did_you_mean<'a>(value: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (value.chars().count() / 3).max(1);
+
+    candidates
+        .iter()
+        .map(|candidate| (*candidate, edit_distance(value, candidate)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// Formats the given values as a list, such as ``one of `a`, `b` or `c` ``.
+fn one_of(values: &[&str]) -> String {
+    let quoted = values.iter().map(|value| format!("`{value}`")).collect::<Vec<_>>();
+
+    match quoted.split_last() {
+        None => String::from("nothing"),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("one of {} or {last}", rest.join(", ")),
+    }
+}
+
+/// Computes the Levenshtein distance between the two given strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
@@ -1,6 +1,6 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::Ident;
+use syn::{Ident, Member};
 
 use crate::args::DiagnosticArg;
 use crate::diagnostic::{AttrDiagnostic, Severity};
@@ -9,7 +9,7 @@ use crate::fmt::FormattedMessage;
 struct LabelIdent {
     severity: Option<Ident>,
     label: String,
-    ident: Ident,
+    ident: Member,
     has_source: bool,
 }
 
@@ -63,7 +63,7 @@ impl AttrDiagnostic {
     }
 
     /// Gets the value of the `code` attribute, if any was given. If not, returns `None`.
-    fn code(&self) -> Option<String> {
+    fn code(&self) -> Option<TokenStream> {
         let arg = self.args.iter().find(|arg| matches!(arg, DiagnosticArg::Code(_)));
 
         match arg {
@@ -93,8 +93,19 @@ impl AttrDiagnostic {
         }
     }
 
+    /// Gets the fields marked with the `#[help]` attribute.
+    fn help_fields(&self) -> Vec<Member> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg {
+                DiagnosticArg::HelpField(ident) => Some(ident.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Gets the source code of the diagnostic, if any was given. If not, returns `None`.
-    fn span(&self) -> Option<Ident> {
+    fn span(&self) -> Option<Member> {
         let arg = self.args.iter().find(|arg| matches!(arg, DiagnosticArg::Span(_)));
 
         match arg {
@@ -177,28 +188,38 @@ impl AttrDiagnostic {
     }
 
     /// Creates the implementation block for the `help` trait function.
+    ///
+    /// Static help messages from `help = "..."` are listed first, followed by the
+    /// help messages of all fields marked with `#[help]`, in order of declaration.
     fn help_block(&self) -> syn::Result<TokenStream> {
-        let stream = if let Some(help) = self.help() {
-            let help_idents = help
-                .into_iter()
-                .map(|h| {
-                    let lit = syn::LitStr::new(&h, proc_macro2::Span::call_site());
+        let help_fields = self.help_fields();
 
-                    FormattedMessage::expand(lit)
-                })
-                .collect::<Vec<TokenStream>>();
+        if self.help().is_none() && help_fields.is_empty() {
+            return Ok(TokenStream::new());
+        }
 
-            quote! {
-                fn help(&self) -> Option<Box<dyn Iterator<Item = ::error_snippet::Help> + '_>> {
-                    Some(Box::new(
-                        vec![ #(#help_idents),* ]
-                            .into_iter()
-                            .map(|h| Into::<::error_snippet::Help>::into(h))
-                    ))
-                }
+        let help_idents = self
+            .help()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|h| {
+                let lit = syn::LitStr::new(&h, proc_macro2::Span::call_site());
+
+                FormattedMessage::expand(lit)
+            })
+            .collect::<Vec<TokenStream>>();
+
+        let stream = quote! {
+            fn help(&self) -> Option<Box<dyn Iterator<Item = ::error_snippet::Help> + '_>> {
+                let help: Vec<String> = vec![ #(#help_idents),* ];
+
+                Some(Box::new(
+                    help
+                        .into_iter()
+                        .map(|h| Into::<::error_snippet::Help>::into(h))
+                        #(.chain(::error_snippet::AsHelp::as_help(&self.#help_fields)))*
+                ))
             }
-        } else {
-            TokenStream::new()
         };
 
         Ok(stream)
@@ -219,7 +240,13 @@ impl AttrDiagnostic {
                         let lit_str = syn::LitStr::new(&label, proc_macro2::Span::call_site());
                         let formatted_str = FormattedMessage::expand(lit_str);
 
-                        let method_name = severity.unwrap_or_else(|| Ident::new("new", proc_macro2::Span::call_site()));
+                        // Note labels are secondary notes, such as "previous definition here",
+                        // which are rendered less prominently than the other labels.
+                        let method_name = match severity {
+                            Some(severity) if severity == "note" => Ident::new("secondary_note", severity.span()),
+                            Some(severity) => severity,
+                            None => Ident::new("new", proc_macro2::Span::call_site()),
+                        };
 
                         if has_source {
                             quote! {
@@ -262,82 +289,101 @@ impl AttrDiagnostic {
         Ok(stream)
     }
 
-    /// Creates the implementation block for the `related` trait function.
-    fn related_block(&self) -> syn::Result<TokenStream> {
-        let arg = self.args.iter().find(|arg| matches!(arg, DiagnosticArg::Related(_, _)));
-
-        let (related, collection) = match arg {
-            Some(DiagnosticArg::Related(related, collection)) => (related.clone(), *collection),
-            _ => return Ok(TokenStream::new()),
-        };
-
-        if collection {
-            Ok(quote! {
-                fn related(&self) -> Box<dyn Iterator<Item = &(dyn ::error_snippet::Diagnostic + Send + Sync)> + '_> {
-                    Box::new(
-                        self.#related
-                            .iter()
-                            .map(|e| e.as_ref() as &(dyn ::error_snippet::Diagnostic + Send + Sync)),
-                    )
-                }
+    /// Gets the fields marked with the `#[related]` attribute.
+    fn related_fields(&self) -> Vec<Member> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg {
+                DiagnosticArg::Related(ident) => Some(ident.clone()),
+                _ => None,
             })
-        } else {
-            Ok(quote! {
-                fn related(&self) -> Box<dyn Iterator<Item = &(dyn ::error_snippet::Diagnostic + Send + Sync)> + '_> {
-                    let related: &(dyn error_snippet::Diagnostic + Send + Sync) =
-                        (&self.#related as &error_snippet::Error).as_ref();
-
-                    let iter = std::iter::once(related)
-                        as std::iter::Once<&(dyn ::error_snippet::Diagnostic + Send + Sync)>;
+            .collect()
+    }
 
-                    Box::new(iter)
-                }
+    /// Gets the fields marked with the `#[cause]` or `#[causes]` attribute.
+    fn cause_fields(&self) -> Vec<Member> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg {
+                DiagnosticArg::Cause(ident) => Some(ident.clone()),
+                _ => None,
             })
-        }
+            .collect()
     }
 
-    /// Creates the implementation block for the `cause` trait function.
-    fn cause_block(&self) -> syn::Result<TokenStream> {
-        let arg = self.args.iter().find(|arg| matches!(arg, DiagnosticArg::Cause(_, _)));
+    /// Creates an expression which chains the diagnostics of all the given fields into
+    /// a single iterator, in the order the fields were declared.
+    ///
+    /// Returns `None` if no fields are given.
+    fn chained_diagnostics(fields: &[Member]) -> Option<TokenStream> {
+        let (first, rest) = fields.split_first()?;
+
+        let mut stream = quote! { ::error_snippet::AsDiagnostics::as_diagnostics(&self.#first) };
+
+        for field in rest {
+            stream = quote! {
+                Box::new(::std::iter::Iterator::chain(
+                    #stream,
+                    ::error_snippet::AsDiagnostics::as_diagnostics(&self.#field)
+                ))
+            };
+        }
+
+        Some(stream)
+    }
 
-        let (cause, collection) = match arg {
-            Some(DiagnosticArg::Cause(cause, collection)) => (cause.clone(), *collection),
-            _ => return Ok(TokenStream::new()),
+    /// Creates the implementation block for the `related` trait function.
+    ///
+    /// If multiple fields are marked with `#[related]`, all of them are chained together.
+    fn related_block(&self) -> syn::Result<TokenStream> {
+        let Some(related) = Self::chained_diagnostics(&self.related_fields()) else {
+            return Ok(TokenStream::new());
         };
 
-        if collection {
-            Ok(quote! {
-                fn causes(&self) -> Box<dyn Iterator<Item = &(dyn ::error_snippet::Diagnostic + Send + Sync)> + '_> {
-                    Box::new(
-                        self.#cause
-                            .iter()
-                            .map(|e| e.as_ref() as &(dyn ::error_snippet::Diagnostic + Send + Sync)),
-                    )
-                }
-            })
-        } else {
-            Ok(quote! {
-                fn causes(&self) -> Box<dyn Iterator<Item = &(dyn ::error_snippet::Diagnostic + Send + Sync)> + '_> {
-                    let causes: &(dyn error_snippet::Diagnostic + Send + Sync) =
-                        (&self.#cause as &error_snippet::Error).as_ref();
+        Ok(quote! {
+            fn related(&self) -> Box<dyn Iterator<Item = &(dyn ::error_snippet::Diagnostic + Send + Sync)> + '_> {
+                #related
+            }
+        })
+    }
 
-                    let iter = std::iter::once(causes)
-                        as std::iter::Once<&(dyn ::error_snippet::Diagnostic + Send + Sync)>;
+    /// Creates the implementation block for the `cause` trait function.
+    ///
+    /// If multiple fields are marked with `#[cause]`, all of them are chained together.
+    fn cause_block(&self) -> syn::Result<TokenStream> {
+        let Some(causes) = Self::chained_diagnostics(&self.cause_fields()) else {
+            return Ok(TokenStream::new());
+        };
 
-                    Box::new(iter)
-                }
-            })
-        }
+        Ok(quote! {
+            fn causes(&self) -> Box<dyn Iterator<Item = &(dyn ::error_snippet::Diagnostic + Send + Sync)> + '_> {
+                #causes
+            }
+        })
     }
 
     /// Creates the implementation block for the `source_code` trait function.
+    ///
+    /// If no `#[span]` field is defined, the source of the first label marked with
+    /// `#[label(source, ...)]` is used instead, so nested diagnostics can inherit it.
     fn source_block(&self) -> syn::Result<TokenStream> {
+        let labelled_source = self
+            .labels()
+            .and_then(|labels| labels.into_iter().find(|label| label.has_source))
+            .map(|label| label.ident);
+
         let stream = if let Some(span) = self.span() {
             quote! {
                 fn source_code(&self) -> Option<std::sync::Arc<dyn ::error_snippet::Source>> {
                     Some(self.#span.clone())
                 }
             }
+        } else if let Some(ident) = labelled_source {
+            quote! {
+                fn source_code(&self) -> Option<std::sync::Arc<dyn ::error_snippet::Source>> {
+                    Some(Into::<std::sync::Arc<dyn ::error_snippet::Source>>::into(self.#ident.clone()))
+                }
+            }
         } else {
             TokenStream::new()
         };
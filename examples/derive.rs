@@ -38,6 +38,6 @@ fn main() {
         test: "test".to_string(),
     };
 
-    let mut renderer = GraphicalRenderer::new();
+    let renderer = GraphicalRenderer::new();
     renderer.render_stderr(&error).unwrap();
 }
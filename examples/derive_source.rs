@@ -49,6 +49,6 @@ fn main() {
         },
     };
 
-    let mut renderer = GraphicalRenderer::new();
+    let renderer = GraphicalRenderer::new();
     renderer.render_stderr(&error).unwrap();
 }
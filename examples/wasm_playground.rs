@@ -0,0 +1,121 @@
+//! Browser playground, which checks the delimiters of the given source and renders the
+//! diagnostics into a string, without touching the terminal.
+//!
+//! Build the example for the web and serve `examples/wasm_playground.html` next to the built module:
+//!
+//! ```sh
+//! cargo build --release --example wasm_playground --target wasm32-unknown-unknown --no-default-features
+//! cp target/wasm32-unknown-unknown/release/examples/wasm_playground.wasm examples/
+//! python3 -m http.server --directory examples
+//! ```
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use error_snippet::{BufferedDiagnosticHandler, GraphicalRenderer, Handler, Label, NamedSource, SimpleDiagnostic};
+
+thread_local! {
+    static OUTPUT: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Allocates a buffer of `len` bytes, which the host writes the source into.
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+
+    std::mem::forget(buffer);
+    ptr
+}
+
+/// Frees a buffer, which was allocated by [`alloc()`].
+///
+/// # Safety
+///
+/// `ptr` must have been returned from [`alloc()`] with the same `len`.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Checks the source of `len` bytes at `ptr` and returns the length of the rendered
+/// diagnostics, which can be read from [`output()`].
+///
+/// # Safety
+///
+/// `ptr` must point to `len` initialized bytes.
+#[no_mangle]
+pub unsafe extern "C" fn check(ptr: *const u8, len: usize) -> usize {
+    let content = String::from_utf8_lossy(std::slice::from_raw_parts(ptr, len)).into_owned();
+    let rendered = render(content);
+
+    OUTPUT.with(|output| {
+        *output.borrow_mut() = rendered;
+        output.borrow().len()
+    })
+}
+
+/// Gets a pointer to the diagnostics, which were rendered by the last call to [`check()`].
+#[no_mangle]
+pub extern "C" fn output() -> *const u8 {
+    OUTPUT.with(|output| output.borrow().as_ptr())
+}
+
+fn render(content: String) -> String {
+    let source = Arc::new(NamedSource::new("playground.lm", content.as_str()));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+    let mut open = Vec::new();
+
+    for (idx, c) in content.char_indices() {
+        match c {
+            '(' | '[' | '{' => open.push((idx, c)),
+            ')' | ']' | '}' => match open.pop() {
+                Some((_, delimiter)) if closing(delimiter) == c => {}
+                Some((start, delimiter)) => handler.report(
+                    SimpleDiagnostic::new(format!("mismatched closing delimiter: `{c}`"))
+                        .with_label(Label::note(
+                            Some(source.clone()),
+                            start..start + 1,
+                            "unclosed delimiter",
+                        ))
+                        .with_label(Label::error(
+                            Some(source.clone()),
+                            idx..idx + 1,
+                            format!("expected `{}`", closing(delimiter)),
+                        ))
+                        .into(),
+                ),
+                None => handler.report(
+                    SimpleDiagnostic::new(format!("unexpected closing delimiter: `{c}`"))
+                        .with_label(Label::error(Some(source.clone()), idx..idx + 1, "unexpected delimiter"))
+                        .into(),
+                ),
+            },
+            _ => {}
+        }
+    }
+
+    for (start, delimiter) in open {
+        handler.report(
+            SimpleDiagnostic::new(format!("unclosed delimiter: `{delimiter}`"))
+                .with_label(Label::error(Some(source.clone()), start..start + 1, "never closed"))
+                .into(),
+        );
+    }
+
+    let _ = handler.drain();
+
+    handler.buffer().to_string()
+}
+
+fn closing(delimiter: char) -> char {
+    match delimiter {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
@@ -19,8 +19,8 @@ impl error_snippet::Source for Source {
         Some(&self.name)
     }
 
-    fn content(&self) -> Box<&str> {
-        Box::new(&self.content)
+    fn text(&self) -> &str {
+        &self.content
     }
 }
 
@@ -50,6 +50,6 @@ struct ArbitraryDiagnostic {
 }
 
 fuzz_target!(|input: ArbitraryDiagnostic| {
-    let mut renderer = GraphicalRenderer::new();
+    let renderer = GraphicalRenderer::new();
     renderer.render(&input).unwrap();
 });
@@ -0,0 +1,62 @@
+//! Implementation details of the derive macros, which are not part of the public API.
+//!
+//! Fields interpolated into formatted messages are wrapped in [`FormatArg`], which uses
+//! auto-ref specialization to pick the best way to display the field:
+//!
+//! 1. Fields which implement [`Display`] are displayed as-is.
+//! 2. Fields which implement [`AsRef<Path>`], such as [`std::path::PathBuf`] or [`std::ffi::OsStr`],
+//!    are displayed lossily using [`Path::display()`].
+//! 3. Fields which only implement [`Debug`] are displayed using their [`Debug`] implementation.
+
+use std::fmt::{Debug, Display};
+use std::path::Path;
+
+/// Wrapper around a field, which is interpolated into a formatted message.
+pub struct FormatArg<'a, T: ?Sized>(pub &'a T);
+
+/// Displays a [`Debug`]-only value using it's [`Debug`] implementation.
+pub struct DebugDisplay<'a, T: ?Sized>(&'a T);
+
+impl<T: Debug + ?Sized> Display for DebugDisplay<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.0, f)
+    }
+}
+
+pub trait DisplayArg<'a> {
+    type Target: Display + ?Sized;
+
+    fn as_display_arg(&self) -> &'a Self::Target;
+}
+
+impl<'a, T: Display + ?Sized> DisplayArg<'a> for &&FormatArg<'a, T> {
+    type Target = T;
+
+    fn as_display_arg(&self) -> &'a T {
+        self.0
+    }
+}
+
+pub trait PathArg<'a> {
+    fn as_display_arg(&self) -> std::path::Display<'a>;
+}
+
+impl<'a, T: AsRef<Path> + ?Sized> PathArg<'a> for &FormatArg<'a, T> {
+    fn as_display_arg(&self) -> std::path::Display<'a> {
+        self.0.as_ref().display()
+    }
+}
+
+pub trait DebugArg<'a> {
+    type Target: Debug + ?Sized;
+
+    fn as_display_arg(&self) -> DebugDisplay<'a, Self::Target>;
+}
+
+impl<'a, T: Debug + ?Sized> DebugArg<'a> for FormatArg<'a, T> {
+    type Target = T;
+
+    fn as_display_arg(&self) -> DebugDisplay<'a, T> {
+        DebugDisplay(self.0)
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use crate::{Diagnostic, Help, Label, Source, SpanRange};
+
+/// Defines the default maximum width of the message, before the expected
+/// tokens are moved into a wrapped help message instead.
+const DEFAULT_MAX_WIDTH: usize = 80;
+
+/// Diagnostic for parser errors, where one of a set of tokens was expected, but another was found.
+///
+/// The expected tokens are sorted and deduplicated, so the message stays stable no matter the order
+/// in which the parser attempted to match them. If the list of expected tokens is too long to fit
+/// in a single line, the tokens are listed in a wrapped help message instead.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Diagnostic, ExpectedOneOf};
+///
+/// let diag = ExpectedOneOf::new([";", ",", ")", ","], "}");
+/// assert_eq!(diag.message(), "expected one of `)`, `,`, `;`; found `}`");
+///
+/// let diag = ExpectedOneOf::new([")", ","], "}");
+/// assert_eq!(diag.message(), "expected `)` or `,`, found `}`");
+///
+/// let diag = ExpectedOneOf::new([";"], "}");
+/// assert_eq!(diag.message(), "expected `;`, found `}`");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExpectedOneOf {
+    /// Defines the sorted and deduplicated list of expected tokens.
+    expected: Vec<String>,
+
+    /// Defines the token which was found instead.
+    found: String,
+
+    /// Defines the unique code of the diagnostic, if any.
+    code: Option<String>,
+
+    /// Defines the source which contains the found token, if any.
+    source: Option<Arc<dyn Source>>,
+
+    /// Defines the span of the found token, if any.
+    span: Option<SpanRange>,
+
+    /// Defines the maximum width of the message, before the expected
+    /// tokens are moved into a help message instead.
+    max_width: usize,
+}
+
+impl ExpectedOneOf {
+    /// Creates a new [`ExpectedOneOf`] diagnostic, from the given expected tokens and found token.
+    pub fn new(expected: impl IntoIterator<Item = impl Into<String>>, found: impl Into<String>) -> Self {
+        let mut expected = expected.into_iter().map(Into::into).collect::<Vec<String>>();
+        expected.sort();
+        expected.dedup();
+
+        Self {
+            expected,
+            found: found.into(),
+            code: None,
+            source: None,
+            span: None,
+            max_width: DEFAULT_MAX_WIDTH,
+        }
+    }
+
+    /// Sets the location of the found token, which is labelled in the rendered diagnostic.
+    ///
+    /// If `source` is `None`, the diagnostic can still be given a source using [`crate::WithSource`].
+    pub fn with_location(mut self, source: Option<Arc<dyn Source>>, span: impl Into<SpanRange>) -> Self {
+        self.source = source;
+        self.span = Some(span.into());
+        self
+    }
+
+    /// Sets the unique code of the diagnostic.
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Sets the maximum width of the message, before the expected tokens are listed
+    /// in a wrapped help message instead. Defaults to `80`.
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = width;
+        self
+    }
+
+    /// Gets the sorted and deduplicated list of expected tokens.
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    /// Gets the token which was found instead of the expected tokens.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// Formats the list of expected tokens as a comma-separated list.
+    fn expected_list(&self) -> String {
+        self.expected
+            .iter()
+            .map(|e| format!("`{e}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Formats the inline message of the diagnostic, without any wrapping.
+    fn inline_message(&self) -> String {
+        let found = &self.found;
+
+        match self.expected.as_slice() {
+            [] => format!("unexpected `{found}`"),
+            [single] => format!("expected `{single}`, found `{found}`"),
+            [first, second] => format!("expected `{first}` or `{second}`, found `{found}`"),
+            _ => format!("expected one of {}; found `{found}`", self.expected_list()),
+        }
+    }
+
+    /// Determines whether the message is too wide, so the expected tokens must be wrapped.
+    fn is_wrapped(&self) -> bool {
+        self.inline_message().len() > self.max_width
+    }
+
+    /// Wraps the expected tokens into lines, which are at most `max_width` wide.
+    fn wrapped_expected(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+
+        for (idx, token) in self.expected.iter().enumerate() {
+            let mut item = format!("`{token}`");
+
+            if idx + 1 < self.expected.len() {
+                item.push(',');
+            }
+
+            if !current.is_empty() && current.len() + item.len() + 1 > self.max_width {
+                lines.push(std::mem::take(&mut current));
+            }
+
+            if !current.is_empty() {
+                current.push(' ');
+            }
+
+            current.push_str(&item);
+        }
+
+        lines.push(current);
+        lines.join("\n")
+    }
+}
+
+impl Diagnostic for ExpectedOneOf {
+    fn message(&self) -> String {
+        if self.is_wrapped() {
+            format!("expected one of {} tokens, found `{}`", self.expected.len(), self.found)
+        } else {
+            self.inline_message()
+        }
+    }
+
+    fn code(&self) -> Option<Box<dyn std::fmt::Display + '_>> {
+        self.code.as_ref().map(|c| Box::new(c) as Box<dyn std::fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = Label> + '_>> {
+        let span = self.span.clone()?;
+        let label = Label::error(self.source.clone(), span, format!("unexpected `{}`", self.found));
+
+        Some(Box::new(std::iter::once(label)))
+    }
+
+    fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
+        if !self.is_wrapped() {
+            return None;
+        }
+
+        let help = Help::new(format!("expected one of:\n{}", self.wrapped_expected()));
+
+        Some(Box::new(std::iter::once(help)))
+    }
+}
+
+impl std::fmt::Display for ExpectedOneOf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
@@ -0,0 +1,700 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::render::layout::normalize_span;
+use crate::{Diagnostic, Severity, SimpleDiagnostic, Source, SourceRange, Suggestion};
+
+/// Defines the amount of unchanged lines to include around each change in unified diffs.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Collection of suggestions from a batch of diagnostics, grouped per file.
+///
+/// Suggestions are added in order and identical suggestions are only kept once. Suggestions
+/// which overlap with an earlier suggestion in the same file can't be applied together with it,
+/// so they are rejected and recorded as a [`SuggestionConflict`] instead.
+///
+/// The collected suggestions can be previewed as a unified diff, such as for a `--fix --dry-run`
+/// flag, which can also be piped into tools like `patch`.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::*;
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = new Tesitng();\n"));
+///
+/// let diagnostic = SimpleDiagnostic::new("could not find type `Tesitng`").with_help(
+///     Help::new("a type with a similar name exists")
+///         .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 12..19), "Testing")),
+/// );
+///
+/// let set = SuggestionSet::from_diagnostics([&diagnostic as &dyn Diagnostic]);
+///
+/// assert_eq!(
+///     set.to_unified_diff(),
+///     "--- a/src/main.lm\n\
+///      +++ b/src/main.lm\n\
+///      @@ -1 +1 @@\n\
+///      -let a = new Tesitng();\n\
+///      +let a = new Testing();\n"
+/// );
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct SuggestionSet {
+    /// Defines the edits of each file, in the order which the files were first seen.
+    files: Vec<FileEdits>,
+
+    /// Defines all file-level operations, such as creating or renaming files.
+    file_operations: Vec<Suggestion>,
+
+    /// Defines all suggestions which were rejected, since they overlap with another suggestion.
+    conflicts: Vec<SuggestionConflict>,
+}
+
+/// Defines all edits within a single file, sorted by their position.
+#[derive(Debug, Clone)]
+struct FileEdits {
+    source: Arc<dyn Source>,
+    edits: Vec<Edit>,
+}
+
+/// Defines a single edit within a file, where the given range is replaced with some text.
+#[derive(Debug, Clone)]
+struct Edit {
+    range: Range<usize>,
+    replacement: String,
+    suggestion: Suggestion,
+}
+
+/// A suggestion which was rejected from a [`SuggestionSet`], since it
+/// overlaps with a suggestion which was added to the set earlier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionConflict {
+    /// Defines the suggestion which was rejected.
+    pub suggestion: Suggestion,
+
+    /// Defines the suggestion in the set, which the rejected suggestion overlaps with.
+    pub existing: Suggestion,
+}
+
+impl std::fmt::Display for SuggestionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "suggestion to {} overlaps with suggestion to {}",
+            self.suggestion, self.existing
+        )
+    }
+}
+
+impl std::error::Error for SuggestionConflict {}
+
+impl SuggestionSet {
+    /// Creates a new, empty [`SuggestionSet`].
+    pub fn new() -> Self {
+        SuggestionSet::default()
+    }
+
+    /// Creates a new [`SuggestionSet`] with all suggestions of the given diagnostics.
+    ///
+    /// See [`SuggestionSet::add_diagnostic`] for which suggestions are collected.
+    pub fn from_diagnostics<'a>(diagnostics: impl IntoIterator<Item = &'a dyn Diagnostic>) -> Self {
+        let mut set = SuggestionSet::new();
+
+        for diagnostic in diagnostics {
+            set.add_diagnostic(diagnostic);
+        }
+
+        set
+    }
+
+    /// Adds all suggestions of the given diagnostic to the set.
+    ///
+    /// This includes the suggestions of all help messages and the inline suggestions of all labels,
+    /// which replace the labelled span. The suggestions of all causes and related diagnostics are
+    /// added as well, recursively. Of all the help messages marked with [`crate::Help::as_alternative`],
+    /// only the first one is added, since they're mutually exclusive.
+    pub fn add_diagnostic(&mut self, diagnostic: &dyn Diagnostic) {
+        self.add_diagnostic_with(diagnostic, None);
+    }
+
+    /// Adds all suggestions of the given diagnostic, where labels without any source of their own
+    /// fall back to the source of the diagnostic, followed by the source of the closest parent.
+    fn add_diagnostic_with(&mut self, diagnostic: &dyn Diagnostic, inherited_source: Option<Arc<dyn Source>>) {
+        let source = diagnostic.source_code().or(inherited_source);
+
+        for label in diagnostic.labels().into_iter().flatten() {
+            let (Some(suggestion), Some(source)) = (label.suggestion(), label.source().or_else(|| source.clone()))
+            else {
+                continue;
+            };
+
+            let range = SourceRange::new(source, label.range().clone());
+            let _ = self.add(Suggestion::replace(range, suggestion));
+        }
+
+        let mut applied_alternative = false;
+
+        let mut help = diagnostic.help().into_iter().flatten().collect::<Vec<_>>();
+        help.sort_by_key(|h| std::cmp::Reverse(h.priority));
+
+        for help in help {
+            // Alternatives are mutually exclusive, so only the first one is applied.
+            if help.alternative && std::mem::replace(&mut applied_alternative, true) {
+                continue;
+            }
+
+            for suggestion in help.suggestions {
+                let _ = self.add(suggestion);
+            }
+        }
+
+        for nested in diagnostic.causes().chain(diagnostic.related()) {
+            self.add_diagnostic_with(nested, source.clone());
+        }
+    }
+
+    /// Adds the given suggestion to the set.
+    ///
+    /// If the suggestion overlaps with a suggestion which was added earlier, it's rejected and the
+    /// conflict is returned, which is also recorded in [`SuggestionSet::conflicts`]. Edits which only
+    /// touch each other, such as an insertion directly after a replaced span, don't overlap.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{NamedSource, SourceLocation, SourceRange, Suggestion, SuggestionSet};
+    ///
+    /// let source = Arc::new(NamedSource::new("src/main.lm", "return (0);"));
+    ///
+    /// let mut set = SuggestionSet::new();
+    ///
+    /// assert!(set.add(Suggestion::delete(SourceRange::new(source.clone(), 7..8))).is_ok());
+    /// assert!(set.add(Suggestion::insert(SourceLocation::new(source.clone(), 8), "1")).is_ok());
+    /// assert!(set.add(Suggestion::replace(SourceRange::new(source.clone(), 6..9), " 2")).is_err());
+    ///
+    /// assert_eq!(set.conflicts().len(), 1);
+    /// ```
+    pub fn add(&mut self, suggestion: Suggestion) -> Result<(), SuggestionConflict> {
+        let Some(source) = suggestion.source() else {
+            if !self.file_operations.contains(&suggestion) {
+                self.file_operations.push(suggestion);
+            }
+
+            return Ok(());
+        };
+
+        let content = source.try_text().unwrap_or_default();
+
+        let (range, replacement) = match &suggestion {
+            Suggestion::Deletion { range } => (normalize_span(content, range.span().0.clone()), String::new()),
+            Suggestion::Replacement { range, replacement } => {
+                (normalize_span(content, range.span().0.clone()), replacement.clone())
+            }
+            Suggestion::Insertion { value, .. } => {
+                let offset = suggestion.span().unwrap_or_default().start;
+                let range = normalize_span(content, offset..offset);
+
+                (range.start..range.start, value.clone())
+            }
+            Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => {
+                unreachable!("file operations don't refer to any source")
+            }
+        };
+
+        let file = match self.files.iter().position(|file| same_source(&file.source, &source)) {
+            Some(idx) => &mut self.files[idx],
+            None => {
+                self.files.push(FileEdits {
+                    source,
+                    edits: Vec::new(),
+                });
+
+                self.files.last_mut().unwrap()
+            }
+        };
+
+        if file
+            .edits
+            .iter()
+            .any(|edit| edit.range == range && edit.replacement == replacement)
+        {
+            return Ok(());
+        }
+
+        if let Some(existing) = file.edits.iter().find(|edit| edits_overlap(&edit.range, &range)) {
+            let conflict = SuggestionConflict {
+                suggestion,
+                existing: existing.suggestion.clone(),
+            };
+
+            self.conflicts.push(conflict.clone());
+            return Err(conflict);
+        }
+
+        // Keep the edits sorted by position, where insertions are placed before any
+        // edit starting at the same offset, so they're applied in a stable order.
+        let idx = file
+            .edits
+            .partition_point(|edit| (edit.range.start, edit.range.end) <= (range.start, range.end));
+
+        file.edits.insert(
+            idx,
+            Edit {
+                range,
+                replacement,
+                suggestion,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Gets all suggestions which were rejected, since they overlap with another suggestion.
+    pub fn conflicts(&self) -> &[SuggestionConflict] {
+        &self.conflicts
+    }
+
+    /// Determines whether the set doesn't contain any suggestions.
+    ///
+    /// Rejected suggestions aren't part of the set, so they're not considered.
+    pub fn is_empty(&self) -> bool {
+        self.files.iter().all(|file| file.edits.is_empty()) && self.file_operations.is_empty()
+    }
+
+    /// Gets all suggestions in the set, where the edits within each file are sorted by their position.
+    ///
+    /// Edits are returned per file, in the order which the files were first seen, followed by all
+    /// file-level operations in the order they were added.
+    pub fn suggestions(&self) -> impl Iterator<Item = &Suggestion> + '_ {
+        self.files
+            .iter()
+            .flat_map(|file| file.edits.iter().map(|edit| &edit.suggestion))
+            .chain(self.file_operations.iter())
+    }
+
+    /// Applies all edits in the set, which refer to the given source, onto the content of the source.
+    ///
+    /// Returns `None` if the set doesn't contain any edits for the given source,
+    /// or if the content of the source is unavailable.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{NamedSource, Source, SourceRange, Suggestion, SuggestionSet};
+    ///
+    /// let source: Arc<dyn Source> = Arc::new(NamedSource::new("src/main.lm", "return (0);"));
+    ///
+    /// let mut set = SuggestionSet::new();
+    /// set.add(Suggestion::delete(SourceRange::new(source.clone(), 7..8))).unwrap();
+    /// set.add(Suggestion::delete(SourceRange::new(source.clone(), 9..10))).unwrap();
+    ///
+    /// assert_eq!(set.apply(&source).unwrap(), "return 0;");
+    /// ```
+    pub fn apply(&self, source: &Arc<dyn Source>) -> Option<String> {
+        let file = self.files.iter().find(|file| same_source(&file.source, source))?;
+
+        file.apply()
+    }
+
+    /// Formats all suggestions in the set as a unified diff, with one section per file.
+    ///
+    /// Edits are formatted as hunks against the current content of their source, where the name of the
+    /// source is used as the path. Created files are formatted as additions to `/dev/null`, while deleted
+    /// and renamed files are formatted using the extended headers of `git diff`, since their content
+    /// isn't known. Edits in sources where the content is unavailable are skipped.
+    pub fn to_unified_diff(&self) -> String {
+        let mut diff = String::new();
+
+        for file in &self.files {
+            let (Some(content), Some(fixed)) = (file.source.try_text(), file.apply()) else {
+                continue;
+            };
+
+            let hunks = diff_hunks(content, &file.edits);
+            if hunks.is_empty() || content == fixed {
+                continue;
+            }
+
+            let path = file.source.name().unwrap_or("<unknown>");
+
+            diff.push_str(&format!("--- a/{path}\n+++ b/{path}\n"));
+
+            for hunk in hunks {
+                diff.push_str(&hunk);
+            }
+        }
+
+        for operation in &self.file_operations {
+            match operation {
+                Suggestion::CreateFile { path, content } => {
+                    diff.push_str(&format!("--- /dev/null\n+++ b/{path}\n"));
+
+                    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+                    if !lines.is_empty() {
+                        diff.push_str(&format!("@@ -0,0 {} @@\n", hunk_range('+', 0, lines.len())));
+                        push_diff_lines(&mut diff, '+', lines);
+                    }
+                }
+                Suggestion::DeleteFile { path } => {
+                    diff.push_str(&format!("diff --git a/{path} b/{path}\ndeleted file mode 100644\n"));
+                }
+                Suggestion::RenameFile { from, to } => {
+                    diff.push_str(&format!(
+                        "diff --git a/{from} b/{to}\nrename from {from}\nrename to {to}\n"
+                    ));
+                }
+                Suggestion::Deletion { .. } | Suggestion::Insertion { .. } | Suggestion::Replacement { .. } => {}
+            }
+        }
+
+        diff
+    }
+}
+
+/// Summary of the fixes in a [`SuggestionSet`], such as after applying them with a `--fix` flag.
+///
+/// The report implements [`Diagnostic`], so it can be rendered and reported using the same pipeline as
+/// all other diagnostics. The amount of fixes per file is attached as related diagnostics, along with
+/// any file-level operations and a warning about the suggestions which were skipped due to conflicts.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::*;
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "return (0);"));
+///
+/// let mut set = SuggestionSet::new();
+/// set.add(Suggestion::delete(SourceRange::new(source.clone(), 7..8))).unwrap();
+/// set.add(Suggestion::delete(SourceRange::new(source.clone(), 9..10))).unwrap();
+///
+/// let report = FixReport::new(&set);
+///
+/// assert_eq!(report.message(), "applied 2 fixes in 1 file");
+/// assert_eq!(report.related().next().unwrap().message(), "fixed `src/main.lm` (2 fixes)");
+/// ```
+#[derive(Debug)]
+pub struct FixReport {
+    /// Defines the amount of fixes per file, in the order which the files were first seen.
+    files: Vec<FixedFile>,
+
+    /// Defines the amount of file-level operations, such as creating or renaming files.
+    operations: usize,
+
+    /// Defines the amount of suggestions which were skipped, since they overlap with another suggestion.
+    skipped: usize,
+
+    /// Defines the related diagnostics, which describe the fixes of each file.
+    related: Vec<SimpleDiagnostic>,
+}
+
+/// Defines the amount of fixes which were applied to a single file, within a [`FixReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedFile {
+    /// Defines the path of the file, which is the name of the source.
+    pub path: String,
+
+    /// Defines the amount of fixes which were applied to the file.
+    pub fixes: usize,
+}
+
+impl FixReport {
+    /// Creates a new [`FixReport`], which summarizes all suggestions in the given set.
+    pub fn new(set: &SuggestionSet) -> Self {
+        let files = set
+            .files
+            .iter()
+            .filter(|file| !file.edits.is_empty())
+            .map(|file| FixedFile {
+                path: file.source.name().unwrap_or("<unknown>").to_string(),
+                fixes: file.edits.len(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut related = files
+            .iter()
+            .map(|file| {
+                SimpleDiagnostic::new(format!(
+                    "fixed `{}` ({})",
+                    file.path,
+                    plural(file.fixes, "fix", "fixes")
+                ))
+                .with_severity(Severity::Note)
+            })
+            .collect::<Vec<_>>();
+
+        for operation in &set.file_operations {
+            related.push(
+                SimpleDiagnostic::new(format!("applied suggestion to {operation}")).with_severity(Severity::Note),
+            );
+        }
+
+        let skipped = set.conflicts.len();
+
+        if skipped > 0 {
+            related.push(
+                SimpleDiagnostic::new(format!(
+                    "skipped {} which overlapped with other suggestions",
+                    plural(skipped, "suggestion", "suggestions")
+                ))
+                .with_severity(Severity::Warning),
+            );
+        }
+
+        FixReport {
+            files,
+            operations: set.file_operations.len(),
+            skipped,
+            related,
+        }
+    }
+
+    /// Gets the amount of fixes per file, in the order which the files were first seen.
+    pub fn files(&self) -> &[FixedFile] {
+        &self.files
+    }
+
+    /// Gets the total amount of fixes, including all file-level operations.
+    pub fn total(&self) -> usize {
+        self.files.iter().map(|file| file.fixes).sum::<usize>() + self.operations
+    }
+
+    /// Gets the amount of suggestions which were skipped, since they overlap with another suggestion.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+impl Diagnostic for FixReport {
+    fn message(&self) -> String {
+        match (self.total(), self.files.len()) {
+            (0, _) => String::from("no fixes were applied"),
+            (total, 0) => format!("applied {}", plural(total, "fix", "fixes")),
+            (total, files) => format!(
+                "applied {} in {}",
+                plural(total, "fix", "fixes"),
+                plural(files, "file", "files")
+            ),
+        }
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn related(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(self.related.iter().map(|d| d as &(dyn Diagnostic + Send + Sync)))
+    }
+}
+
+/// Formats the given count along with the singular or plural form of a noun, such as `2 fixes`.
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{count} {singular}")
+    } else {
+        format!("{count} {plural}")
+    }
+}
+
+impl FileEdits {
+    /// Applies all edits onto the content of the source.
+    fn apply(&self) -> Option<String> {
+        let content = self.source.try_text()?;
+
+        Some(apply_edits(content, 0, &self.edits))
+    }
+}
+
+/// Determines whether the given sources refer to the same file, using the same rules as [`SourceRange::same_source`].
+fn same_source(a: &Arc<dyn Source>, b: &Arc<dyn Source>) -> bool {
+    Arc::ptr_eq(a, b) || (a.name() == b.name() && a.try_text() == b.try_text())
+}
+
+/// Determines whether two edits overlap, so they can't both be applied.
+///
+/// Edits which only touch each other don't overlap, except for two insertions at the same
+/// offset, since the order of the inserted values would be ambiguous.
+fn edits_overlap(a: &Range<usize>, b: &Range<usize>) -> bool {
+    if a.is_empty() && b.is_empty() {
+        return a.start == b.start;
+    }
+
+    if a.is_empty() {
+        return b.start < a.start && a.start < b.end;
+    }
+
+    if b.is_empty() {
+        return a.start < b.start && b.start < a.end;
+    }
+
+    a.start < b.end && b.start < a.end
+}
+
+/// Applies the given sorted edits onto the given text, which starts at `offset` within the source.
+///
+/// All edits must lie within the text.
+fn apply_edits(text: &str, offset: usize, edits: &[Edit]) -> String {
+    let mut fixed = String::with_capacity(text.len());
+    let mut cursor = 0;
+
+    for edit in edits {
+        let start = edit.range.start - offset;
+        let end = edit.range.end - offset;
+
+        fixed.push_str(&text[cursor..start]);
+        fixed.push_str(&edit.replacement);
+
+        cursor = end;
+    }
+
+    fixed.push_str(&text[cursor..]);
+    fixed
+}
+
+/// Creates the hunks of a unified diff, from applying the given sorted edits onto the given content.
+fn diff_hunks(content: &str, edits: &[Edit]) -> Vec<String> {
+    let lines = content.split_inclusive('\n').collect::<Vec<_>>();
+
+    // Defines the start offset of each line, as well as the start of the empty line after a trailing
+    // newline, so insertions at the very end of the content still fall within a line.
+    let mut starts = Vec::with_capacity(lines.len() + 1);
+    let mut offset = 0;
+
+    for line in &lines {
+        starts.push(offset);
+        offset += line.len();
+    }
+
+    if content.is_empty() || content.ends_with('\n') {
+        starts.push(content.len());
+    }
+
+    let line_of = |offset: usize| starts.partition_point(|start| *start <= offset).saturating_sub(1);
+
+    // Group all edits into blocks of changed lines, where edits on the same or adjacent lines share a block.
+    let mut blocks: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+
+    for (idx, edit) in edits.iter().enumerate() {
+        let first = line_of(edit.range.start);
+        let last = line_of(edit.range.end.saturating_sub(1).max(edit.range.start));
+
+        match blocks.last_mut() {
+            Some((block_lines, block_edits)) if first <= block_lines.end => {
+                block_lines.end = block_lines.end.max(last + 1);
+                block_edits.end = idx + 1;
+            }
+            _ => blocks.push((first..last + 1, idx..idx + 1)),
+        }
+    }
+
+    // Group the blocks into hunks, where blocks with overlapping context lines share a hunk.
+    let mut hunks: Vec<Vec<(Range<usize>, Range<usize>)>> = Vec::new();
+
+    for block in blocks {
+        match hunks.last_mut() {
+            Some(hunk) if block.0.start <= hunk.last().unwrap().0.end + DIFF_CONTEXT_LINES * 2 => hunk.push(block),
+            _ => hunks.push(vec![block]),
+        }
+    }
+
+    let mut delta = 0isize;
+    let mut formatted = Vec::with_capacity(hunks.len());
+
+    for hunk in hunks {
+        let context_start = hunk[0].0.start.saturating_sub(DIFF_CONTEXT_LINES);
+        let context_end = (hunk.last().unwrap().0.end + DIFF_CONTEXT_LINES).min(lines.len());
+
+        let mut body = String::new();
+        let mut old_count = 0;
+        let mut new_count = 0;
+        let mut cursor = context_start;
+
+        for (block_lines, block_edits) in hunk {
+            let context = &lines[cursor..block_lines.start.min(lines.len())];
+
+            old_count += context.len();
+            new_count += context.len();
+            push_diff_lines(&mut body, ' ', context.iter().copied());
+
+            let block_start = starts[block_lines.start];
+            let block_end = starts.get(block_lines.end).copied().unwrap_or(content.len());
+
+            let old_text = &content[block_start..block_end];
+            let new_text = apply_edits(old_text, block_start, &edits[block_edits]);
+
+            let old_lines = old_text.split_inclusive('\n').collect::<Vec<_>>();
+            let new_lines = new_text.split_inclusive('\n').collect::<Vec<_>>();
+
+            old_count += old_lines.len();
+            new_count += new_lines.len();
+
+            // Lines at the start or end of the block which are left unchanged, such as the line
+            // following an insertion at the start of a line, are displayed as context instead.
+            let prefix = old_lines.iter().zip(&new_lines).take_while(|(a, b)| a == b).count();
+            let suffix = old_lines[prefix..]
+                .iter()
+                .rev()
+                .zip(new_lines[prefix..].iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            push_diff_lines(&mut body, ' ', old_lines[..prefix].iter().copied());
+            push_diff_lines(
+                &mut body,
+                '-',
+                old_lines[prefix..old_lines.len() - suffix].iter().copied(),
+            );
+            push_diff_lines(
+                &mut body,
+                '+',
+                new_lines[prefix..new_lines.len() - suffix].iter().copied(),
+            );
+            push_diff_lines(&mut body, ' ', old_lines[old_lines.len() - suffix..].iter().copied());
+
+            cursor = block_lines.end.min(lines.len());
+        }
+
+        let context = &lines[cursor..context_end.max(cursor)];
+
+        old_count += context.len();
+        new_count += context.len();
+        push_diff_lines(&mut body, ' ', context.iter().copied());
+
+        let new_start = (context_start as isize + delta) as usize;
+        delta += new_count as isize - old_count as isize;
+
+        formatted.push(format!(
+            "@@ {} {} @@\n{body}",
+            hunk_range('-', context_start, old_count),
+            hunk_range('+', new_start, new_count)
+        ));
+    }
+
+    formatted
+}
+
+/// Formats the range of a hunk header, such as `-3,4`, from the zero-indexed start line and line count.
+///
+/// Ranges of a single line omit the count, while empty ranges refer to the line before the range.
+fn hunk_range(prefix: char, start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{prefix}{start},0"),
+        1 => format!("{prefix}{}", start + 1),
+        _ => format!("{prefix}{},{count}", start + 1),
+    }
+}
+
+/// Appends the given lines to the diff, each prefixed with the given marker.
+///
+/// Lines without a trailing newline are followed by a `\ No newline at end of file` marker.
+fn push_diff_lines<'a>(diff: &mut String, prefix: char, lines: impl IntoIterator<Item = &'a str>) {
+    for line in lines {
+        diff.push(prefix);
+        diff.push_str(line);
+
+        if !line.ends_with('\n') {
+            diff.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use indexmap::IndexMap;
+
+use crate::{Diagnostic, Severity, SimpleDiagnostic};
+
+/// Defines how a handler aggregates repeated diagnostics with the same code when draining,
+/// which keeps the output tractable for noisy lints.
+///
+/// Within a single drain, only the first `threshold` diagnostics of each code are kept, while all
+/// further occurrences are replaced by a single summary diagnostic, such as:
+///
+/// ```text
+/// warning[W0042]: unused variable — 37 occurrences, rerun with --verbose to see all
+/// ```
+///
+/// The summary takes the message, code and severity of the first occurrence, and is placed where
+/// the first replaced occurrence would have been. Errors, fatal errors and bugs are never aggregated,
+/// nor are diagnostics without any code.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{BufferedDiagnosticHandler, CodeAggregation, GraphicalRenderer, Handler, Severity, SimpleDiagnostic};
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+///
+/// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+/// handler.aggregate_by_code(CodeAggregation::new(1).with_hint("rerun with --verbose to see all"));
+///
+/// for _ in 0..3 {
+///     handler.report(Box::new(
+///         SimpleDiagnostic::new("unused variable")
+///             .with_code("W0042")
+///             .with_severity(Severity::Warning),
+///     ));
+/// }
+///
+/// handler.drain().unwrap();
+///
+/// assert_eq!(
+///     handler.buffer(),
+///     concat!(
+///         "⚠ warning[W0042]: unused variable\n",
+///         "⚠ warning[W0042]: unused variable — 3 occurrences, rerun with --verbose to see all\n",
+///     )
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAggregation {
+    /// Defines the amount of diagnostics of each code to keep, before aggregating the rest.
+    threshold: usize,
+
+    /// Defines the hint which is appended to the message of the summary, if any.
+    hint: Option<String>,
+}
+
+impl CodeAggregation {
+    /// Creates a new [`CodeAggregation`], which keeps the first `threshold` diagnostics of each code.
+    ///
+    /// A threshold of zero replaces all occurrences of a code with the summary.
+    pub fn new(threshold: usize) -> Self {
+        CodeAggregation { threshold, hint: None }
+    }
+
+    /// Sets the hint which is appended to the message of the summary, such as
+    /// how to see all the occurrences.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Gets the amount of diagnostics of each code to keep, before aggregating the rest.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Gets the hint which is appended to the message of the summary, if any.
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// Creates the summary diagnostic, which replaces all further occurrences of a code.
+    fn summarize(&self, code: String, occurrences: Occurrences) -> Box<dyn Diagnostic> {
+        let noun = if occurrences.count == 1 {
+            "occurrence"
+        } else {
+            "occurrences"
+        };
+        let mut message = format!("{} — {} {noun}", occurrences.message, occurrences.count);
+
+        if let Some(hint) = &self.hint {
+            message.push_str(", ");
+            message.push_str(hint);
+        }
+
+        Box::new(
+            SimpleDiagnostic::new(message)
+                .with_code(code)
+                .with_severity(occurrences.severity),
+        )
+    }
+}
+
+/// Gets the code of the given diagnostic, if it can be aggregated.
+fn aggregated_code(diagnostic: &dyn Diagnostic) -> Option<String> {
+    if diagnostic.severity().is_at_least(Severity::Error) {
+        return None;
+    }
+
+    diagnostic.code().map(|code| code.to_string())
+}
+
+/// Defines all occurrences of a single code, within a single drain.
+struct Occurrences {
+    /// Defines the message of the first occurrence.
+    message: String,
+
+    /// Defines the severity of the first occurrence.
+    severity: Severity,
+
+    /// Defines the amount of occurrences.
+    count: usize,
+}
+
+/// Aggregates the given items, so only the first items of each code are kept, while the
+/// first item beyond the threshold is replaced by the summary, using `replace`. All other items
+/// beyond the threshold are discarded.
+pub(crate) fn aggregate_by_code<T>(
+    items: Vec<T>,
+    aggregation: &CodeAggregation,
+    diagnostic: impl Fn(&T) -> &dyn Diagnostic,
+    replace: impl Fn(T, Box<dyn Diagnostic>) -> T,
+) -> Vec<T> {
+    let mut occurrences: IndexMap<String, Occurrences> = IndexMap::new();
+
+    for item in &items {
+        let diagnostic = diagnostic(item);
+
+        if let Some(code) = aggregated_code(diagnostic) {
+            occurrences
+                .entry(code)
+                .or_insert_with(|| Occurrences {
+                    message: diagnostic.message(),
+                    severity: diagnostic.severity(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+    }
+
+    let mut seen: IndexMap<String, usize> = IndexMap::new();
+    let mut aggregated = Vec::with_capacity(items.len());
+
+    for item in items {
+        let Some(code) = aggregated_code(diagnostic(&item)) else {
+            aggregated.push(item);
+            continue;
+        };
+
+        let seen = seen.entry(code.clone()).or_default();
+        *seen += 1;
+
+        if *seen <= aggregation.threshold {
+            aggregated.push(item);
+        } else if let Some(occurrences) = occurrences.swap_remove(&code) {
+            aggregated.push(replace(item, aggregation.summarize(code, occurrences)));
+        }
+    }
+
+    aggregated
+}
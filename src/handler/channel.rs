@@ -0,0 +1,200 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::{Diagnostic, DrainError, DrainResult, DrainSummary, Handler, Renderer, Severity};
+
+/// Creates a new [`ChannelHandler`], along with the receiving end of the channel
+/// which all diagnostic events are sent to.
+///
+/// This is intended for GUI and IDE frontends, which need to consume diagnostics on
+/// another thread, while the compiler thread keeps using the ordinary [`Handler`] API.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{channel_handler, DiagnosticEvent, Handler, SimpleDiagnostic};
+///
+/// let (mut handler, receiver) = channel_handler();
+///
+/// let frontend = std::thread::spawn(move || receiver.iter().collect::<Vec<_>>());
+///
+/// handler.report(Box::new(SimpleDiagnostic::new("An error occurred")));
+/// let _ = handler.drain();
+///
+/// // Dropping the handler closes the channel.
+/// drop(handler);
+///
+/// let events = frontend.join().unwrap();
+///
+/// assert!(matches!(events[0], DiagnosticEvent::Reported(_)));
+/// assert!(matches!(events[1], DiagnosticEvent::Drained { .. }));
+/// assert!(matches!(events[2], DiagnosticEvent::Summary { errors: 1, .. }));
+/// ```
+pub fn channel_handler() -> (ChannelHandler, Receiver<DiagnosticEvent>) {
+    let (sender, receiver) = channel();
+
+    (ChannelHandler::new(sender), receiver)
+}
+
+/// Owned snapshot of the top-level properties of a diagnostic.
+///
+/// Since diagnostics themselves aren't guaranteed to be [`Send`], only
+/// the snapshot is sent across threads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticRecord {
+    /// Defines the message of the diagnostic.
+    pub message: String,
+
+    /// Defines the code of the diagnostic, if any.
+    pub code: Option<String>,
+
+    /// Defines the severity of the diagnostic.
+    pub severity: Severity,
+}
+
+impl DiagnosticRecord {
+    /// Creates a new [`DiagnosticRecord`] from the given diagnostic.
+    pub fn from_diagnostic(diagnostic: &dyn Diagnostic) -> Self {
+        Self {
+            message: diagnostic.message(),
+            code: diagnostic.code().map(|c| c.to_string()),
+            severity: diagnostic.severity(),
+        }
+    }
+}
+
+/// Represents an event, which is sent from a [`ChannelHandler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticEvent {
+    /// Defines that a diagnostic was reported to the handler.
+    Reported(DiagnosticRecord),
+
+    /// Defines that a diagnostic was drained from the handler.
+    ///
+    /// If the handler has a renderer attached, `rendered` contains the rendered diagnostic.
+    Drained {
+        record: DiagnosticRecord,
+        rendered: Option<String>,
+    },
+
+    /// Defines that the handler was drained, along with the amount of diagnostics
+    /// which were drained, grouped by severity.
+    Summary {
+        errors: usize,
+        warnings: usize,
+        total: usize,
+    },
+}
+
+/// A [`Handler`] which sends all diagnostics as events over a channel.
+///
+/// Use [`channel_handler()`] to create a new handler.
+pub struct ChannelHandler {
+    /// Defines whether to exit upon draining an error.
+    exit_on_error: bool,
+
+    /// Stores all the diagnostics which have been reported.
+    emitted_diagnostics: Vec<Box<dyn Diagnostic>>,
+
+    /// Defines the renderer to use when rendering the diagnostics, if any.
+    renderer: Option<Box<dyn Renderer + Send + Sync>>,
+
+    /// Defines the sending end of the event channel.
+    sender: Sender<DiagnosticEvent>,
+}
+
+impl ChannelHandler {
+    fn new(sender: Sender<DiagnosticEvent>) -> Self {
+        Self {
+            exit_on_error: false,
+            emitted_diagnostics: Vec::new(),
+            renderer: None,
+            sender,
+        }
+    }
+
+    /// Sets the renderer of the handler, so drained events include the rendered diagnostic.
+    pub fn set_renderer(&mut self, renderer: Box<dyn Renderer + Send + Sync>) {
+        self.renderer = Some(renderer);
+    }
+
+    /// Enables the handler to exit upon draining an error.
+    pub fn exit_on_error(&mut self) {
+        self.exit_on_error = true
+    }
+
+    /// Gets the amount of diagnostics within the handler, which have
+    /// yet to be drained.
+    pub fn count(&self) -> usize {
+        self.emitted_diagnostics.len()
+    }
+
+    /// Sends the given event to the receiver.
+    ///
+    /// If the receiver has been dropped, the event is discarded, since
+    /// the frontend has no interest in receiving any more events.
+    fn send(&self, event: DiagnosticEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Sends the summary of a drain to the receiver.
+    fn send_summary(&self, summary: &DrainSummary) {
+        self.send(DiagnosticEvent::Summary {
+            errors: summary.errors,
+            warnings: summary.warnings,
+            total: summary.total,
+        });
+    }
+}
+
+impl Handler for ChannelHandler {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        self.send(DiagnosticEvent::Reported(DiagnosticRecord::from_diagnostic(
+            diagnostic.as_ref(),
+        )));
+
+        self.emitted_diagnostics.push(diagnostic);
+    }
+
+    fn drain(&mut self) -> DrainResult {
+        let mut summary = DrainSummary::default();
+
+        let mut diagnostics = std::mem::take(&mut self.emitted_diagnostics).into_iter();
+
+        while let Some(diagnostic) = diagnostics.next() {
+            let rendered = match &self.renderer {
+                Some(renderer) => match renderer.render(diagnostic.as_ref()) {
+                    Ok(rendered) => Some(rendered),
+                    Err(err) => {
+                        // Keep the failed diagnostic and all the remaining ones, so they can be drained again.
+                        self.emitted_diagnostics = std::iter::once(diagnostic).chain(diagnostics).collect();
+                        self.send_summary(&summary);
+
+                        return Err(err.into());
+                    }
+                },
+                None => None,
+            };
+
+            summary.record(diagnostic.severity());
+
+            self.send(DiagnosticEvent::Drained {
+                record: DiagnosticRecord::from_diagnostic(diagnostic.as_ref()),
+                rendered,
+            });
+
+            // Fatal diagnostics abort the drain immediately, discarding the rest.
+            if diagnostic.severity() == Severity::Fatal {
+                self.send_summary(&summary);
+
+                return Err(DrainError::Fatal);
+            }
+        }
+
+        self.send_summary(&summary);
+
+        if summary.errors > 0 && self.exit_on_error {
+            return Err(DrainError::CompoundError(summary.errors));
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,335 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::{Diagnostic, DrainError, DrainResult, DrainSummary, Handler, Severity, Source};
+
+/// Owned snapshot of a single label within a [`CapturedDiagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedLabel {
+    /// Defines the name of the source which the label refers to, if any.
+    ///
+    /// Labels without any source of their own use the source of their
+    /// diagnostic instead, followed by the source of the closest parent.
+    pub file: Option<String>,
+
+    /// Defines the labelled byte range within the source.
+    pub range: Range<usize>,
+
+    /// Defines the message of the label.
+    pub message: String,
+
+    /// Defines the severity of the label, if any.
+    pub severity: Option<Severity>,
+}
+
+/// Owned snapshot of a diagnostic, along with all it's labels, help messages and nested diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedDiagnostic {
+    /// Defines the message of the diagnostic.
+    pub message: String,
+
+    /// Defines the code of the diagnostic, if any.
+    pub code: Option<String>,
+
+    /// Defines the severity of the diagnostic.
+    pub severity: Severity,
+
+    /// Defines all the labels of the diagnostic.
+    pub labels: Vec<CapturedLabel>,
+
+    /// Defines the messages of all help messages of the diagnostic.
+    pub help: Vec<String>,
+
+    /// Defines all the causes of the diagnostic.
+    pub causes: Vec<CapturedDiagnostic>,
+
+    /// Defines all the related diagnostics of the diagnostic.
+    pub related: Vec<CapturedDiagnostic>,
+}
+
+impl CapturedDiagnostic {
+    /// Creates a new [`CapturedDiagnostic`] from the given diagnostic.
+    pub fn from_diagnostic(diagnostic: &dyn Diagnostic) -> Self {
+        Self::capture(diagnostic, None)
+    }
+
+    /// Captures the given diagnostic, where labels without any source of their own fall back
+    /// to the source of the diagnostic, followed by the source of the closest parent.
+    fn capture(diagnostic: &dyn Diagnostic, inherited_source: Option<Arc<dyn Source>>) -> Self {
+        let source = diagnostic.source_code().or(inherited_source);
+
+        let labels = diagnostic
+            .labels()
+            .into_iter()
+            .flatten()
+            .map(|label| CapturedLabel {
+                file: label
+                    .source()
+                    .or_else(|| source.clone())
+                    .and_then(|source| source.name().map(String::from)),
+                range: label.range().0.clone(),
+                message: label.message().to_string(),
+                severity: label.severity(),
+            })
+            .collect();
+
+        Self {
+            message: diagnostic.message(),
+            code: diagnostic.code().map(|code| code.to_string()),
+            severity: diagnostic.severity(),
+            labels,
+            help: diagnostic
+                .help()
+                .into_iter()
+                .flatten()
+                .map(|help| help.message)
+                .collect(),
+            causes: diagnostic
+                .causes()
+                .map(|cause| Self::capture(cause, source.clone()))
+                .collect(),
+            related: diagnostic
+                .related()
+                .map(|related| Self::capture(related, source.clone()))
+                .collect(),
+        }
+    }
+
+    /// Gets an iterator over the diagnostic itself, followed by all it's nested diagnostics, recursively.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &CapturedDiagnostic> + '_> {
+        Box::new(
+            std::iter::once(self).chain(
+                self.causes
+                    .iter()
+                    .chain(self.related.iter())
+                    .flat_map(|nested| nested.iter()),
+            ),
+        )
+    }
+}
+
+impl std::fmt::Display for CapturedDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.severity)?;
+
+        if let Some(code) = &self.code {
+            write!(f, "[{code}]")?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// A [`Handler`] which captures structured snapshots of all reported diagnostics, instead
+/// of rendering them, so tests can make assertions on diagnostics without matching on
+/// the rendered output.
+///
+/// The original diagnostics are kept as well, so they can be replayed into another handler
+/// with [`CollectingHandler::replay()`], such as to render them after the assertions.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{CollectingHandler, Handler, Label, NamedSource, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a: Int = \"\";"));
+///
+/// let mut handler = CollectingHandler::new();
+/// handler.report(Box::new(
+///     SimpleDiagnostic::new("mismatched types")
+///         .with_code("E0308")
+///         .with_label(Label::error(Some(source), 13..15, "expected `Int`, found `String`")),
+/// ));
+///
+/// handler.assert_has_error_code("E0308");
+/// handler.assert_label_at("src/main.lm", 13..15);
+/// ```
+#[derive(Debug, Default)]
+pub struct CollectingHandler {
+    /// Defines whether to exit upon draining an error.
+    exit_on_error: bool,
+
+    /// Stores the snapshots of all the diagnostics which have been reported.
+    captured: Vec<CapturedDiagnostic>,
+
+    /// Stores all the diagnostics which have been reported.
+    reported: Vec<Box<dyn Diagnostic>>,
+
+    /// Defines the amount of diagnostics which have been drained.
+    drained: usize,
+}
+
+impl CollectingHandler {
+    /// Creates a new empty handler.
+    pub fn new() -> Self {
+        CollectingHandler::default()
+    }
+
+    /// Enables the handler to return an error upon draining an error, like [`crate::DiagnosticHandler`].
+    pub fn exit_on_error(&mut self) {
+        self.exit_on_error = true
+    }
+
+    /// Gets the snapshots of all the diagnostics which have been reported, including drained diagnostics.
+    pub fn diagnostics(&self) -> &[CapturedDiagnostic] {
+        &self.captured
+    }
+
+    /// Gets an iterator over all the reported diagnostics, along with all their nested diagnostics.
+    pub fn iter_all(&self) -> impl Iterator<Item = &CapturedDiagnostic> {
+        self.captured.iter().flat_map(|captured| captured.iter())
+    }
+
+    /// Gets an iterator over the original diagnostics which have been reported, including drained diagnostics.
+    pub fn reported(&self) -> impl Iterator<Item = &dyn Diagnostic> {
+        self.reported.iter().map(|diagnostic| diagnostic.as_ref())
+    }
+
+    /// Gets the amount of diagnostics which have been reported.
+    pub fn count(&self) -> usize {
+        self.captured.len()
+    }
+
+    /// Removes all the captured diagnostics from the handler.
+    pub fn clear(&mut self) {
+        self.captured.clear();
+        self.reported.clear();
+        self.drained = 0;
+    }
+
+    /// Reports all the captured diagnostics into the given handler, in the order they were reported,
+    /// and removes them from this handler.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{BufferedDiagnosticHandler, CollectingHandler, GraphicalRenderer, Handler, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut handler = CollectingHandler::new();
+    /// handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+    ///
+    /// let mut buffered = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+    /// handler.replay(&mut buffered);
+    /// buffered.drain().unwrap();
+    ///
+    /// assert_eq!(handler.count(), 0);
+    /// assert_eq!(buffered.buffer(), "× error: mismatched types\n");
+    /// ```
+    pub fn replay(&mut self, handler: &mut dyn Handler) {
+        for diagnostic in std::mem::take(&mut self.reported) {
+            handler.report(diagnostic);
+        }
+
+        self.clear();
+    }
+
+    /// Asserts that any reported diagnostic, or any of their nested diagnostics,
+    /// has the given code, regardless of it's severity.
+    #[track_caller]
+    pub fn assert_has_code(&self, code: &str) {
+        if !self.iter_all().any(|d| d.code.as_deref() == Some(code)) {
+            panic!("expected a diagnostic with code `{code}`, found:\n{}", self.summary());
+        }
+    }
+
+    /// Asserts that any reported diagnostic, or any of their nested diagnostics, has the
+    /// given code, while being an error. Bugs and fatal errors are counted as errors as well.
+    #[track_caller]
+    pub fn assert_has_error_code(&self, code: &str) {
+        let found = self
+            .iter_all()
+            .any(|d| d.code.as_deref() == Some(code) && d.severity.is_at_least(Severity::Error));
+
+        if !found {
+            panic!("expected an error with code `{code}`, found:\n{}", self.summary());
+        }
+    }
+
+    /// Asserts that no reported diagnostic is an error. Bugs and fatal errors are counted as errors as well.
+    #[track_caller]
+    pub fn assert_no_errors(&self) {
+        if self.captured.iter().any(|d| d.severity.is_at_least(Severity::Error)) {
+            panic!("expected no errors, found:\n{}", self.summary());
+        }
+    }
+
+    /// Asserts that any reported diagnostic, or any of their nested diagnostics, has
+    /// a label in the source with the given name, which spans the exact given range.
+    #[track_caller]
+    pub fn assert_label_at(&self, file: &str, range: Range<usize>) {
+        let found = self
+            .iter_all()
+            .flat_map(|d| d.labels.iter())
+            .any(|label| label.file.as_deref() == Some(file) && label.range == range);
+
+        if !found {
+            let labels = self
+                .iter_all()
+                .flat_map(|d| d.labels.iter())
+                .map(|label| {
+                    format!(
+                        "  {}:{:?}: {}",
+                        label.file.as_deref().unwrap_or("<unknown>"),
+                        label.range,
+                        label.message
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            panic!(
+                "expected a label at {file}:{range:?}, found:\n{}",
+                if labels.is_empty() {
+                    String::from("  (no labels)")
+                } else {
+                    labels.join("\n")
+                }
+            );
+        }
+    }
+
+    /// Formats a summary of all reported diagnostics, which is used in assertion failures.
+    fn summary(&self) -> String {
+        if self.captured.is_empty() {
+            return String::from("  (no diagnostics)");
+        }
+
+        self.captured
+            .iter()
+            .map(|d| format!("  {d}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Handler for CollectingHandler {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        self.captured
+            .push(CapturedDiagnostic::from_diagnostic(diagnostic.as_ref()));
+        self.reported.push(diagnostic);
+    }
+
+    /// Marks all the diagnostics as drained, while keeping them in the handler, so
+    /// assertions can still be made afterwards.
+    fn drain(&mut self) -> DrainResult {
+        let pending = &self.captured[self.drained..];
+        self.drained = self.captured.len();
+
+        if pending.iter().any(|d| d.severity == Severity::Fatal) {
+            return Err(DrainError::Fatal);
+        }
+
+        let mut summary = DrainSummary::default();
+
+        for diagnostic in pending {
+            summary.record(diagnostic.severity);
+        }
+
+        if summary.errors > 0 && self.exit_on_error {
+            return Err(DrainError::CompoundError(summary.errors));
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::DrainResult;
+
+/// Defines the outcome of a drain, which is turned into the exit code of the process.
+///
+/// The conventional exit codes are `0` upon success, `1` when any errors were drained and `2`
+/// for invalid usage, such as unknown command-line arguments. Warnings exit with `0` by default,
+/// which can be changed with [`ExitCodes`], such as when warnings are denied.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{DrainSummary, ExitCodes, ExitStatus};
+///
+/// let result = Ok(DrainSummary { errors: 0, warnings: 2, total: 2 });
+/// let status = ExitStatus::from_drain(&result);
+///
+/// assert_eq!(status, ExitStatus::Warnings);
+/// assert_eq!(status.code(), 0);
+///
+/// let codes = ExitCodes { warnings: 1, ..ExitCodes::default() };
+/// assert_eq!(status.code_with(&codes), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExitStatus {
+    /// Defines that no errors or warnings were drained.
+    Success,
+
+    /// Defines that one-or-more warnings were drained, but no errors.
+    Warnings,
+
+    /// Defines that one-or-more errors were drained, or that the drain failed.
+    Errors,
+
+    /// Defines that the program was invoked incorrectly, such as with unknown arguments.
+    Usage,
+}
+
+impl ExitStatus {
+    /// Determines the status from the result of a drain.
+    ///
+    /// Compound errors, fatal errors and failures to write the diagnostics are all treated as errors.
+    pub fn from_drain(result: &DrainResult) -> Self {
+        match result {
+            Ok(summary) if summary.errors > 0 => ExitStatus::Errors,
+            Ok(summary) if summary.warnings > 0 => ExitStatus::Warnings,
+            Ok(_) => ExitStatus::Success,
+            Err(_) => ExitStatus::Errors,
+        }
+    }
+
+    /// Gets the conventional exit code of the status.
+    pub fn code(self) -> u8 {
+        self.code_with(&ExitCodes::default())
+    }
+
+    /// Gets the exit code of the status, using the given exit codes.
+    pub fn code_with(self, codes: &ExitCodes) -> u8 {
+        match self {
+            ExitStatus::Success => codes.success,
+            ExitStatus::Warnings => codes.warnings,
+            ExitStatus::Errors => codes.errors,
+            ExitStatus::Usage => codes.usage,
+        }
+    }
+}
+
+impl From<ExitStatus> for std::process::ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        std::process::ExitCode::from(status.code())
+    }
+}
+
+/// Defines the exit code of each [`ExitStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExitCodes {
+    /// Defines the exit code when no errors or warnings were drained. Defaults to `0`.
+    pub success: u8,
+
+    /// Defines the exit code when warnings, but no errors, were drained. Defaults to `0`.
+    pub warnings: u8,
+
+    /// Defines the exit code when any errors were drained. Defaults to `1`.
+    pub errors: u8,
+
+    /// Defines the exit code upon invalid usage. Defaults to `2`.
+    pub usage: u8,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        ExitCodes {
+            success: 0,
+            warnings: 0,
+            errors: 1,
+            usage: 2,
+        }
+    }
+}
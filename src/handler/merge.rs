@@ -0,0 +1,199 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::{Diagnostic, Help, Label, MessageKey, RenderHints, Severity, Source, Tag};
+
+/// Defines the source and byte range of the primary span of a diagnostic.
+type PrimarySpan = (Arc<dyn Source>, Range<usize>);
+
+/// Gets the primary span of the given diagnostic, which is the span of the first label.
+///
+/// Returns `None` if the diagnostic has no labels, or if the first label has no source attached.
+fn primary_span_of(diagnostic: &dyn Diagnostic) -> Option<PrimarySpan> {
+    let label = diagnostic.labels()?.next()?;
+    let source = label.source().or_else(|| diagnostic.source_code())?;
+
+    Some((source, label.range().0.clone()))
+}
+
+/// Determines whether both sources refer to the same source.
+fn same_source(a: &Arc<dyn Source>, b: &Arc<dyn Source>) -> bool {
+    Arc::ptr_eq(a, b) || (a.name() == b.name() && a.text() == b.text())
+}
+
+/// Determines whether two labels refer to the same source, given the sources they resolve to.
+///
+/// Labels which don't resolve to any source are compared by their source identifier instead.
+fn same_label_source(
+    a: Option<&Arc<dyn Source>>,
+    b: Option<&Arc<dyn Source>>,
+    a_label: &Label,
+    b_label: &Label,
+) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => same_source(a, b),
+        (None, None) => a_label.source_id() == b_label.source_id(),
+        _ => false,
+    }
+}
+
+/// Clusters the given items, so items which point to the exact same primary span with the
+/// same severity are placed within the same cluster.
+///
+/// Clusters are ordered by the first appearance of their span, and items within each
+/// cluster retain the order in which they were reported. Items without any primary span
+/// are never clustered with other items.
+pub(crate) fn cluster_identical_spans<T>(items: Vec<T>, diagnostic: impl Fn(&T) -> &dyn Diagnostic) -> Vec<Vec<T>> {
+    let mut clusters: Vec<(Option<PrimarySpan>, Severity, Vec<T>)> = Vec::new();
+
+    for item in items {
+        let severity = diagnostic(&item).severity();
+        let span = primary_span_of(diagnostic(&item));
+
+        let existing = span.as_ref().and_then(|(source, range)| {
+            clusters.iter_mut().find(|(key, key_severity, _)| match key {
+                Some((key_source, key_range)) => {
+                    *key_severity == severity && key_range == range && same_source(key_source, source)
+                }
+                None => false,
+            })
+        });
+
+        match existing {
+            Some((_, _, cluster)) => cluster.push(item),
+            None => clusters.push((span, severity, vec![item])),
+        }
+    }
+
+    clusters.into_iter().map(|(_, _, cluster)| cluster).collect()
+}
+
+/// Diagnostic which merges multiple diagnostics, all pointing to the same primary span.
+///
+/// The message, severity, code and source of the merged diagnostic are taken from the first
+/// diagnostic. The labels of all diagnostics are combined, where duplicate labels are only included once
+/// and primary labels without a message take the message of their diagnostic instead.
+#[derive(Debug)]
+pub(crate) struct MergedDiagnostic {
+    diagnostics: Vec<Box<dyn Diagnostic>>,
+    labels: Vec<Label>,
+}
+
+impl MergedDiagnostic {
+    /// Merges the given diagnostics into a single diagnostic.
+    ///
+    /// If only a single diagnostic is given, it is returned as-is.
+    pub(crate) fn merge(mut diagnostics: Vec<Box<dyn Diagnostic>>) -> Box<dyn Diagnostic> {
+        if diagnostics.len() == 1 {
+            return diagnostics.remove(0);
+        }
+
+        // Labels are paired with the source they resolve to, since equal labels within different
+        // sources are distinct, even if they don't hold a source of their own.
+        let mut labels: Vec<(Option<Arc<dyn Source>>, Label)> = Vec::new();
+
+        for (idx, diagnostic) in diagnostics.iter().enumerate() {
+            let Some(diagnostic_labels) = diagnostic.labels() else {
+                continue;
+            };
+
+            for (label_idx, mut label) in diagnostic_labels.enumerate() {
+                if idx > 0 {
+                    // Only the first diagnostic keeps it's message in the header, so the
+                    // primary labels of the others take their message, if they have none.
+                    let message = if label_idx == 0 && label.message().is_empty() {
+                        diagnostic.message()
+                    } else {
+                        label.message().to_string()
+                    };
+
+                    // Labels might rely on the source of their own diagnostic, which isn't
+                    // necessarily the same as the source of the first diagnostic.
+                    let source = label.source().or_else(|| diagnostic.source_code());
+
+                    label = Label::new(source, label.range().clone(), message)
+                        .with_severity(label.severity().unwrap_or(diagnostic.severity()));
+                }
+
+                let source = label.source().or_else(|| diagnostic.source_code());
+
+                let duplicate = labels.iter().any(|(existing_source, existing)| {
+                    *existing == label && same_label_source(existing_source.as_ref(), source.as_ref(), existing, &label)
+                });
+
+                if !duplicate {
+                    labels.push((source, label));
+                }
+            }
+        }
+
+        let labels = labels.into_iter().map(|(_, label)| label).collect();
+
+        Box::new(MergedDiagnostic { diagnostics, labels })
+    }
+
+    fn primary(&self) -> &dyn Diagnostic {
+        self.diagnostics[0].as_ref()
+    }
+}
+
+impl Diagnostic for MergedDiagnostic {
+    fn message(&self) -> String {
+        self.primary().message()
+    }
+
+    fn message_key(&self) -> Option<MessageKey> {
+        self.primary().message_key()
+    }
+
+    fn severity(&self) -> Severity {
+        self.primary().severity()
+    }
+
+    fn code(&self) -> Option<Box<dyn std::fmt::Display + '_>> {
+        self.primary().code()
+    }
+
+    fn group_key(&self) -> Option<String> {
+        self.primary().group_key()
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        self.primary().tags()
+    }
+
+    fn source_code(&self) -> Option<Arc<dyn Source>> {
+        self.primary().source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = Label> + '_>> {
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+
+    fn causes(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(self.diagnostics.iter().flat_map(|d| d.causes()))
+    }
+
+    fn related(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(self.diagnostics.iter().flat_map(|d| d.related()))
+    }
+
+    fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
+        let help = self
+            .diagnostics
+            .iter()
+            .filter_map(|d| d.help())
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if help.is_empty() {
+            return None;
+        }
+
+        Some(Box::new(help.into_iter()))
+    }
+
+    fn render_hints(&self) -> RenderHints {
+        self.primary().render_hints()
+    }
+}
@@ -0,0 +1,1736 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+
+#[cfg(not(feature = "terminal"))]
+use crate::IoWriter;
+use crate::{
+    BatchOptions, Diagnostic, DiagnosticKey, Help, Label, MessageKey, RenderHints, Renderer, Severity, Source, Tag,
+};
+
+mod aggregate;
+pub mod channel;
+pub mod collecting;
+mod exit;
+mod merge;
+pub mod null;
+#[cfg(feature = "terminal")]
+pub mod watch;
+
+use aggregate::aggregate_by_code;
+pub use aggregate::CodeAggregation;
+pub use channel::*;
+pub use collecting::*;
+pub use exit::{ExitCodes, ExitStatus};
+use merge::{cluster_identical_spans, MergedDiagnostic};
+pub use null::*;
+#[cfg(feature = "terminal")]
+pub use watch::*;
+
+/// Represents an error which can occur when draining errors
+/// from the [`DiagnosticHandler::drain()`] and [`DiagnosticHandler::report_and_drain`].
+#[non_exhaustive]
+pub enum DrainError {
+    /// Defines that the error occured when attempting to write
+    /// the diagnostic to the output buffer.
+    Fmt(std::fmt::Error),
+
+    /// Defines that one-or-more errors were reported during the drain,
+    /// which are not propogating upwards to the calling function.
+    ///
+    /// The variant defines the number of errors which were reported. Note that
+    /// this number does *not* include non-errors such as warnings, nor does
+    /// it count any sub-diagnostics, such as labels or related errors.
+    CompoundError(usize),
+
+    /// Defines that a diagnostic with [`Severity::Fatal`] was drained, which aborted
+    /// the drain immediately. Any diagnostics after it were discarded.
+    Fatal,
+}
+
+impl From<std::fmt::Error> for DrainError {
+    fn from(err: std::fmt::Error) -> Self {
+        Self::Fmt(err)
+    }
+}
+
+impl std::error::Error for DrainError {}
+
+impl std::fmt::Debug for DrainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fmt(e) => e.fmt(f),
+            Self::CompoundError(cnt) => f.debug_tuple("CompoundError").field(cnt).finish(),
+            Self::Fatal => f.write_str("Fatal"),
+        }
+    }
+}
+
+impl std::fmt::Display for DrainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fmt(e) => e.fmt(f),
+            Self::CompoundError(cnt) => write!(f, "aborting due to {cnt} previous errors"),
+            Self::Fatal => f.write_str("aborting due to fatal error"),
+        }
+    }
+}
+
+/// Defines the amount of diagnostics which were drained from a handler, by severity.
+///
+/// Bugs and fatal errors are counted as errors as well.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DrainSummary {
+    /// Defines the amount of errors which were drained.
+    pub errors: usize,
+
+    /// Defines the amount of warnings which were drained.
+    pub warnings: usize,
+
+    /// Defines the total amount of diagnostics which were drained, regardless of their severity.
+    pub total: usize,
+}
+
+impl DrainSummary {
+    /// Counts a single drained diagnostic with the given severity.
+    pub fn record(&mut self, severity: Severity) {
+        if severity.is_at_least(Severity::Error) {
+            self.errors += 1;
+        } else if severity == Severity::Warning {
+            self.warnings += 1;
+        }
+
+        self.total += 1;
+    }
+}
+
+/// Result of draining the diagnostics from a handler.
+pub type DrainResult = Result<DrainSummary, DrainError>;
+
+/// Transformer which is applied to every diagnostic when it's reported to a handler,
+/// such as to attach a standard help message, remap codes or localize messages.
+///
+/// Since [`Handler::report()`] accepts diagnostics which aren't [`Send`], transformers operate on
+/// `Box<dyn Diagnostic>` instead of [`Error`](crate::Error).
+pub type DiagnosticTransformer = Box<dyn Fn(Box<dyn Diagnostic>) -> Box<dyn Diagnostic> + Send + Sync>;
+
+/// Defines how diagnostics with a specific code should be treated by a handler.
+///
+/// Lint levels are applied when a diagnostic is reported to the handler, which allows
+/// applications to implement `#[allow(...)]`-like attributes or `-W`/`-D` command-line flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    /// The diagnostic is silently discarded when reported.
+    Allow,
+
+    /// The diagnostic is reported with a severity of [`Severity::Warning`].
+    Warn,
+
+    /// The diagnostic is reported with a severity of at least [`Severity::Error`], so
+    /// bugs and fatal errors keep their severity.
+    Deny,
+}
+
+/// Table of lint levels, keyed by diagnostic code or by [`Tag`].
+///
+/// If a diagnostic has a level defined for it's code, the level of it's tags is ignored. Otherwise,
+/// the level of the first tag of the diagnostic which has a level defined is applied.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{LintLevel, SeverityOverrides};
+///
+/// let overrides = SeverityOverrides::new()
+///     .allow("W0001")
+///     .deny("W0002");
+///
+/// assert_eq!(overrides.get("W0001"), Some(LintLevel::Allow));
+/// assert_eq!(overrides.get("W0002"), Some(LintLevel::Deny));
+/// assert_eq!(overrides.get("W0003"), None);
+/// ```
+///
+/// To discard all diagnostics of unused code, regardless of their code:
+///
+/// ```
+/// use error_snippet::{Diagnostic, LintLevel, SeverityOverrides, SimpleDiagnostic, Tag};
+///
+/// let overrides = SeverityOverrides::new().with_tag_level(Tag::Unused, LintLevel::Allow);
+///
+/// let diagnostic = SimpleDiagnostic::new("unused variable `a`").with_tag(Tag::Unused);
+/// assert!(overrides.apply(Box::new(diagnostic)).is_none());
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SeverityOverrides {
+    levels: HashMap<String, LintLevel>,
+    tag_levels: HashMap<Tag, LintLevel>,
+}
+
+impl SeverityOverrides {
+    /// Creates a new empty table of overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the lint level of diagnostics with the given code.
+    pub fn with_level(mut self, code: impl Into<String>, level: LintLevel) -> Self {
+        self.set(code, level);
+        self
+    }
+
+    /// Discards all diagnostics with the given code.
+    pub fn allow(self, code: impl Into<String>) -> Self {
+        self.with_level(code, LintLevel::Allow)
+    }
+
+    /// Reports all diagnostics with the given code as warnings.
+    pub fn warn(self, code: impl Into<String>) -> Self {
+        self.with_level(code, LintLevel::Warn)
+    }
+
+    /// Reports all diagnostics with the given code as errors, or worse.
+    pub fn deny(self, code: impl Into<String>) -> Self {
+        self.with_level(code, LintLevel::Deny)
+    }
+
+    /// Sets the lint level of diagnostics with the given code, replacing
+    /// any existing level for the code.
+    pub fn set(&mut self, code: impl Into<String>, level: LintLevel) {
+        self.levels.insert(code.into(), level);
+    }
+
+    /// Removes the lint level of diagnostics with the given code, if any.
+    pub fn remove(&mut self, code: &str) -> Option<LintLevel> {
+        self.levels.remove(code)
+    }
+
+    /// Gets the lint level of diagnostics with the given code, if any is defined.
+    pub fn get(&self, code: &str) -> Option<LintLevel> {
+        self.levels.get(code).copied()
+    }
+
+    /// Sets the lint level of diagnostics with the given tag.
+    pub fn with_tag_level(mut self, tag: Tag, level: LintLevel) -> Self {
+        self.set_tag(tag, level);
+        self
+    }
+
+    /// Sets the lint level of diagnostics with the given tag, replacing
+    /// any existing level for the tag.
+    pub fn set_tag(&mut self, tag: Tag, level: LintLevel) {
+        self.tag_levels.insert(tag, level);
+    }
+
+    /// Removes the lint level of diagnostics with the given tag, if any.
+    pub fn remove_tag(&mut self, tag: &Tag) -> Option<LintLevel> {
+        self.tag_levels.remove(tag)
+    }
+
+    /// Gets the lint level of diagnostics with the given tag, if any is defined.
+    pub fn get_tag(&self, tag: &Tag) -> Option<LintLevel> {
+        self.tag_levels.get(tag).copied()
+    }
+
+    /// Determines whether the table has no overrides defined.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty() && self.tag_levels.is_empty()
+    }
+
+    /// Applies the overrides to the given diagnostic.
+    ///
+    /// Returns `None` if the diagnostic should be discarded. Diagnostics without
+    /// any matching override are returned unchanged.
+    pub fn apply(&self, diagnostic: Box<dyn Diagnostic>) -> Option<Box<dyn Diagnostic>> {
+        if self.is_empty() {
+            return Some(diagnostic);
+        }
+
+        let level = match diagnostic.code() {
+            Some(code) => self.get(&code.to_string()),
+            None => None,
+        };
+
+        let level = level.or_else(|| match self.tag_levels.is_empty() {
+            true => None,
+            false => diagnostic.tags().iter().find_map(|tag| self.get_tag(tag)),
+        });
+
+        match level {
+            Some(LintLevel::Allow) => None,
+            Some(LintLevel::Warn) => Some(Box::new(SeverityOverride::new(diagnostic, Severity::Warning))),
+            Some(LintLevel::Deny) => {
+                let severity = diagnostic.severity().max(Severity::Error);
+
+                Some(Box::new(SeverityOverride::new(diagnostic, severity)))
+            }
+            None => Some(diagnostic),
+        }
+    }
+}
+
+/// Wraps a diagnostic and overrides the severity of it, while forwarding everything else.
+///
+/// This is used by [`Handler::report_with_severity()`] and [`SeverityOverrides`], but can also
+/// be used on it's own, such as when a lint is configured to a different severity.
+///
+/// Only the severity of the diagnostic itself is overridden, so the severity
+/// of any causes or related diagnostics is left as-is.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Diagnostic, Severity, SeverityOverride, SimpleDiagnostic};
+///
+/// let diagnostic = SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning);
+/// let diagnostic = SeverityOverride::new(Box::new(diagnostic), Severity::Error);
+///
+/// assert_eq!(diagnostic.severity(), Severity::Error);
+/// assert_eq!(diagnostic.message(), "unused variable");
+/// ```
+#[derive(Debug)]
+pub struct SeverityOverride {
+    diagnostic: Box<dyn Diagnostic>,
+    severity: Severity,
+}
+
+impl SeverityOverride {
+    /// Creates a new [`SeverityOverride`], which overrides the severity of the given diagnostic.
+    pub fn new(diagnostic: Box<dyn Diagnostic>, severity: Severity) -> Self {
+        Self { diagnostic, severity }
+    }
+
+    /// Gets the wrapped diagnostic, whose severity is overridden.
+    pub fn inner(&self) -> &dyn Diagnostic {
+        self.diagnostic.as_ref()
+    }
+
+    /// Unwraps the diagnostic, discarding the overridden severity.
+    pub fn into_inner(self) -> Box<dyn Diagnostic> {
+        self.diagnostic
+    }
+}
+
+impl Diagnostic for SeverityOverride {
+    fn message(&self) -> String {
+        self.diagnostic.message()
+    }
+
+    fn message_key(&self) -> Option<MessageKey> {
+        self.diagnostic.message_key()
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn code(&self) -> Option<Box<dyn std::fmt::Display + '_>> {
+        self.diagnostic.code()
+    }
+
+    fn group_key(&self) -> Option<String> {
+        self.diagnostic.group_key()
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        self.diagnostic.tags()
+    }
+
+    fn source_code(&self) -> Option<Arc<dyn Source>> {
+        self.diagnostic.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = Label> + '_>> {
+        self.diagnostic.labels()
+    }
+
+    fn causes(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        self.diagnostic.causes()
+    }
+
+    fn related(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        self.diagnostic.related()
+    }
+
+    fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
+        self.diagnostic.help()
+    }
+
+    fn render_hints(&self) -> RenderHints {
+        self.diagnostic.render_hints()
+    }
+}
+
+/// Wraps a diagnostic without any source and attaches the default source of a handler to it.
+#[derive(Debug)]
+struct DefaultSourced {
+    diagnostic: Box<dyn Diagnostic>,
+    source: Arc<dyn Source>,
+}
+
+impl DefaultSourced {
+    /// Attaches the given default source to the diagnostic, if it has no source of it's own.
+    fn apply(diagnostic: Box<dyn Diagnostic>, source: Option<&Arc<dyn Source>>) -> Box<dyn Diagnostic> {
+        match source {
+            Some(source) if diagnostic.source_code().is_none() => Box::new(DefaultSourced {
+                diagnostic,
+                source: source.clone(),
+            }),
+            _ => diagnostic,
+        }
+    }
+}
+
+impl Diagnostic for DefaultSourced {
+    fn message(&self) -> String {
+        self.diagnostic.message()
+    }
+
+    fn message_key(&self) -> Option<MessageKey> {
+        self.diagnostic.message_key()
+    }
+
+    fn severity(&self) -> Severity {
+        self.diagnostic.severity()
+    }
+
+    fn code(&self) -> Option<Box<dyn std::fmt::Display + '_>> {
+        self.diagnostic.code()
+    }
+
+    fn group_key(&self) -> Option<String> {
+        self.diagnostic.group_key()
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        self.diagnostic.tags()
+    }
+
+    fn source_code(&self) -> Option<Arc<dyn Source>> {
+        Some(self.source.clone())
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = Label> + '_>> {
+        self.diagnostic.labels()
+    }
+
+    fn causes(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        self.diagnostic.causes()
+    }
+
+    fn related(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        self.diagnostic.related()
+    }
+
+    fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
+        self.diagnostic.help()
+    }
+
+    fn render_hints(&self) -> RenderHints {
+        self.diagnostic.render_hints()
+    }
+}
+
+/// Gets the current system time, or [`std::time::UNIX_EPOCH`] on targets without a system clock,
+/// where [`std::time::SystemTime::now()`] would panic.
+fn now() -> std::time::SystemTime {
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    return std::time::SystemTime::now();
+
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    return std::time::UNIX_EPOCH;
+}
+
+/// Clusters the given items by the key returned from `key`.
+///
+/// Groups are ordered by the first appearance of their key, and items within
+/// each group retain the order in which they were reported.
+pub(crate) fn cluster_by<T>(items: Vec<T>, key: impl Fn(&T) -> Option<String>) -> IndexMap<Option<String>, Vec<T>> {
+    let mut groups: IndexMap<Option<String>, Vec<T>> = IndexMap::new();
+
+    for item in items {
+        groups.entry(key(&item)).or_default().push(item);
+    }
+
+    groups
+}
+
+/// Removes all items whose diagnostic has the same [`DiagnosticKey`] as an earlier item,
+/// keeping the order of the remaining items.
+pub(crate) fn deduplicate_by_key<T>(items: Vec<T>, diagnostic: impl Fn(&T) -> &dyn Diagnostic) -> Vec<T> {
+    let mut seen = HashSet::new();
+
+    items
+        .into_iter()
+        .filter(|item| seen.insert(DiagnosticKey::of(diagnostic(item))))
+        .collect()
+}
+
+/// Applies all the given transformers to the diagnostic, in order.
+pub(crate) fn apply_transformers(
+    transformers: &[DiagnosticTransformer],
+    diagnostic: Box<dyn Diagnostic>,
+) -> Box<dyn Diagnostic> {
+    transformers
+        .iter()
+        .fold(diagnostic, |diagnostic, transformer| transformer(diagnostic))
+}
+
+/// Formats the header which is written before each group of diagnostics, when
+/// grouping is enabled on a handler.
+///
+/// Diagnostics without any group key are grouped under `ungrouped`.
+pub(crate) fn group_header(key: Option<&str>, count: usize) -> String {
+    let key = key.unwrap_or("ungrouped");
+    let noun = if count == 1 { "diagnostic" } else { "diagnostics" };
+
+    format!("{key} ({count} {noun})\n")
+}
+
+/// Metadata which is attached to diagnostics, when reported to a [`DiagnosticHandler`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticMetadata {
+    /// Defines the sequence number of the diagnostic, which is incremented for each
+    /// diagnostic reported to the handler, across drains.
+    pub sequence: usize,
+
+    /// Defines the point in time where the diagnostic was reported.
+    ///
+    /// On `wasm32-unknown-unknown`, which has no system clock, this is always [`std::time::UNIX_EPOCH`].
+    pub timestamp: std::time::SystemTime,
+
+    /// Defines the phase or stage which reported the diagnostic, if any.
+    pub stage: Option<String>,
+}
+
+/// A diagnostic which has been reported to a [`DiagnosticHandler`], along with it's metadata.
+///
+/// The struct dereferences to the inner diagnostic, so it can be used as a [`Diagnostic`] directly.
+#[derive(Debug)]
+pub struct EmittedDiagnostic {
+    /// Defines the reported diagnostic.
+    pub diagnostic: Box<dyn Diagnostic>,
+
+    /// Defines the metadata which was attached to the diagnostic when reported.
+    pub metadata: DiagnosticMetadata,
+}
+
+impl std::ops::Deref for EmittedDiagnostic {
+    type Target = dyn Diagnostic;
+
+    fn deref(&self) -> &Self::Target {
+        self.diagnostic.as_ref()
+    }
+}
+
+/// Defines how a handler clusters diagnostics when draining them.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainGrouping {
+    /// Diagnostics are drained in the order they were reported, without any headers.
+    #[default]
+    None,
+
+    /// Diagnostics are clustered by their [`Diagnostic::group_key()`].
+    Key,
+
+    /// Diagnostics are clustered by the stage which reported them.
+    Stage,
+}
+
+/// Defines what a handler writes between consecutive diagnostics when draining.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{BufferedDiagnosticHandler, DrainSeparator, GraphicalRenderer, Handler, SimpleDiagnostic};
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+///
+/// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+/// handler.set_separator(DrainSeparator::BlankLines(1));
+///
+/// handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+/// handler.report(Box::new(SimpleDiagnostic::new("unused variable")));
+/// handler.drain().unwrap();
+///
+/// assert_eq!(handler.buffer(), "× error: mismatched types\n\n× error: unused variable\n");
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainSeparator {
+    /// Diagnostics are written directly after each other, without any spacing.
+    #[default]
+    Compact,
+
+    /// Diagnostics are separated by the given amount of blank lines.
+    BlankLines(usize),
+
+    /// Diagnostics are separated by a horizontal rule with the given width.
+    Rule(usize),
+}
+
+impl DrainSeparator {
+    /// Formats the separator, which is written between two consecutive diagnostics.
+    pub fn format(&self) -> String {
+        match self {
+            DrainSeparator::Compact => String::new(),
+            DrainSeparator::BlankLines(count) => "\n".repeat(*count),
+            DrainSeparator::Rule(width) => format!("{}\n", "─".repeat(*width)),
+        }
+    }
+}
+
+/// Abstract handler type for reporting diagnostics.
+///
+/// Handlers are nothing more than a "store" for diagnostics, which
+/// decides when to drain the diagnostics to the user.
+///
+/// The trait is object safe, so handlers can be passed around as `Box<dyn Handler + Send>` or
+/// `&mut dyn Handler`. Both mutable references and boxes of handlers are handlers themselves,
+/// so they can be given to functions which take any `impl Handler`.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Handler, NullHandler, SimpleDiagnostic};
+///
+/// fn check(mut handler: impl Handler) {
+///     handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+/// }
+///
+/// let mut handler: Box<dyn Handler + Send> = Box::new(NullHandler::new());
+///
+/// check(&mut handler);
+/// check(handler);
+/// ```
+pub trait Handler {
+    /// Reports the diagnostic to the handler, without emitting it immediately.
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>);
+
+    /// Drains all the diagnostics to the console and empties the local store.
+    ///
+    /// Upon success, the amount of drained diagnostics is returned, which can be turned
+    /// into the exit code of the process using [`ExitStatus::from_drain()`].
+    fn drain(&mut self) -> DrainResult;
+
+    /// Reports the diagnostic to the handler with the given severity, instead of the severity
+    /// of the diagnostic itself, such as when a lint is configured as deny.
+    ///
+    /// The diagnostic is wrapped in a [`SeverityOverride`] before being reported.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Severity, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// let diagnostic = SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning);
+    /// handler.report_with_severity(Box::new(diagnostic), Severity::Error);
+    ///
+    /// assert!(handler.has_errors());
+    /// ```
+    fn report_with_severity(&mut self, diagnostic: Box<dyn Diagnostic>, severity: Severity) {
+        self.report(Box::new(SeverityOverride::new(diagnostic, severity)));
+    }
+
+    /// Reports the diagnostic to the handler and emits it immediately, along
+    /// with all other stored diagnostics within the handler.
+    fn report_and_drain(&mut self, diagnostic: Box<dyn Diagnostic>) -> DrainResult {
+        self.report(diagnostic);
+
+        self.drain()
+    }
+}
+
+impl<H: Handler + ?Sized> Handler for &mut H {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        (**self).report(diagnostic);
+    }
+
+    fn drain(&mut self) -> DrainResult {
+        (**self).drain()
+    }
+
+    fn report_with_severity(&mut self, diagnostic: Box<dyn Diagnostic>, severity: Severity) {
+        (**self).report_with_severity(diagnostic, severity);
+    }
+
+    fn report_and_drain(&mut self, diagnostic: Box<dyn Diagnostic>) -> DrainResult {
+        (**self).report_and_drain(diagnostic)
+    }
+}
+
+impl<H: Handler + ?Sized> Handler for Box<H> {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        (**self).report(diagnostic);
+    }
+
+    fn drain(&mut self) -> DrainResult {
+        (**self).drain()
+    }
+
+    fn report_with_severity(&mut self, diagnostic: Box<dyn Diagnostic>, severity: Severity) {
+        (**self).report_with_severity(diagnostic, severity);
+    }
+
+    fn report_and_drain(&mut self, diagnostic: Box<dyn Diagnostic>) -> DrainResult {
+        (**self).report_and_drain(diagnostic)
+    }
+}
+
+/// The default diagnostic handler.
+///
+/// The [`DiagnosticHandler`] allows to report to the user immediately or deferred until drained,
+/// and aborting upon draining an error (or worse) diagnostic.
+///
+/// Drained diagnostics are written to the standard error stream. Where it isn't available, such
+/// as on `wasm32-unknown-unknown` in a browser, use [`DiagnosticHandler::drain_iter()`] or
+/// [`BufferedDiagnosticHandler`] instead, which render diagnostics into strings.
+///
+/// # Examples
+///
+/// To use deferred reporting:
+///
+/// ```
+/// use error_snippet::{SimpleDiagnostic, GraphicalRenderer, Handler, DiagnosticHandler};
+///
+/// let diagnostic = SimpleDiagnostic::new("An error occurred");
+///
+/// let renderer = GraphicalRenderer::new();
+/// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+///
+/// handler.report(Box::new(diagnostic));
+/// ```
+///
+/// If not, you can drain the diagnostics immediately after reporting it:
+///
+/// ```
+/// use error_snippet::{SimpleDiagnostic, GraphicalRenderer, Handler, DiagnosticHandler};
+///
+/// let diagnostic = SimpleDiagnostic::new("An error occurred");
+///
+/// let renderer = GraphicalRenderer::new();
+/// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+///
+/// handler.report_and_drain(Box::new(diagnostic));
+/// ```
+///
+/// To abort upon draining an error diagnostic, use the [`DiagnosticHandler::exit_on_error()`] method:
+///
+/// ```
+/// use error_snippet::{DiagnosticHandler, GraphicalRenderer};
+///
+/// let renderer = GraphicalRenderer::new();
+/// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+/// handler.exit_on_error();
+///
+/// // ...
+/// ```
+pub struct DiagnosticHandler {
+    /// Defines whether to exit upon emitting an error.
+    exit_on_error: bool,
+
+    /// Stores all the diagnostics which have been reported.
+    emitted_diagnostics: Vec<EmittedDiagnostic>,
+
+    /// Defines the renderer to use when rendering the diagnostics.
+    renderer: Box<dyn Renderer + Send + Sync>,
+
+    /// Defines the lint levels to apply to diagnostics when reported.
+    overrides: SeverityOverrides,
+
+    /// Defines how to cluster diagnostics when draining.
+    grouping: DrainGrouping,
+
+    /// Defines what to write between consecutive diagnostics when draining.
+    separator: DrainSeparator,
+
+    /// Defines the sequence number of the next reported diagnostic.
+    next_sequence: usize,
+
+    /// Defines whether to align the gutters of all diagnostics within a single drain.
+    align_gutters: bool,
+
+    /// Defines whether to merge diagnostics which point to the same span when draining.
+    merge_identical_spans: bool,
+
+    /// Defines whether to discard diagnostics with the same key as an earlier diagnostic when draining.
+    deduplicate: bool,
+
+    /// Defines how to aggregate repeated diagnostics with the same code when draining, if at all.
+    aggregation: Option<CodeAggregation>,
+
+    /// Defines the source to attach to reported diagnostics without any source.
+    default_source: Option<Arc<dyn Source>>,
+
+    /// Defines the transformers to apply to diagnostics when reported, in order of registration.
+    transformers: Vec<DiagnosticTransformer>,
+}
+
+impl DiagnosticHandler {
+    /// Creates a new empty handler.
+    pub fn with_renderer(renderer: Box<dyn Renderer + Send + Sync>) -> Self {
+        DiagnosticHandler {
+            exit_on_error: false,
+            emitted_diagnostics: Vec::new(),
+            renderer,
+            overrides: SeverityOverrides::new(),
+            grouping: DrainGrouping::None,
+            separator: DrainSeparator::Compact,
+            next_sequence: 0,
+            align_gutters: false,
+            merge_identical_spans: false,
+            deduplicate: false,
+            aggregation: None,
+            default_source: None,
+            transformers: Vec::new(),
+        }
+    }
+
+    /// Gets a mutable reference to the renderer, which is used when draining.
+    pub fn renderer_mut(&mut self) -> &mut (dyn Renderer + Send + Sync) {
+        self.renderer.as_mut()
+    }
+
+    /// Replaces the renderer, which is used when draining, such as when the output
+    /// format is changed mid-session.
+    ///
+    /// To reconfigure a renderer without replacing it, or to share it between multiple
+    /// handlers, pass an `Arc<Mutex<R>>` to the handler instead, which is a renderer itself.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, ShortRenderer};
+    ///
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(GraphicalRenderer::new()));
+    /// handler.set_renderer(Box::new(ShortRenderer::new()));
+    /// ```
+    pub fn set_renderer(&mut self, renderer: Box<dyn Renderer + Send + Sync>) {
+        self.renderer = renderer
+    }
+
+    /// Sets the source which is attached to all reported diagnostics without any source,
+    /// so labels without any source can still be rendered.
+    ///
+    /// This is useful for tools which only operate on a single file, such as formatters
+    /// and REPLs, where callers would otherwise have to attach the source to every diagnostic.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Label, NamedSource, SimpleDiagnostic};
+    ///
+    /// let source = Arc::new(NamedSource::new("<repl>", "let a = b;"));
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer)).with_default_source(source);
+    ///
+    /// handler.report(Box::new(
+    ///     SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(None, 8..9, "not found")),
+    /// ));
+    ///
+    /// let diagnostic = handler.emitted().next().unwrap();
+    /// assert_eq!(diagnostic.source_code().unwrap().name(), Some("<repl>"));
+    /// ```
+    pub fn with_default_source(mut self, source: Arc<dyn Source>) -> Self {
+        self.default_source = Some(source);
+        self
+    }
+
+    /// Registers a transformer, which is applied to every diagnostic when it's reported to the handler.
+    ///
+    /// This allows for application-wide policies, such as attaching a standard help message or remapping
+    /// codes, without having to apply them at every call site. Transformers are applied in the order they
+    /// were registered, before any lint levels, so lint levels match the transformed code.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Severity, SeverityOverride, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// // Treat all warnings as errors, such as with `--strict`.
+    /// handler.add_transformer(|diagnostic| match diagnostic.severity() {
+    ///     Severity::Warning => Box::new(SeverityOverride::new(diagnostic, Severity::Error)),
+    ///     _ => diagnostic,
+    /// });
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning)));
+    ///
+    /// assert_eq!(handler.emitted().next().unwrap().severity(), Severity::Error);
+    /// ```
+    pub fn add_transformer<F>(&mut self, transformer: F)
+    where
+        F: Fn(Box<dyn Diagnostic>) -> Box<dyn Diagnostic> + Send + Sync + 'static,
+    {
+        self.transformers.push(Box::new(transformer));
+    }
+
+    /// Enables the handler to exit upon emitting an error.
+    pub fn exit_on_error(&mut self) {
+        self.exit_on_error = true
+    }
+
+    /// Enables the handler to cluster diagnostics by their [`Diagnostic::group_key()`]
+    /// when draining, where each group is preceded by a header with the key and the
+    /// amount of diagnostics within the group.
+    ///
+    /// Groups are ordered by the first appearance of their key.
+    pub fn group_by_key(&mut self) {
+        self.grouping = DrainGrouping::Key
+    }
+
+    /// Enables the handler to cluster diagnostics by the stage which reported them
+    /// when draining, where each group is preceded by a header with the stage and the
+    /// amount of diagnostics within the group.
+    ///
+    /// Groups are ordered by the first appearance of their stage, so stages are drained
+    /// in the order they were run.
+    pub fn group_by_stage(&mut self) {
+        self.grouping = DrainGrouping::Stage
+    }
+
+    /// Sets how the handler should cluster diagnostics when draining.
+    pub fn set_grouping(&mut self, grouping: DrainGrouping) {
+        self.grouping = grouping
+    }
+
+    /// Sets what the handler should write between consecutive diagnostics when draining.
+    ///
+    /// The separator is written before the group header of each group, except the first.
+    pub fn set_separator(&mut self, separator: DrainSeparator) {
+        self.separator = separator
+    }
+
+    /// Enables the handler to compute a common gutter width across all diagnostics
+    /// within a single drain, so the snippets of all diagnostics are visually aligned,
+    /// even if they refer to files with very different line counts.
+    pub fn align_gutters(&mut self) {
+        self.align_gutters = true
+    }
+
+    /// Enables the handler to merge diagnostics when draining, if they point to the exact
+    /// same primary span with the same severity. The primary span of a diagnostic is the span
+    /// of it's first label.
+    ///
+    /// The message of the merged diagnostic is taken from the first reported diagnostic,
+    /// while the labels, help messages, causes and related diagnostics of all the merged
+    /// diagnostics are combined.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Label, NamedSource, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    /// handler.merge_identical_spans();
+    ///
+    /// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+    ///
+    /// handler.report(Box::new(
+    ///     SimpleDiagnostic::new("mismatched types").with_label(Label::error(Some(source.clone()), 8..9, "expected `Int`")),
+    /// ));
+    /// handler.report(Box::new(
+    ///     SimpleDiagnostic::new("cannot infer type").with_label(Label::error(Some(source.clone()), 8..9, "")),
+    /// ));
+    ///
+    /// // Both diagnostics are rendered as a single diagnostic with two labels.
+    /// handler.drain().unwrap();
+    /// ```
+    pub fn merge_identical_spans(&mut self) {
+        self.merge_identical_spans = true
+    }
+
+    /// Enables the handler to discard duplicate diagnostics when draining, which is useful
+    /// when multiple passes may report the same problem.
+    ///
+    /// Diagnostics are considered duplicates if they have the same [`DiagnosticKey`], where only
+    /// the first reported diagnostic is kept.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    /// handler.deduplicate();
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("mismatched types").with_code("E0308")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("mismatched types").with_code("E0308")));
+    ///
+    /// assert_eq!(handler.drain_iter().count(), 1);
+    /// ```
+    pub fn deduplicate(&mut self) {
+        self.deduplicate = true
+    }
+
+    /// Enables the handler to aggregate repeated diagnostics with the same code when draining,
+    /// where all occurrences beyond the threshold are replaced by a single summary.
+    ///
+    /// See [`CodeAggregation`] for more information.
+    pub fn aggregate_by_code(&mut self, aggregation: CodeAggregation) {
+        self.aggregation = Some(aggregation)
+    }
+
+    /// Reports the diagnostic to the handler, tagged with the phase or stage which reported it.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// handler.report_with_stage(Box::new(SimpleDiagnostic::new("unexpected token")), "parse");
+    /// handler.report_with_stage(Box::new(SimpleDiagnostic::new("mismatched types")), "typeck");
+    ///
+    /// let stages = handler
+    ///     .emitted_with_metadata()
+    ///     .map(|d| d.metadata.stage.clone().unwrap())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(stages, vec!["parse", "typeck"]);
+    /// ```
+    pub fn report_with_stage(&mut self, diagnostic: Box<dyn Diagnostic>, stage: impl Into<String>) {
+        self.report_with(diagnostic, Some(stage.into()));
+    }
+
+    /// Reports the diagnostic to the handler, attaching metadata with the given stage.
+    fn report_with(&mut self, diagnostic: Box<dyn Diagnostic>, stage: Option<String>) {
+        let diagnostic = apply_transformers(&self.transformers, diagnostic);
+
+        let Some(diagnostic) = self.overrides.apply(diagnostic) else {
+            return;
+        };
+
+        let diagnostic = DefaultSourced::apply(diagnostic, self.default_source.as_ref());
+
+        let metadata = DiagnosticMetadata {
+            sequence: self.next_sequence,
+            timestamp: now(),
+            stage,
+        };
+
+        self.next_sequence += 1;
+        self.emitted_diagnostics
+            .push(EmittedDiagnostic { diagnostic, metadata });
+    }
+
+    /// Sets the lint levels to apply to diagnostics, when they're reported to the handler.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, SeverityOverrides, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    /// handler.set_severity_overrides(SeverityOverrides::new().allow("W0001"));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_code("W0001")));
+    ///
+    /// assert_eq!(handler.count(), 0);
+    /// ```
+    pub fn set_severity_overrides(&mut self, overrides: SeverityOverrides) {
+        self.overrides = overrides;
+    }
+
+    /// Gets a mutable reference to the lint levels of the handler.
+    pub fn severity_overrides_mut(&mut self) -> &mut SeverityOverrides {
+        &mut self.overrides
+    }
+
+    /// Gets an [`Iterator`] over all the emitted diagnostics to the handler,
+    /// which have yet to be drained.
+    pub fn emitted(&self) -> impl Iterator<Item = &Box<dyn Diagnostic>> {
+        self.emitted_diagnostics.iter().map(|emitted| &emitted.diagnostic)
+    }
+
+    /// Gets an [`Iterator`] over all the emitted diagnostics to the handler,
+    /// which have yet to be drained, along with their metadata.
+    pub fn emitted_with_metadata(&self) -> impl Iterator<Item = &EmittedDiagnostic> {
+        self.emitted_diagnostics.iter()
+    }
+
+    /// Gets the amount of diagnostics within the handler, which have
+    /// yet to be drained.
+    pub fn count(&self) -> usize {
+        self.emitted_diagnostics.len()
+    }
+
+    /// Gets the amount of diagnostics within the handler with the given severity,
+    /// which have yet to be drained.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Severity, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning)));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused import").with_severity(Severity::Warning)));
+    ///
+    /// assert_eq!(handler.count_by(Severity::Error), 1);
+    /// assert_eq!(handler.count_by(Severity::Warning), 2);
+    /// assert_eq!(handler.count_by(Severity::Note), 0);
+    /// ```
+    pub fn count_by(&self, severity: Severity) -> usize {
+        self.emitted_diagnostics
+            .iter()
+            .filter(|d| d.severity() == severity)
+            .count()
+    }
+
+    /// Gets the amount of diagnostics within the handler with the given code,
+    /// which have yet to be drained.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_code("W0042")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_code("W0042")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused import").with_code("W0043")));
+    ///
+    /// assert_eq!(handler.count_by_code("W0042"), 2);
+    /// assert_eq!(handler.count_by_code("W0001"), 0);
+    /// ```
+    pub fn count_by_code(&self, code: &str) -> usize {
+        self.emitted_diagnostics
+            .iter()
+            .filter(|d| d.code().is_some_and(|c| c.to_string() == code))
+            .count()
+    }
+
+    /// Gets the amount of errors within the handler, which have yet to be drained.
+    ///
+    /// Bugs and fatal errors are counted as errors as well.
+    pub fn error_count(&self) -> usize {
+        self.emitted_diagnostics
+            .iter()
+            .filter(|d| d.severity().is_at_least(Severity::Error))
+            .count()
+    }
+
+    /// Gets the amount of warnings within the handler, which have yet to be drained.
+    pub fn warning_count(&self) -> usize {
+        self.count_by(Severity::Warning)
+    }
+
+    /// Determines whether the handler contains any errors, which have yet to be drained.
+    ///
+    /// Bugs and fatal errors are counted as errors as well.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Severity, SimpleDiagnostic};
+    ///
+    /// let renderer = GraphicalRenderer::new();
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning)));
+    /// assert!(!handler.has_errors());
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+    /// assert!(handler.has_errors());
+    /// ```
+    pub fn has_errors(&self) -> bool {
+        self.emitted_diagnostics
+            .iter()
+            .any(|d| d.severity().is_at_least(Severity::Error))
+    }
+
+    /// Drains all the diagnostics from the handler, returning an [`Iterator`] which renders
+    /// and yields a single diagnostic at a time, instead of writing them to the console.
+    ///
+    /// Diagnostics are only rendered when requested from the iterator, so consumers can
+    /// stream them to a UI at their own pace. The local store is emptied immediately, so any
+    /// diagnostics which are left in the iterator when it's dropped are discarded.
+    ///
+    /// When grouping is enabled, the header of each group is prepended to the rendered
+    /// output of the first diagnostic within the group. Since no error is raised upon
+    /// yielding an error, [`DiagnosticHandler::exit_on_error()`] has no effect on the iterator.
+    /// After yielding a diagnostic with [`Severity::Fatal`], the iterator yields [`DrainError::Fatal`]
+    /// and then ends, discarding any remaining diagnostics.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{DiagnosticHandler, GraphicalRenderer, Handler, Severity, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut handler = DiagnosticHandler::with_renderer(Box::new(renderer));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning)));
+    ///
+    /// let mut drained = handler.drain_iter();
+    ///
+    /// let first = drained.next().unwrap().unwrap();
+    /// assert_eq!(first.rendered, "× error: mismatched types\n");
+    /// assert_eq!(first.severity, Severity::Error);
+    ///
+    /// let second = drained.next().unwrap().unwrap();
+    /// assert_eq!(second.severity, Severity::Warning);
+    ///
+    /// assert!(drained.next().is_none());
+    /// ```
+    pub fn drain_iter(&mut self) -> DrainIter<'_> {
+        let (options, mut diagnostics) = self.take_prepared();
+
+        // Diagnostics after the first fatal diagnostic are never yielded, so they're discarded up-front.
+        if let Some(fatal) = diagnostics.iter().position(|(_, d)| d.severity() == Severity::Fatal) {
+            diagnostics.truncate(fatal + 1);
+        }
+
+        DrainIter {
+            renderer: self.renderer.as_ref(),
+            options,
+            diagnostics: diagnostics.into_iter(),
+            aborted: false,
+        }
+    }
+
+    /// Drains all the diagnostics from the handler into the given writer, where each diagnostic
+    /// is written along with it's separator and group header, if any.
+    fn drain_into(&mut self, f: &mut dyn std::fmt::Write) -> DrainResult {
+        self.drain_each(|renderer, prefix, diagnostic, options| {
+            let rendered = renderer.render_with(diagnostic, options)?;
+
+            // Separators and group headers are included in the rendered output,
+            // so each diagnostic is written in a single call.
+            f.write_str(&(prefix.to_owned() + &rendered))
+        })
+    }
+
+    /// Drains all the diagnostics from the handler, where each diagnostic is emitted using the
+    /// given closure, along with it's separator and group header, if any.
+    ///
+    /// If a diagnostic fails to be emitted, it's kept in the handler along with all the remaining
+    /// diagnostics, so they can be drained again. After emitting a diagnostic with [`Severity::Fatal`],
+    /// the drain is aborted and all the remaining diagnostics are discarded.
+    fn drain_each(
+        &mut self,
+        mut emit: impl FnMut(&(dyn Renderer + Send + Sync), &str, &dyn Diagnostic, &BatchOptions) -> std::fmt::Result,
+    ) -> DrainResult {
+        let (options, diagnostics) = self.take_prepared();
+
+        let mut summary = DrainSummary::default();
+        let mut diagnostics = diagnostics.into_iter();
+
+        while let Some((prefix, diagnostic)) = diagnostics.next() {
+            if let Err(err) = emit(
+                self.renderer.as_ref(),
+                &prefix,
+                diagnostic.diagnostic.as_ref(),
+                &options,
+            ) {
+                // Keep the failed diagnostic and all the remaining ones, so they can be drained again.
+                self.emitted_diagnostics = std::iter::once(diagnostic).chain(diagnostics.map(|(_, d)| d)).collect();
+
+                return Err(err.into());
+            }
+
+            if diagnostic.severity() == Severity::Fatal {
+                return Err(DrainError::Fatal);
+            }
+
+            summary.record(diagnostic.severity());
+        }
+
+        // If we've encountered any errors, and we're enabled to propogate errors upwards,
+        // return a specific error to compound all encountered errors.
+        if summary.errors > 0 && self.exit_on_error {
+            return Err(DrainError::CompoundError(summary.errors));
+        }
+
+        Ok(summary)
+    }
+
+    /// Takes all the diagnostics out of the handler and prepares them for rendering, where
+    /// each diagnostic is paired with the separator and group header to write before it.
+    fn take_prepared(&mut self) -> (BatchOptions, Vec<(String, EmittedDiagnostic)>) {
+        let (options, groups) = self.take_drained();
+
+        let mut diagnostics = Vec::new();
+
+        for (key, group) in groups {
+            let mut header = (self.grouping != DrainGrouping::None).then(|| group_header(key.as_deref(), group.len()));
+
+            for diagnostic in group {
+                let mut prefix = if diagnostics.is_empty() {
+                    String::new()
+                } else {
+                    self.separator.format()
+                };
+
+                prefix.push_str(&header.take().unwrap_or_default());
+
+                diagnostics.push((prefix, diagnostic));
+            }
+        }
+
+        (options, diagnostics)
+    }
+
+    /// Takes all the diagnostics out of the handler and prepares them for draining,
+    /// by merging and clustering them, as well as computing the batch options.
+    fn take_drained(&mut self) -> (BatchOptions, IndexMap<Option<String>, Vec<EmittedDiagnostic>>) {
+        let mut diagnostics = std::mem::take(&mut self.emitted_diagnostics);
+
+        if self.deduplicate {
+            diagnostics = deduplicate_by_key(diagnostics, |d| d.diagnostic.as_ref());
+        }
+
+        if let Some(aggregation) = &self.aggregation {
+            diagnostics = aggregate_by_code(
+                diagnostics,
+                aggregation,
+                |d| d.diagnostic.as_ref(),
+                |d, diagnostic| EmittedDiagnostic {
+                    diagnostic,
+                    metadata: d.metadata,
+                },
+            );
+        }
+
+        if self.merge_identical_spans {
+            diagnostics = cluster_identical_spans(diagnostics, |d| d.diagnostic.as_ref())
+                .into_iter()
+                .map(|cluster| {
+                    let metadata = cluster[0].metadata.clone();
+                    let diagnostic = MergedDiagnostic::merge(cluster.into_iter().map(|d| d.diagnostic).collect());
+
+                    EmittedDiagnostic { diagnostic, metadata }
+                })
+                .collect();
+        }
+
+        let options = if self.align_gutters {
+            BatchOptions::aligned(diagnostics.iter().map(|d| d.diagnostic.as_ref()))
+        } else {
+            BatchOptions::default()
+        };
+
+        let groups = match self.grouping {
+            DrainGrouping::None => IndexMap::from([(None, diagnostics)]),
+            DrainGrouping::Key => cluster_by(diagnostics, |d| d.group_key()),
+            DrainGrouping::Stage => cluster_by(diagnostics, |d| d.metadata.stage.clone()),
+        };
+
+        (options, groups)
+    }
+}
+
+/// A single diagnostic, which was rendered when yielded from a [`DrainIter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedDiagnostic {
+    /// Defines the rendered output of the diagnostic.
+    pub rendered: String,
+
+    /// Defines the severity of the diagnostic.
+    pub severity: Severity,
+
+    /// Defines the metadata which was attached to the diagnostic when reported.
+    pub metadata: DiagnosticMetadata,
+}
+
+/// Iterator which renders and yields drained diagnostics one at a time.
+///
+/// This `struct` is created by [`DiagnosticHandler::drain_iter()`].
+pub struct DrainIter<'a> {
+    renderer: &'a (dyn Renderer + Send + Sync),
+    options: BatchOptions,
+    diagnostics: std::vec::IntoIter<(String, EmittedDiagnostic)>,
+    aborted: bool,
+}
+
+impl Iterator for DrainIter<'_> {
+    type Item = Result<RenderedDiagnostic, DrainError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some((prefix, diagnostic)) = self.diagnostics.next() else {
+            // Signal that the drain was aborted, after the fatal diagnostic itself was yielded.
+            if std::mem::take(&mut self.aborted) {
+                return Some(Err(DrainError::Fatal));
+            }
+
+            return None;
+        };
+
+        if diagnostic.severity() == Severity::Fatal {
+            self.diagnostics = Vec::new().into_iter();
+            self.aborted = true;
+        }
+
+        let rendered = match self.renderer.render_with(diagnostic.diagnostic.as_ref(), &self.options) {
+            Ok(rendered) => rendered,
+            Err(err) => return Some(Err(err.into())),
+        };
+
+        Some(Ok(RenderedDiagnostic {
+            rendered: prefix + &rendered,
+            severity: diagnostic.severity(),
+            metadata: diagnostic.metadata,
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Fatal diagnostics are followed by an additional error, which signals the abort.
+        let remaining = self.diagnostics.as_slice();
+        let aborts = self.aborted || remaining.iter().any(|(_, d)| d.severity() == Severity::Fatal);
+
+        let len = remaining.len() + usize::from(aborts);
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for DrainIter<'_> {}
+
+impl Handler for DiagnosticHandler {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        self.report_with(diagnostic, None);
+    }
+
+    #[cfg(feature = "terminal")]
+    fn drain(&mut self) -> DrainResult {
+        // Diagnostics are emitted through the renderer, so renderers can write to the terminal directly.
+        self.drain_each(|renderer, prefix, diagnostic, options| {
+            if !prefix.is_empty() {
+                eprint!("{prefix}");
+            }
+
+            renderer.render_stderr_with(diagnostic, options)
+        })
+    }
+
+    #[cfg(not(feature = "terminal"))]
+    fn drain(&mut self) -> DrainResult {
+        self.drain_into(&mut IoWriter::new(std::io::stderr()))
+    }
+}
+
+/// Marker which is appended to the buffer of a [`BufferedDiagnosticHandler`],
+/// when the buffer has been truncated to it's maximum size.
+pub const TRUNCATION_MARKER: &str = "\n... (output truncated)\n";
+
+/// A buffered version of [`DiagnosticHandler`].
+///
+/// The [`BufferedDiagnosticHandler`] will save rendered diagnostics to an internal buffer,
+/// allowing them to be read back as [`String`]-values. This is mostly used for UI testing.
+///
+/// Diagnostics are drained through the same pipeline as [`DiagnosticHandler`], so all of
+/// it's options, such as grouping and merging, apply to the buffered handler as well.
+pub struct BufferedDiagnosticHandler {
+    /// Defines the handler which stores the reported diagnostics and prepares them for draining.
+    inner: DiagnosticHandler,
+
+    /// Stores all the rendered diagnostics which have been drained.
+    buffer: String,
+
+    /// Defines the maximum size of the buffer in bytes, if any.
+    max_buffer_size: Option<usize>,
+
+    /// Defines whether the buffer has been truncated, since it was last cleared.
+    truncated: bool,
+}
+
+impl BufferedDiagnosticHandler {
+    /// Creates a new empty handler.
+    pub fn with_renderer(capacity: usize, renderer: Box<dyn Renderer + Send + Sync>) -> Self {
+        Self {
+            inner: DiagnosticHandler::with_renderer(renderer),
+            buffer: String::with_capacity(capacity),
+            max_buffer_size: None,
+            truncated: false,
+        }
+    }
+
+    /// Gets a mutable reference to the renderer, which is used when draining.
+    pub fn renderer_mut(&mut self) -> &mut (dyn Renderer + Send + Sync) {
+        self.inner.renderer_mut()
+    }
+
+    /// Replaces the renderer, which is used when draining.
+    ///
+    /// See [`DiagnosticHandler::set_renderer()`] for more information.
+    pub fn set_renderer(&mut self, renderer: Box<dyn Renderer + Send + Sync>) {
+        self.inner.set_renderer(renderer)
+    }
+
+    /// Sets the source which is attached to all reported diagnostics without any source.
+    ///
+    /// See [`DiagnosticHandler::with_default_source()`] for more information.
+    pub fn with_default_source(mut self, source: Arc<dyn Source>) -> Self {
+        self.inner = self.inner.with_default_source(source);
+        self
+    }
+
+    /// Registers a transformer, which is applied to every diagnostic when it's reported to the handler.
+    ///
+    /// See [`DiagnosticHandler::add_transformer()`] for more information.
+    pub fn add_transformer<F>(&mut self, transformer: F)
+    where
+        F: Fn(Box<dyn Diagnostic>) -> Box<dyn Diagnostic> + Send + Sync + 'static,
+    {
+        self.inner.add_transformer(transformer)
+    }
+
+    /// Enables the handler to merge diagnostics when draining, if they point to the exact
+    /// same primary span with the same severity.
+    ///
+    /// See [`DiagnosticHandler::merge_identical_spans()`] for more information.
+    pub fn merge_identical_spans(&mut self) {
+        self.inner.merge_identical_spans()
+    }
+
+    /// Enables the handler to discard duplicate diagnostics when draining.
+    ///
+    /// See [`DiagnosticHandler::deduplicate()`] for more information.
+    pub fn deduplicate(&mut self) {
+        self.inner.deduplicate()
+    }
+
+    /// Enables the handler to aggregate repeated diagnostics with the same code when draining.
+    ///
+    /// See [`CodeAggregation`] for more information.
+    pub fn aggregate_by_code(&mut self, aggregation: CodeAggregation) {
+        self.inner.aggregate_by_code(aggregation)
+    }
+
+    /// Enables the handler to return an error upon draining an error.
+    ///
+    /// The rendered diagnostics are still written to the buffer before the error is returned.
+    pub fn exit_on_error(&mut self) {
+        self.inner.exit_on_error()
+    }
+
+    /// Sets the maximum size of the buffer in bytes.
+    ///
+    /// When rendered diagnostics would exceed the maximum size, the buffer is truncated and
+    /// [`TRUNCATION_MARKER`] is appended to it. Any diagnostics drained after the buffer has been
+    /// truncated are discarded, until the buffer is cleared or taken.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{BufferedDiagnosticHandler, GraphicalRenderer, Handler, SimpleDiagnostic, TRUNCATION_MARKER};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+    /// handler.set_max_buffer_size(8);
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable")));
+    /// handler.drain().unwrap();
+    ///
+    /// assert_eq!(handler.buffer(), format!("× error{TRUNCATION_MARKER}"));
+    /// ```
+    pub fn set_max_buffer_size(&mut self, size: usize) {
+        self.max_buffer_size = Some(size)
+    }
+
+    /// Enables the handler to cluster diagnostics by their [`Diagnostic::group_key()`]
+    /// when draining, where each group is preceded by a header with the key and the
+    /// amount of diagnostics within the group.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{BufferedDiagnosticHandler, GraphicalRenderer, Handler, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+    /// handler.group_by_key();
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable").with_code("W0001")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused import").with_code("W0002")));
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused function").with_code("W0001")));
+    /// handler.drain().unwrap();
+    ///
+    /// assert!(handler.buffer().starts_with("W0001 (2 diagnostics)\n"));
+    /// assert!(handler.buffer().contains("W0002 (1 diagnostic)\n"));
+    /// ```
+    pub fn group_by_key(&mut self) {
+        self.inner.group_by_key()
+    }
+
+    /// Enables the handler to cluster diagnostics by the stage which reported them when draining.
+    ///
+    /// See [`DiagnosticHandler::group_by_stage()`] for more information.
+    pub fn group_by_stage(&mut self) {
+        self.inner.group_by_stage()
+    }
+
+    /// Sets how the handler should cluster diagnostics when draining.
+    pub fn set_grouping(&mut self, grouping: DrainGrouping) {
+        self.inner.set_grouping(grouping)
+    }
+
+    /// Sets what the handler should write between consecutive diagnostics when draining.
+    ///
+    /// See [`DiagnosticHandler::set_separator()`] for more information.
+    pub fn set_separator(&mut self, separator: DrainSeparator) {
+        self.inner.set_separator(separator)
+    }
+
+    /// Enables the handler to compute a common gutter width across all diagnostics
+    /// within a single drain, so the snippets of all diagnostics are visually aligned,
+    /// even if they refer to files with very different line counts.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{BufferedDiagnosticHandler, GraphicalRenderer, Handler, Label, NamedSource, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+    /// handler.align_gutters();
+    ///
+    /// let short = Arc::new(NamedSource::new("short.lm", "let a = 1;"));
+    /// let long = Arc::new(NamedSource::new("long.lm", "let b = 2;\n".repeat(120)));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("foo").with_label(Label::new(Some(short), 4..5, "here"))));
+    /// handler.report(Box::new(SimpleDiagnostic::new("bar").with_label(Label::new(Some(long), 4..5, "here"))));
+    /// handler.drain().unwrap();
+    ///
+    /// assert!(handler.buffer().contains("  1  │ let a = 1;"));
+    /// assert!(handler.buffer().contains("  1  │ let b = 2;"));
+    /// ```
+    pub fn align_gutters(&mut self) {
+        self.inner.align_gutters()
+    }
+
+    /// Reports the diagnostic to the handler, tagged with the phase or stage which reported it.
+    ///
+    /// See [`DiagnosticHandler::report_with_stage()`] for more information.
+    pub fn report_with_stage(&mut self, diagnostic: Box<dyn Diagnostic>, stage: impl Into<String>) {
+        self.inner.report_with_stage(diagnostic, stage)
+    }
+
+    /// Sets the lint levels to apply to diagnostics, when they're reported to the handler.
+    ///
+    /// See [`DiagnosticHandler::set_severity_overrides()`] for more information.
+    pub fn set_severity_overrides(&mut self, overrides: SeverityOverrides) {
+        self.inner.set_severity_overrides(overrides)
+    }
+
+    /// Gets a mutable reference to the lint levels of the handler.
+    pub fn severity_overrides_mut(&mut self) -> &mut SeverityOverrides {
+        self.inner.severity_overrides_mut()
+    }
+
+    /// Gets the [`String`] buffer which contains the rendered diagnostics.
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Takes the [`String`] buffer which contains the rendered diagnostics,
+    /// leaving an empty buffer in it's place.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{BufferedDiagnosticHandler, GraphicalRenderer, Handler, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+    ///
+    /// handler.report(Box::new(SimpleDiagnostic::new("unused variable")));
+    /// handler.drain().unwrap();
+    ///
+    /// assert_eq!(handler.take_buffer(), "× error: unused variable\n");
+    /// assert_eq!(handler.buffer(), "");
+    /// ```
+    pub fn take_buffer(&mut self) -> String {
+        self.truncated = false;
+
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Clears the buffer which contains the rendered diagnostics, while keeping
+    /// the allocated capacity of it.
+    pub fn clear(&mut self) {
+        self.truncated = false;
+        self.buffer.clear();
+    }
+
+    /// Gets an [`Iterator`] over all the emitted diagnostics to the handler,
+    /// which have yet to be drained.
+    pub fn emitted(&self) -> impl Iterator<Item = &Box<dyn Diagnostic>> {
+        self.inner.emitted()
+    }
+
+    /// Gets an [`Iterator`] over all the emitted diagnostics to the handler,
+    /// which have yet to be drained, along with their metadata.
+    pub fn emitted_with_metadata(&self) -> impl Iterator<Item = &EmittedDiagnostic> {
+        self.inner.emitted_with_metadata()
+    }
+
+    /// Gets the amount of diagnostics within the handler, which have
+    /// yet to be drained.
+    pub fn count(&self) -> usize {
+        self.inner.count()
+    }
+}
+
+impl Handler for BufferedDiagnosticHandler {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        self.inner.report(diagnostic);
+    }
+
+    fn drain(&mut self) -> DrainResult {
+        let mut writer = TruncatingWriter {
+            buffer: &mut self.buffer,
+            max_size: self.max_buffer_size,
+            truncated: &mut self.truncated,
+        };
+
+        self.inner.drain_into(&mut writer)
+    }
+}
+
+/// Writer which appends to the buffer of a [`BufferedDiagnosticHandler`], respecting the maximum buffer size.
+struct TruncatingWriter<'a> {
+    buffer: &'a mut String,
+    max_size: Option<usize>,
+    truncated: &'a mut bool,
+}
+
+impl std::fmt::Write for TruncatingWriter<'_> {
+    fn write_str(&mut self, rendered: &str) -> std::fmt::Result {
+        if *self.truncated {
+            return Ok(());
+        }
+
+        let Some(max_size) = self.max_size else {
+            self.buffer.push_str(rendered);
+            return Ok(());
+        };
+
+        if self.buffer.len() + rendered.len() <= max_size {
+            self.buffer.push_str(rendered);
+            return Ok(());
+        }
+
+        let mut end = max_size.saturating_sub(self.buffer.len()).min(rendered.len());
+        while !rendered.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        self.buffer.push_str(&rendered[..end]);
+        self.buffer.push_str(TRUNCATION_MARKER);
+        *self.truncated = true;
+
+        Ok(())
+    }
+}
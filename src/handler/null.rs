@@ -0,0 +1,46 @@
+use crate::{Diagnostic, DrainResult, DrainSummary, Handler};
+
+/// A [`Handler`] which discards all reported diagnostics, without rendering them.
+///
+/// This is useful for benchmarking the code which reports diagnostics, without measuring
+/// the cost of rendering them, or for tests which aren't interested in the diagnostics at all.
+/// Draining the handler never fails, regardless of the severity of the discarded diagnostics.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Handler, NullHandler, Severity, SimpleDiagnostic};
+///
+/// let mut handler = NullHandler::new();
+/// handler.report(Box::new(SimpleDiagnostic::new("mismatched types").with_severity(Severity::Fatal)));
+///
+/// assert!(handler.drain().is_ok());
+/// assert_eq!(handler.count(), 1);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NullHandler {
+    /// Defines the amount of diagnostics which have been discarded.
+    discarded: usize,
+}
+
+impl NullHandler {
+    /// Creates a new [`NullHandler`].
+    pub fn new() -> Self {
+        NullHandler::default()
+    }
+
+    /// Gets the amount of diagnostics which have been reported and discarded.
+    pub fn count(&self) -> usize {
+        self.discarded
+    }
+}
+
+impl Handler for NullHandler {
+    fn report(&mut self, _diagnostic: Box<dyn Diagnostic>) {
+        self.discarded += 1;
+    }
+
+    /// Always succeeds, without counting any of the discarded diagnostics as drained.
+    fn drain(&mut self) -> DrainResult {
+        Ok(DrainSummary::default())
+    }
+}
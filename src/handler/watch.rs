@@ -0,0 +1,246 @@
+use std::time::{Duration, Instant};
+
+use crate::{Diagnostic, DrainError, DrainResult, DrainSeparator, DrainSummary, Handler, Renderer, Severity};
+
+/// Escape sequence which clears the terminal and moves the cursor to the top-left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Defines how a [`WatchHandler`] replaces the previous output, before writing the latest diagnostics.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchRefresh {
+    /// The terminal is cleared before writing the latest diagnostics.
+    #[default]
+    ClearScreen,
+
+    /// The given separator is written between the previous and the latest diagnostics,
+    /// so the output of all previous builds is kept in the scrollback.
+    Separator(DrainSeparator),
+}
+
+/// A [`Handler`] for `--watch` style loops, which re-renders the latest set of diagnostics on
+/// every drain, replacing the output of the previous drain.
+///
+/// All diagnostics reported between two drains make up a single set. Upon draining, the set
+/// is rendered and written in full, after clearing the terminal, unless the output is identical to
+/// the previous output, in which case nothing is written at all.
+///
+/// Drains in rapid succession, such as when multiple files are saved at once, can be coalesced
+/// using [`WatchHandler::set_coalesce_window()`]. Drains within the window after the last write only
+/// replace the pending set, which is written by the first drain after the window has passed, or by
+/// calling [`WatchHandler::flush()`] explicitly.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{DrainSeparator, GraphicalRenderer, Handler, SimpleDiagnostic, WatchHandler, WatchRefresh};
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+///
+/// let mut handler = WatchHandler::with_output(Box::new(renderer), Vec::new());
+/// handler.set_refresh(WatchRefresh::Separator(DrainSeparator::Rule(3)));
+///
+/// for _ in 0..2 {
+///     handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+///     handler.drain().unwrap();
+/// }
+///
+/// handler.drain().unwrap();
+///
+/// let output = String::from_utf8(handler.into_output()).unwrap();
+///
+/// assert_eq!(output, "× error: mismatched types\n───\n");
+/// ```
+pub struct WatchHandler<W: std::io::Write = std::io::Stderr> {
+    /// Defines the renderer to use when rendering the diagnostics.
+    renderer: Box<dyn Renderer + Send + Sync>,
+
+    /// Defines the sink which the rendered diagnostics are written to.
+    output: W,
+
+    /// Stores all the diagnostics which have been reported since the last drain.
+    reported: Vec<Box<dyn Diagnostic>>,
+
+    /// Stores the latest set of diagnostics, which has been drained but not yet written.
+    pending: Option<Vec<Box<dyn Diagnostic>>>,
+
+    /// Stores the output of the last write, if any.
+    previous: Option<String>,
+
+    /// Defines when the output was last written, if ever.
+    last_write: Option<Instant>,
+
+    /// Defines the window after each write, in which drains are coalesced.
+    coalesce_window: Duration,
+
+    /// Defines how the previous output is replaced.
+    refresh: WatchRefresh,
+
+    /// Defines what to write between consecutive diagnostics within a single set.
+    separator: DrainSeparator,
+
+    /// Defines whether to return an error upon draining an error.
+    exit_on_error: bool,
+}
+
+impl WatchHandler {
+    /// Creates a new [`WatchHandler`] which writes to the standard error stream.
+    pub fn with_renderer(renderer: Box<dyn Renderer + Send + Sync>) -> Self {
+        WatchHandler::with_output(renderer, std::io::stderr())
+    }
+}
+
+impl<W: std::io::Write> WatchHandler<W> {
+    /// Creates a new [`WatchHandler`] which writes to the given output.
+    pub fn with_output(renderer: Box<dyn Renderer + Send + Sync>, output: W) -> Self {
+        Self {
+            renderer,
+            output,
+            reported: Vec::new(),
+            pending: None,
+            previous: None,
+            last_write: None,
+            coalesce_window: Duration::ZERO,
+            refresh: WatchRefresh::default(),
+            separator: DrainSeparator::default(),
+            exit_on_error: false,
+        }
+    }
+
+    /// Enables the handler to return an error upon draining an error.
+    pub fn exit_on_error(&mut self) {
+        self.exit_on_error = true
+    }
+
+    /// Sets how the previous output is replaced, before writing the latest diagnostics.
+    pub fn set_refresh(&mut self, refresh: WatchRefresh) {
+        self.refresh = refresh;
+    }
+
+    /// Sets what to write between consecutive diagnostics within a single set.
+    pub fn set_separator(&mut self, separator: DrainSeparator) {
+        self.separator = separator;
+    }
+
+    /// Sets the window after each write, in which drains are coalesced into a single write.
+    ///
+    /// Defaults to zero, which writes the output upon every drain.
+    pub fn set_coalesce_window(&mut self, window: Duration) {
+        self.coalesce_window = window;
+    }
+
+    /// Gets the output which the rendered diagnostics are written to.
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// Consumes the handler, returning the output which the rendered diagnostics were written to.
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    /// Determines whether a drained set of diagnostics is waiting to be written,
+    /// since it was drained within the coalescing window.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Writes the pending set of diagnostics, if any, regardless of the coalescing window.
+    pub fn flush(&mut self) -> Result<(), DrainError> {
+        let Some(diagnostics) = self.pending.take() else {
+            return Ok(());
+        };
+
+        let mut rendered = String::new();
+
+        for (idx, diagnostic) in diagnostics.iter().enumerate() {
+            if idx > 0 {
+                rendered.push_str(&self.separator.format());
+            }
+
+            rendered.push_str(&self.renderer.render(diagnostic.as_ref())?);
+
+            if diagnostic.severity() == Severity::Fatal {
+                break;
+            }
+        }
+
+        // Identical output is skipped entirely, so the terminal doesn't flicker
+        // when a rebuild yields the same diagnostics.
+        if self.previous.as_deref() == Some(rendered.as_str()) {
+            return Ok(());
+        }
+
+        let refresh = match self.refresh {
+            WatchRefresh::ClearScreen => String::from(CLEAR_SCREEN),
+            WatchRefresh::Separator(_) if self.previous.is_none() => String::new(),
+            WatchRefresh::Separator(separator) => separator.format(),
+        };
+
+        self.output
+            .write_all(refresh.as_bytes())
+            .and_then(|_| self.output.write_all(rendered.as_bytes()))
+            .and_then(|_| self.output.flush())
+            .map_err(|_| std::fmt::Error)?;
+
+        self.previous = Some(rendered);
+        self.last_write = Some(Instant::now());
+
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> std::fmt::Debug for WatchHandler<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchHandler")
+            .field("reported", &self.reported)
+            .field("pending", &self.pending)
+            .field("coalesce_window", &self.coalesce_window)
+            .field("refresh", &self.refresh)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: std::io::Write> Handler for WatchHandler<W> {
+    fn report(&mut self, diagnostic: Box<dyn Diagnostic>) {
+        self.reported.push(diagnostic);
+    }
+
+    /// Replaces the pending set with all diagnostics reported since the last drain, and writes it,
+    /// unless the output was written within the coalescing window.
+    ///
+    /// The returned summary always describes the latest set, even if it hasn't been written yet.
+    fn drain(&mut self) -> DrainResult {
+        let diagnostics = std::mem::take(&mut self.reported);
+        let mut summary = DrainSummary::default();
+        let mut fatal = false;
+
+        for diagnostic in &diagnostics {
+            summary.record(diagnostic.severity());
+
+            if diagnostic.severity() == Severity::Fatal {
+                fatal = true;
+                break;
+            }
+        }
+
+        self.pending = Some(diagnostics);
+
+        let coalesced = self
+            .last_write
+            .is_some_and(|last_write| last_write.elapsed() < self.coalesce_window);
+
+        if !coalesced {
+            self.flush()?;
+        }
+
+        if fatal {
+            return Err(DrainError::Fatal);
+        }
+
+        if summary.errors > 0 && self.exit_on_error {
+            return Err(DrainError::CompoundError(summary.errors));
+        }
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,73 @@
+use std::ops::Range;
+
+use crate::{Diagnostic, Severity};
+
+/// Structural identity of a diagnostic, which can be used to deduplicate diagnostics
+/// or to compare them in tests, without comparing the rendered output.
+///
+/// Two diagnostics have the same key if they have the same code, message and severity,
+/// and their primary span is the same. The primary span of a diagnostic is the span of it's
+/// first label, where the source is identified by it's name.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{DiagnosticKey, Label, NamedSource, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+///
+/// let first = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source.clone()), 8..9, "not found in this scope"));
+///
+/// let second = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source.clone()), 8..9, "not found"));
+///
+/// let third = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source), 4..5, "not found in this scope"));
+///
+/// assert_eq!(DiagnosticKey::of(&first), DiagnosticKey::of(&second));
+/// assert_ne!(DiagnosticKey::of(&first), DiagnosticKey::of(&third));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiagnosticKey {
+    /// Defines the code of the diagnostic, if any.
+    pub code: Option<String>,
+
+    /// Defines the message of the diagnostic.
+    pub message: String,
+
+    /// Defines the severity of the diagnostic.
+    pub severity: Severity,
+
+    /// Defines the name of the source of the primary span, if any.
+    pub source: Option<String>,
+
+    /// Defines the byte range of the primary span, if the diagnostic has any labels.
+    pub span: Option<Range<usize>>,
+}
+
+impl DiagnosticKey {
+    /// Computes the key of the given diagnostic.
+    ///
+    /// Labels without any source of their own use the source of the diagnostic instead.
+    pub fn of(diagnostic: &(impl Diagnostic + ?Sized)) -> Self {
+        let label = diagnostic.labels().and_then(|mut labels| labels.next());
+
+        let source = label
+            .as_ref()
+            .and_then(|label| label.source())
+            .or_else(|| diagnostic.source_code())
+            .and_then(|source| source.name().map(String::from));
+
+        DiagnosticKey {
+            code: diagnostic.code().map(|code| code.to_string()),
+            message: diagnostic.message(),
+            severity: diagnostic.severity(),
+            source,
+            span: label.map(|label| label.range().0.clone()),
+        }
+    }
+}
@@ -2,13 +2,42 @@ use std::fmt::Display;
 use std::ops::Range;
 use std::sync::Arc;
 
+pub mod expected;
+pub mod fix;
 pub mod handler;
+pub mod key;
+pub mod loader;
 pub mod render;
 pub mod source;
+pub mod walk;
 
+#[cfg(feature = "lsp")]
+pub mod lsp;
+
+#[cfg(feature = "miette-compat")]
+pub mod miette_compat;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "renderer-testkit")]
+pub mod testkit;
+
+#[doc(hidden)]
+pub mod __private;
+
+pub use crate::expected::*;
+pub use crate::fix::*;
 pub use crate::handler::*;
+pub use crate::key::*;
+pub use crate::loader::*;
+#[cfg(feature = "lsp")]
+pub use crate::lsp::*;
+#[cfg(feature = "miette-compat")]
+pub use crate::miette_compat::*;
 pub use crate::render::*;
 pub use crate::source::*;
+pub use crate::walk::*;
 
 pub type Error = Box<dyn Diagnostic + Send + Sync>;
 
@@ -18,8 +47,30 @@ pub type Result<T> = std::result::Result<T, Error>;
 ///
 /// Intended to be used by the reporter to change how the diagnostic is displayed.
 /// Diagnostics of [`Error`] or higher also cause the reporter to halt upon draining.
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Severities are ordered by how severe they are, so `Bug > Fatal > Error > Warning > Info > Note > Help`.
+///
+/// The enum is non-exhaustive, so more severities may be added without a breaking change.
+///
+/// # Examples
+/// ```
+/// use error_snippet::Severity;
+///
+/// assert!(Severity::Bug > Severity::Fatal);
+/// assert!(Severity::Fatal > Severity::Error);
+/// assert!(Severity::Error > Severity::Warning);
+/// assert!(Severity::Note > Severity::Help);
+/// assert_eq!(Severity::Info.max(Severity::Warning), Severity::Warning);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum Severity {
+    /// Internal bug, such as an internal compiler error. Program cannot continue.
+    Bug,
+
+    /// Unrecoverable failure. Program cannot continue and must stop immediately.
+    Fatal,
+
     /// Failure. Program cannot continue.
     #[default]
     Error,
@@ -37,9 +88,52 @@ pub enum Severity {
     Help,
 }
 
+impl Severity {
+    /// Gets the rank of the severity, where more severe diagnostics have a higher rank.
+    fn rank(self) -> u8 {
+        match self {
+            Severity::Bug => 6,
+            Severity::Fatal => 5,
+            Severity::Error => 4,
+            Severity::Warning => 3,
+            Severity::Info => 2,
+            Severity::Note => 1,
+            Severity::Help => 0,
+        }
+    }
+
+    /// Determines whether the severity is at least as severe as the given severity.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Severity;
+    ///
+    /// assert!(Severity::Error.is_at_least(Severity::Warning));
+    /// assert!(Severity::Warning.is_at_least(Severity::Warning));
+    /// assert!(!Severity::Note.is_at_least(Severity::Info));
+    /// ```
+    pub fn is_at_least(self, severity: Severity) -> bool {
+        self >= severity
+    }
+}
+
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Severity {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
 impl std::fmt::Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Severity::Bug => f.write_str("bug"),
+            Severity::Fatal => f.write_str("fatal"),
             Severity::Error => f.write_str("error"),
             Severity::Warning => f.write_str("warning"),
             Severity::Info => f.write_str("info"),
@@ -49,13 +143,202 @@ impl std::fmt::Display for Severity {
     }
 }
 
+/// Defines a category of a diagnostic, which tools can use to display or filter diagnostics.
+///
+/// Editors render some tags specially, such as fading out unused code or striking through
+/// deprecated items, which renderers for the [Language Server Protocol][lsp] surface.
+///
+/// [lsp]: https://microsoft.github.io/language-server-protocol/
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Diagnostic, SimpleDiagnostic, Tag};
+///
+/// let diagnostic = SimpleDiagnostic::new("unused variable `a`")
+///     .with_tag(Tag::Unused)
+///     .with_tag(Tag::custom("style"));
+///
+/// assert_eq!(diagnostic.tags(), vec![Tag::Unused, Tag::custom("style")]);
+/// assert_eq!(Tag::Unused.to_string(), "unused");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Tag {
+    /// Defines that the diagnostic refers to unused or unnecessary code.
+    Unused,
+
+    /// Defines that the diagnostic refers to the use of a deprecated item.
+    Deprecated,
+
+    /// Defines that the diagnostic refers to a security issue.
+    Security,
+
+    /// Defines an application-specific tag.
+    Custom(String),
+}
+
+impl Tag {
+    /// Creates a new application-specific tag with the given name.
+    pub fn custom(name: impl Into<String>) -> Self {
+        Tag::Custom(name.into())
+    }
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Tag::Unused => f.write_str("unused"),
+            Tag::Deprecated => f.write_str("deprecated"),
+            Tag::Security => f.write_str("security"),
+            Tag::Custom(name) => f.write_str(name),
+        }
+    }
+}
+
 /// Defines some span within a [`Source`] instance.
 ///
-/// The range within the span is an absolute zero-indexed range of characters within the source file.
+/// The range within the span is an absolute zero-indexed range of bytes within the source file.
 /// It is not a line-column representation and does not provide information about the line and column numbers.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpanRange(pub Range<usize>);
 
+impl SpanRange {
+    /// Creates a new [`SpanRange`] from the given start and end offsets.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self(start..end)
+    }
+
+    /// Gets the start offset of the span.
+    pub fn start(&self) -> usize {
+        self.0.start
+    }
+
+    /// Gets the end offset of the span (exclusive).
+    pub fn end(&self) -> usize {
+        self.0.end
+    }
+
+    /// Gets the length of the span. Spans where the end is before
+    /// the start are treated as being empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert_eq!(SpanRange(4..10).len(), 6);
+    /// assert_eq!(SpanRange(10..4).len(), 0);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.0.end.saturating_sub(self.0.start)
+    }
+
+    /// Determines whether the span is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert!(SpanRange(4..4).is_empty());
+    /// assert!(!SpanRange(4..5).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Determines whether the given offset is contained within the span.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert!(SpanRange(4..10).contains(4));
+    /// assert!(!SpanRange(4..10).contains(10));
+    /// ```
+    pub fn contains(&self, offset: usize) -> bool {
+        self.0.contains(&offset)
+    }
+
+    /// Determines whether the given span is entirely contained within the span.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert!(SpanRange(4..10).covers(&SpanRange(5..10)));
+    /// assert!(!SpanRange(4..10).covers(&SpanRange(5..11)));
+    /// ```
+    pub fn covers(&self, other: &SpanRange) -> bool {
+        self.0.start <= other.0.start && other.0.end <= self.0.end
+    }
+
+    /// Creates a new span, which covers both the current and the given span.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert_eq!(SpanRange(4..10).join(&SpanRange(12..16)), SpanRange(4..16));
+    /// ```
+    pub fn join(&self, other: &SpanRange) -> SpanRange {
+        SpanRange(self.0.start.min(other.0.start)..self.0.end.max(other.0.end))
+    }
+
+    /// Gets the span which is shared between the current and the given span, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert_eq!(SpanRange(4..10).intersect(&SpanRange(8..16)), Some(SpanRange(8..10)));
+    /// assert_eq!(SpanRange(4..10).intersect(&SpanRange(12..16)), None);
+    /// ```
+    pub fn intersect(&self, other: &SpanRange) -> Option<SpanRange> {
+        let start = self.0.start.max(other.0.start);
+        let end = self.0.end.min(other.0.end);
+
+        if start < end {
+            Some(SpanRange(start..end))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a sub-span of the span, where the given range is relative to the start of the span.
+    ///
+    /// The resulting span is clamped, so it never extends past the end of the current span.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::SpanRange;
+    ///
+    /// assert_eq!(SpanRange(4..10).subspan(1..3), SpanRange(5..7));
+    /// assert_eq!(SpanRange(4..10).subspan(2..20), SpanRange(6..10));
+    /// ```
+    pub fn subspan(&self, range: Range<usize>) -> SpanRange {
+        let end = self.0.start.saturating_add(range.end).min(self.0.end);
+        let start = self.0.start.saturating_add(range.start).min(end);
+
+        SpanRange(start..end)
+    }
+}
+
+impl std::ops::Add<usize> for SpanRange {
+    type Output = SpanRange;
+
+    /// Shifts the span forward by the given offset.
+    fn add(self, offset: usize) -> SpanRange {
+        SpanRange(self.0.start.saturating_add(offset)..self.0.end.saturating_add(offset))
+    }
+}
+
+impl std::ops::Sub<usize> for SpanRange {
+    type Output = SpanRange;
+
+    /// Shifts the span backward by the given offset, saturating at zero.
+    fn sub(self, offset: usize) -> SpanRange {
+        SpanRange(self.0.start.saturating_sub(offset)..self.0.end.saturating_sub(offset))
+    }
+}
+
 impl From<Range<usize>> for SpanRange {
     fn from(range: Range<usize>) -> SpanRange {
         SpanRange(Range {
@@ -79,14 +362,14 @@ impl std::fmt::Display for SpanRange {
 
 /// Defines some location with a [`Source`] instance.
 ///
-/// The location within the structure is an absolute zero-indexed position of characters within the source file.
+/// The location within the structure is an absolute zero-indexed byte position within the source file.
 /// It is not a line-column representation and does not provide information about the line and column numbers.
 #[derive(Debug, Clone)]
 pub struct SourceLocation {
     /// Defines the source which the range is referring to.
     source: Arc<dyn Source>,
 
-    /// Defines the character offset into the file.
+    /// Defines the byte offset into the file.
     offset: usize,
 }
 
@@ -95,12 +378,22 @@ impl SourceLocation {
     pub fn new(source: Arc<dyn Source>, offset: usize) -> Self {
         Self { source, offset }
     }
+
+    /// Gets the source which the location is referring to.
+    pub fn source(&self) -> Arc<dyn Source> {
+        self.source.clone()
+    }
+
+    /// Gets the byte offset into the source.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
 }
 
 impl PartialEq for SourceLocation {
     fn eq(&self, other: &Self) -> bool {
         self.source.name() == other.source.name()
-            && self.source.content() == other.source.content()
+            && self.source.text() == other.source.text()
             && self.offset == other.offset
     }
 }
@@ -127,7 +420,7 @@ impl std::cmp::Ord for SourceLocation {
 
 /// Defines some span with a [`Source`] instance.
 ///
-/// The range within the span is an absolute zero-indexed range of characters within the source file.
+/// The range within the span is an absolute zero-indexed range of bytes within the source file.
 /// It is not a line-column representation and does not provide information about the line and column numbers.
 #[derive(Debug, Clone)]
 pub struct SourceRange {
@@ -146,12 +439,103 @@ impl SourceRange {
             span: span.into(),
         }
     }
+
+    /// Gets the source which the range is referring to.
+    pub fn source(&self) -> Arc<dyn Source> {
+        self.source.clone()
+    }
+
+    /// Gets the underlying span of the range.
+    pub fn span(&self) -> &SpanRange {
+        &self.span
+    }
+
+    /// Gets the length of the range.
+    pub fn len(&self) -> usize {
+        self.span.len()
+    }
+
+    /// Determines whether the range is empty.
+    pub fn is_empty(&self) -> bool {
+        self.span.is_empty()
+    }
+
+    /// Determines whether the given offset is contained within the range.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.span.contains(offset)
+    }
+
+    /// Determines whether the given range refers to the same source as the current range.
+    pub fn same_source(&self, other: &SourceRange) -> bool {
+        Arc::ptr_eq(&self.source, &other.source)
+            || (self.source.name() == other.source.name() && self.source.text() == other.source.text())
+    }
+
+    /// Creates a new range, which covers both the current and the given range.
+    ///
+    /// Returns `None` if the ranges refer to different sources.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{NamedSource, SourceRange, SpanRange};
+    ///
+    /// let source = Arc::new(NamedSource::new("src/lib.rs", "let a = b + c;"));
+    ///
+    /// let left = SourceRange::new(source.clone(), 8..9);
+    /// let right = SourceRange::new(source.clone(), 12..13);
+    ///
+    /// assert_eq!(left.join(&right).unwrap().span(), &SpanRange(8..13));
+    /// ```
+    pub fn join(&self, other: &SourceRange) -> Option<SourceRange> {
+        if !self.same_source(other) {
+            return None;
+        }
+
+        Some(SourceRange::new(self.source.clone(), self.span.join(&other.span)))
+    }
+
+    /// Gets the range which is shared between the current and the given range, if any.
+    ///
+    /// Returns `None` if the ranges refer to different sources or don't overlap.
+    pub fn intersect(&self, other: &SourceRange) -> Option<SourceRange> {
+        if !self.same_source(other) {
+            return None;
+        }
+
+        let span = self.span.intersect(&other.span)?;
+
+        Some(SourceRange::new(self.source.clone(), span))
+    }
+
+    /// Creates a sub-range of the range, where the given range is relative to the start of the range.
+    pub fn subspan(&self, range: Range<usize>) -> SourceRange {
+        SourceRange::new(self.source.clone(), self.span.subspan(range))
+    }
+}
+
+impl std::ops::Add<usize> for SourceRange {
+    type Output = SourceRange;
+
+    /// Shifts the range forward by the given offset.
+    fn add(self, offset: usize) -> SourceRange {
+        SourceRange::new(self.source, self.span + offset)
+    }
+}
+
+impl std::ops::Sub<usize> for SourceRange {
+    type Output = SourceRange;
+
+    /// Shifts the range backward by the given offset, saturating at zero.
+    fn sub(self, offset: usize) -> SourceRange {
+        SourceRange::new(self.source, self.span - offset)
+    }
 }
 
 impl PartialEq for SourceRange {
     fn eq(&self, other: &Self) -> bool {
         self.source.name() == other.source.name()
-            && self.source.content() == other.source.content()
+            && self.source.text() == other.source.text()
             && self.span == other.span
     }
 }
@@ -176,6 +560,44 @@ impl std::cmp::Ord for SourceRange {
     }
 }
 
+impl From<SourceRange> for SpanRange {
+    fn from(range: SourceRange) -> SpanRange {
+        range.span
+    }
+}
+
+impl From<SourceRange> for Arc<dyn Source> {
+    fn from(range: SourceRange) -> Arc<dyn Source> {
+        range.source
+    }
+}
+
+impl From<(Arc<dyn Source>, Range<usize>)> for SourceRange {
+    fn from((source, range): (Arc<dyn Source>, Range<usize>)) -> SourceRange {
+        SourceRange::new(source, range)
+    }
+}
+
+impl From<SourceLocation> for SourceRange {
+    /// Creates a range which covers the single character at the location.
+    fn from(location: SourceLocation) -> SourceRange {
+        SourceRange::new(location.source, location.offset..location.offset.saturating_add(1))
+    }
+}
+
+impl From<SourceLocation> for SpanRange {
+    /// Creates a span which covers the single character at the location.
+    fn from(location: SourceLocation) -> SpanRange {
+        SpanRange(location.offset..location.offset.saturating_add(1))
+    }
+}
+
+impl From<SourceLocation> for Arc<dyn Source> {
+    fn from(location: SourceLocation) -> Arc<dyn Source> {
+        location.source
+    }
+}
+
 /// Represents a labelled span of some source code.
 ///
 /// Each label is meant to be used as a snippet within a larger source code. It provides
@@ -192,11 +614,26 @@ pub struct Label {
     /// a source attached via the [`Diagnostic::source_code()`] method.
     source: Option<Arc<dyn Source>>,
 
+    /// Defines the identifier of the source where the label should be placed, which
+    /// is resolved by the [`SourceResolver`] of the renderer.
+    ///
+    /// This is only used when no source is attached to the label itself.
+    source_id: Option<SourceId>,
+
     /// Defines the index range where the label should be placed.
     range: SpanRange,
 
     /// Defines the severity of the label, which can be independant from the parent diagnostic.
     severity: Option<Severity>,
+
+    /// Defines the replacement to suggest inline, directly below the label.
+    suggestion: Option<String>,
+
+    /// Defines whether the label is a secondary note, which is rendered less prominently.
+    secondary: bool,
+
+    /// Defines the fingerprint of the source, as it was when the label was created.
+    fingerprint: Option<SourceFingerprint>,
 }
 
 impl PartialEq for Label {
@@ -230,9 +667,13 @@ impl Label {
     pub fn new(source: Option<Arc<dyn Source>>, range: impl Into<SpanRange>, message: impl Into<String>) -> Self {
         Self {
             source,
+            source_id: None,
             range: range.into(),
             message: message.into(),
             severity: None,
+            suggestion: None,
+            secondary: false,
+            fingerprint: None,
         }
     }
 
@@ -259,9 +700,13 @@ impl Label {
     pub fn error(source: Option<Arc<dyn Source>>, range: impl Into<SpanRange>, label: impl Into<String>) -> Self {
         Self {
             source,
+            source_id: None,
             range: range.into(),
             message: label.into(),
             severity: Some(Severity::Error),
+            suggestion: None,
+            secondary: false,
+            fingerprint: None,
         }
     }
 
@@ -288,9 +733,13 @@ impl Label {
     pub fn warning(source: Option<Arc<dyn Source>>, range: impl Into<SpanRange>, label: impl Into<String>) -> Self {
         Self {
             source,
+            source_id: None,
             range: range.into(),
             message: label.into(),
             severity: Some(Severity::Warning),
+            suggestion: None,
+            secondary: false,
+            fingerprint: None,
         }
     }
 
@@ -317,9 +766,13 @@ impl Label {
     pub fn info(source: Option<Arc<dyn Source>>, range: impl Into<SpanRange>, label: impl Into<String>) -> Self {
         Self {
             source,
+            source_id: None,
             range: range.into(),
             message: label.into(),
             severity: Some(Severity::Info),
+            suggestion: None,
+            secondary: false,
+            fingerprint: None,
         }
     }
 
@@ -346,9 +799,13 @@ impl Label {
     pub fn note(source: Option<Arc<dyn Source>>, range: impl Into<SpanRange>, label: impl Into<String>) -> Self {
         Self {
             source,
+            source_id: None,
             range: range.into(),
             message: label.into(),
             severity: Some(Severity::Note),
+            suggestion: None,
+            secondary: false,
+            fingerprint: None,
         }
     }
 
@@ -375,9 +832,86 @@ impl Label {
     pub fn help(source: Option<Arc<dyn Source>>, range: impl Into<SpanRange>, label: impl Into<String>) -> Self {
         Self {
             source,
+            source_id: None,
             range: range.into(),
             message: label.into(),
             severity: Some(Severity::Help),
+            suggestion: None,
+            secondary: false,
+            fingerprint: None,
+        }
+    }
+
+    /// Creates a new [`Label`] from the given source, range, and label, which is a secondary note
+    /// with a severity of [`Severity::Note`], such as "previous definition here".
+    ///
+    /// Secondary notes are rendered in the same snippet as the other labels of the diagnostic, but
+    /// less prominently, so they don't need to be reported as separate related diagnostics.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{Label, Severity};
+    ///
+    /// let source = Arc::new("fn foo() {}\nfn foo() {}");
+    ///
+    /// let label = Label::secondary_note(Some(source.clone()), 3..6, "previous definition here");
+    ///
+    /// assert_eq!(label.severity(), Some(Severity::Note));
+    /// assert!(label.is_secondary());
+    /// ```
+    pub fn secondary_note(
+        source: Option<Arc<dyn Source>>,
+        range: impl Into<SpanRange>,
+        label: impl Into<String>,
+    ) -> Self {
+        Self {
+            secondary: true,
+            ..Self::note(source, range, label)
+        }
+    }
+
+    /// Creates a new [`Label`] from the given source range and label, so spans which
+    /// already carry their source don't need to be split up first.
+    ///
+    /// Since [`SourceLocation`] converts into a [`SourceRange`] covering the single character at the
+    /// location, locations can be given as well, such as the location of a [`Suggestion::insert`].
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{Label, NamedSource, SourceRange, SpanRange};
+    ///
+    /// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+    ///
+    /// let label = Label::from_range(SourceRange::new(source, 8..9), "not found in this scope");
+    ///
+    /// assert_eq!(label.range(), &SpanRange(8..9));
+    /// assert_eq!(label.source().unwrap().name(), Some("src/main.lm"));
+    /// ```
+    pub fn from_range(range: impl Into<SourceRange>, message: impl Into<String>) -> Self {
+        let range = range.into();
+
+        Self::new(Some(range.source), range.span, message)
+    }
+
+    /// Creates a new [`Label`] which refers to it's source by the given identifier, instead of
+    /// holding the source itself, which is resolved by the [`SourceResolver`] of the renderer.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{Label, SourceId, SpanRange};
+    ///
+    /// let label = Label::in_source(SourceId(3), 8..9, "not found in this scope");
+    ///
+    /// assert_eq!(label.source_id(), Some(SourceId(3)));
+    /// assert_eq!(label.range(), &SpanRange(8..9));
+    /// assert!(label.source().is_none());
+    /// ```
+    pub fn in_source(id: impl Into<SourceId>, range: impl Into<SpanRange>, message: impl Into<String>) -> Self {
+        Self {
+            source_id: Some(id.into()),
+            ..Self::new(None, range, message)
         }
     }
 
@@ -442,41 +976,194 @@ impl Label {
     /// let label = Label::new(Some(source.clone()), 60..65, "could not find method 'invok'");
     ///
     /// assert_eq!(label.source().unwrap().name(), source.name());
-    /// assert_eq!(label.source().unwrap().content(), source.content());
+    /// assert_eq!(label.source().unwrap().text(), source.text());
     /// ```
     pub fn source(&self) -> Option<Arc<dyn Source>> {
         self.source.clone()
     }
 
-    /// Gets the severity of the current label instance.
+    /// Gets the identifier of the source of the current label instance, if any.
+    pub fn source_id(&self) -> Option<SourceId> {
+        self.source_id
+    }
+
+    /// Gets the source of the current label instance, where labels without any source
+    /// attached resolve their source identifier using the given resolver, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{Label, NamedSource, Source, SourceId};
+    ///
+    /// let sources: Vec<Arc<dyn Source>> = vec![Arc::new(NamedSource::new("src/main.lm", "let a = b;"))];
+    /// let label = Label::in_source(SourceId(0), 8..9, "not found in this scope");
+    ///
+    /// assert_eq!(label.resolve_source(Some(&sources)).unwrap().name(), Some("src/main.lm"));
+    /// assert!(label.resolve_source(None).is_none());
+    /// ```
+    pub fn resolve_source(&self, resolver: Option<&dyn SourceResolver>) -> Option<Arc<dyn Source>> {
+        if let Some(source) = &self.source {
+            return Some(source.clone());
+        }
+
+        resolver?.resolve(self.source_id?)
+    }
+
+    /// Gets the source of the current label instance, like [`Label::resolve_source()`], where labels
+    /// without any source or source identifier fall back to the given source, such as the source of
+    /// their diagnostic.
+    ///
+    /// Labels with a source identifier which can't be resolved don't fall back, since their
+    /// span refers to another source entirely, so `None` is returned instead.
+    pub(crate) fn resolve_source_or(
+        &self,
+        resolver: Option<&dyn SourceResolver>,
+        fallback: Option<Arc<dyn Source>>,
+    ) -> Option<Arc<dyn Source>> {
+        match self.source_id {
+            Some(_) if self.source.is_none() => self.resolve_source(resolver),
+            _ => self.resolve_source(resolver).or(fallback),
+        }
+    }
+
+    /// Gets the source range of the current label instance, if it has a source attached.
+    ///
+    /// This allows the span of a label to be used directly in a [`Suggestion`].
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{Label, NamedSource, SourceRange, Suggestion};
+    ///
+    /// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+    /// let label = Label::new(Some(source.clone()), 8..9, "not found in this scope");
+    ///
+    /// let suggestion = Suggestion::replace(label.source_range().unwrap(), "a");
+    ///
+    /// assert_eq!(label.source_range(), Some(SourceRange::new(source, 8..9)));
+    /// assert!(Label::new(None, 8..9, "").source_range().is_none());
+    /// ```
+    pub fn source_range(&self) -> Option<SourceRange> {
+        let source = self.source.clone()?;
+
+        Some(SourceRange::new(source, self.range.clone()))
+    }
+
+    /// Gets the severity of the current label instance.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{Label, Severity};
+    ///
+    /// let source = Arc::new(r#"fn main() -> int {
+    ///     let a = new Testing();
+    ///     let b = a.invok();
+    ///
+    ///     return 0;
+    /// }"#);
+    ///
+    /// let label = Label::new(Some(source.clone()), 60..65, "could not find method 'invok'")
+    ///     .with_severity(Severity::Warning);
+    ///
+    /// assert_eq!(label.severity(), Some(Severity::Warning));
+    /// ```
+    pub fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    /// Sets the severity for the current label instance.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use error_snippet::{Label, Severity};
+    ///
+    /// let source = Arc::new(r#"fn main() -> int {
+    ///     let a = new Testing();
+    ///     let b = a.invok();
+    ///
+    ///     return 0;
+    /// }"#);
+    ///
+    /// let label = Label::new(Some(source.clone()), 60..65, "could not find method 'invok'")
+    ///     .with_severity(Severity::Warning);
+    ///
+    /// assert_eq!(label.message(), "could not find method 'invok'");
+    /// assert_eq!(label.severity(), Some(Severity::Warning));
+    /// ```
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Determines whether the current label is a secondary note.
+    ///
+    /// See [`Label::secondary_note()`] for more information.
+    pub fn is_secondary(&self) -> bool {
+        self.secondary
+    }
+
+    /// Captures the fingerprint of the source of the label, as it currently is, so renderers can
+    /// detect when the source has changed before the label is rendered.
+    ///
+    /// Labels without any source attached, or whose source content is unavailable, are left unchanged.
+    /// Labels which refer to their source by identifier should use [`Label::with_fingerprint()`] instead.
     ///
     /// # Examples
     /// ```
     /// use std::sync::Arc;
-    /// use error_snippet::{Label, Severity};
-    ///
-    /// let source = Arc::new(r#"fn main() -> int {
-    ///     let a = new Testing();
-    ///     let b = a.invok();
+    /// use error_snippet::{Label, NamedSource};
     ///
-    ///     return 0;
-    /// }"#);
+    /// let before = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+    /// let after = Arc::new(NamedSource::new("src/main.lm", "// moved\nlet a = b;"));
     ///
-    /// let label = Label::new(Some(source.clone()), 60..65, "could not find method 'invok'")
-    ///     .with_severity(Severity::Warning);
+    /// let label = Label::error(Some(before.clone()), 8..9, "not found in this scope").fingerprinted();
     ///
-    /// assert_eq!(label.severity(), Some(Severity::Warning));
+    /// assert!(!label.is_stale(before.as_ref()));
+    /// assert!(label.is_stale(after.as_ref()));
     /// ```
-    pub fn severity(&self) -> Option<Severity> {
-        self.severity
+    pub fn fingerprinted(mut self) -> Self {
+        if let Some(source) = &self.source {
+            self.fingerprint = SourceFingerprint::of(source.as_ref());
+        }
+
+        self
     }
 
-    /// Sets the severity for the current label instance.
+    /// Sets the fingerprint of the source of the label, as it was when the label was created.
+    pub fn with_fingerprint(mut self, fingerprint: SourceFingerprint) -> Self {
+        self.fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Gets the fingerprint of the source of the label, as it was when the label was created, if any.
+    pub fn fingerprint(&self) -> Option<SourceFingerprint> {
+        self.fingerprint
+    }
+
+    /// Determines whether the given source has changed since the fingerprint of the label was captured,
+    /// in which case the range of the label may no longer refer to the intended content.
+    ///
+    /// Labels without any fingerprint are never considered stale.
+    pub fn is_stale(&self, source: &dyn Source) -> bool {
+        self.fingerprint.is_some_and(|fingerprint| !fingerprint.matches(source))
+    }
+
+    /// Gets the replacement which is suggested inline, directly below the label, if any.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+
+    /// Sets a replacement to suggest inline, which is rendered as a `did you mean` hint
+    /// directly below the label, instead of in the footer of the diagnostic.
+    ///
+    /// For suggestions which should modify the source code, use [`Suggestion`] within [`Help`] instead.
     ///
     /// # Examples
     /// ```
     /// use std::sync::Arc;
-    /// use error_snippet::{Label, Severity};
+    /// use error_snippet::Label;
     ///
     /// let source = Arc::new(r#"fn main() -> int {
     ///     let a = new Testing();
@@ -486,13 +1173,12 @@ impl Label {
     /// }"#);
     ///
     /// let label = Label::new(Some(source.clone()), 60..65, "could not find method 'invok'")
-    ///     .with_severity(Severity::Warning);
+    ///     .with_suggestion("invoke");
     ///
-    /// assert_eq!(label.message(), "could not find method 'invok'");
-    /// assert_eq!(label.severity(), Some(Severity::Warning));
+    /// assert_eq!(label.suggestion(), Some("invoke"));
     /// ```
-    pub fn with_severity(mut self, severity: Severity) -> Self {
-        self.severity = Some(severity);
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
         self
     }
 
@@ -522,7 +1208,7 @@ impl Label {
         let diag_source = diagnostic.and_then(|d| d.source_code());
         let source = self.source.clone().or(diag_source)?;
 
-        let content = source.content();
+        let content = source.text();
         let range = self.range().0.clone();
 
         let mut line_start = 0;
@@ -603,8 +1289,10 @@ impl LabelSpan {
 /// Represents a suggested fix with a source file attached.
 ///
 /// Suggestions can guide the user to change some part of the source code,
-/// in order to fix diagnostics.
+/// in order to fix diagnostics. Suggestions can also be file-level operations,
+/// such as creating, deleting or renaming files.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
 pub enum Suggestion {
     /// Defines some span within a file should be deleted.
     Deletion { range: SourceRange },
@@ -614,13 +1302,22 @@ pub enum Suggestion {
 
     /// Defines some span within a file should be replaced.
     Replacement { range: SourceRange, replacement: String },
+
+    /// Defines a new file should be created with the given content.
+    CreateFile { path: String, content: String },
+
+    /// Defines an existing file should be deleted.
+    DeleteFile { path: String },
+
+    /// Defines an existing file should be renamed or moved.
+    RenameFile { from: String, to: String },
 }
 
 impl Suggestion {
     /// Creates a new [`Suggestion`] where a certain span within
     /// a file should be deleted.
-    pub fn delete(range: SourceRange) -> Self {
-        Self::Deletion { range }
+    pub fn delete(range: impl Into<SourceRange>) -> Self {
+        Self::Deletion { range: range.into() }
     }
 
     /// Creates a new [`Suggestion`] where a certain location
@@ -634,31 +1331,90 @@ impl Suggestion {
 
     /// Creates a new [`Suggestion`] where a certain span should
     /// be replaced with a string value.
-    pub fn replace(range: SourceRange, replacement: impl Into<String>) -> Self {
+    pub fn replace(range: impl Into<SourceRange>, replacement: impl Into<String>) -> Self {
         Self::Replacement {
-            range,
+            range: range.into(),
             replacement: replacement.into(),
         }
     }
 
+    /// Creates a new [`Suggestion`] where a new file should be created, with the given content.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Suggestion;
+    ///
+    /// let suggestion = Suggestion::create_file("src/point.lm", "struct Point {}");
+    ///
+    /// assert!(suggestion.is_file_operation());
+    /// assert!(suggestion.source().is_none());
+    /// ```
+    pub fn create_file(path: impl Into<String>, content: impl Into<String>) -> Self {
+        Self::CreateFile {
+            path: path.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Creates a new [`Suggestion`] where an existing file should be deleted.
+    pub fn delete_file(path: impl Into<String>) -> Self {
+        Self::DeleteFile { path: path.into() }
+    }
+
+    /// Creates a new [`Suggestion`] where an existing file should be renamed or moved.
+    pub fn rename_file(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self::RenameFile {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// Determines whether the suggestion is a file-level operation, instead
+    /// of an edit within an existing source file.
+    pub fn is_file_operation(&self) -> bool {
+        matches!(
+            self,
+            Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. }
+        )
+    }
+
     /// Gets the source file of the suggestion.
-    pub fn source(&self) -> Arc<dyn Source> {
+    ///
+    /// File-level operations don't refer to any source, so `None` is returned for those.
+    pub fn source(&self) -> Option<Arc<dyn Source>> {
         match self {
-            Suggestion::Deletion { range, .. } => range.source.clone(),
-            Suggestion::Insertion { location, .. } => location.source.clone(),
-            Suggestion::Replacement { range, .. } => range.source.clone(),
+            Suggestion::Deletion { range, .. } => Some(range.source.clone()),
+            Suggestion::Insertion { location, .. } => Some(location.source.clone()),
+            Suggestion::Replacement { range, .. } => Some(range.source.clone()),
+            Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => None,
         }
     }
 
     /// Gets the span which the suggestion refers to.
     ///
     /// All suggestion types, except insertions, returns the inner span directly,
-    /// where-as insertions will create a new span with a distance of 1.
-    pub fn span(&self) -> Range<usize> {
+    /// where-as insertions will create a new span with a distance of 1. File-level
+    /// operations don't refer to any span, so `None` is returned for those.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Suggestion::Replacement { range, .. } => Some(range.span.0.clone()),
+            Suggestion::Deletion { range, .. } => Some(range.span.0.clone()),
+            Suggestion::Insertion { location, .. } => Some(location.offset..location.offset.saturating_add(1)),
+            Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Suggestion {
+    /// Formats a short description of the suggestion, such as ``create file `src/main.lm` ``.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Suggestion::Replacement { range, .. } => range.span.0.clone(),
-            Suggestion::Deletion { range, .. } => range.span.0.clone(),
-            Suggestion::Insertion { location, .. } => location.offset..location.offset + 1,
+            Suggestion::Deletion { range } => write!(f, "delete {}", range.span),
+            Suggestion::Insertion { value, .. } => write!(f, "insert `{value}`"),
+            Suggestion::Replacement { replacement, .. } => write!(f, "replace with `{replacement}`"),
+            Suggestion::CreateFile { path, .. } => write!(f, "create file `{path}`"),
+            Suggestion::DeleteFile { path } => write!(f, "delete file `{path}`"),
+            Suggestion::RenameFile { from, to } => write!(f, "rename `{from}` to `{to}`"),
         }
     }
 }
@@ -674,6 +1430,25 @@ pub struct Help {
 
     /// A list of zero-or-more suggestions to apply to the original source code.
     pub suggestions: Vec<Suggestion>,
+
+    /// Defines a short title of the fix, which is rendered above the suggestions.
+    ///
+    /// This is useful to tell multiple fix variants apart, when a diagnostic has multiple
+    /// help messages with suggestions, each being an alternative way to fix the diagnostic.
+    pub title: Option<String>,
+
+    /// Defines whether the help message is one of multiple mutually exclusive fixes.
+    ///
+    /// All alternative help messages of a single diagnostic are numbered when rendered, while
+    /// [`SuggestionSet`] only applies the suggestions of the first alternative, since applying
+    /// more than one of them would break the code.
+    pub alternative: bool,
+
+    /// Defines the priority of the help message, where help messages with a higher priority
+    /// are shown first. Help messages with the same priority are shown in their original order.
+    ///
+    /// Defaults to `0`.
+    pub priority: i32,
 }
 
 impl Help {
@@ -692,9 +1467,74 @@ impl Help {
         Self {
             message: message.into(),
             suggestions: Vec::new(),
+            title: None,
+            alternative: false,
+            priority: 0,
         }
     }
 
+    /// Marks the help message as one of multiple mutually exclusive fixes, such as
+    /// either adding a cast or changing the return type.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Help, Renderer, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let diagnostic = SimpleDiagnostic::new("mismatched types")
+    ///     .with_help(Help::new("add a cast to `Int`").as_alternative())
+    ///     .with_help(Help::new("change the return type to `Float`").as_alternative());
+    ///
+    /// let rendered = renderer.render(&diagnostic).unwrap();
+    ///
+    /// assert!(rendered.contains("help: add a cast to `Int` (option 1 of 2)"));
+    /// assert!(rendered.contains("help: change the return type to `Float` (option 2 of 2)"));
+    /// ```
+    pub fn as_alternative(mut self) -> Self {
+        self.alternative = true;
+        self
+    }
+
+    /// Sets the priority of the help message, where help messages with a higher priority are shown first.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Help, Renderer, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let diagnostic = SimpleDiagnostic::new("mismatched types")
+    ///     .with_help("consider changing the return type")
+    ///     .with_help(Help::new("add a cast to `Int`").with_priority(1));
+    ///
+    /// let rendered = renderer.render(&diagnostic).unwrap();
+    ///
+    /// assert!(rendered.find("add a cast").unwrap() < rendered.find("consider changing").unwrap());
+    /// ```
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the title of the fix, which is rendered above the suggestions.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Help;
+    ///
+    /// let help = Help::new("the types of both operands must match")
+    ///     .with_title("convert the right operand");
+    ///
+    /// assert_eq!(help.title.as_deref(), Some("convert the right operand"));
+    /// ```
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
     /// Adds the given suggestion to the help message.
     ///
     /// # Examples
@@ -775,12 +1615,111 @@ impl From<&String> for Help {
     }
 }
 
+/// Represents the key of a message within a message catalog, along with the arguments of the message.
+///
+/// Diagnostics can return a message key from [`Diagnostic::message_key`], so renderers which support
+/// localization can look up the message in the catalog of the user's language, instead of displaying
+/// the message returned from [`Diagnostic::message`].
+///
+/// # Examples
+/// ```
+/// use error_snippet::{MessageArg, MessageKey};
+///
+/// let key = MessageKey::new("unknown-value").with_arg("name", "fals").with_arg("candidates", 2);
+///
+/// assert_eq!(key.key, "unknown-value");
+/// assert_eq!(key.args[0], (String::from("name"), MessageArg::String(String::from("fals"))));
+/// assert_eq!(key.args[1], (String::from("candidates"), MessageArg::Number(2.0)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageKey {
+    /// Defines the key of the message within the catalog.
+    pub key: String,
+
+    /// Defines the named arguments to substitute into the message.
+    pub args: Vec<(String, MessageArg)>,
+}
+
+impl MessageKey {
+    /// Creates a new [`MessageKey`] with the given key and no arguments.
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Adds the given named argument to the message.
+    pub fn with_arg(mut self, name: impl Into<String>, value: impl Into<MessageArg>) -> Self {
+        self.args.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Represents the value of a single argument of a [`MessageKey`].
+///
+/// Numbers are kept separate from strings, so message catalogs can use them
+/// for selecting plural forms.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageArg {
+    /// Defines a textual argument.
+    String(String),
+
+    /// Defines a numeric argument.
+    Number(f64),
+}
+
+impl std::fmt::Display for MessageArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageArg::String(value) => write!(f, "{value}"),
+            MessageArg::Number(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<&str> for MessageArg {
+    fn from(value: &str) -> Self {
+        MessageArg::String(value.to_string())
+    }
+}
+
+impl From<String> for MessageArg {
+    fn from(value: String) -> Self {
+        MessageArg::String(value)
+    }
+}
+
+macro_rules! impl_numeric_message_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for MessageArg {
+                fn from(value: $ty) -> Self {
+                    MessageArg::Number(value as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_message_arg!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
 /// Represents a single diagnostic message, which can be
 /// pretty-printed into an intuitive and fancy error message.
 pub trait Diagnostic: std::fmt::Debug {
     /// Defines which message to be raised to the user, when reported.
     fn message(&self) -> String;
 
+    /// Defines the key and arguments of the message within a message catalog, if any.
+    ///
+    /// Renderers which support localization, such as `LocalizedRenderer`, use the key to look up the
+    /// message in the catalog of the user's language. If the key is `None` or missing from the catalog,
+    /// the message returned from [`Diagnostic::message`] is displayed instead, so it should always be
+    /// defined as well, such as in english.
+    fn message_key(&self) -> Option<MessageKey> {
+        None
+    }
+
     /// Diagnostic severity level.
     ///
     /// This may be used by the renderer to determine how to display the diagnostic or
@@ -794,6 +1733,31 @@ pub trait Diagnostic: std::fmt::Debug {
         None
     }
 
+    /// Key which is used to cluster diagnostics together, when draining them from a handler.
+    ///
+    /// By default, the key is the code of the diagnostic. Implementations can override this to
+    /// group diagnostics by other criteria, such as lint category, subsystem or team ownership.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{Diagnostic, SimpleDiagnostic};
+    ///
+    /// let diagnostic = SimpleDiagnostic::new("unused variable").with_code("W0001");
+    ///
+    /// assert_eq!(diagnostic.group_key(), Some(String::from("W0001")));
+    /// assert_eq!(SimpleDiagnostic::new("unused variable").group_key(), None);
+    /// ```
+    fn group_key(&self) -> Option<String> {
+        self.code().map(|code| code.to_string())
+    }
+
+    /// Tags which categorize the diagnostic, such as [`Tag::Unused`] or [`Tag::Deprecated`].
+    ///
+    /// Only the tags of the diagnostic itself are returned, not the tags of it's causes or related diagnostics.
+    fn tags(&self) -> Vec<Tag> {
+        Vec::new()
+    }
+
     /// Gets the source code which the diagnostic refers to.
     ///
     /// This isn't used if only defined by itself. It will only be used if one or more
@@ -821,6 +1785,219 @@ pub trait Diagnostic: std::fmt::Debug {
     fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
         None
     }
+
+    /// Hints on how the diagnostic should be rendered, such as skipping source snippets.
+    ///
+    /// Renderers may honor the hints, but aren't required to.
+    fn render_hints(&self) -> RenderHints {
+        RenderHints::default()
+    }
+
+    /// Gets the [`TypeId`](std::any::TypeId) of the concrete type of the diagnostic, which is used when downcasting.
+    ///
+    /// The method cannot be overridden, since the argument type cannot be named outside of this crate.
+    #[doc(hidden)]
+    fn __type_id(&self, _: private::Internal) -> std::any::TypeId
+    where
+        Self: 'static,
+    {
+        std::any::TypeId::of::<Self>()
+    }
+}
+
+mod private {
+    /// Type which cannot be named outside of this crate, so [`super::Diagnostic::__type_id`] cannot be overridden.
+    #[derive(Debug)]
+    pub struct Internal;
+}
+
+/// Implements downcasting for the given [`Diagnostic`] trait object types.
+macro_rules! impl_diagnostic_downcast {
+    ($($ty:ty),*) => {
+        $(
+            impl $ty {
+                /// Determines whether the concrete type of the diagnostic is `T`.
+                ///
+                /// Diagnostics which are wrapped by another diagnostic, such as [`SeverityOverride`],
+                /// have the type of the wrapper, not of the wrapped diagnostic.
+                pub fn is<T: Diagnostic + 'static>(&self) -> bool {
+                    self.__type_id(private::Internal) == std::any::TypeId::of::<T>()
+                }
+
+                /// Gets a reference to the concrete diagnostic, if it is of type `T`.
+                ///
+                /// # Examples
+                /// ```
+                /// use error_snippet::{Diagnostic, Error, SimpleDiagnostic};
+                ///
+                /// #[derive(Debug)]
+                /// struct UnusedVariable {
+                ///     name: String,
+                /// }
+                ///
+                /// impl Diagnostic for UnusedVariable {
+                ///     fn message(&self) -> String {
+                ///         format!("unused variable `{}`", self.name)
+                ///     }
+                /// }
+                ///
+                /// let error: Error = UnusedVariable { name: String::from("a") }.into();
+                ///
+                /// assert_eq!(error.downcast_ref::<UnusedVariable>().unwrap().name, "a");
+                /// assert!(error.downcast_ref::<SimpleDiagnostic>().is_none());
+                /// ```
+                pub fn downcast_ref<T: Diagnostic + 'static>(&self) -> Option<&T> {
+                    if self.is::<T>() {
+                        // SAFETY: the concrete type of the diagnostic was just checked to be `T`.
+                        Some(unsafe { &*(self as *const Self as *const T) })
+                    } else {
+                        None
+                    }
+                }
+
+                /// Gets a mutable reference to the concrete diagnostic, if it is of type `T`.
+                pub fn downcast_mut<T: Diagnostic + 'static>(&mut self) -> Option<&mut T> {
+                    if self.is::<T>() {
+                        // SAFETY: the concrete type of the diagnostic was just checked to be `T`.
+                        Some(unsafe { &mut *(self as *mut Self as *mut T) })
+                    } else {
+                        None
+                    }
+                }
+
+                /// Converts the boxed diagnostic into the concrete diagnostic, if it is of type `T`.
+                ///
+                /// If the diagnostic is of another type, the box is returned as-is.
+                pub fn downcast<T: Diagnostic + 'static>(self: Box<Self>) -> std::result::Result<Box<T>, Box<Self>> {
+                    if self.is::<T>() {
+                        // SAFETY: the concrete type of the diagnostic was just checked to be `T`.
+                        Ok(unsafe { Box::from_raw(Box::into_raw(self) as *mut T) })
+                    } else {
+                        Err(self)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_diagnostic_downcast!(dyn Diagnostic, dyn Diagnostic + Send, dyn Diagnostic + Send + Sync);
+
+/// Trait for types which contain zero-or-more nested diagnostics, such as causes or related diagnostics.
+///
+/// This is mostly used by the derive-macro, so fields marked with `#[cause]` or `#[related]` can
+/// be of type [`Error`], `Option<Error>` or `Vec<Error>`, without needing any extra annotations.
+///
+/// Since nested diagnostics are shared across threads, they must be `Send + Sync`. Fields of type
+/// `Box<dyn Diagnostic>` are therefore not supported, use [`Error`] instead.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{AsDiagnostics, SimpleDiagnostic};
+///
+/// let single: error_snippet::Error = SimpleDiagnostic::new("failed to read file").into();
+/// let optional: Option<error_snippet::Error> = None;
+/// let multiple: Vec<error_snippet::Error> = vec![
+///     SimpleDiagnostic::new("failed to read file").into(),
+///     SimpleDiagnostic::new("permission denied").into(),
+/// ];
+///
+/// assert_eq!(single.as_diagnostics().count(), 1);
+/// assert_eq!(optional.as_diagnostics().count(), 0);
+/// assert_eq!(multiple.as_diagnostics().count(), 2);
+/// ```
+pub trait AsDiagnostics {
+    /// Gets an iterator over all the contained diagnostics.
+    fn as_diagnostics(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_>;
+}
+
+impl AsDiagnostics for Box<dyn Diagnostic + Send + Sync> {
+    fn as_diagnostics(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(std::iter::once(self.as_ref()))
+    }
+}
+
+impl<T: AsDiagnostics> AsDiagnostics for Option<T> {
+    fn as_diagnostics(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        match self {
+            Some(inner) => inner.as_diagnostics(),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl<T: AsDiagnostics> AsDiagnostics for [T] {
+    fn as_diagnostics(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(self.iter().flat_map(|d| d.as_diagnostics()))
+    }
+}
+
+impl<T: AsDiagnostics> AsDiagnostics for Vec<T> {
+    fn as_diagnostics(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        self.as_slice().as_diagnostics()
+    }
+}
+
+/// Trait for types which contain zero-or-more help messages, such as a
+/// single message, an optional message or a collection of messages.
+///
+/// This trait is mostly used by the derive macro, so fields marked with `#[help]`
+/// can be a single message, an optional message or a collection of messages.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{AsHelp, Help};
+///
+/// let single = String::from("did you mean `invoke`?");
+/// let optional: Option<String> = None;
+/// let multiple = vec![String::from("did you mean `invoke`?"), String::from("or perhaps `invert`?")];
+///
+/// assert_eq!(single.as_help().count(), 1);
+/// assert_eq!(optional.as_help().count(), 0);
+/// assert_eq!(multiple.as_help().count(), 2);
+/// ```
+pub trait AsHelp {
+    /// Gets an iterator over all the contained help messages.
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_>;
+}
+
+impl AsHelp for str {
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_> {
+        Box::new(std::iter::once(Help::from(self)))
+    }
+}
+
+impl AsHelp for String {
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_> {
+        self.as_str().as_help()
+    }
+}
+
+impl AsHelp for Help {
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_> {
+        Box::new(std::iter::once(self.clone()))
+    }
+}
+
+impl<T: AsHelp> AsHelp for Option<T> {
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_> {
+        match self {
+            Some(inner) => inner.as_help(),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl<T: AsHelp> AsHelp for [T] {
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_> {
+        Box::new(self.iter().flat_map(|h| h.as_help()))
+    }
+}
+
+impl<T: AsHelp> AsHelp for Vec<T> {
+    fn as_help(&self) -> Box<dyn Iterator<Item = Help> + '_> {
+        self.as_slice().as_help()
+    }
 }
 
 impl std::fmt::Display for Box<dyn Diagnostic + Send + Sync + 'static> {
@@ -859,9 +2036,10 @@ impl From<std::io::Error> for Box<dyn Diagnostic + Send + Sync> {
     }
 }
 
+/// Diagnostics are compared structurally, using their [`DiagnosticKey`].
 impl std::cmp::PartialEq for Box<dyn Diagnostic + Send + Sync> {
     fn eq(&self, other: &Self) -> bool {
-        self.message() == other.message()
+        DiagnosticKey::of(self.as_ref()) == DiagnosticKey::of(other.as_ref())
     }
 }
 
@@ -903,6 +2081,15 @@ pub struct SimpleDiagnostic {
 
     /// Defines the diagnostics which are related to the current one, if any.
     pub related: Vec<Box<dyn Diagnostic + Send + Sync>>,
+
+    /// Defines hints on how the diagnostic should be rendered.
+    pub render_hints: RenderHints,
+
+    /// Defines the key of the message within a message catalog, if any.
+    pub message_key: Option<MessageKey>,
+
+    /// Defines the tags which categorize the diagnostic.
+    pub tags: Vec<Tag>,
 }
 
 impl SimpleDiagnostic {
@@ -1176,6 +2363,55 @@ impl SimpleDiagnostic {
         self.causes.extend(causes);
         self
     }
+
+    /// Adds a tag to the current diagnostic instance.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{SimpleDiagnostic, Tag};
+    ///
+    /// let diag = SimpleDiagnostic::new("use of deprecated function `foo`").with_tag(Tag::Deprecated);
+    ///
+    /// assert_eq!(diag.tags, vec![Tag::Deprecated]);
+    /// ```
+    pub fn with_tag(mut self, tag: Tag) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Sets the hints on how the current diagnostic should be rendered.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{RenderHints, SimpleDiagnostic};
+    ///
+    /// let diag = SimpleDiagnostic::new("generated code is invalid").with_render_hints(RenderHints {
+    ///     suppress_snippets: true,
+    ///     ..RenderHints::default()
+    /// });
+    ///
+    /// assert!(diag.render_hints.suppress_snippets);
+    /// ```
+    pub fn with_render_hints(mut self, hints: RenderHints) -> Self {
+        self.render_hints = hints;
+        self
+    }
+
+    /// Sets the key of the message within a message catalog, which is used to localize the message.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{Diagnostic, MessageKey, SimpleDiagnostic};
+    ///
+    /// let diag = SimpleDiagnostic::new("cannot find value `b`")
+    ///     .with_message_key(MessageKey::new("unknown-value").with_arg("name", "b"));
+    ///
+    /// assert_eq!(diag.message_key().unwrap().key, "unknown-value");
+    /// ```
+    pub fn with_message_key(mut self, key: MessageKey) -> Self {
+        self.message_key = Some(key);
+        self
+    }
 }
 
 impl Diagnostic for SimpleDiagnostic {
@@ -1183,6 +2419,10 @@ impl Diagnostic for SimpleDiagnostic {
         self.message.clone()
     }
 
+    fn message_key(&self) -> Option<MessageKey> {
+        self.message_key.clone()
+    }
+
     fn severity(&self) -> Severity {
         self.severity
     }
@@ -1210,6 +2450,14 @@ impl Diagnostic for SimpleDiagnostic {
     fn causes(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
         Box::new(self.causes.iter().map(|b| b.as_ref()))
     }
+
+    fn tags(&self) -> Vec<Tag> {
+        self.tags.clone()
+    }
+
+    fn render_hints(&self) -> RenderHints {
+        self.render_hints
+    }
 }
 
 impl std::fmt::Display for SimpleDiagnostic {
@@ -1229,6 +2477,10 @@ impl Diagnostic for SourceWrapped {
         self.diagnostic.message()
     }
 
+    fn message_key(&self) -> Option<MessageKey> {
+        self.diagnostic.message_key()
+    }
+
     fn severity(&self) -> Severity {
         self.diagnostic.severity()
     }
@@ -1237,6 +2489,14 @@ impl Diagnostic for SourceWrapped {
         self.diagnostic.code()
     }
 
+    fn group_key(&self) -> Option<String> {
+        self.diagnostic.group_key()
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        self.diagnostic.tags()
+    }
+
     fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
         self.diagnostic.help()
     }
@@ -1256,6 +2516,10 @@ impl Diagnostic for SourceWrapped {
     fn source_code(&self) -> Option<Arc<dyn Source>> {
         self.diagnostic.source_code().or_else(|| Some(self.source.clone()))
     }
+
+    fn render_hints(&self) -> RenderHints {
+        self.diagnostic.render_hints()
+    }
 }
 
 impl std::fmt::Display for SourceWrapped {
@@ -1295,7 +2559,7 @@ pub trait WithSource {
     ///
     /// assert_eq!(diag.message(), "Whoops, that wasn't supposed to happen!");
     /// assert_eq!(diag.source_code().unwrap().name(), source.name());
-    /// assert_eq!(diag.source_code().unwrap().content(), source.content());
+    /// assert_eq!(diag.source_code().unwrap().text(), source.text());
     /// ```
     fn with_source(self, source: Arc<dyn Source>) -> impl Diagnostic;
 }
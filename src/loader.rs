@@ -0,0 +1,342 @@
+//! Loading of source files through an abstract [`SourceLoader`], so sources can be read
+//! from an in-memory file system or an archive, instead of the real file system.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::{Diagnostic, Encoding, Source, SourceId, SourceResolver};
+
+/// Defines how the raw bytes of source files are read, given their path.
+///
+/// # Implementations
+///
+/// - [`FsLoader`], which reads from the real file system,
+/// - [`MemoryLoader`], which reads from an in-memory map of files,
+/// - any function or closure of `Fn(&Path) -> std::io::Result<Vec<u8>>`, such as one
+///   which reads entries from a zip archive.
+///
+/// # Examples
+/// ```
+/// use std::path::Path;
+/// use error_snippet::{FileSource, Source};
+///
+/// let loader = |path: &Path| match path.to_str() {
+///     Some("src/main.lm") => Ok(b"let a = 1;".to_vec()),
+///     _ => Err(std::io::Error::from(std::io::ErrorKind::NotFound)),
+/// };
+///
+/// let source = FileSource::load_with("src/main.lm", &loader).unwrap();
+///
+/// assert_eq!(source.text(), "let a = 1;");
+/// ```
+pub trait SourceLoader: Send + Sync {
+    /// Reads the raw bytes of the file at the given path.
+    fn load(&self, path: &Path) -> std::io::Result<Vec<u8>>;
+}
+
+impl<F> SourceLoader for F
+where
+    F: Fn(&Path) -> std::io::Result<Vec<u8>> + Send + Sync,
+{
+    fn load(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        self(path)
+    }
+}
+
+impl std::fmt::Debug for dyn SourceLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SourceLoader")
+    }
+}
+
+/// Implementation of [`SourceLoader`] which reads files from the real file system.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct FsLoader;
+
+impl SourceLoader for FsLoader {
+    fn load(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+}
+
+/// Implementation of [`SourceLoader`] which reads files from an in-memory map, which is
+/// useful for tests and sandboxed environments without access to the file system.
+///
+/// Paths are matched exactly as they were inserted, without any normalization.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{FileSource, MemoryLoader, Source};
+///
+/// let loader = MemoryLoader::new().with_file("src/main.lm", "let a = 1;");
+///
+/// let source = FileSource::load_with("src/main.lm", &loader).unwrap();
+/// assert_eq!(source.name(), Some("src/main.lm"));
+///
+/// let err = FileSource::load_with("src/lib.lm", &loader).unwrap_err();
+/// assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct MemoryLoader {
+    files: HashMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryLoader {
+    /// Creates a new, empty [`MemoryLoader`].
+    pub fn new() -> Self {
+        MemoryLoader::default()
+    }
+
+    /// Adds the file at the given path, replacing any existing file at the same path.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        self.files.insert(path.into(), content.into());
+    }
+
+    /// Adds the file at the given path, replacing any existing file at the same path.
+    pub fn with_file(mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) -> Self {
+        self.insert(path, content);
+        self
+    }
+}
+
+impl SourceLoader for MemoryLoader {
+    fn load(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "file not found"))
+    }
+}
+
+/// Failure to load a source file, which is a diagnostic itself, so it can be
+/// reported through a [`crate::Handler`] just like any other diagnostic.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Diagnostic, FileSource, MemoryLoader};
+///
+/// let err = FileSource::load_with("src/main.lm", &MemoryLoader::new()).unwrap_err();
+///
+/// assert_eq!(err.message(), "failed to load source `src/main.lm`: file not found");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLoadError {
+    path: PathBuf,
+    kind: std::io::ErrorKind,
+    reason: String,
+}
+
+impl SourceLoadError {
+    /// Creates a new [`SourceLoadError`] for the file at the given path, from the error of the loader.
+    pub fn new(path: impl Into<PathBuf>, err: std::io::Error) -> Self {
+        Self {
+            path: path.into(),
+            kind: err.kind(),
+            reason: err.to_string(),
+        }
+    }
+
+    /// Gets the path of the file which failed to load.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the kind of the error, which was returned from the loader.
+    pub fn kind(&self) -> std::io::ErrorKind {
+        self.kind
+    }
+}
+
+impl Diagnostic for SourceLoadError {
+    fn message(&self) -> String {
+        format!("failed to load source `{}`: {}", self.path.display(), self.reason)
+    }
+}
+
+impl std::fmt::Display for SourceLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for SourceLoadError {}
+
+/// Represents a source file, which was read through a [`SourceLoader`].
+///
+/// The encoding of the file is detected from it's byte order mark, defaulting to UTF-8, where
+/// invalid sequences are replaced with `U+FFFD`, just like [`crate::BinarySource`]. The path
+/// of the file is used as the name of the source.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{FileSource, MemoryLoader, Source};
+///
+/// let loader = MemoryLoader::new().with_file("src/main.lm", b"\xEF\xBB\xBFlet a = 1;".to_vec());
+/// let source = FileSource::load_with("src/main.lm", &loader).unwrap();
+///
+/// assert_eq!(source.path().to_str(), Some("src/main.lm"));
+/// assert_eq!(source.text(), "let a = 1;");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSource {
+    path: PathBuf,
+    name: String,
+    content: String,
+}
+
+impl FileSource {
+    /// Loads the file at the given path from the real file system.
+    pub fn load(path: impl AsRef<Path>) -> Result<FileSource, SourceLoadError> {
+        FileSource::load_with(path, &FsLoader)
+    }
+
+    /// Loads the file at the given path, using the given loader.
+    pub fn load_with(path: impl AsRef<Path>, loader: &dyn SourceLoader) -> Result<FileSource, SourceLoadError> {
+        let path = path.as_ref();
+
+        let bytes = loader.load(path).map_err(|err| SourceLoadError::new(path, err))?;
+        let encoding = Encoding::detect(&bytes).unwrap_or_default();
+
+        Ok(FileSource {
+            path: path.to_path_buf(),
+            name: path.to_string_lossy().into_owned(),
+            content: encoding.decode(&bytes),
+        })
+    }
+
+    /// Gets the path which the file was loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Source for FileSource {
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str())
+    }
+
+    fn text(&self) -> &str {
+        self.content.as_str()
+    }
+}
+
+/// Collection of source files, which are loaded on demand through a [`SourceLoader`] and assigned a
+/// [`SourceId`], so labels can refer to them using [`crate::Label::in_source()`].
+///
+/// Each file is only loaded once, so loading the same path again returns the same identifier. Since
+/// the map implements [`SourceResolver`], it can be passed to a renderer directly. Loading takes
+/// a shared reference, so files can still be loaded after the map has been passed to a renderer.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{GraphicalRenderer, Label, MemoryLoader, Renderer, SimpleDiagnostic, SourceMap};
+///
+/// let loader = MemoryLoader::new().with_file("src/main.lm", "let a = b;");
+/// let sources = Arc::new(SourceMap::with_loader(Arc::new(loader)));
+///
+/// let id = sources.load("src/main.lm").unwrap();
+/// assert_eq!(sources.load("src/main.lm").unwrap(), id);
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.source_resolver = Some(sources.clone());
+///
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::in_source(id, 8..9, "not found in this scope"));
+///
+/// assert!(renderer.render(&diagnostic).unwrap().contains("src/main.lm:1:9"));
+/// ```
+#[derive(Debug)]
+pub struct SourceMap {
+    loader: Arc<dyn SourceLoader>,
+    files: Mutex<LoadedFiles>,
+}
+
+/// Defines all files, which have been loaded by a [`SourceMap`].
+#[derive(Default, Debug)]
+struct LoadedFiles {
+    /// Defines all loaded sources, where the index is the identifier of the source.
+    sources: Vec<Arc<dyn Source>>,
+
+    /// Defines the identifier of each loaded path.
+    ids: HashMap<PathBuf, SourceId>,
+}
+
+impl SourceMap {
+    /// Creates a new, empty [`SourceMap`], which loads files from the real file system.
+    pub fn new() -> Self {
+        SourceMap::with_loader(Arc::new(FsLoader))
+    }
+
+    /// Creates a new, empty [`SourceMap`], which loads files using the given loader.
+    pub fn with_loader(loader: Arc<dyn SourceLoader>) -> Self {
+        SourceMap {
+            loader,
+            files: Mutex::new(LoadedFiles::default()),
+        }
+    }
+
+    /// Loads the file at the given path, if it hasn't been loaded already, and returns it's identifier.
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<SourceId, SourceLoadError> {
+        let path = path.as_ref();
+
+        if let Some(id) = self.id_of(path) {
+            return Ok(id);
+        }
+
+        // The file is loaded without holding the lock, since loaders may be slow.
+        let source = FileSource::load_with(path, self.loader.as_ref())?;
+
+        let mut files = self.files.lock().unwrap_or_else(|err| err.into_inner());
+
+        // Another thread may have loaded the same file in the meantime.
+        if let Some(id) = files.ids.get(path) {
+            return Ok(*id);
+        }
+
+        let id = SourceId(files.sources.len() as u32);
+
+        files.sources.push(Arc::new(source));
+        files.ids.insert(path.to_path_buf(), id);
+
+        Ok(id)
+    }
+
+    /// Gets the identifier of the file at the given path, if it has been loaded.
+    pub fn id_of(&self, path: impl AsRef<Path>) -> Option<SourceId> {
+        let files = self.files.lock().unwrap_or_else(|err| err.into_inner());
+
+        files.ids.get(path.as_ref()).copied()
+    }
+
+    /// Gets the source with the given identifier, if it has been loaded.
+    pub fn get(&self, id: SourceId) -> Option<Arc<dyn Source>> {
+        let files = self.files.lock().unwrap_or_else(|err| err.into_inner());
+
+        files.sources.get(id.0 as usize).cloned()
+    }
+
+    /// Gets the amount of loaded files.
+    pub fn len(&self) -> usize {
+        self.files.lock().unwrap_or_else(|err| err.into_inner()).sources.len()
+    }
+
+    /// Determines whether no files have been loaded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for SourceMap {
+    fn default() -> Self {
+        SourceMap::new()
+    }
+}
+
+impl SourceResolver for SourceMap {
+    fn resolve(&self, id: SourceId) -> Option<Arc<dyn Source>> {
+        self.get(id)
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lsp_types::{
+    CreateFile, DeleteFile, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, DocumentChangeOperation,
+    DocumentChanges, Location, NumberOrString, OneOf, OptionalVersionedTextDocumentIdentifier, Position, RenameFile,
+    ResourceOp, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{Diagnostic, Label, Severity, Source, SourceResolver, Suggestion, Tag};
+
+/// Converts the given diagnostic into a [Language Server Protocol][lsp] diagnostic, along with
+/// the URI of the file which it should be published for.
+///
+/// The diagnostic is placed at the range of it's first label, while all other labels are attached as
+/// related information, along with the first label itself, if it has a message. Help messages are
+/// appended to the message of the diagnostic, while causes and related diagnostics are attached as
+/// related information at the location of their own first label.
+///
+/// The tags [`Tag::Unused`] and [`Tag::Deprecated`] are converted into the tags of the protocol, so
+/// editors can fade out or strike through the range, while other tags have no equivalent and are ignored.
+///
+/// Diagnostics without any labels, or whose source has no name or content, have no location
+/// to be published at, so `None` is returned.
+///
+/// [lsp]: https://microsoft.github.io/language-server-protocol/
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{to_lsp_diagnostic, Label, NamedSource, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("file:///src/main.lm", "fn main() {\n    let a = b;\n}"));
+///
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source), 24..25, "not found in this scope"));
+///
+/// let (uri, diagnostic) = to_lsp_diagnostic(&diagnostic).unwrap();
+///
+/// assert_eq!(uri.as_str(), "file:///src/main.lm");
+/// assert_eq!(diagnostic.range.start, lsp_types::Position::new(1, 12));
+/// assert_eq!(diagnostic.message, "cannot find value `b`");
+/// ```
+pub fn to_lsp_diagnostic(diagnostic: &dyn Diagnostic) -> Option<(Url, lsp_types::Diagnostic)> {
+    to_lsp_diagnostic_with(diagnostic, None)
+}
+
+/// Converts the given diagnostic into a [Language Server Protocol][lsp] diagnostic, like [`to_lsp_diagnostic()`],
+/// where labels which refer to their source by a [`crate::SourceId`] are resolved using the given resolver.
+///
+/// Without a resolver, or if the identifier can't be resolved, such labels have no location.
+///
+/// [lsp]: https://microsoft.github.io/language-server-protocol/
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{to_lsp_diagnostic_with, Label, NamedSource, SimpleDiagnostic, Source, SourceId};
+///
+/// let sources: Vec<Arc<dyn Source>> = vec![Arc::new(NamedSource::new("file:///src/main.lm", "let a = b;"))];
+///
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::in_source(SourceId(0), 8..9, "not found in this scope"));
+///
+/// let (uri, diagnostic) = to_lsp_diagnostic_with(&diagnostic, Some(&sources)).unwrap();
+///
+/// assert_eq!(uri.as_str(), "file:///src/main.lm");
+/// assert_eq!(diagnostic.range.start, lsp_types::Position::new(0, 8));
+/// ```
+pub fn to_lsp_diagnostic_with(
+    diagnostic: &dyn Diagnostic,
+    resolver: Option<&dyn SourceResolver>,
+) -> Option<(Url, lsp_types::Diagnostic)> {
+    let mut labels = diagnostic.labels()?;
+
+    let primary = labels.next()?;
+    let location = label_location(diagnostic, &primary, resolver)?;
+
+    let mut related = Vec::new();
+
+    for label in std::iter::once(primary).chain(labels) {
+        if label.message().is_empty() {
+            continue;
+        }
+
+        if let Some(location) = label_location(diagnostic, &label, resolver) {
+            related.push(DiagnosticRelatedInformation {
+                location,
+                message: label.message().to_string(),
+            });
+        }
+    }
+
+    for nested in diagnostic.causes().chain(diagnostic.related()) {
+        let Some(location) = nested
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .and_then(|label| label_location(nested, &label, resolver))
+        else {
+            continue;
+        };
+
+        related.push(DiagnosticRelatedInformation {
+            location,
+            message: nested.message(),
+        });
+    }
+
+    let mut message = diagnostic.message();
+
+    for help in diagnostic.help().into_iter().flatten() {
+        message.push_str("\nhelp: ");
+        message.push_str(&help.message);
+    }
+
+    let tags = diagnostic.tags().iter().filter_map(lsp_tag).collect::<Vec<_>>();
+
+    let converted = lsp_types::Diagnostic {
+        range: location.range,
+        severity: Some(lsp_severity(diagnostic.severity())),
+        code: diagnostic.code().map(|code| NumberOrString::String(code.to_string())),
+        message,
+        related_information: if related.is_empty() { None } else { Some(related) },
+        tags: if tags.is_empty() { None } else { Some(tags) },
+        ..Default::default()
+    };
+
+    Some((location.uri, converted))
+}
+
+/// Converts all the given diagnostics into [Language Server Protocol][lsp] diagnostics, grouped by the
+/// URI of their file, so each entry can be sent as a single `textDocument/publishDiagnostics` notification.
+///
+/// Diagnostics are converted using [`to_lsp_diagnostic()`], so causes and related diagnostics are merged
+/// into their parent as related information, instead of being published separately. Diagnostics without
+/// any location are skipped. Within each file, diagnostics retain the order they were given in.
+///
+/// Since a language server must publish an empty list to clear the diagnostics of a file, files which
+/// no longer have any diagnostics need to be tracked by the caller.
+///
+/// [lsp]: https://microsoft.github.io/language-server-protocol/
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{to_publish_diagnostics, CollectingHandler, Handler, Label, NamedSource, SimpleDiagnostic};
+///
+/// let main = Arc::new(NamedSource::new("file:///src/main.lm", "let a = b;"));
+/// let lib = Arc::new(NamedSource::new("file:///src/lib.lm", "let c = d;"));
+///
+/// let mut handler = CollectingHandler::new();
+/// handler.report(Box::new(SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(Some(main.clone()), 8..9, ""))));
+/// handler.report(Box::new(SimpleDiagnostic::new("unused variable `a`").with_label(Label::warning(Some(main), 4..5, ""))));
+/// handler.report(Box::new(SimpleDiagnostic::new("cannot find value `d`").with_label(Label::error(Some(lib), 8..9, ""))));
+///
+/// let published = to_publish_diagnostics(handler.reported());
+///
+/// assert_eq!(published.len(), 2);
+/// assert_eq!(published[&"file:///src/main.lm".parse().unwrap()].len(), 2);
+/// ```
+pub fn to_publish_diagnostics<'a>(
+    diagnostics: impl IntoIterator<Item = &'a dyn Diagnostic>,
+) -> HashMap<Url, Vec<lsp_types::Diagnostic>> {
+    to_publish_diagnostics_with(diagnostics, None)
+}
+
+/// Converts all the given diagnostics into [Language Server Protocol][lsp] diagnostics, grouped by the URI of their
+/// file, like [`to_publish_diagnostics()`], where diagnostics are converted using [`to_lsp_diagnostic_with()`].
+///
+/// [lsp]: https://microsoft.github.io/language-server-protocol/
+pub fn to_publish_diagnostics_with<'a>(
+    diagnostics: impl IntoIterator<Item = &'a dyn Diagnostic>,
+    resolver: Option<&dyn SourceResolver>,
+) -> HashMap<Url, Vec<lsp_types::Diagnostic>> {
+    let mut published: HashMap<Url, Vec<lsp_types::Diagnostic>> = HashMap::new();
+
+    for diagnostic in diagnostics {
+        if let Some((uri, converted)) = to_lsp_diagnostic_with(diagnostic, resolver) {
+            published.entry(uri).or_default().push(converted);
+        }
+    }
+
+    published
+}
+
+/// Converts the given suggestions into a [Language Server Protocol][lsp] workspace edit, which can be
+/// returned from a code action, so editors can apply the suggestions.
+///
+/// Insertions, deletions and replacements are grouped into a single text document edit per file,
+/// since all of their spans refer to the original content of the file. File-level operations are
+/// converted into resource operations and placed after all text edits, in the order they were given.
+/// The content of created files is inserted with a text edit, directly after the file is created.
+///
+/// Suggestions whose file has no name, or whose path cannot be converted into a URI, are skipped.
+///
+/// [lsp]: https://microsoft.github.io/language-server-protocol/
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{to_workspace_edit, NamedSource, SourceRange, Suggestion};
+/// use lsp_types::DocumentChanges;
+///
+/// let source = Arc::new(NamedSource::new("file:///src/main.lm", "struct Point {}"));
+///
+/// let suggestions = vec![
+///     Suggestion::delete(SourceRange::new(source, 0..15)),
+///     Suggestion::create_file("file:///src/point.lm", "struct Point {}"),
+/// ];
+///
+/// let edit = to_workspace_edit(&suggestions);
+///
+/// let Some(DocumentChanges::Operations(operations)) = edit.document_changes else { unreachable!() };
+/// assert_eq!(operations.len(), 3);
+/// ```
+pub fn to_workspace_edit<'a>(suggestions: impl IntoIterator<Item = &'a Suggestion>) -> WorkspaceEdit {
+    let mut edits: Vec<TextDocumentEdit> = Vec::new();
+    let mut resource_ops = Vec::new();
+
+    for suggestion in suggestions {
+        match suggestion {
+            Suggestion::Deletion { .. } | Suggestion::Insertion { .. } | Suggestion::Replacement { .. } => {
+                let Some((uri, edit)) = text_edit(suggestion) else {
+                    continue;
+                };
+
+                match edits.iter_mut().find(|edit| edit.text_document.uri == uri) {
+                    Some(existing) => existing.edits.push(OneOf::Left(edit)),
+                    None => edits.push(text_document_edit(uri, edit)),
+                }
+            }
+            Suggestion::CreateFile { path, content } => {
+                let Some(uri) = name_uri(path) else { continue };
+
+                resource_ops.push(DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                    uri: uri.clone(),
+                    options: None,
+                    annotation_id: None,
+                })));
+
+                if !content.is_empty() {
+                    let edit = TextEdit::new(lsp_types::Range::default(), content.clone());
+
+                    resource_ops.push(DocumentChangeOperation::Edit(text_document_edit(uri, edit)));
+                }
+            }
+            Suggestion::DeleteFile { path } => {
+                let Some(uri) = name_uri(path) else { continue };
+
+                resource_ops.push(DocumentChangeOperation::Op(ResourceOp::Delete(DeleteFile {
+                    uri,
+                    options: None,
+                })));
+            }
+            Suggestion::RenameFile { from, to } => {
+                let (Some(old_uri), Some(new_uri)) = (name_uri(from), name_uri(to)) else {
+                    continue;
+                };
+
+                resource_ops.push(DocumentChangeOperation::Op(ResourceOp::Rename(RenameFile {
+                    old_uri,
+                    new_uri,
+                    options: None,
+                    annotation_id: None,
+                })));
+            }
+        }
+    }
+
+    let operations = edits
+        .into_iter()
+        .map(DocumentChangeOperation::Edit)
+        .chain(resource_ops)
+        .collect();
+
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    }
+}
+
+/// Gets the URI of the given source, based on it's name.
+///
+/// Names which are already URIs, such as `file:///src/main.lm` or `untitled:Untitled-1`, are used as-is,
+/// while all other names are treated as file paths. Relative paths are resolved against the
+/// current working directory.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{source_uri, NamedSource};
+///
+/// let source = NamedSource::new("/src/main.lm", "let a = b;");
+///
+/// assert_eq!(source_uri(&source).unwrap().as_str(), "file:///src/main.lm");
+/// ```
+pub fn source_uri(source: &dyn Source) -> Option<Url> {
+    name_uri(source.name()?)
+}
+
+/// Gets the URI of the given source name or file path.
+///
+/// See [`source_uri()`] for more information.
+fn name_uri(name: &str) -> Option<Url> {
+    // Windows paths, such as `C:\src\main.lm`, parse as URIs with a single-letter scheme.
+    if let Ok(uri) = Url::parse(name) {
+        if uri.scheme().len() > 1 {
+            return Some(uri);
+        }
+    }
+
+    let path = std::path::Path::new(name);
+
+    if path.is_absolute() {
+        Url::from_file_path(path).ok()
+    } else {
+        Url::from_file_path(std::env::current_dir().ok()?.join(path)).ok()
+    }
+}
+
+/// Gets the location of the given label, where source identifiers are resolved using the given
+/// resolver and labels without any source use the source of the diagnostic.
+fn label_location(
+    diagnostic: &dyn Diagnostic,
+    label: &Label,
+    resolver: Option<&dyn SourceResolver>,
+) -> Option<Location> {
+    let source: Arc<dyn Source> = label.resolve_source_or(resolver, diagnostic.source_code())?;
+
+    let uri = source_uri(source.as_ref())?;
+    let content = source.try_text()?;
+
+    let range = label.range();
+    let start = lsp_position(content, range.start());
+    let end = lsp_position(content, range.end());
+
+    Some(Location::new(uri, lsp_types::Range::new(start, end)))
+}
+
+/// Converts the given insertion, deletion or replacement into a text edit, along with the URI of the file it applies to.
+fn text_edit(suggestion: &Suggestion) -> Option<(Url, TextEdit)> {
+    let source = suggestion.source()?;
+    let span = suggestion.span()?;
+
+    let uri = source_uri(source.as_ref())?;
+    let content = source.try_text()?;
+
+    // Insertions replace nothing, so their range is empty.
+    let (span, new_text) = match suggestion {
+        Suggestion::Insertion { location, value } => (location.offset()..location.offset(), value.clone()),
+        Suggestion::Replacement { replacement, .. } => (span, replacement.clone()),
+        _ => (span, String::new()),
+    };
+
+    let range = lsp_types::Range::new(lsp_position(content, span.start), lsp_position(content, span.end));
+
+    Some((uri, TextEdit::new(range, new_text)))
+}
+
+/// Creates a text document edit of the given file, which contains only the given edit.
+fn text_document_edit(uri: Url, edit: TextEdit) -> TextDocumentEdit {
+    TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits: vec![OneOf::Left(edit)],
+    }
+}
+
+/// Gets the zero-indexed position of the given byte offset, where the character is counted
+/// in UTF-16 code units, which is the default position encoding of the protocol.
+///
+/// Offsets past the end of the content are clamped to the end of the content.
+fn lsp_position(content: &str, offset: usize) -> Position {
+    let mut offset = offset.min(content.len());
+
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    let line = before.matches('\n').count();
+    let character = before[line_start..].encode_utf16().count();
+
+    Position::new(line as u32, character as u32)
+}
+
+/// Gets the severity of the protocol which corresponds to the given severity.
+fn lsp_severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Bug | Severity::Fatal | Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+        Severity::Note | Severity::Help => DiagnosticSeverity::HINT,
+    }
+}
+
+/// Gets the tag of the protocol which corresponds to the given tag, if any.
+fn lsp_tag(tag: &Tag) -> Option<DiagnosticTag> {
+    match tag {
+        Tag::Unused => Some(DiagnosticTag::UNNECESSARY),
+        Tag::Deprecated => Some(DiagnosticTag::DEPRECATED),
+        Tag::Security | Tag::Custom(_) => None,
+    }
+}
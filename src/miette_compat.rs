@@ -0,0 +1,274 @@
+//! Conversions between [`crate::Diagnostic`] and [`miette::Diagnostic`].
+//!
+//! This module is only available with the `miette-compat` feature, and is intended for
+//! crates which are migrating between the two libraries, so diagnostics can be passed across
+//! library boundaries without having to re-wrap them by hand.
+//!
+//! # Examples
+//!
+//! Converting a [`miette::Report`] into an [`Error`]:
+//!
+//! ```
+//! use error_snippet::{Diagnostic, Error};
+//!
+//! let report = miette::miette!(code = "E0001", "mismatched types");
+//! let error: Error = report.into();
+//!
+//! assert_eq!(error.message(), "mismatched types");
+//! assert_eq!(error.code().unwrap().to_string(), "E0001");
+//! ```
+//!
+//! Converting an [`Error`] into a [`miette::Report`]:
+//!
+//! ```
+//! use error_snippet::{Error, SimpleDiagnostic};
+//!
+//! let error: Error = SimpleDiagnostic::new("mismatched types").with_code("E0001").into();
+//! let report: miette::Report = error.into();
+//!
+//! assert_eq!(report.to_string(), "mismatched types");
+//! assert_eq!(report.code().unwrap().to_string(), "E0001");
+//! ```
+
+use std::sync::Arc;
+
+use miette::{LabeledSpan, SourceSpan};
+
+use crate::{Diagnostic, Error, Label, NamedSource, Severity, SimpleDiagnostic, Source, StringSource};
+
+impl From<Severity> for miette::Severity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Bug | Severity::Fatal | Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Info | Severity::Note | Severity::Help => miette::Severity::Advice,
+        }
+    }
+}
+
+impl From<miette::Severity> for Severity {
+    fn from(severity: miette::Severity) -> Self {
+        match severity {
+            miette::Severity::Error => Severity::Error,
+            miette::Severity::Warning => Severity::Warning,
+            miette::Severity::Advice => Severity::Help,
+        }
+    }
+}
+
+/// Reads the entire content of the given [`miette::SourceCode`] into a [`Source`].
+fn read_miette_source(source: &dyn miette::SourceCode) -> Option<Arc<dyn Source>> {
+    let contents = source.read_span(&SourceSpan::from(0..0), 0, usize::MAX).ok()?;
+    let content = String::from_utf8_lossy(contents.data()).into_owned();
+
+    match contents.name() {
+        Some(name) => Some(Arc::new(NamedSource::new(name, content))),
+        None => Some(Arc::new(StringSource::new(content))),
+    }
+}
+
+impl From<&dyn miette::Diagnostic> for SimpleDiagnostic {
+    /// Converts a [`miette::Diagnostic`] into a [`SimpleDiagnostic`], including
+    /// it's source code, labels, help message, related diagnostics and cause.
+    fn from(diagnostic: &dyn miette::Diagnostic) -> Self {
+        let mut converted = SimpleDiagnostic::new(diagnostic.to_string())
+            .with_severity(diagnostic.severity().map(Severity::from).unwrap_or_default());
+
+        if let Some(code) = diagnostic.code() {
+            converted = converted.with_code(code.to_string());
+        }
+
+        if let Some(help) = diagnostic.help() {
+            converted = converted.with_help(help.to_string());
+        }
+
+        let source = diagnostic.source_code().and_then(read_miette_source);
+
+        if let Some(labels) = diagnostic.labels() {
+            for label in labels {
+                let range = label.offset()..label.offset() + label.len();
+                let message = label.label().unwrap_or_default();
+
+                converted = converted.with_label(Label::new(source.clone(), range, message));
+            }
+        }
+
+        if let Some(related) = diagnostic.related() {
+            for related in related {
+                converted = converted.add_related(SimpleDiagnostic::from(related));
+            }
+        }
+
+        if let Some(cause) = diagnostic.diagnostic_source() {
+            converted = converted.add_cause(SimpleDiagnostic::from(cause));
+        }
+
+        converted
+    }
+}
+
+impl From<miette::Report> for Error {
+    fn from(report: miette::Report) -> Self {
+        let diagnostic: &dyn miette::Diagnostic = report.as_ref();
+
+        Box::new(SimpleDiagnostic::from(diagnostic))
+    }
+}
+
+/// Owned copy of a [`Diagnostic`], which implements [`miette::Diagnostic`].
+///
+/// Since [`miette::Diagnostic`] only supports a single source file per diagnostic, labels which
+/// refer to another source file than the diagnostic itself are not included in the copy.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use error_snippet::{Diagnostic, Label, MietteDiagnostic, NamedSource, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = 1 + true;"));
+/// let diagnostic = SimpleDiagnostic::new("mismatched types")
+///     .with_label(Label::error(Some(source), 12..16, "expected `Int`"));
+///
+/// let converted = MietteDiagnostic::from(&diagnostic as &dyn Diagnostic);
+/// let label = miette::Diagnostic::labels(&converted).unwrap().next().unwrap();
+///
+/// assert_eq!(label.label(), Some("expected `Int`"));
+/// assert_eq!(label.offset(), 12);
+/// assert_eq!(label.len(), 4);
+/// ```
+#[derive(Debug)]
+pub struct MietteDiagnostic {
+    message: String,
+    severity: Severity,
+    code: Option<String>,
+    help: Option<String>,
+    source: Option<miette::NamedSource<String>>,
+    labels: Vec<LabeledSpan>,
+    related: Vec<MietteDiagnostic>,
+    cause: Option<Box<MietteDiagnostic>>,
+}
+
+impl From<&dyn Diagnostic> for MietteDiagnostic {
+    fn from(diagnostic: &dyn Diagnostic) -> Self {
+        let labels = diagnostic.labels().map(|l| l.collect::<Vec<_>>()).unwrap_or_default();
+
+        // Use the source of the diagnostic itself, if any, otherwise use the first source
+        // attached to any of the labels.
+        let source = diagnostic
+            .source_code()
+            .or_else(|| labels.iter().find_map(|label| label.source()));
+
+        let labels = labels
+            .iter()
+            .filter(|label| match (label.source(), &source) {
+                (None, _) => true,
+                (Some(label_source), Some(source)) => Arc::ptr_eq(&label_source, source),
+                (Some(_), None) => false,
+            })
+            .map(|label| {
+                let message = Some(label.message().to_string()).filter(|m| !m.is_empty());
+                let span = SourceSpan::from(label.range().0.clone());
+
+                LabeledSpan::new_with_span(message, span)
+            })
+            .collect();
+
+        let help = diagnostic
+            .help()
+            .map(|help| help.map(|h| h.message).collect::<Vec<_>>().join("\n"))
+            .filter(|help| !help.is_empty());
+
+        let source = source.map(|source| {
+            let name = source.name().unwrap_or_default().to_string();
+            let content = source.text().to_string();
+
+            miette::NamedSource::new(name, content)
+        });
+
+        Self {
+            message: diagnostic.message(),
+            severity: diagnostic.severity(),
+            code: diagnostic.code().map(|code| code.to_string()),
+            help,
+            source,
+            labels,
+            related: diagnostic
+                .related()
+                .map(|r| MietteDiagnostic::from(r as &dyn Diagnostic))
+                .collect(),
+            cause: diagnostic
+                .causes()
+                .next()
+                .map(|cause| Box::new(MietteDiagnostic::from(cause as &dyn Diagnostic))),
+        }
+    }
+}
+
+impl From<Error> for MietteDiagnostic {
+    fn from(error: Error) -> Self {
+        MietteDiagnostic::from(error.as_ref() as &dyn Diagnostic)
+    }
+}
+
+impl From<Error> for miette::Report {
+    fn from(error: Error) -> Self {
+        miette::Report::new(MietteDiagnostic::from(error))
+    }
+}
+
+impl std::fmt::Display for MietteDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for MietteDiagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl miette::Diagnostic for MietteDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.code
+            .as_ref()
+            .map(|code| Box::new(code) as Box<dyn std::fmt::Display>)
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(self.severity.into())
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|help| Box::new(help) as Box<dyn std::fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.source.as_ref().map(|source| source as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            return None;
+        }
+
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+        if self.related.is_empty() {
+            return None;
+        }
+
+        Some(Box::new(self.related.iter().map(|r| r as &dyn miette::Diagnostic)))
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn miette::Diagnostic> {
+        self.cause.as_deref().map(|cause| cause as &dyn miette::Diagnostic)
+    }
+}
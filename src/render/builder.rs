@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+use super::{
+    ColumnPolicy, ContentFilter, GraphicalRenderer, LabelOrder, LabelPosition, Strings, SuggestionStyle,
+    SyntaxHighlighter, Theme,
+};
+use crate::{Severity, SourceResolver};
+
+/// Represents an invalid combination of options, which was given to a [`GraphicalRendererBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RendererConfigError {
+    /// Defines that the given option only has an effect when colors are used, but colors are disabled.
+    RequiresColors(&'static str),
+
+    /// Defines that the width of the renderer is zero.
+    ZeroWidth,
+
+    /// Defines that the template of code URLs doesn't contain the `{code}` placeholder.
+    MissingCodePlaceholder(String),
+
+    /// Defines that the amount of related diagnostics to render in full exceeds the
+    /// maximum amount of related diagnostics to render at all.
+    RelatedLimits {
+        /// Defines the maximum amount of related diagnostics to render in full.
+        full: usize,
+
+        /// Defines the maximum amount of related diagnostics to render.
+        max: usize,
+    },
+}
+
+impl std::error::Error for RendererConfigError {}
+
+impl std::fmt::Display for RendererConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RequiresColors(option) => write!(f, "`{option}` requires colors to be enabled"),
+            Self::ZeroWidth => write!(f, "width must be greater than zero"),
+            Self::MissingCodePlaceholder(template) => {
+                write!(f, "code URL template `{template}` doesn't contain `{{code}}`")
+            }
+            Self::RelatedLimits { full, max } => write!(
+                f,
+                "cannot render {full} related diagnostics in full, when at most {max} are rendered"
+            ),
+        }
+    }
+}
+
+/// Builder for [`GraphicalRenderer`], which validates the combination of options before
+/// creating the renderer.
+///
+/// All options start out with the same defaults as [`GraphicalRenderer::new()`]. Options
+/// which would silently have no effect, such as highlighting the source without any colors,
+/// are rejected by [`GraphicalRendererBuilder::build()`].
+///
+/// # Examples
+/// ```
+/// use error_snippet::{GraphicalRenderer, LabelPosition, RendererConfigError, Theme};
+///
+/// let renderer = GraphicalRenderer::builder()
+///     .theme(Theme::ascii())
+///     .context_lines(2)
+///     .label_position(LabelPosition::Above)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(renderer.context_lines, 2);
+///
+/// let err = GraphicalRenderer::builder().use_colors(false).highlight_source(true).build();
+///
+/// assert_eq!(err.unwrap_err(), RendererConfigError::RequiresColors("highlight_source"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GraphicalRendererBuilder {
+    renderer: GraphicalRenderer,
+}
+
+impl GraphicalRenderer {
+    /// Creates a new [`GraphicalRendererBuilder`] with default settings.
+    pub fn builder() -> GraphicalRendererBuilder {
+        GraphicalRendererBuilder::new()
+    }
+}
+
+impl GraphicalRendererBuilder {
+    /// Creates a new [`GraphicalRendererBuilder`] with default settings.
+    pub fn new() -> Self {
+        GraphicalRendererBuilder {
+            renderer: GraphicalRenderer::new(),
+        }
+    }
+
+    /// Sets the theme of the renderer. See [`GraphicalRenderer::theme`].
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.renderer.theme = theme;
+        self
+    }
+
+    /// Sets the maximum length of the terminal. See [`GraphicalRenderer::width`].
+    pub fn width(mut self, width: usize) -> Self {
+        self.renderer.width = width;
+        self
+    }
+
+    /// Sets the padding per level of indentation. See [`GraphicalRenderer::padding`].
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.renderer.padding = padding;
+        self
+    }
+
+    /// Sets the margin in the gutter of snippets. See [`GraphicalRenderer::gutter_margin`].
+    pub fn gutter_margin(mut self, margin: usize) -> Self {
+        self.renderer.gutter_margin = margin;
+        self
+    }
+
+    /// Sets whether to display line numbers. See [`GraphicalRenderer::show_line_numbers`].
+    pub fn show_line_numbers(mut self, show: bool) -> Self {
+        self.renderer.show_line_numbers = show;
+        self
+    }
+
+    /// Sets the amount of context lines around labels. See [`GraphicalRenderer::context_lines`].
+    pub fn context_lines(mut self, lines: usize) -> Self {
+        self.renderer.context_lines = lines;
+        self
+    }
+
+    /// Sets the distance for merging nearby snippets. See [`GraphicalRenderer::merge_distance`].
+    pub fn merge_distance(mut self, distance: usize) -> Self {
+        self.renderer.merge_distance = Some(distance);
+        self
+    }
+
+    /// Sets the maximum amount of lines per label snippet. See [`GraphicalRenderer::max_label_lines`].
+    pub fn max_label_lines(mut self, lines: usize) -> Self {
+        self.renderer.max_label_lines = Some(lines);
+        self
+    }
+
+    /// Sets the maximum amount of lines of multiline labels before folding. See [`GraphicalRenderer::fold_label_lines`].
+    pub fn fold_label_lines(mut self, lines: usize) -> Self {
+        self.renderer.fold_label_lines = Some(lines);
+        self
+    }
+
+    /// Sets the amount of lines to render for diagnostics without labels. See [`GraphicalRenderer::excerpt_lines`].
+    pub fn excerpt_lines(mut self, lines: usize) -> Self {
+        self.renderer.excerpt_lines = Some(lines);
+        self
+    }
+
+    /// Sets whether to use colors. See [`GraphicalRenderer::use_colors`].
+    pub fn use_colors(mut self, use_colors: bool) -> Self {
+        self.renderer.use_colors = use_colors;
+        self
+    }
+
+    /// Sets whether to highlight labelled spans. See [`GraphicalRenderer::highlight_source`].
+    ///
+    /// This requires colors to be enabled.
+    pub fn highlight_source(mut self, highlight: bool) -> Self {
+        self.renderer.highlight_source = highlight;
+        self
+    }
+
+    /// Sets how offsets are converted into columns. See [`GraphicalRenderer::column_policy`].
+    pub fn column_policy(mut self, policy: ColumnPolicy) -> Self {
+        self.renderer.column_policy = policy;
+        self
+    }
+
+    /// Sets whether to show the amount of nested diagnostics. See [`GraphicalRenderer::show_related_count`].
+    pub fn show_related_count(mut self, show: bool) -> Self {
+        self.renderer.show_related_count = show;
+        self
+    }
+
+    /// Sets the maximum amount of related diagnostics. See [`GraphicalRenderer::max_related`].
+    pub fn max_related(mut self, max: usize) -> Self {
+        self.renderer.max_related = Some(max);
+        self
+    }
+
+    /// Sets the maximum amount of related diagnostics in full. See [`GraphicalRenderer::max_full_related`].
+    ///
+    /// This must not exceed the maximum amount of related diagnostics, if any.
+    pub fn max_full_related(mut self, max: usize) -> Self {
+        self.renderer.max_full_related = Some(max);
+        self
+    }
+
+    /// Sets the minimum severity of nested diagnostics. See [`GraphicalRenderer::min_nested_severity`].
+    pub fn min_nested_severity(mut self, severity: Severity) -> Self {
+        self.renderer.min_nested_severity = Some(severity);
+        self
+    }
+
+    /// Sets the filter to apply to source lines. See [`GraphicalRenderer::content_filter`].
+    pub fn content_filter(mut self, filter: Arc<dyn ContentFilter>) -> Self {
+        self.renderer.content_filter = Some(filter);
+        self
+    }
+
+    /// Sets how suggestions are displayed. See [`GraphicalRenderer::suggestion_style`].
+    pub fn suggestion_style(mut self, style: SuggestionStyle) -> Self {
+        self.renderer.suggestion_style = style;
+        self
+    }
+
+    /// Sets the syntax highlighter of source lines. See [`GraphicalRenderer::syntax_highlighter`].
+    ///
+    /// This requires colors to be enabled.
+    pub fn syntax_highlighter(mut self, highlighter: Arc<dyn SyntaxHighlighter>) -> Self {
+        self.renderer.syntax_highlighter = Some(highlighter);
+        self
+    }
+
+    /// Sets the resolver of source identifiers. See [`GraphicalRenderer::source_resolver`].
+    pub fn source_resolver(mut self, resolver: Arc<dyn SourceResolver>) -> Self {
+        self.renderer.source_resolver = Some(resolver);
+        self
+    }
+
+    /// Sets the template of URLs to link codes to. See [`GraphicalRenderer::code_url_template`].
+    ///
+    /// This requires colors to be enabled and the template to contain a `{code}` placeholder.
+    pub fn code_url_template(mut self, template: impl Into<String>) -> Self {
+        self.renderer.code_url_template = Some(template.into());
+        self
+    }
+
+    /// Sets where label underlines are placed. See [`GraphicalRenderer::label_position`].
+    pub fn label_position(mut self, position: LabelPosition) -> Self {
+        self.renderer.label_position = position;
+        self
+    }
+
+    /// Sets the order in which labels are rendered. See [`GraphicalRenderer::label_order`].
+    pub fn label_order(mut self, order: LabelOrder) -> Self {
+        self.renderer.label_order = order;
+        self
+    }
+
+    /// Sets the phrases displayed around diagnostics. See [`GraphicalRenderer::strings`].
+    pub fn strings(mut self, strings: Strings) -> Self {
+        self.renderer.strings = strings;
+        self
+    }
+
+    /// Validates the combination of options and creates the renderer.
+    pub fn build(self) -> Result<GraphicalRenderer, RendererConfigError> {
+        let renderer = self.renderer;
+
+        if renderer.width == 0 {
+            return Err(RendererConfigError::ZeroWidth);
+        }
+
+        if !renderer.use_colors {
+            if renderer.highlight_source {
+                return Err(RendererConfigError::RequiresColors("highlight_source"));
+            }
+
+            if renderer.syntax_highlighter.is_some() {
+                return Err(RendererConfigError::RequiresColors("syntax_highlighter"));
+            }
+
+            if renderer.code_url_template.is_some() {
+                return Err(RendererConfigError::RequiresColors("code_url_template"));
+            }
+        }
+
+        if let Some(template) = &renderer.code_url_template {
+            if !template.contains("{code}") {
+                return Err(RendererConfigError::MissingCodePlaceholder(template.clone()));
+            }
+        }
+
+        if let (Some(full), Some(max)) = (renderer.max_full_related, renderer.max_related) {
+            if full > max {
+                return Err(RendererConfigError::RelatedLimits { full, max });
+            }
+        }
+
+        Ok(renderer)
+    }
+}
@@ -0,0 +1,282 @@
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Defines how byte offsets are converted into display columns.
+///
+/// The same policy is used by [`crate::GraphicalRenderer`] when placing label markers,
+/// so external tools using [`display_column()`] with the same policy will always compute
+/// the same columns as the rendered output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnPolicy {
+    /// Defines how many columns a single tab character occupies.
+    pub tab_width: usize,
+
+    /// Defines whether to use the display width of characters, so wide characters
+    /// (such as CJK characters and emojis) occupy two columns.
+    ///
+    /// If `false`, each grapheme cluster occupies a single column.
+    pub unicode_width: bool,
+
+    /// Defines whether invisible characters, such as zero-width spaces, bidirectional overrides and
+    /// byte order marks, are displayed as their code point, such as `<U+200B>`. The placeholder occupies
+    /// as many columns as it's length, so labels after it still point at the correct columns.
+    ///
+    /// This is useful for diagnostics about confusable identifiers or "Trojan Source" attacks,
+    /// where the offending characters would otherwise be impossible to spot.
+    pub show_invisible: bool,
+}
+
+impl Default for ColumnPolicy {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            unicode_width: true,
+            show_invisible: false,
+        }
+    }
+}
+
+impl ColumnPolicy {
+    /// Gets the amount of columns which the given grapheme cluster occupies.
+    fn width_of(&self, grapheme: &str) -> usize {
+        if self.show_invisible && grapheme.chars().any(is_invisible) {
+            // Invisible characters may be part of a larger cluster, such as zero-width joiners within emojis,
+            // so the visible characters of the cluster are measured on their own.
+            let visible = grapheme.chars().filter(|c| !is_invisible(*c)).collect::<String>();
+            let placeholders = grapheme
+                .chars()
+                .filter(|c| is_invisible(*c))
+                .map(|c| invisible_placeholder(c).len())
+                .sum::<usize>();
+
+            return placeholders + if visible.is_empty() { 0 } else { self.width_of(&visible) };
+        }
+
+        if grapheme == "\t" {
+            self.tab_width
+        } else if self.unicode_width {
+            grapheme.width()
+        } else {
+            1
+        }
+    }
+}
+
+/// Gets the zero-indexed display column of the given byte offset, within the line which contains it.
+///
+/// Tabs are expanded to [`ColumnPolicy::tab_width`] columns, wide characters occupy two columns (if enabled)
+/// and grapheme clusters are never split, so an offset inside of a cluster refers to the start of the cluster.
+/// Offsets past the end of the source are clamped to the end of the source.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{display_column, ColumnPolicy};
+///
+/// let policy = ColumnPolicy::default();
+///
+/// assert_eq!(display_column("let a = 1;", 4, policy), 4);
+/// assert_eq!(display_column("let a = 1;\nlet b = 2;", 15, policy), 4);
+/// assert_eq!(display_column("\tlet a = 1;", 1, policy), 4);
+/// assert_eq!(display_column("let 名前 = 1;", 10, policy), 8);
+/// ```
+pub fn display_column(source: &str, offset: usize, policy: ColumnPolicy) -> usize {
+    let mut offset = offset.min(source.len());
+
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = &source[line_start..];
+
+    let mut column = 0;
+
+    for (idx, grapheme) in line.grapheme_indices(true) {
+        if line_start + idx + grapheme.len() > offset {
+            break;
+        }
+
+        column += policy.width_of(grapheme);
+    }
+
+    column
+}
+
+/// Gets the display width of the given line of text.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{display_width, ColumnPolicy};
+///
+/// assert_eq!(display_width("\tlet 名前", ColumnPolicy::default()), 12);
+/// ```
+pub fn display_width(line: &str, policy: ColumnPolicy) -> usize {
+    line.graphemes(true).map(|g| policy.width_of(g)).sum()
+}
+
+/// Expands all tabs in the given line into spaces, as defined by the policy.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{expand_tabs, ColumnPolicy};
+///
+/// assert_eq!(expand_tabs("\tlet a = 1;", ColumnPolicy::default()), "    let a = 1;");
+/// ```
+pub fn expand_tabs(line: &str, policy: ColumnPolicy) -> Cow<'_, str> {
+    if line.contains('\t') {
+        Cow::Owned(line.replace('\t', &" ".repeat(policy.tab_width)))
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+/// Determines whether the given character is invisible when displayed, such as zero-width
+/// characters, bidirectional control characters and byte order marks.
+///
+/// # Examples
+/// ```
+/// use error_snippet::is_invisible;
+///
+/// assert!(is_invisible('\u{200B}'));
+/// assert!(is_invisible('\u{202E}'));
+/// assert!(!is_invisible(' '));
+/// ```
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}'
+            | '\u{061C}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{FEFF}'
+    )
+}
+
+/// Gets the placeholder which is displayed in place of the given invisible character, such as `<U+200B>`.
+fn invisible_placeholder(c: char) -> String {
+    format!("<U+{:04X}>", c as u32)
+}
+
+/// Replaces all invisible characters in the given line with their code point, such as `<U+200B>`,
+/// if enabled by the policy.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{visualize_invisible, ColumnPolicy};
+///
+/// let policy = ColumnPolicy { show_invisible: true, ..ColumnPolicy::default() };
+///
+/// assert_eq!(visualize_invisible("let a\u{200B} = 1;", policy), "let a<U+200B> = 1;");
+/// assert_eq!(visualize_invisible("let a\u{200B} = 1;", ColumnPolicy::default()), "let a\u{200B} = 1;");
+/// ```
+pub fn visualize_invisible(line: &str, policy: ColumnPolicy) -> Cow<'_, str> {
+    visualize_invisible_with_ranges(line, policy).0
+}
+
+/// Replaces all invisible characters in the given line with their code point, if enabled by the policy,
+/// along with the character ranges of each placeholder within the returned line.
+pub(crate) fn visualize_invisible_with_ranges(
+    line: &str,
+    policy: ColumnPolicy,
+) -> (Cow<'_, str>, Vec<std::ops::Range<usize>>) {
+    if !policy.show_invisible || !line.chars().any(is_invisible) {
+        return (Cow::Borrowed(line), Vec::new());
+    }
+
+    let mut visualized = String::with_capacity(line.len());
+    let mut ranges = Vec::new();
+    let mut char_idx = 0;
+
+    for c in line.chars() {
+        if is_invisible(c) {
+            let placeholder = invisible_placeholder(c);
+
+            ranges.push(char_idx..char_idx + placeholder.len());
+            char_idx += placeholder.len();
+            visualized.push_str(&placeholder);
+        } else {
+            char_idx += 1;
+            visualized.push(c);
+        }
+    }
+
+    (Cow::Owned(visualized), ranges)
+}
+
+/// Converts the given range of display columns into a range of character indices within the line.
+///
+/// This assumes the line has already had it's tabs expanded, using [`expand_tabs()`].
+pub(crate) fn char_range_of_columns(
+    line: &str,
+    columns: std::ops::Range<usize>,
+    policy: ColumnPolicy,
+) -> std::ops::Range<usize> {
+    let mut column = 0;
+    let mut char_idx = 0;
+
+    let mut start = None;
+    let mut end = None;
+
+    for grapheme in line.graphemes(true) {
+        if start.is_none() && column >= columns.start {
+            start = Some(char_idx);
+        }
+
+        if end.is_none() && column >= columns.end {
+            end = Some(char_idx);
+        }
+
+        column += policy.width_of(grapheme);
+        char_idx += grapheme.chars().count();
+    }
+
+    let start = start.unwrap_or(char_idx);
+    let end = end.unwrap_or(char_idx).max(start);
+
+    start..end
+}
+
+/// Wraps the given line of text at word boundaries, so no line exceeds the given display width.
+///
+/// Leading whitespace is kept on the first line only. Words which are wider than the given width
+/// are never broken up, so they're placed on a line of their own instead. If the line already
+/// fits within the width, or the width is zero, the line is returned as-is.
+pub(crate) fn wrap_words(line: &str, width: usize, policy: ColumnPolicy) -> Vec<Cow<'_, str>> {
+    if width == 0 || display_width(line, policy) <= width {
+        return vec![Cow::Borrowed(line)];
+    }
+
+    let content = line.trim_start();
+    let mut current = line[..line.len() - content.len()].to_string();
+    let mut current_width = display_width(&current, policy);
+    let mut has_word = false;
+
+    let mut lines = Vec::new();
+
+    for word in content.split_whitespace() {
+        let word_width = display_width(word, policy);
+
+        if has_word && current_width + 1 + word_width > width {
+            lines.push(Cow::Owned(std::mem::take(&mut current)));
+            current_width = 0;
+            has_word = false;
+        }
+
+        if has_word {
+            current.push(' ');
+            current_width += 1;
+        }
+
+        current.push_str(word);
+        current_width += word_width;
+        has_word = true;
+    }
+
+    lines.push(Cow::Owned(current));
+    lines
+}
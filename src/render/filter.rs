@@ -0,0 +1,100 @@
+use std::borrow::Cow;
+
+/// Defines a hook for transforming source text before it's displayed in a snippet,
+/// such as masking secrets or tokens embedded in configuration files.
+///
+/// The filter is invoked once per displayed line of source code, without the trailing newline.
+/// Since labels are positioned using the original source, filters should preserve the width
+/// of the line, so markers still point at the correct columns.
+///
+/// Any function or closure of `Fn(&str) -> String` implements [`ContentFilter`].
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{ContentFilter, GraphicalRenderer, Label, NamedSource, Renderer, SimpleDiagnostic};
+///
+/// let filter = |line: &str| line.replace("hunter2", "*******");
+/// assert_eq!(filter.filter("password = hunter2"), "password = *******");
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.content_filter = Some(Arc::new(filter));
+///
+/// let source = Arc::new(NamedSource::new("config.toml", "password = hunter2"));
+/// let diagnostic = SimpleDiagnostic::new("invalid password")
+///     .with_label(Label::error(Some(source), 11..18, "password too short"));
+///
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.contains("password = *******"));
+/// assert!(!rendered.contains("hunter2"));
+/// ```
+pub trait ContentFilter: Send + Sync {
+    /// Transforms the given line of source code, before it's displayed.
+    fn filter<'a>(&self, line: &'a str) -> Cow<'a, str>;
+}
+
+impl<F> ContentFilter for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn filter<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(line))
+    }
+}
+
+impl std::fmt::Debug for dyn ContentFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ContentFilter")
+    }
+}
+
+/// Content filter which masks all matches of a regular expression.
+///
+/// Each character within a match is replaced with the mask character,
+/// so the width of the line is preserved.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{ContentFilter, RegexFilter};
+///
+/// let filter = RegexFilter::new(r"ghp_[A-Za-z0-9]+").unwrap();
+///
+/// assert_eq!(filter.filter("token = \"ghp_abc123\""), "token = \"**********\"");
+/// assert_eq!(filter.with_mask('#').filter("ghp_x"), "#####");
+/// ```
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct RegexFilter {
+    pattern: regex::Regex,
+    mask: char,
+}
+
+#[cfg(feature = "regex")]
+impl RegexFilter {
+    /// Creates a new [`RegexFilter`], which masks all matches of the given pattern with `*`.
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: regex::Regex::new(pattern)?,
+            mask: '*',
+        })
+    }
+
+    /// Sets the character which replaces each character within a match.
+    pub fn with_mask(mut self, mask: char) -> Self {
+        self.mask = mask;
+        self
+    }
+}
+
+#[cfg(feature = "regex")]
+impl ContentFilter for RegexFilter {
+    fn filter<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        self.pattern.replace_all(line, |captures: &regex::Captures<'_>| {
+            let length = captures[0].chars().count();
+
+            std::iter::repeat_n(self.mask, length).collect::<String>()
+        })
+    }
+}
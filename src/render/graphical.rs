@@ -1,19 +1,32 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::ops::Range;
 use std::sync::Arc;
 
 use indexmap::IndexMap;
 use owo_colors::{OwoColorize, Style, Styled};
-
-use super::Formatter;
+use unicode_width::UnicodeWidthChar;
+
+use super::column::{
+    char_range_of_columns, display_width, expand_tabs, visualize_invisible_with_ranges, wrap_words, ColumnPolicy,
+};
+use super::layout::{
+    clamp_span_to_lines, group_overlapping_labels, merge_nearby_contexts, normalize_span, LabelContext, LineIndex, Span,
+};
+pub use super::layout::{extract_with_context, extract_with_context_offset};
+use super::report::SegmentWriter;
+use super::{BatchOptions, ContentFilter, Formatter, RenderSession, Report, StripAnsi, SyntaxHighlighter, Tee};
 use crate::render::Renderer;
-use crate::{Diagnostic, Help, Label, Severity, Source, SpanRange, Suggestion};
+use crate::{
+    Diagnostic, Help, Label, Severity, Source, SourceLocation, SourceRange, SourceResolver, SpanRange, Suggestion,
+};
 
 const DEFAULT_TERM_WIDTH: usize = 80;
 
 #[derive(Debug, Clone)]
 pub struct ThemeStyle {
+    pub bug: Style,
+    pub fatal: Style,
     pub error: Style,
     pub warning: Style,
     pub info: Style,
@@ -31,6 +44,8 @@ impl ThemeStyle {
     /// Defines a preset which utilizes RGB colors within the terminal.
     pub fn rgb() -> Self {
         ThemeStyle {
+            bug: Style::new().fg_rgb::<203, 119, 236>().bold(),
+            fatal: Style::new().fg_rgb::<233, 114, 99>().bold().underline(),
             error: Style::new().fg_rgb::<233, 114, 99>().bold(),
             warning: Style::new().fg_rgb::<235, 191, 131>().bold(),
             info: Style::new().fg_rgb::<114, 159, 207>(),
@@ -45,9 +60,59 @@ impl ThemeStyle {
         }
     }
 
+    /// Defines a preset which utilizes RGB colors, tuned for readability on dark backgrounds.
+    ///
+    /// This is the same as [`ThemeStyle::rgb()`].
+    pub fn dark() -> Self {
+        ThemeStyle::rgb()
+    }
+
+    /// Defines a preset which utilizes RGB colors, tuned for readability on light backgrounds.
+    ///
+    /// Compared to [`ThemeStyle::dark()`], the colors are darker and more saturated, since
+    /// pastel colors, such as the yellow of warnings, are barely visible on white.
+    pub fn light() -> Self {
+        ThemeStyle {
+            bug: Style::new().fg_rgb::<136, 57, 239>().bold(),
+            fatal: Style::new().fg_rgb::<192, 28, 40>().bold().underline(),
+            error: Style::new().fg_rgb::<192, 28, 40>().bold(),
+            warning: Style::new().fg_rgb::<154, 103, 0>().bold(),
+            info: Style::new().fg_rgb::<30, 102, 180>(),
+            note: Style::new().fg_rgb::<30, 128, 60>(),
+            help: Style::new().fg_rgb::<94, 64, 190>(),
+
+            deletion: Style::new().fg_rgb::<192, 28, 40>(),
+            insertion: Style::new().fg_rgb::<30, 128, 60>(),
+
+            link: Style::new().fg_rgb::<70, 78, 110>(),
+            gutter: Style::new().fg_rgb::<110, 110, 140>(),
+        }
+    }
+
+    /// Defines a monochrome preset, which doesn't apply any colors or text effects.
+    pub fn none() -> Self {
+        ThemeStyle {
+            bug: Style::new(),
+            fatal: Style::new(),
+            error: Style::new(),
+            warning: Style::new(),
+            info: Style::new(),
+            note: Style::new(),
+            help: Style::new(),
+
+            deletion: Style::new(),
+            insertion: Style::new(),
+
+            link: Style::new(),
+            gutter: Style::new(),
+        }
+    }
+
     /// Defines a preset which utilizes ANSI color codes within the terminal.
     pub fn ansi() -> Self {
         ThemeStyle {
+            bug: Style::new().bright_magenta().bold(),
+            fatal: Style::new().bright_red().bold().underline(),
             error: Style::new().bright_red().bold(),
             warning: Style::new().bright_yellow().bold(),
             info: Style::new().bright_blue().bold(),
@@ -65,6 +130,8 @@ impl ThemeStyle {
     /// Retrieves the style which is utilized for the given severity.
     pub fn from_severity(&self, severity: Severity) -> Style {
         match severity {
+            Severity::Bug => self.bug,
+            Severity::Fatal => self.fatal,
             Severity::Error => self.error,
             Severity::Warning => self.warning,
             Severity::Info => self.info,
@@ -76,35 +143,218 @@ impl ThemeStyle {
 
 #[derive(Debug, Clone)]
 pub struct ThemeSymbols {
-    pub error: &'static str,
-    pub warning: &'static str,
-    pub info: &'static str,
-    pub note: &'static str,
-    pub help: &'static str,
+    pub bug: String,
+    pub fatal: String,
+    pub error: String,
+    pub warning: String,
+    pub info: String,
+    pub note: String,
+    pub help: String,
 }
 
 impl ThemeSymbols {
     pub fn unicode() -> Self {
         ThemeSymbols {
-            error: "×",
-            warning: "⚠",
-            info: "☞",
-            note: "☞",
-            help: "☞",
+            bug: "×".into(),
+            fatal: "×".into(),
+            error: "×".into(),
+            warning: "⚠".into(),
+            info: "☞".into(),
+            note: "☞".into(),
+            help: "☞".into(),
+        }
+    }
+
+    /// Defines a preset which only uses ASCII characters, for terminals without Unicode support.
+    pub fn ascii() -> Self {
+        ThemeSymbols {
+            bug: "x".into(),
+            fatal: "x".into(),
+            error: "x".into(),
+            warning: "!".into(),
+            info: "*".into(),
+            note: "*".into(),
+            help: "*".into(),
         }
     }
 
-    pub fn from_severity(&self, severity: Severity) -> &'static str {
+    pub fn from_severity(&self, severity: Severity) -> &str {
         match severity {
-            Severity::Error => self.error,
-            Severity::Warning => self.warning,
-            Severity::Info => self.info,
-            Severity::Note => self.note,
-            Severity::Help => self.help,
+            Severity::Bug => &self.bug,
+            Severity::Fatal => &self.fatal,
+            Severity::Error => &self.error,
+            Severity::Warning => &self.warning,
+            Severity::Info => &self.info,
+            Severity::Note => &self.note,
+            Severity::Help => &self.help,
+        }
+    }
+}
+
+/// Defines the names of each severity, as displayed in the headers and footers of diagnostics.
+///
+/// The names can be overridden for localization or for domain-specific wording.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{GraphicalRenderer, Renderer, Severity, SimpleDiagnostic, ThemeNames};
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.theme.names = ThemeNames {
+///     error: "violation".into(),
+///     ..ThemeNames::english()
+/// };
+///
+/// let rendered = renderer.render(&SimpleDiagnostic::new("line too long")).unwrap();
+///
+/// assert_eq!(rendered, "× violation: line too long\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThemeNames {
+    pub bug: String,
+    pub fatal: String,
+    pub error: String,
+    pub warning: String,
+    pub info: String,
+    pub note: String,
+    pub help: String,
+}
+
+impl ThemeNames {
+    /// Defines a preset with the english names of each severity.
+    pub fn english() -> Self {
+        ThemeNames {
+            bug: Severity::Bug.to_string(),
+            fatal: Severity::Fatal.to_string(),
+            error: Severity::Error.to_string(),
+            warning: Severity::Warning.to_string(),
+            info: Severity::Info.to_string(),
+            note: Severity::Note.to_string(),
+            help: Severity::Help.to_string(),
+        }
+    }
+
+    /// Retrieves the name which is displayed for the given severity.
+    pub fn from_severity(&self, severity: Severity) -> &str {
+        match severity {
+            Severity::Bug => &self.bug,
+            Severity::Fatal => &self.fatal,
+            Severity::Error => &self.error,
+            Severity::Warning => &self.warning,
+            Severity::Info => &self.info,
+            Severity::Note => &self.note,
+            Severity::Help => &self.help,
+        }
+    }
+}
+
+/// Defines the phrases which are displayed around diagnostics, such as the prefix of help messages.
+///
+/// Together with [`ThemeNames`], which defines the names of each severity, the strings can be
+/// overridden to localize the output of the renderer. Each string is a template, where placeholders
+/// such as `{count}` are substituted when rendering. All placeholders are optional, so they can be
+/// reordered or left out entirely, as the target language requires.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::*;
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.theme.names.help = "aide".into();
+/// renderer.strings = Strings {
+///     help_prefix: "{help} :".into(),
+///     ..Strings::english()
+/// };
+///
+/// let diagnostic = SimpleDiagnostic::new("valeur inconnue").with_help("vouliez-vous dire `false` ?");
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.contains("aide : vouliez-vous dire `false` ?"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Strings {
+    /// Defines the prefix of help messages, where `{help}` is the name of the help severity.
+    pub help_prefix: String,
+
+    /// Defines the inline suggestion of labels, where `{help}` is the name of the
+    /// help severity and `{suggestion}` is the suggested replacement.
+    pub label_suggestion: String,
+
+    /// Defines the amount of causes in the header, where `{count}` is the amount of causes.
+    pub cause_count: String,
+
+    /// Defines the amount of causes in the header, when the diagnostic has exactly one cause.
+    pub cause_count_singular: String,
+
+    /// Defines the amount of related diagnostics in the header, where `{count}` is the amount of related diagnostics.
+    pub related_count: String,
+
+    /// Defines the line which replaces collapsed related diagnostics, where `{count}` is the amount of collapsed diagnostics.
+    pub more_related: String,
+
+    /// Defines the text preceding the location which a source was expanded from.
+    pub in_expansion_of: String,
+
+    /// Defines the text displayed instead of the location of sources without any name or content.
+    pub unknown_source: String,
+
+    /// Defines the line which replaces omitted lines of long labels, where `{count}` is the amount of omitted lines.
+    pub lines_omitted: String,
+
+    /// Defines the suffix of the message of folded multiline labels, where `{count}` is the amount of lines
+    /// which the label spans.
+    pub spans_lines: String,
+
+    /// Defines the suffix of alternative help messages, where `{index}` is the number of
+    /// the alternative and `{count}` is the amount of alternatives.
+    pub help_option: String,
+
+    /// Defines the message of secondary note labels, where `{note}` is the name of the
+    /// note severity and `{message}` is the message of the label.
+    pub secondary_note: String,
+
+    /// Defines the warning which replaces the snippet of labels whose source has changed since
+    /// the labels were created, where `{warning}` is the name of the warning severity.
+    pub stale_source: String,
+}
+
+impl Strings {
+    /// Defines a preset with the english strings.
+    pub fn english() -> Self {
+        Strings {
+            help_prefix: "{help}:".into(),
+            label_suggestion: "{help}: did you mean `{suggestion}`?".into(),
+            cause_count: "+{count} causes".into(),
+            cause_count_singular: "+{count} cause".into(),
+            related_count: "+{count} related".into(),
+            more_related: "… and {count} more".into(),
+            in_expansion_of: "in expansion of".into(),
+            unknown_source: "<unknown>".into(),
+            lines_omitted: "… {count} lines omitted …".into(),
+            spans_lines: "(… spans {count} lines)".into(),
+            help_option: "(option {index} of {count})".into(),
+            secondary_note: "{note}: {message}".into(),
+            stale_source: "{warning}: source has changed since this diagnostic was created".into(),
         }
     }
 }
 
+impl Default for Strings {
+    fn default() -> Self {
+        Strings::english()
+    }
+}
+
+/// Substitutes all the given placeholders, such as `{count}`, in the given template.
+fn fill_template(template: &str, placeholders: &[(&str, &dyn Display)]) -> String {
+    placeholders.iter().fold(template.to_string(), |filled, (name, value)| {
+        filled.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct ArrowSymbols {
     /// "─"
@@ -113,6 +363,9 @@ pub struct ArrowSymbols {
     /// "┬"
     pub hbot: char,
 
+    /// "┴"
+    pub htop: char,
+
     /// "│"
     pub vertical: char,
 
@@ -131,6 +384,9 @@ pub struct ArrowSymbols {
     /// "^"
     pub arrow_up: char,
 
+    /// "v"
+    pub arrow_down: char,
+
     /// ">"
     pub arrow_right: char,
 }
@@ -140,15 +396,58 @@ impl ArrowSymbols {
         ArrowSymbols {
             hbar: '─',
             hbot: '┬',
+            htop: '┴',
             vertical: '│',
             vertical_break: '∶',
             top_left: '╭',
             bottom_left: '╰',
             horizontal_right: '├',
             arrow_up: '^',
+            arrow_down: 'v',
             arrow_right: '▶',
         }
     }
+
+    /// Defines a preset which only uses ASCII characters, for terminals without Unicode support.
+    pub fn ascii() -> Self {
+        ArrowSymbols {
+            hbar: '-',
+            hbot: '+',
+            htop: '+',
+            vertical: '|',
+            vertical_break: ':',
+            top_left: ',',
+            bottom_left: '`',
+            horizontal_right: '|',
+            arrow_up: '^',
+            arrow_down: 'v',
+            arrow_right: '>',
+        }
+    }
+}
+
+/// Defines the background color of a terminal, which determines the colors that are readable on it.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Background {
+    /// Defines a light background, such as white.
+    Light,
+
+    /// Defines a dark background, such as black.
+    #[default]
+    Dark,
+}
+
+impl Background {
+    /// Detects the background color of the terminal from the `COLORFGBG` environment variable,
+    /// which is set by terminals such as `rxvt`, `Konsole` and `iTerm2`.
+    ///
+    /// The variable contains the ANSI color indices of the foreground and background, such as
+    /// `15;0` for white on black. Background colors `7` (light gray) and `9` through `15`, except
+    /// `8` (dark gray), are considered light. Returns `None` if the background can't be determined.
+    #[cfg(feature = "detect-background")]
+    pub fn detect() -> Option<Background> {
+        detect_background(|name| std::env::var(name).ok())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -156,6 +455,7 @@ pub struct Theme {
     pub style: ThemeStyle,
     pub symbols: ThemeSymbols,
     pub arrows: ArrowSymbols,
+    pub names: ThemeNames,
 }
 
 impl Theme {
@@ -167,6 +467,115 @@ impl Theme {
             style: ThemeStyle::rgb(),
             symbols: ThemeSymbols::unicode(),
             arrows: ArrowSymbols::unicode(),
+            names: ThemeNames::english(),
+        }
+    }
+
+    /// Returns an instance of [`Theme`] which uses the "fancy" preset, tuned for dark backgrounds.
+    ///
+    /// This is the same as [`Theme::fancy()`].
+    pub fn dark() -> Self {
+        Theme::fancy()
+    }
+
+    /// Returns an instance of [`Theme`] which uses the "fancy" preset, tuned for light backgrounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Theme};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.theme = Theme::light();
+    /// ```
+    pub fn light() -> Self {
+        Theme {
+            style: ThemeStyle::light(),
+            ..Theme::fancy()
+        }
+    }
+
+    /// Returns an instance of [`Theme`] which is tuned for the given background.
+    pub fn for_background(background: Background) -> Self {
+        match background {
+            Background::Light => Theme::light(),
+            Background::Dark => Theme::dark(),
+        }
+    }
+
+    /// Returns an instance of [`Theme`] which matches the color support of the terminal.
+    ///
+    /// The color support is detected from the environment, in the following order:
+    /// - if `NO_COLOR` is set to a non-empty value, no colors are used.
+    /// - if `FORCE_COLOR` is set, colors are used even if the output isn't a terminal. A value of `0` or
+    ///   `false` disables colors, while a value of `3` enables RGB colors.
+    /// - if the standard error stream isn't a terminal or `TERM` is `dumb`, no colors are used.
+    /// - if `COLORTERM` is `truecolor` or `24bit`, RGB colors are used.
+    /// - otherwise, ANSI colors are used.
+    ///
+    /// If the `detect-background` feature is enabled and RGB colors are used, the colors are
+    /// tuned for the background of the terminal, as detected by [`Background::detect()`].
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Theme};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.theme = Theme::detect();
+    /// ```
+    #[cfg(feature = "terminal")]
+    pub fn detect() -> Self {
+        use std::io::IsTerminal;
+
+        let support = detect_color_support(|name| std::env::var(name).ok(), std::io::stderr().is_terminal());
+
+        let style = match support {
+            ColorSupport::None => ThemeStyle::none(),
+            ColorSupport::Ansi => ThemeStyle::ansi(),
+            #[cfg(feature = "detect-background")]
+            ColorSupport::TrueColor if Background::detect() == Some(Background::Light) => ThemeStyle::light(),
+            ColorSupport::TrueColor => ThemeStyle::rgb(),
+        };
+
+        Theme {
+            style,
+            ..Theme::fancy()
+        }
+    }
+
+    /// Returns an instance of [`Theme`] which uses the monochrome preset.
+    ///
+    /// The monochrome preset uses unicode symbols, but doesn't apply any colors or text effects.
+    pub fn none() -> Self {
+        Theme {
+            style: ThemeStyle::none(),
+            symbols: ThemeSymbols::unicode(),
+            arrows: ArrowSymbols::unicode(),
+            names: ThemeNames::english(),
+        }
+    }
+
+    /// Returns an instance of [`Theme`] which uses the ASCII preset.
+    ///
+    /// The ASCII preset only uses ASCII symbols and doesn't apply any colors or text effects, which
+    /// is useful for terminals without Unicode support, as well as for logs and snapshot tests.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Renderer, SimpleDiagnostic, Theme};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.theme = Theme::ascii();
+    ///
+    /// let rendered = renderer.render(&SimpleDiagnostic::new("mismatched types")).unwrap();
+    ///
+    /// assert!(rendered.starts_with("x error: mismatched types"));
+    /// ```
+    pub fn ascii() -> Self {
+        Theme {
+            style: ThemeStyle::none(),
+            symbols: ThemeSymbols::ascii(),
+            arrows: ArrowSymbols::ascii(),
+            names: ThemeNames::english(),
         }
     }
 }
@@ -174,6 +583,9 @@ impl Theme {
 /// An implementation of [`Renderer`] which displays diagnostics in a graphical way
 /// in the console using colors, Unicode symbols and highlighting.
 ///
+/// Options can either be set directly on the public fields, or through [`GraphicalRenderer::builder()`],
+/// which also validates the combination of options.
+///
 /// # Examples
 ///
 /// ```
@@ -189,6 +601,9 @@ pub struct GraphicalRenderer {
     pub theme: Theme,
 
     /// Defines the maximum length of the terminal.
+    ///
+    /// Source lines which don't fit within the width, including the indentation and gutter in front of
+    /// them, are truncated, unless a label is placed beyond the edge.
     pub width: usize,
 
     /// Defines the padding to use per level of identation.
@@ -197,9 +612,44 @@ pub struct GraphicalRenderer {
     /// Defines the margin to use in the gutter of snippets.
     pub gutter_margin: usize,
 
+    /// Defines whether to display line numbers in the gutter of snippets.
+    ///
+    /// If `false`, the gutter collapses into a single vertical bar, which is useful where line
+    /// numbers are meaningless and horizontal space is precious, such as in REPLs or chat bots.
+    pub show_line_numbers: bool,
+
     /// Defines the amount of lines surrounding a label to include as context.
     pub context_lines: usize,
 
+    /// Defines the maximum distance in lines between the snippets of two labels, for them
+    /// to be merged into a single continuous snippet.
+    ///
+    /// Snippets which overlap are merged as well, as long as the distance is set. If `None`, each
+    /// label is rendered in a snippet of it's own, separated by breaks in the gutter.
+    pub merge_distance: Option<usize>,
+
+    /// Defines the maximum amount of lines to render per label snippet.
+    ///
+    /// Snippets which exceed the limit only show the first and last few lines, along with any
+    /// lines which have labels of their own, while the rest are replaced by a single
+    /// `… N lines omitted …` line. If `None`, all lines of the snippet are rendered.
+    pub max_label_lines: Option<usize>,
+
+    /// Defines the maximum amount of lines which a multiline label may span, before it's folded.
+    ///
+    /// Folded labels only show the first and last lines of the labelled region, along with their context
+    /// lines and any lines which have labels of their own, while the rest are replaced by a single
+    /// `… N lines omitted …` line. The message of folded labels is suffixed with `(… spans N lines)`.
+    /// If `None`, multiline labels are never folded.
+    pub fold_label_lines: Option<usize>,
+
+    /// Defines the maximum amount of lines to render from the source of diagnostics without any labels.
+    ///
+    /// Diagnostics with a source, but without any labels, usually don't show any of the source. When set,
+    /// the first lines of the source are rendered as an excerpt instead, which is useful for short files,
+    /// such as configuration files. If `None`, no excerpt is rendered.
+    pub excerpt_lines: Option<usize>,
+
     /// Defines whether to use colors in the output.
     pub use_colors: bool,
 
@@ -207,8 +657,207 @@ pub struct GraphicalRenderer {
     /// is marked. This is only used if `use_colors` is `true`.
     pub highlight_source: bool,
 
-    /// Defiens the current indentation level.
-    current_indent: usize,
+    /// Defines how byte offsets within sources are converted into display columns.
+    pub column_policy: ColumnPolicy,
+
+    /// Defines whether to append the amount of causes and related diagnostics
+    /// to the header of diagnostics, such as `(+12 related)`.
+    pub show_related_count: bool,
+
+    /// Defines the maximum amount of related diagnostics to render per diagnostic.
+    ///
+    /// Any related diagnostics beyond the limit are collapsed into a single `… and N more` line.
+    /// If `None`, all related diagnostics are rendered.
+    pub max_related: Option<usize>,
+
+    /// Defines the maximum amount of related diagnostics to render in full per diagnostic.
+    ///
+    /// Any related diagnostics beyond the limit are rendered in a compact form, using a single line
+    /// per diagnostic with the location of the first label, until [`GraphicalRenderer::max_related`]
+    /// is reached. If `None`, all related diagnostics are rendered in full.
+    pub max_full_related: Option<usize>,
+
+    /// Defines the minimum severity of nested diagnostics to render.
+    ///
+    /// Causes and related diagnostics which are less severe than the given severity
+    /// are skipped entirely. If `None`, all nested diagnostics are rendered.
+    pub min_nested_severity: Option<Severity>,
+
+    /// Defines the filter to apply to all source lines, before they're displayed.
+    ///
+    /// If `None`, source lines are displayed as-is.
+    pub content_filter: Option<Arc<dyn ContentFilter>>,
+
+    /// Defines how suggestions are displayed within source snippets.
+    pub suggestion_style: SuggestionStyle,
+
+    /// Defines the syntax highlighter to apply to all source lines in snippets.
+    ///
+    /// This is only used if `use_colors` is `true`. If `None`, source lines aren't highlighted.
+    pub syntax_highlighter: Option<Arc<dyn SyntaxHighlighter>>,
+
+    /// Defines the resolver of source identifiers, for labels which refer to their
+    /// source by a [`crate::SourceId`] instead of holding the source itself.
+    ///
+    /// If `None`, such labels fall back to the source of their diagnostic.
+    pub source_resolver: Option<Arc<dyn SourceResolver>>,
+
+    /// Defines the template of the URL to link the code of each diagnostic to, where `{code}`
+    /// is replaced by the code, such as `https://errors.lume.dev/{code}`.
+    ///
+    /// The code is wrapped in an OSC 8 hyperlink, which is clickable in supported terminals. This is only
+    /// used if `use_colors` is `true`, since the hyperlink is an escape sequence. If `None`, codes aren't linked.
+    pub code_url_template: Option<String>,
+
+    /// Defines whether label underlines and messages are placed above or below the labelled line.
+    pub label_position: LabelPosition,
+
+    /// Defines the order in which labels within the same source are rendered.
+    pub label_order: LabelOrder,
+
+    /// Defines the phrases displayed around diagnostics, which can be overridden for localization.
+    pub strings: Strings,
+}
+
+/// Defines how suggestions are displayed within source snippets.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::*;
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = new Tesitng();"));
+///
+/// let diagnostic = SimpleDiagnostic::new("could not find type `Tesitng`").with_help(
+///     Help::new("a type with a similar name exists")
+///         .with_suggestion(Suggestion::replace(SourceRange::new(source, 12..19), "Testing")),
+/// );
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.suggestion_style = SuggestionStyle::Diff;
+///
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.contains(" 1 │ let a = new Tesitng();"));
+/// assert!(rendered.contains(" + │ let a = new Testing();"));
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionStyle {
+    /// Applies the suggestions directly onto the original line,
+    /// with arrows beneath the changed sections of the line.
+    #[default]
+    Inline,
+
+    /// Displays the original line as-is, followed by the line after applying the suggestions
+    /// on it's own `+` line beneath it, where inserted text is highlighted.
+    ///
+    /// Lines which only contain deletions are still displayed inline, since they
+    /// don't have any inserted text to display.
+    Diff,
+}
+
+/// Defines where label underlines and messages are placed, relative to the labelled line.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::*;
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+///
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::error(Some(source), 8..9, "not found in this scope"));
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.label_position = LabelPosition::Above;
+///
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.contains("v not found in this scope\n 1 │ let a = b;"));
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelPosition {
+    /// Places the underlines beneath the labelled line, pointing up at the labelled spans.
+    #[default]
+    Below,
+
+    /// Places the underlines above the labelled line, pointing down at the labelled spans.
+    ///
+    /// Messages of labels are ordered so they still read from top to bottom.
+    Above,
+}
+
+/// Defines the order in which the labels of a diagnostic are rendered, within each source.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{GraphicalRenderer, Label, LabelOrder, NamedSource, Renderer, SimpleDiagnostic, WithSource};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;\n\nlet c = d;\n"));
+///
+/// let diagnostic = SimpleDiagnostic::new("unknown variables")
+///     .with_label(Label::error(None, 20..21, "second"))
+///     .with_label(Label::error(None, 8..9, "first"))
+///     .with_source(source);
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.label_order = LabelOrder::Span;
+///
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.find("first").unwrap() < rendered.find("second").unwrap());
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelOrder {
+    /// Renders the labels in the order they were returned from [`Diagnostic::labels()`],
+    /// so the most important label can be placed first.
+    #[default]
+    Display,
+
+    /// Renders the labels in the order they appear in the source.
+    Span,
+}
+
+/// Defines the state of a single render pass, which is passed down through the rendering methods.
+///
+/// Keeping the state out of the [`GraphicalRenderer`] itself allows rendering through a shared
+/// reference, so a single renderer can be shared between threads.
+#[derive(Default, Clone)]
+struct RenderContext {
+    /// Defines the current indentation level.
+    indent: usize,
+
+    /// Defines the source inherited from the closest parent diagnostic, which is used
+    /// for labels where neither the label nor the diagnostic itself has any source attached.
+    inherited_source: Option<Arc<dyn Source>>,
+
+    /// Defines the minimum amount of digits to reserve for line numbers in gutters.
+    line_number_width: usize,
+
+    /// Defines whether to skip all source snippets of the current diagnostic.
+    suppress_snippets: bool,
+
+    /// Defines the session which caches layout data of sources across diagnostics.
+    session: RenderSession,
+}
+
+impl RenderContext {
+    /// Creates the context for a diagnostic nested within the given parent diagnostic.
+    fn nested(&self, parent: &dyn Diagnostic) -> Self {
+        RenderContext {
+            indent: self.indent + 1,
+            inherited_source: parent.source_code().or_else(|| self.inherited_source.clone()),
+            line_number_width: self.line_number_width,
+            suppress_snippets: false,
+            session: self.session.clone(),
+        }
+    }
 }
 
 impl Default for GraphicalRenderer {
@@ -218,8 +867,52 @@ impl Default for GraphicalRenderer {
 }
 
 impl Renderer for GraphicalRenderer {
-    fn render_fmt(&mut self, f: &mut Formatter<'_>, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
-        self.render_diagnostic(f, diagnostic)
+    fn render_fmt(&self, f: &mut Formatter<'_>, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        self.render_diagnostic(f, &RenderContext::default(), diagnostic)
+    }
+
+    fn render_fmt_with(
+        &self,
+        f: &mut Formatter<'_>,
+        diagnostic: &dyn Diagnostic,
+        options: &BatchOptions,
+    ) -> std::fmt::Result {
+        let cx = RenderContext {
+            line_number_width: options.line_number_width,
+            session: options.session.clone(),
+            ..RenderContext::default()
+        };
+
+        self.render_diagnostic(f, &cx, diagnostic)
+    }
+
+    /// Renders the diagnostic into a [`Report`], where the styled segments are collected
+    /// while rendering, instead of being parsed from the finished output.
+    ///
+    /// The segments are always styled using the theme of the renderer, even if colors are
+    /// disabled, in which case only the output of the report is left unstyled.
+    fn render_report(&self, diagnostic: &dyn Diagnostic) -> Result<Report, std::fmt::Error> {
+        let renderer = if self.use_colors {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(GraphicalRenderer {
+                use_colors: true,
+                ..self.clone()
+            })
+        };
+
+        let mut ansi = String::new();
+        let mut segments = SegmentWriter::new();
+
+        if self.use_colors {
+            let mut writer = Tee::new(&mut ansi, &mut segments);
+            renderer.render_fmt(&mut Formatter::new(&mut writer), diagnostic)?;
+        } else {
+            let mut writer = Tee::new(StripAnsi::new(&mut ansi), &mut segments);
+            renderer.render_fmt(&mut Formatter::new(&mut writer), diagnostic)?;
+        }
+
+        Ok(Report::from_parts(ansi, segments.finish()))
     }
 }
 
@@ -231,10 +924,27 @@ impl GraphicalRenderer {
             width: terminal_width(),
             padding: 6,
             gutter_margin: 2,
+            show_line_numbers: true,
             context_lines: 1,
+            merge_distance: None,
+            max_label_lines: None,
+            fold_label_lines: None,
+            excerpt_lines: None,
             use_colors: true,
             highlight_source: false,
-            current_indent: 0,
+            column_policy: ColumnPolicy::default(),
+            show_related_count: false,
+            max_related: None,
+            max_full_related: None,
+            min_nested_severity: None,
+            content_filter: None,
+            suggestion_style: SuggestionStyle::default(),
+            syntax_highlighter: None,
+            source_resolver: None,
+            code_url_template: None,
+            label_position: LabelPosition::default(),
+            label_order: LabelOrder::default(),
+            strings: Strings::english(),
         }
     }
 
@@ -246,9 +956,26 @@ impl GraphicalRenderer {
         }
     }
 
+    /// Gets the syntax highlighting of the given source line, using the syntax highlighter
+    /// of the renderer. If colors are disabled or no highlighter is set, no styles are returned.
+    fn highlight_syntax(&self, source_name: Option<&str>, line: &str) -> Vec<(Range<usize>, Style)> {
+        match &self.syntax_highlighter {
+            Some(highlighter) if self.use_colors => highlighter.highlight_line(source_name, line),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Applies the content filter of the renderer to the given source line, if any.
+    fn filter_content<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        match &self.content_filter {
+            Some(filter) => filter.filter(line),
+            None => Cow::Borrowed(line),
+        }
+    }
+
     /// Gets the current indentation to use, in amounts of spaces.
-    fn ident(&self) -> usize {
-        self.current_indent * self.padding
+    fn ident(&self, cx: &RenderContext) -> usize {
+        cx.indent * self.padding
     }
 
     /// Writes the the given amount of padding to the provided writer.
@@ -257,8 +984,8 @@ impl GraphicalRenderer {
     }
 
     /// Writes the current indentation to the given writer.
-    fn write_ident(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
-        self.write_padding(f, self.ident())
+    fn write_ident(&self, f: &mut impl std::fmt::Write, cx: &RenderContext) -> std::fmt::Result {
+        self.write_padding(f, self.ident(cx))
     }
 
     /// Styles the given value with the provided style.
@@ -273,12 +1000,31 @@ impl GraphicalRenderer {
         }
     }
 
-    /// Determines how much padding to use for the gutter of the
-    /// given source code. The gutter margin is included in the result.
-    fn gutter_size_of(&self, source: &str) -> usize {
-        let largest_line_size = source.lines().count().to_string().len();
+    /// Gets the line index of the given source, which is cached within the render session.
+    fn line_index(&self, cx: &RenderContext, source: &Arc<dyn Source>, content: &str) -> Arc<LineIndex> {
+        cx.session
+            .line_index(source)
+            .unwrap_or_else(|| Arc::new(LineIndex::new(content)))
+    }
+
+    /// Gets the line numbers and display columns which contains the given span,
+    /// using the column policy of the renderer.
+    fn display_coords_of_span(&self, index: &LineIndex, str: &str, span: impl Into<Range<usize>>) -> Span {
+        index.display_coords_of_span(str, span, self.column_policy)
+    }
+
+    /// Determines how much padding to use for the gutter of a source
+    /// with the given amount of lines. The gutter margin is included in the result.
+    ///
+    /// If line numbers are hidden, the gutter is collapsed entirely.
+    fn gutter_size_of(&self, cx: &RenderContext, line_count: usize) -> usize {
+        if !self.show_line_numbers {
+            return 0;
+        }
+
+        let largest_line_size = line_count.to_string().len();
 
-        largest_line_size + self.gutter_margin
+        largest_line_size.max(cx.line_number_width) + self.gutter_margin
     }
 
     /// Renders the given diagnostic to the provided writer.
@@ -296,11 +1042,36 @@ impl GraphicalRenderer {
     ///     ╰──
     ///    help: doc comments are only allowed on definitions
     /// ```
-    fn render_diagnostic(&mut self, f: &mut impl std::fmt::Write, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+    fn render_diagnostic(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        diagnostic: &dyn Diagnostic,
+    ) -> std::fmt::Result {
+        let hints = diagnostic.render_hints();
+
+        if hints.no_color && self.use_colors {
+            let renderer = GraphicalRenderer {
+                use_colors: false,
+                ..self.clone()
+            };
+
+            return renderer.render_diagnostic(f, cx, diagnostic);
+        }
+
+        if hints.compact {
+            return self.render_compact(f, cx, diagnostic);
+        }
+
+        let cx = &RenderContext {
+            suppress_snippets: hints.suppress_snippets,
+            ..cx.clone()
+        };
+
         owo_colors::with_override(self.use_colors, || {
-            self.render_header(f, diagnostic)?;
-            self.render_source(f, diagnostic)?;
-            self.render_footer(f, diagnostic)?;
+            self.render_header(f, cx, diagnostic)?;
+            self.render_source(f, cx, diagnostic)?;
+            self.render_footer(f, cx, diagnostic)?;
 
             Result::Ok(())
         })
@@ -313,12 +1084,33 @@ impl GraphicalRenderer {
     /// ```text
     ///   × error[E4012]: invalid doc comment found
     /// ```
-    fn render_header(&self, f: &mut impl std::fmt::Write, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+    fn render_header(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        diagnostic: &dyn Diagnostic,
+    ) -> std::fmt::Result {
+        self.write_header(f, cx, diagnostic)?;
+
+        if self.show_related_count {
+            self.render_related_count(f, diagnostic)?;
+        }
+
+        writeln!(f)
+    }
+
+    /// Writes the severity, code and message of the diagnostic, without any trailing newline.
+    fn write_header(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        diagnostic: &dyn Diagnostic,
+    ) -> std::fmt::Result {
         let severity_symbol = self.theme.symbols.from_severity(diagnostic.severity());
         let severity_style = self.severity_style(diagnostic.severity());
-        let severity_str = diagnostic.severity().to_string();
+        let severity_str = self.theme.names.from_severity(diagnostic.severity());
 
-        self.write_ident(f)?;
+        self.write_ident(f, cx)?;
         write!(
             f,
             "{} {}",
@@ -327,10 +1119,57 @@ impl GraphicalRenderer {
         )?;
 
         if let Some(code) = &diagnostic.code() {
-            write!(f, "{}", self.style(&format!("[{code}]"), severity_style))?;
+            let code_str = format!("[{code}]");
+            let styled = self.style(&code_str, severity_style);
+
+            match self.code_url_template.as_ref().filter(|_| self.use_colors) {
+                Some(template) => {
+                    let url = fill_template(template, &[("code", code)]);
+
+                    write!(f, "\x1b]8;;{url}\x1b\\{styled}\x1b]8;;\x1b\\")?;
+                }
+                None => write!(f, "{styled}")?,
+            }
         }
 
-        writeln!(f, ": {}", diagnostic.message())
+        write!(f, ": {}", diagnostic.message())
+    }
+
+    /// Renders the amount of causes and related diagnostics of the diagnostic, if any.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///  (+1 cause, +12 related)
+    /// ```
+    fn render_related_count(&self, f: &mut impl std::fmt::Write, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        let causes = diagnostic.causes().count();
+        let related = diagnostic.related().count();
+
+        let mut counts = Vec::new();
+
+        if causes > 0 {
+            let template = match causes {
+                1 => &self.strings.cause_count_singular,
+                _ => &self.strings.cause_count,
+            };
+
+            counts.push(fill_template(template, &[("count", &causes)]));
+        }
+
+        if related > 0 {
+            counts.push(fill_template(&self.strings.related_count, &[("count", &related)]));
+        }
+
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        write!(
+            f,
+            " {}",
+            self.style(&format!("({})", counts.join(", ")), self.theme.style.gutter)
+        )
     }
 
     /// Renders the source span of the diagnostic, if any, attached with any associated labels.
@@ -348,17 +1187,29 @@ impl GraphicalRenderer {
     ///       │        ^^^^^^^^^^^^ expected `Array<T>`, found `Boolean`
     ///       ╰──
     /// ```
-    fn render_source(&mut self, f: &mut impl std::fmt::Write, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+    fn render_source(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        diagnostic: &dyn Diagnostic,
+    ) -> std::fmt::Result {
         for cause in diagnostic.causes() {
-            self.current_indent += 1;
+            if !self.should_render_nested(cause) {
+                continue;
+            }
+
+            self.render_nested(f, cx, diagnostic, cause)?;
+        }
 
-            self.render_diagnostic(f, cause)?;
-            writeln!(f)?;
+        let has_labels = diagnostic.labels().is_some_and(|mut labels| labels.next().is_some());
 
-            self.current_indent -= 1;
+        if let (false, Some(max_lines), Some(source)) = (has_labels, self.excerpt_lines, diagnostic.source_code()) {
+            if !cx.suppress_snippets {
+                self.render_source_excerpt(f, cx, source, max_lines)?;
+            }
         }
 
-        if let Some(labels) = diagnostic.labels() {
+        if let Some(labels) = diagnostic.labels().filter(|_| !cx.suppress_snippets) {
             let mut label_groups: IndexMap<Option<String>, LabelGroup> = IndexMap::new();
 
             // Group the labels into groups where all elements have
@@ -371,9 +1222,9 @@ impl GraphicalRenderer {
                 // If no source is found on either, skip over the label entirely.
                 //
                 // TODO: should be print a warning when no source is found?
-                let source = match label.source() {
+                let source = match label.resolve_source(self.source_resolver.as_deref()) {
                     Some(s) => s.clone(),
-                    None => match diagnostic.source_code() {
+                    None => match diagnostic.source_code().or_else(|| cx.inherited_source.clone()) {
                         Some(s) => s,
                         None => continue,
                     },
@@ -392,22 +1243,140 @@ impl GraphicalRenderer {
             }
 
             for (_, group) in label_groups {
-                self.render_label_group(f, group, diagnostic.severity())?;
+                self.render_label_group(f, cx, group, diagnostic.severity())?;
             }
         }
 
-        for related in diagnostic.related() {
-            self.current_indent += 1;
+        let related = diagnostic
+            .related()
+            .filter(|d| self.should_render_nested(*d))
+            .collect::<Vec<_>>();
+
+        let related_count = related.len();
+        let related_limit = self.max_related.unwrap_or(usize::MAX);
+        let full_limit = self.max_full_related.unwrap_or(usize::MAX);
 
-            self.render_diagnostic(f, related)?;
-            writeln!(f)?;
+        for (idx, related) in related.into_iter().take(related_limit).enumerate() {
+            if idx < full_limit {
+                self.render_nested(f, cx, diagnostic, related)?;
+            } else {
+                self.render_compact(f, &cx.nested(diagnostic), related)?;
+            }
+        }
 
-            self.current_indent -= 1;
+        if related_count > related_limit {
+            self.render_collapsed_related(f, &cx.nested(diagnostic), related_count - related_limit)?;
         }
 
         Ok(())
     }
 
+    /// Renders a nested diagnostic, such as a cause or related diagnostic, one level deeper
+    /// than the parent diagnostic.
+    ///
+    /// The source of the parent diagnostic is inherited by the nested diagnostic, so labels
+    /// without any source attached can still be rendered.
+    fn render_nested(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        parent: &dyn Diagnostic,
+        nested: &dyn Diagnostic,
+    ) -> std::fmt::Result {
+        self.render_diagnostic(f, &cx.nested(parent), nested)?;
+
+        writeln!(f)
+    }
+
+    /// Determines whether the given nested diagnostic should be rendered,
+    /// depending on the minimum nested severity of the renderer.
+    fn should_render_nested(&self, diagnostic: &dyn Diagnostic) -> bool {
+        match self.min_nested_severity {
+            Some(min) => diagnostic.severity().is_at_least(min),
+            None => true,
+        }
+    }
+
+    /// Renders a diagnostic as a single line, containing only the header of the diagnostic,
+    /// the amount of nested diagnostics and the location of the first label, if any.
+    ///
+    /// This is used for related diagnostics beyond [`GraphicalRenderer::max_full_related`], as well
+    /// as diagnostics which hint that they should be rendered compactly.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///       ☞ note: definition 3 (+1 related) [src/test.lm:4:5]
+    /// ```
+    fn render_compact(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        diagnostic: &dyn Diagnostic,
+    ) -> std::fmt::Result {
+        self.write_header(f, cx, diagnostic)?;
+        self.render_related_count(f, diagnostic)?;
+
+        let location = diagnostic
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .and_then(|label| {
+                let source = label
+                    .resolve_source(self.source_resolver.as_deref())
+                    .or_else(|| diagnostic.source_code())
+                    .or_else(|| cx.inherited_source.clone())?;
+
+                Some((source, label.range().clone()))
+            });
+
+        let Some((source, range)) = location else {
+            return writeln!(f);
+        };
+
+        write!(f, " ")?;
+
+        let Some(content) = source.try_text() else {
+            return match source.name() {
+                Some(name) => writeln!(f, "[{}]", self.style(&name, self.theme.style.link)),
+                None => writeln!(f),
+            };
+        };
+
+        let index = self.line_index(cx, &source, content);
+        let range = normalize_span(content, range);
+        let Span { start, .. } = self.display_coords_of_span(&index, content, range);
+
+        match source.name() {
+            Some(name) => self.render_source_path(f, name, start.line + 1, start.column),
+            None => writeln!(f, "{}:{}", start.line + 1, start.column + 1),
+        }
+    }
+
+    /// Renders a placeholder for the related diagnostics, which were collapsed.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///       … and 4 more
+    /// ```
+    fn render_collapsed_related(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        count: usize,
+    ) -> std::fmt::Result {
+        self.write_ident(f, cx)?;
+
+        writeln!(
+            f,
+            "{}",
+            self.style(
+                &fill_template(&self.strings.more_related, &[("count", &count)]),
+                self.theme.style.gutter
+            )
+        )
+    }
+
     /// Renders a label group context with one-or-more labels, all sharing the same source file.
     ///
     /// # Example
@@ -421,19 +1390,34 @@ impl GraphicalRenderer {
     ///    35 │        return true;
     ///       │        ^^^^^^^^^^^^ expected `Array<T>`, found `Boolean`
     /// ```
+    ///
+    /// The content and line index of the source are passed in from the label group,
+    /// so they're only looked up once per group, instead of once per context.
     fn render_label_context(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         context: LabelContext,
+        source_content: &str,
+        index: &LineIndex,
         severity: Severity,
     ) -> std::fmt::Result {
-        let source_content = context.source.content();
-        let gutter_size = self.gutter_size_of(&source_content);
+        let gutter_size = self.gutter_size_of(cx, index.line_count());
 
         let joined_span = context.max_span();
-        let span = coords_of_span(&source_content, joined_span.clone());
+        let span = self.display_coords_of_span(index, source_content, joined_span.clone());
 
-        let style = self.severity_style(severity);
+        // Only parent labels which span multiple lines are drawn as a bracket in the gutter. Single-line
+        // parents may still have children on other lines, when nearby contexts have been merged,
+        // in which case the parent is rendered on it's own line, just like the children.
+        let parent_span = self.display_coords_of_span(index, source_content, context.parent.range.0.clone());
+        let bracket = parent_span.is_multiline();
+
+        // Elements of the context use the most specific severity available, so labels without
+        // any severity inherit it from the parent label, before falling back to the diagnostic.
+        let severity = context.parent.severity.unwrap_or(severity);
+
+        let style = self.label_style(&context.parent, severity);
         let arrows = &self.theme.arrows;
 
         // Render all the labels in in the group, along with joiners in the vertical gutter.
@@ -445,31 +1429,86 @@ impl GraphicalRenderer {
         //  34 │
         //  35 │        return true;
         //     │        ^^^^^^^^^^^^ expected `Array<T>`, found `Boolean`
-        let content = extract_with_context(&source_content, joined_span.0, self.context_lines);
+        let (content, _) = index.extract_with_context_offset(source_content, joined_span.0, self.context_lines);
 
         let lines = content.lines().collect::<Vec<_>>();
         let line_count = lines.len();
 
         // Save all the coordinates of each label span, since we'll be needing them in this function.
-        let labels = context
+        let mut labels = context
             .children
             .iter()
-            .map(|(_, l)| (l, coords_of_span(&source_content, l.range.0.clone())))
+            .map(|(_, l)| (l, self.display_coords_of_span(index, source_content, l.range.0.clone())))
             .collect::<Vec<_>>();
 
+        if !bracket {
+            labels.push((&context.parent, parent_span));
+        }
+
+        // Defines the zero-indexed line of the first line of the snippet, including context lines.
+        let first_line = span.start.line.saturating_sub(self.context_lines);
+
+        // Labelled regions which are too long are folded, so only the first and last lines of the region
+        // are shown, along with their context lines.
+        let label_line_count = parent_span.end.line - parent_span.start.line + 1;
+        let folded = bracket && self.fold_label_lines.is_some_and(|max| label_line_count > max);
+
+        let fold_range = if folded {
+            let start = parent_span.start.line + self.context_lines + 1;
+            let end = parent_span.end.line.saturating_sub(self.context_lines);
+
+            start.saturating_sub(first_line)..end.saturating_sub(first_line)
+        } else {
+            0..0
+        };
+
+        let omitted = self.omitted_lines(line_count, fold_range, |idx| {
+            labels
+                .iter()
+                .any(|(_, s)| !s.is_multiline() && s.start.line == first_line + idx)
+        });
+
         for (idx, line) in lines.into_iter().enumerate() {
-            let line_num = span.start.line.saturating_sub(self.context_lines) + idx + 1;
+            let line_num = first_line + idx + 1;
+
+            if omitted[idx] {
+                // Only the first line of each omitted run is replaced by a marker.
+                if idx == 0 || !omitted[idx - 1] {
+                    let count = omitted[idx..].iter().take_while(|omitted| **omitted).count();
+
+                    self.render_snippet_break(f, cx, gutter_size)?;
+                    if bracket {
+                        write!(f, "{}   ", arrows.vertical.style(style))?;
+                    }
+
+                    writeln!(
+                        f,
+                        "{}",
+                        self.style(
+                            &fill_template(&self.strings.lines_omitted, &[("count", &count)]),
+                            self.theme.style.gutter
+                        )
+                    )?;
+                }
+
+                continue;
+            }
 
             let mut line_labels = labels
                 .iter()
-                .filter(|(_, s)| !s.is_multiline() && s.start.line == span.start.line + idx)
+                .filter(|(_, s)| !s.is_multiline() && s.start.line == first_line + idx)
                 .collect::<Vec<_>>();
 
-            line_labels.sort_by(|a, b| b.1.start.column.cmp(&a.1.start.column));
+            line_labels.sort_by_key(|l| std::cmp::Reverse(l.1.start.column));
 
-            self.render_snippet_line_gutter(f, gutter_size, line_num)?;
+            if self.label_position == LabelPosition::Above {
+                self.render_label_suggestions(f, cx, severity, &line_labels, gutter_size, bracket)?;
+                self.render_line_labels(f, cx, severity, line_labels.clone(), gutter_size, bracket)?;
+            }
 
-            if span.is_multiline() {
+            self.render_snippet_line_gutter(f, cx, gutter_size, line_num)?;
+
+            if bracket {
                 match idx {
                     0 => write!(
                         f,
@@ -489,23 +1528,36 @@ impl GraphicalRenderer {
                 }
             }
 
-            if self.highlight_source {
-                let mut style_line = StyledText::new(line.to_string());
+            let line = self.filter_content(line);
+            let line = expand_tabs(&line, self.column_policy);
+            let (line, invisible) = visualize_invisible_with_ranges(&line, self.column_policy);
+
+            // Labelled columns are always kept, even if they're beyond the edge of the terminal,
+            // since the underlines below them wouldn't point at anything otherwise.
+            let labelled_width = line_labels.iter().map(|(_, s)| s.columns().end).max().unwrap_or(0);
+            let line = self.fit_snippet_line(&line, cx, gutter_size, bracket, labelled_width);
+
+            let syntax_highlights = self.highlight_syntax(context.source.name(), &line);
+            let invisible = if self.use_colors { invisible } else { Vec::new() };
 
-                for (label, label_span) in &line_labels {
-                    let severity = label.severity.unwrap_or(severity);
-                    let style = self.severity_style(severity);
+            if self.highlight_source || !syntax_highlights.is_empty() || !invisible.is_empty() {
+                let mut style_line = StyledText::new(&line);
 
-                    style_line.style_span(label_span.start.column..label_span.end.column, style);
+                for (range, style) in syntax_highlights {
+                    style_line.style_span(char_range_of_bytes(&line, range), style);
                 }
 
-                // Style the labelled span correctly, if no child labels are directly
-                // defined on the line itself.
-                if !span.is_multiline() && line_num - 1 == span.start.line && line_labels.is_empty() {
-                    let severity = context.parent.severity.unwrap_or(severity);
-                    let style = self.severity_style(severity);
+                for range in invisible {
+                    style_line.style_span(range, self.theme.style.warning);
+                }
+
+                if self.highlight_source {
+                    for (label, label_span) in &line_labels {
+                        let style = self.label_style(label, severity);
+                        let columns = label_span.columns();
 
-                    style_line.style_span(span.start.column..span.end.column, style);
+                        style_line.style_span(char_range_of_columns(&line, columns, self.column_policy), style);
+                    }
                 }
 
                 writeln!(f, "{style_line}")?;
@@ -513,32 +1565,208 @@ impl GraphicalRenderer {
                 writeln!(f, "{line}")?;
             }
 
-            if !span.is_multiline() && line_num - 1 == span.start.line && line_labels.is_empty() {
-                self.render_line_labels(f, severity, vec![&(&context.parent, span)], gutter_size, false)?;
-            } else {
-                self.render_line_labels(f, severity, line_labels, gutter_size, true)?;
+            if self.label_position == LabelPosition::Below {
+                self.render_line_labels(f, cx, severity, line_labels.clone(), gutter_size, bracket)?;
+                self.render_label_suggestions(f, cx, severity, &line_labels, gutter_size, bracket)?;
             }
         }
 
-        if span.is_multiline() {
-            self.render_snippet_break(f, gutter_size)?;
+        if bracket {
+            self.render_snippet_break(f, cx, gutter_size)?;
             writeln!(f, "{}", arrows.vertical.style(style))?;
 
-            self.render_snippet_line_empty_gutter(f, gutter_size)?;
+            let mut message = self.label_message(&context.parent);
+
+            if folded {
+                let suffix = fill_template(&self.strings.spans_lines, &[("count", &label_line_count)]);
+
+                message = match message.is_empty() {
+                    true => Cow::Owned(suffix),
+                    false => Cow::Owned(format!("{message} {suffix}")),
+                };
+            }
+
+            let mut message_lines = message.lines();
+
+            self.render_snippet_line_empty_gutter(f, cx, gutter_size)?;
+            match message_lines.next() {
+                Some(line) => writeln!(f, "{} {}", arrows.bottom_left.style(style), line.style(style))?,
+                None => writeln!(f, "{}", arrows.bottom_left.style(style))?,
+            }
+
+            for line in message_lines {
+                self.render_snippet_line_empty_gutter(f, cx, gutter_size)?;
+                writeln!(f, "  {}", line.style(style))?;
+            }
+
+            if let Some(suggestion) = &context.parent.suggestion {
+                self.render_snippet_line_empty_gutter(f, cx, gutter_size)?;
+                writeln!(
+                    f,
+                    "  {}",
+                    self.style(&self.label_suggestion(suggestion), self.theme.style.help)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Truncates the given snippet line, so it fits within the width of the renderer, after the
+    /// indentation, gutter and multiline bracket, if any, which precede it on the same line.
+    ///
+    /// The first `keep` columns of the line are never truncated. Truncated lines end with an ellipsis.
+    fn fit_snippet_line<'a>(
+        &self,
+        line: &'a str,
+        cx: &RenderContext,
+        gutter_size: usize,
+        bracket: bool,
+        keep: usize,
+    ) -> Cow<'a, str> {
+        let gutter_width = gutter_size + display_width(&self.theme.arrows.vertical.to_string(), self.column_policy) + 1;
+        let bracket_width = if bracket { 4 } else { 0 };
+
+        let consumed = self.ident(cx) + gutter_width + bracket_width;
+        let available = self.width.saturating_sub(consumed);
+
+        if display_width(line, self.column_policy) <= available.max(keep) {
+            return Cow::Borrowed(line);
+        }
+
+        // Leave room for the ellipsis itself, unless a label is placed beyond the edge.
+        let limit = available.saturating_sub(1).max(keep);
+        let chars = char_range_of_columns(line, 0..limit, self.column_policy);
+
+        let mut truncated = line.chars().take(chars.end).collect::<String>();
+        truncated.push('…');
+
+        Cow::Owned(truncated)
+    }
+
+    /// Determines which lines of a label snippet with the given amount of lines should be omitted,
+    /// according to [`GraphicalRenderer::max_label_lines`], along with all lines within `folded`.
+    ///
+    /// The first and last lines are always kept, as well as any line where `is_labelled` returns `true`.
+    /// Runs of only a single line are never omitted, since the marker would take up the same space.
+    fn omitted_lines(&self, line_count: usize, folded: Range<usize>, is_labelled: impl Fn(usize) -> bool) -> Vec<bool> {
+        let mut omitted = vec![false; line_count];
+
+        if let Some(max_lines) = self.max_label_lines.filter(|max_lines| line_count > *max_lines) {
+            let head = max_lines.div_ceil(2).max(1);
+            let tail = (max_lines / 2).max(1);
+
+            for (idx, omit) in omitted.iter_mut().enumerate() {
+                *omit = idx >= head && idx < line_count.saturating_sub(tail);
+            }
+        }
+
+        for omit in omitted.iter_mut().take(folded.end).skip(folded.start) {
+            *omit = true;
+        }
+
+        for (idx, omit) in omitted.iter_mut().enumerate() {
+            *omit = *omit && idx > 0 && idx + 1 < line_count && !is_labelled(idx);
+        }
+
+        for idx in 0..line_count {
+            let prev = idx > 0 && omitted[idx - 1];
+            let next = idx + 1 < line_count && omitted[idx + 1];
+
+            if omitted[idx] && !prev && !next {
+                omitted[idx] = false;
+            }
+        }
+
+        omitted
+    }
+
+    /// Renders the inline suggestions of the given labels, directly below the
+    /// labels themselves, aligned with the start of each labelled span.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///   2 │     return fals;
+    ///     ∶            ^^^^ unknown value
+    ///     ∶            help: did you mean `false`?
+    /// ```
+    fn render_label_suggestions(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        severity: Severity,
+        labels: &[&(&Label, Span)],
+        gutter_size: usize,
+        is_multiline: bool,
+    ) -> std::fmt::Result {
+        for (label, span) in labels {
+            let Some(suggestion) = &label.suggestion else {
+                continue;
+            };
+
+            self.render_snippet_break(f, cx, gutter_size)?;
+            if is_multiline {
+                write!(
+                    f,
+                    "{}   ",
+                    self.theme.arrows.vertical.style(self.severity_style(severity))
+                )?;
+            }
+
             writeln!(
                 f,
-                "{} {}",
-                arrows.bottom_left.style(style),
-                context.parent.message.style(style)
+                "{}{}",
+                " ".repeat(span.start.column),
+                self.style(&self.label_suggestion(suggestion), self.theme.style.help)
             )?;
         }
 
         Ok(())
     }
 
-    /// Renders the labels under a given line, so each labelled span is underlined and
+    /// Gets the style of the given label, using the severity of the label itself, if any.
+    ///
+    /// Secondary notes are dimmed, so they're rendered less prominently than other labels.
+    fn label_style(&self, label: &Label, severity: Severity) -> Style {
+        let style = self.severity_style(label.severity.unwrap_or(severity));
+
+        if label.secondary && self.use_colors {
+            style.dimmed()
+        } else {
+            style
+        }
+    }
+
+    /// Gets the message of the given label, as it should be displayed.
+    ///
+    /// Secondary notes are prefixed with the name of the note severity, so they're explicitly
+    /// marked as notes, instead of being mistaken for the primary label.
+    fn label_message<'a>(&self, label: &'a Label) -> Cow<'a, str> {
+        if !label.secondary || label.message.is_empty() {
+            return Cow::Borrowed(&label.message);
+        }
+
+        Cow::Owned(fill_template(
+            &self.strings.secondary_note,
+            &[("note", &self.theme.names.note), ("message", &label.message)],
+        ))
+    }
+
+    /// Formats the inline suggestion of a label.
+    fn label_suggestion(&self, suggestion: &str) -> String {
+        fill_template(
+            &self.strings.label_suggestion,
+            &[("help", &self.theme.names.help), ("suggestion", &suggestion)],
+        )
+    }
+
+    /// Renders the labels of a given line, so each labelled span is underlined and
     /// directing the reader to the label message.
     ///
+    /// Depending on [`GraphicalRenderer::label_position`], the labels are either rendered
+    /// beneath the line or above it, in which case all the rows are mirrored vertically.
+    ///
     /// # Example
     ///
     /// ```text
@@ -549,6 +1777,7 @@ impl GraphicalRenderer {
     fn render_line_labels(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         severity: Severity,
         labels: Vec<&(&Label, Span)>,
         gutter_size: usize,
@@ -560,106 +1789,176 @@ impl GraphicalRenderer {
 
         // If there is only a single label on the line, we can render it more compactly.
         let render_single_line = labels.len() == 1;
+        let above = self.label_position == LabelPosition::Above;
 
-        let style = self.severity_style(severity);
         let arrows = &self.theme.arrows;
 
-        // Write the underlines of each labelled span of the snippet.
+        // Build the underlines of each labelled span of the snippet.
         //
         //  2 │     () => 5,
         //    │     ─┬    ┬
-        self.render_snippet_break(f, gutter_size)?;
-        if is_multiline {
-            write!(f, "{}   ", arrows.vertical.style(style))?;
-        }
+        let underline_len = labels.iter().map(|(_, s)| s.columns().end).max().unwrap_or_default();
+        let mut underline_str = StyledText::blank(underline_len);
 
-        let underline_len = labels.iter().map(|(_, s)| s.end.column).max().unwrap_or_default();
-        let mut underline_str = StyledText::new(" ".repeat(underline_len));
+        // Any other lines of the label message, which are aligned with the line next to the
+        // underline when only a single label is rendered on the line.
+        let mut message_rows = Vec::new();
 
         for (label, span) in &labels {
-            let severity = label.severity.unwrap_or(severity);
-            let style = self.severity_style(severity);
+            let style = self.label_style(label, severity);
+
+            // Labels without any message are rendered as bare underlines, without any joiner.
+            let has_message = !label.message.is_empty();
 
             for offset in span.columns() {
-                let c = if render_single_line {
-                    arrows.arrow_up
-                } else if offset == span.columns().end - 1 {
-                    arrows.hbot
-                } else {
-                    arrows.hbar
+                let c = match (render_single_line, above) {
+                    (true, false) => arrows.arrow_up,
+                    (true, true) => arrows.arrow_down,
+                    (false, false) if has_message && offset == span.columns().end - 1 => arrows.hbot,
+                    (false, true) if has_message && offset == span.columns().end - 1 => arrows.htop,
+                    (false, _) => arrows.hbar,
                 };
 
-                str_set_char(&mut underline_str.str, offset, c);
+                underline_str.set_char(offset, c);
             }
 
             underline_str.style_span(span.columns(), style);
 
-            if render_single_line {
-                underline_str.append(&format!(" {}", label.message), style);
-            }
-        }
+            if render_single_line && has_message {
+                let message = self.label_message(label);
+                let message_lines = message.lines().collect::<Vec<_>>();
 
-        if self.use_colors {
-            writeln!(f, "{underline_str}")?;
-        } else {
-            writeln!(f, "{}", underline_str.str)?;
+                // The line next to the underline is the first line of the message when rendering
+                // below the line and the last line when rendering above it, so the message
+                // always reads from top to bottom.
+                let (inline, rest) = if above {
+                    message_lines.split_last()
+                } else {
+                    message_lines.split_first()
+                }
+                .map_or(("", &[][..]), |(inline, rest)| (*inline, rest));
+
+                underline_str.append(&format!(" {inline}"), style);
+
+                for line in rest {
+                    let mut row = StyledText::blank(underline_len + 1);
+                    row.append(line, style);
+
+                    message_rows.push(row);
+                }
+            }
         }
 
-        // After writing the underlines, we render the lines which go below it to
-        // point to the message of each underline.
+        // Build the rows which point from the underlines to the message of each underline.
         //
         //    │        │    ╰── This is of type Nat
         //    │        ╰── This is of type Nil
         if !render_single_line {
-            let mut label_text_lines = labels
-                .iter()
-                .map(|(_, span)| StyledText::new(" ".repeat(span.end.column + 1)))
-                .collect::<Vec<_>>();
+            let mut label_rows: Vec<Vec<StyledText>> = Vec::with_capacity(labels.len());
 
-            for (idx, (label, span)) in labels.iter().enumerate() {
-                let severity = label.severity.unwrap_or(severity);
-                let style = self.severity_style(severity);
+            for (label, span) in labels.iter().filter(|(label, _)| !label.message.is_empty()) {
+                let style = self.label_style(label, severity);
 
-                let last_column = span.end.column.saturating_sub(1);
+                // Empty spans are rendered one column wide, so they're visible as a caret.
+                let end_column = span.columns().end;
+                let last_column = end_column - 1;
 
-                #[allow(clippy::needless_range_loop, reason = "not looping entire collection")]
-                for line_idx in 0..idx {
-                    // Sets the vertical line in all preceding lines from the current one.
-                    str_set_char(&mut label_text_lines[line_idx].str, last_column, arrows.vertical);
+                // Sets the vertical line in all rows between the underline and the current one.
+                for line in label_rows.iter_mut().flatten() {
+                    line.set_char(last_column, arrows.vertical);
 
-                    label_text_lines[line_idx].style_span(last_column..span.end.column, style);
+                    line.style_span(last_column..end_column, style);
                 }
 
-                let line = &mut label_text_lines[idx];
+                let mut line = StyledText::blank(end_column + 1);
+
+                let corner = if above { arrows.top_left } else { arrows.bottom_left };
 
-                str_set_char(&mut line.str, last_column, arrows.bottom_left);
-                str_set_char(&mut line.str, span.end.column, arrows.hbar);
-                str_set_char(&mut line.str, span.end.column + 1, arrows.hbar);
+                line.set_char(last_column, corner);
+                line.set_char(end_column, arrows.hbar);
 
-                line.style_span(last_column..span.end.column + 1, style);
+                line.style_span(last_column..end_column + 1, style);
 
                 line.append(" ", style);
-                line.append(&label.message, style);
-            }
 
-            for label_text_line in label_text_lines {
-                self.render_snippet_break(f, gutter_size)?;
+                // Subsequent lines of the message are aligned with the first line of the message.
+                let text_column = line.width();
+                let message = self.label_message(label);
+                let mut message_lines = message.lines();
 
-                if is_multiline {
-                    write!(f, "{}   ", arrows.vertical.style(style))?;
-                }
+                line.append(message_lines.next().unwrap_or_default(), style);
 
-                if self.use_colors {
-                    writeln!(f, "{label_text_line}")?;
-                } else {
-                    writeln!(f, "{}", label_text_line.str)?;
+                let mut rows = vec![line];
+
+                for message_line in message_lines {
+                    let mut line = StyledText::blank(text_column);
+
+                    // When rendering above the line, the vertical line of the label itself
+                    // runs through the subsequent lines of the message, down to the underline.
+                    if above {
+                        line.set_char(last_column, arrows.vertical);
+                        line.style_span(last_column..end_column, style);
+                    }
+
+                    line.append(message_line, style);
+                    rows.push(line);
                 }
+
+                label_rows.push(rows);
+            }
+
+            // When rendering above the line, the rows closest to the underline are rendered last.
+            if above {
+                label_rows.reverse();
+            }
+
+            message_rows.extend(label_rows.into_iter().flatten());
+        }
+
+        if above {
+            for row in &message_rows {
+                self.render_label_row(f, cx, severity, row, gutter_size, is_multiline)?;
+            }
+
+            self.render_label_row(f, cx, severity, &underline_str, gutter_size, is_multiline)?;
+        } else {
+            self.render_label_row(f, cx, severity, &underline_str, gutter_size, is_multiline)?;
+
+            for row in &message_rows {
+                self.render_label_row(f, cx, severity, row, gutter_size, is_multiline)?;
             }
         }
 
         Ok(())
     }
 
+    /// Renders a single row of label underlines or messages, beneath or above a line.
+    fn render_label_row(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        severity: Severity,
+        row: &StyledText,
+        gutter_size: usize,
+        is_multiline: bool,
+    ) -> std::fmt::Result {
+        self.render_snippet_break(f, cx, gutter_size)?;
+
+        if is_multiline {
+            write!(
+                f,
+                "{}   ",
+                self.theme.arrows.vertical.style(self.severity_style(severity))
+            )?;
+        }
+
+        if self.use_colors {
+            writeln!(f, "{row}")
+        } else {
+            writeln!(f, "{}", row.plain())
+        }
+    }
+
     /// Renders a label group with one-or-more labels, all sharing the same source file.
     ///
     /// # Example
@@ -678,28 +1977,49 @@ impl GraphicalRenderer {
     fn render_label_group(
         &self,
         f: &mut impl std::fmt::Write,
-        group: LabelGroup,
+        cx: &RenderContext,
+        mut group: LabelGroup,
         severity: Severity,
     ) -> std::fmt::Result {
         if group.labels.is_empty() {
             return Ok(());
         }
 
+        let source = group.source;
+        let source_name = source.name();
+
+        // If the content of the source is unavailable, we can't render any snippet,
+        // so only the name of the source is rendered, along with the label messages.
+        let Some(source_content) = source.try_text() else {
+            return self.render_unavailable_label_group(f, cx, source.as_ref(), &group.labels, severity, false);
+        };
+
+        // If the source has changed since any of the labels were created, their ranges may refer to
+        // entirely different content, so the snippet is replaced by a warning instead.
+        if group.labels.iter().any(|label| label.is_stale(source.as_ref())) {
+            return self.render_unavailable_label_group(f, cx, source.as_ref(), &group.labels, severity, true);
+        }
+
+        for label in &mut group.labels {
+            label.range = SpanRange(clamp_span_to_lines(
+                source_content,
+                normalize_span(source_content, label.range.0.clone()),
+            ));
+        }
+
         // We're assuming the first label is the "most important one", for no
         // reason in particular, but it seems the most intuitive.
         let first_label = group.labels.first().unwrap();
 
-        let source = group.source;
-        let source_name = source.name();
-        let source_content = source.content();
-        let gutter_size = self.gutter_size_of(&source_content);
+        let index = self.line_index(cx, &source, source_content);
+        let gutter_size = self.gutter_size_of(cx, index.line_count());
 
         // Render header for the label group.
         //
         //    ╭─[std/array.lm:35:8]
         //
-        let Span { start, .. } = coords_of_span(&source_content, first_label.range().clone());
-        self.render_snippet_header(f, source_name, gutter_size, start.line, start.column)?;
+        let Span { start, .. } = self.display_coords_of_span(&index, source_content, first_label.range().clone());
+        self.render_snippet_header(f, cx, source_name, gutter_size, start.line, start.column)?;
 
         // Render all the labels in in the group, along with joiners in the vertical gutter.
         //
@@ -710,15 +2030,24 @@ impl GraphicalRenderer {
         //  34 │
         //  35 │        return true;
         //     │        ^^^^^^^^^^^^ expected `Array<T>`, found `Boolean`
-        let contexts = self.group_overlapping_labels(Some(source.clone()), group.labels.into_iter());
+        let mut contexts = group_overlapping_labels(Some(source.clone()), group.labels.into_iter());
+
+        if let Some(distance) = self.merge_distance {
+            contexts = merge_nearby_contexts(contexts, distance, self.context_lines);
+        }
+
+        if self.label_order == LabelOrder::Span {
+            contexts.sort_by_key(|context| context.max_span().0.start);
+        }
+
         let count = contexts.len();
 
         for (idx, context) in contexts.into_iter().enumerate() {
-            self.render_label_context(f, context, severity)?;
+            self.render_label_context(f, cx, context, source_content, &index, severity)?;
 
             // Unless we're at the last label, print a vertical break in the gutter.
             if idx < count - 1 {
-                self.render_snippet_breakln(f, gutter_size)?;
+                self.render_snippet_breakln(f, cx, gutter_size)?;
             }
         }
 
@@ -726,7 +2055,204 @@ impl GraphicalRenderer {
         //
         //    ╰──
         //
-        self.render_snippet_footer(f, gutter_size)
+        self.render_snippet_footer(f, cx, gutter_size)?;
+
+        // Render the expansion chain of the source, if it was generated from another source.
+        //
+        //    ☞ in expansion of [src/main.lm:3:5]
+        //
+        self.render_expansion_chain(f, cx, source.as_ref(), gutter_size)
+    }
+
+    /// Renders the first lines of the given source, for diagnostics without any labels,
+    /// according to [`GraphicalRenderer::excerpt_lines`].
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///     ╭─[lume.toml:1:1]
+    ///   1 │ [package]
+    ///   2 │ name = "lume"
+    ///     ∶ … 12 lines omitted …
+    ///     ╰──
+    /// ```
+    fn render_source_excerpt(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        source: Arc<dyn Source>,
+        max_lines: usize,
+    ) -> std::fmt::Result {
+        let Some(source_content) = source.try_text() else {
+            return Ok(());
+        };
+
+        let index = self.line_index(cx, &source, source_content);
+        let gutter_size = self.gutter_size_of(cx, index.line_count());
+
+        self.render_snippet_header(f, cx, source.name(), gutter_size, 0, 0)?;
+
+        let line_count = source_content.lines().count();
+
+        for (idx, line) in source_content.lines().take(max_lines).enumerate() {
+            let line = self.filter_content(line);
+            let line = expand_tabs(&line, self.column_policy);
+            let (line, invisible) = visualize_invisible_with_ranges(&line, self.column_policy);
+
+            let syntax_highlights = self.highlight_syntax(source.name(), &line);
+            let invisible = if self.use_colors { invisible } else { Vec::new() };
+
+            if syntax_highlights.is_empty() && invisible.is_empty() {
+                self.render_snippet_line(f, cx, gutter_size, line, idx + 1)?;
+            } else {
+                let mut style_line = StyledText::new(&line);
+
+                for (range, style) in syntax_highlights {
+                    style_line.style_span(char_range_of_bytes(&line, range), style);
+                }
+
+                for range in invisible {
+                    style_line.style_span(range, self.theme.style.warning);
+                }
+
+                self.render_snippet_line(f, cx, gutter_size, style_line, idx + 1)?;
+            }
+        }
+
+        if line_count > max_lines {
+            let omitted = fill_template(&self.strings.lines_omitted, &[("count", &(line_count - max_lines))]);
+
+            self.render_snippet_break(f, cx, gutter_size)?;
+            writeln!(f, "{}", self.style(&omitted, self.theme.style.gutter))?;
+        }
+
+        self.render_snippet_footer(f, cx, gutter_size)
+    }
+
+    /// Renders a label group, where the content of the source is unavailable.
+    ///
+    /// Since there is no content to render a snippet from, only the name of the source
+    /// and the messages of the labels are rendered. If `stale` is set, the content exists
+    /// but has changed since the labels were created, which is noted before the messages.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///     ╭─[target/generated.lm]
+    ///     │ expected `Int`, found `Testing`
+    ///     ╰──
+    /// ```
+    fn render_unavailable_label_group(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        source: &dyn Source,
+        labels: &[Label],
+        severity: Severity,
+        stale: bool,
+    ) -> std::fmt::Result {
+        let gutter_size = self.gutter_size_of(cx, 0);
+
+        self.write_ident(f, cx)?;
+
+        write!(
+            f,
+            "{}{}{}",
+            " ".repeat(gutter_size),
+            self.theme.arrows.top_left,
+            self.theme.arrows.hbar,
+        )?;
+
+        match source.name() {
+            Some(name) => writeln!(f, "[{}]", self.style(&name, self.theme.style.link))?,
+            None => writeln!(
+                f,
+                "{}",
+                std::iter::repeat_n(self.theme.arrows.hbar, 10).collect::<String>()
+            )?,
+        }
+
+        if stale {
+            let warning = fill_template(&self.strings.stale_source, &[("warning", &self.theme.names.warning)]);
+
+            self.render_snippet_line_empty_gutter(f, cx, gutter_size)?;
+            writeln!(f, "{}", self.style(&warning, self.severity_style(Severity::Warning)))?;
+        }
+
+        for label in labels.iter().filter(|l| !l.message().is_empty()) {
+            let style = self.label_style(label, severity);
+
+            for line in self.label_message(label).lines() {
+                self.render_snippet_line_empty_gutter(f, cx, gutter_size)?;
+                writeln!(f, "{}", self.style(&line, style))?;
+            }
+        }
+
+        self.render_snippet_footer(f, cx, gutter_size)?;
+        self.render_expansion_chain(f, cx, source, gutter_size)
+    }
+
+    /// Renders the chain of expansion sites of the given source, if the source
+    /// was generated from another source, such as a macro expansion.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///     ☞ in expansion of [<macro expansion>:1:5]
+    ///     ☞ in expansion of [src/main.lm:3:5]
+    /// ```
+    fn render_expansion_chain(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        source: &dyn Source,
+        padding: usize,
+    ) -> std::fmt::Result {
+        /// Defines the maximum depth of expansion chains, to prevent infinite loops on cyclic sources.
+        const MAX_EXPANSION_DEPTH: usize = 32;
+
+        let mut parent = source.expanded_from().cloned();
+        let mut depth = 0;
+
+        while let Some(site) = parent {
+            if depth >= MAX_EXPANSION_DEPTH {
+                break;
+            }
+
+            self.write_ident(f, cx)?;
+            self.write_padding(f, padding)?;
+
+            write!(
+                f,
+                "{} {} ",
+                self.style(&self.theme.symbols.note, self.theme.style.note),
+                self.strings.in_expansion_of
+            )?;
+
+            match (site.source.name(), site.source.try_text()) {
+                (Some(name), Some(content)) => {
+                    let index = self.line_index(cx, &site.source, content);
+                    let span = normalize_span(content, site.span.clone());
+                    let Span { start, .. } = self.display_coords_of_span(&index, content, span);
+
+                    self.render_source_path(f, name, start.line + 1, start.column)?
+                }
+                (None, Some(content)) => {
+                    let index = self.line_index(cx, &site.source, content);
+                    let span = normalize_span(content, site.span.clone());
+                    let Span { start, .. } = self.display_coords_of_span(&index, content, span);
+
+                    writeln!(f, "{}:{}", start.line + 1, start.column + 1)?
+                }
+                (Some(name), None) => writeln!(f, "[{}]", self.style(&name, self.theme.style.link))?,
+                (None, None) => writeln!(f, "{}", self.strings.unknown_source)?,
+            }
+
+            parent = site.source.expanded_from().cloned();
+            depth += 1;
+        }
+
+        Ok(())
     }
 
     /// Renders the header of a source snippet.
@@ -737,12 +2263,13 @@ impl GraphicalRenderer {
     fn render_snippet_header(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         name: Option<&str>,
         padding: usize,
         line: usize,
         column: usize,
     ) -> std::fmt::Result {
-        self.write_ident(f)?;
+        self.write_ident(f, cx)?;
 
         write!(
             f,
@@ -771,11 +2298,12 @@ impl GraphicalRenderer {
     fn render_snippet_gutter(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         padding: usize,
         gutter: impl std::fmt::Display,
         bar: impl std::fmt::Display,
     ) -> std::fmt::Result {
-        self.write_ident(f)?;
+        self.write_ident(f, cx)?;
 
         write!(f, "{gutter:^padding$}{bar} ")
     }
@@ -785,8 +2313,13 @@ impl GraphicalRenderer {
     /// ```text
     //       │
     /// ```
-    fn render_snippet_line_empty_gutter(&self, f: &mut impl std::fmt::Write, padding: usize) -> std::fmt::Result {
-        self.render_snippet_gutter(f, padding, "", self.theme.arrows.vertical)
+    fn render_snippet_line_empty_gutter(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        padding: usize,
+    ) -> std::fmt::Result {
+        self.render_snippet_gutter(f, cx, padding, "", self.theme.arrows.vertical)
     }
 
     /// Renders the gutter for a single line in a source snippet.
@@ -797,11 +2330,17 @@ impl GraphicalRenderer {
     fn render_snippet_line_gutter(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         padding: usize,
         line_num: usize,
     ) -> std::fmt::Result {
+        if !self.show_line_numbers {
+            return self.render_snippet_line_empty_gutter(f, cx, padding);
+        }
+
         self.render_snippet_gutter(
             f,
+            cx,
             padding,
             self.style(&line_num, self.theme.style.gutter),
             self.theme.arrows.vertical,
@@ -816,11 +2355,12 @@ impl GraphicalRenderer {
     fn render_snippet_line(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         padding: usize,
         line: impl std::fmt::Display,
         line_num: usize,
     ) -> std::fmt::Result {
-        self.render_snippet_line_gutter(f, padding, line_num)?;
+        self.render_snippet_line_gutter(f, cx, padding, line_num)?;
 
         writeln!(f, "{line}")
     }
@@ -830,8 +2370,13 @@ impl GraphicalRenderer {
     /// ```text
     //      ∶
     /// ```
-    fn render_snippet_break(&self, f: &mut impl std::fmt::Write, padding: usize) -> std::fmt::Result {
-        self.render_snippet_gutter(f, padding, "", self.theme.arrows.vertical_break)
+    fn render_snippet_break(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        padding: usize,
+    ) -> std::fmt::Result {
+        self.render_snippet_gutter(f, cx, padding, "", self.theme.arrows.vertical_break)
     }
 
     /// Renders a single vertical break in a source snippet.
@@ -839,8 +2384,13 @@ impl GraphicalRenderer {
     /// ```text
     //      ∶
     /// ```
-    fn render_snippet_breakln(&self, f: &mut impl std::fmt::Write, padding: usize) -> std::fmt::Result {
-        self.render_snippet_gutter(f, padding, "", self.theme.arrows.vertical_break)?;
+    fn render_snippet_breakln(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        padding: usize,
+    ) -> std::fmt::Result {
+        self.render_snippet_gutter(f, cx, padding, "", self.theme.arrows.vertical_break)?;
 
         writeln!(f)
     }
@@ -850,8 +2400,13 @@ impl GraphicalRenderer {
     /// ```text
     //    ╰──
     /// ```
-    fn render_snippet_footer(&self, f: &mut impl std::fmt::Write, padding: usize) -> std::fmt::Result {
-        self.write_ident(f)?;
+    fn render_snippet_footer(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        padding: usize,
+    ) -> std::fmt::Result {
+        self.write_ident(f, cx)?;
         self.write_padding(f, padding)?;
 
         writeln!(
@@ -891,11 +2446,35 @@ impl GraphicalRenderer {
     ///   help: doc comments are only allowed on definitions
     ///   help: you can use triple forward-slash to denote doc comments
     /// ```
-    fn render_footer(&self, f: &mut impl std::fmt::Write, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
-        if let Some(help) = diagnostic.help() {
-            for line in help {
-                self.render_help(f, &line)?;
-            }
+    fn render_footer(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        diagnostic: &dyn Diagnostic,
+    ) -> std::fmt::Result {
+        let Some(help) = diagnostic.help() else {
+            return Ok(());
+        };
+
+        let mut help = help.collect::<Vec<_>>();
+
+        // Help messages with a higher priority are shown first, while help messages
+        // with the same priority retain their original order.
+        help.sort_by_key(|h| std::cmp::Reverse(h.priority));
+
+        // Alternatives are only numbered, if there's more than one of them.
+        let alternatives = help.iter().filter(|h| h.alternative).count();
+        let mut index = 0;
+
+        for line in &help {
+            let option = if line.alternative && alternatives > 1 {
+                index += 1;
+                Some((index, alternatives))
+            } else {
+                None
+            };
+
+            self.render_help(f, cx, line, option)?;
         }
 
         Ok(())
@@ -922,9 +2501,21 @@ impl GraphicalRenderer {
     ///  34 │         return (0..10);
     ///     |                ^     ^
     /// ```
-    fn render_help(&self, f: &mut impl std::fmt::Write, help: &Help) -> std::fmt::Result {
-        let help_gutter = "   help: ";
-        let help_padding = help_gutter.to_string().len();
+    ///
+    /// Alternative fixes are suffixed with their number, given as `option`:
+    /// ```text
+    ///   help: add a cast to `Int` (option 1 of 2)
+    /// ```
+    fn render_help(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        help: &Help,
+        option: Option<(usize, usize)>,
+    ) -> std::fmt::Result {
+        let help_prefix = fill_template(&self.strings.help_prefix, &[("help", &self.theme.names.help)]);
+        let help_gutter = format!("   {help_prefix} ");
+        let help_padding = display_width(&help_gutter, self.column_policy);
 
         // If the help message has multiple lines, we need to indent the other lines
         // with the same padding, so it lines up correctly.
@@ -940,8 +2531,32 @@ impl GraphicalRenderer {
         //   help: expected type `Array<T>`
         //         found type `Boolean`
         // ```
-        for (i, line) in help.message.lines().enumerate() {
-            self.write_ident(f)?;
+        //
+        // Lines which are wider than the renderer are wrapped at word boundaries,
+        // where the continuation lines are indented the same way.
+        let wrap_width = self.width.saturating_sub(self.ident(cx) + help_padding);
+
+        let mut message = help.message.clone();
+
+        if let Some((index, count)) = option {
+            let suffix = format!(
+                " {}",
+                fill_template(&self.strings.help_option, &[("index", &index), ("count", &count)])
+            );
+
+            // The suffix is placed on the first line, directly after the message.
+            match message.find('\n') {
+                Some(end) => message.insert_str(end, &suffix),
+                None => message.push_str(&suffix),
+            }
+        }
+
+        let lines = message
+            .lines()
+            .flat_map(|line| wrap_words(line, wrap_width, self.column_policy));
+
+        for (i, line) in lines.enumerate() {
+            self.write_ident(f, cx)?;
 
             if i == 0 {
                 writeln!(f, "{}{}", self.style(&help_gutter, self.theme.style.help), line)?;
@@ -954,24 +2569,112 @@ impl GraphicalRenderer {
         let mut suggestion_groups: IndexMap<Option<String>, Vec<Suggestion>> = IndexMap::new();
 
         for suggestion in &help.suggestions {
-            let source = suggestion.source();
+            // File-level operations have no source, so they're rendered as bullet points instead.
+            let Some(source) = suggestion.source() else {
+                continue;
+            };
+
+            // Suggestions can't be rendered without the content of their source.
+            let Some(source_content) = source.try_text() else {
+                continue;
+            };
+
             let source_name = source.name().map(|n| n.to_string());
-            let source_content = source.content();
 
-            padding = padding.max(self.gutter_size_of(&source_content));
+            let index = self.line_index(cx, &source, source_content);
+            padding = padding.max(self.gutter_size_of(cx, index.line_count()));
+
+            let suggestion = normalize_suggestion(suggestion, source_content);
+
+            if let Some(group) = suggestion_groups.get_mut(&source_name) {
+                group.push(suggestion);
+            } else {
+                suggestion_groups.insert(source_name, vec![suggestion]);
+            }
+        }
+
+        // Render the title of the fix above the suggestions, so multiple fix variants can be told apart.
+        //
+        //   help: the types of both operands must match
+        //         convert the right operand:
+        //
+        if let Some(title) = help.title.as_ref().filter(|_| !help.suggestions.is_empty()) {
+            self.write_ident(f, cx)?;
+            self.write_padding(f, help_padding)?;
+
+            writeln!(f, "{}", self.style(&format!("{title}:"), self.theme.style.help))?;
+        }
+
+        if !cx.suppress_snippets {
+            // When the suggestions span multiple files, each group is preceded by the path of it's file.
+            let show_paths = suggestion_groups.len() > 1;
+
+            for (name, suggestions) in suggestion_groups {
+                if let Some(name) = name.as_deref().filter(|_| show_paths) {
+                    self.render_suggestion_path(f, cx, name, padding)?;
+                }
+
+                self.render_suggestion_group(f, cx, &suggestions, padding)?;
+            }
+        }
+
+        for suggestion in help.suggestions.iter().filter(|s| s.is_file_operation()) {
+            self.render_file_operation(f, cx, suggestion, help_padding)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the path of the file which a group of suggestions refer to.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///    ╭─[src/main.lm]
+    /// ```
+    fn render_suggestion_path(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        name: &str,
+        padding: usize,
+    ) -> std::fmt::Result {
+        self.write_ident(f, cx)?;
+        self.write_padding(f, padding)?;
+
+        writeln!(
+            f,
+            "{}{}[{}]",
+            self.theme.arrows.top_left,
+            self.theme.arrows.hbar,
+            self.style(&name, self.theme.style.link)
+        )
+    }
 
-            if let Some(group) = suggestion_groups.get_mut(&source_name) {
-                group.push(suggestion.clone());
-            } else {
-                suggestion_groups.insert(source_name, vec![suggestion.clone()]);
-            }
-        }
+    /// Renders a single file-level operation, which is attached to a help message.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///    help: move this type into its own module
+    ///          • create file `src/point.lm`
+    /// ```
+    fn render_file_operation(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        suggestion: &Suggestion,
+        padding: usize,
+    ) -> std::fmt::Result {
+        self.write_ident(f, cx)?;
+        self.write_padding(f, padding)?;
 
-        for (_, suggestions) in suggestion_groups {
-            self.render_suggestion_group(f, &suggestions, padding)?;
-        }
+        let style = match suggestion {
+            Suggestion::DeleteFile { .. } => self.theme.style.deletion,
+            _ => self.theme.style.insertion,
+        };
 
-        Ok(())
+        writeln!(f, "{} {suggestion}", self.style(&"•", style))
     }
 
     /// Renders a group of suggestions defined within a help message, where
@@ -990,6 +2693,7 @@ impl GraphicalRenderer {
     fn render_suggestion_group(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
         suggestions: &[Suggestion],
         padding: usize,
     ) -> std::fmt::Result {
@@ -998,8 +2702,12 @@ impl GraphicalRenderer {
         }
 
         let first_suggestion = suggestions.first().unwrap().clone();
-        let source = first_suggestion.source();
-        let source_content = source.content();
+        let Some(source) = first_suggestion.source() else {
+            return Ok(());
+        };
+
+        let source_content = source.text();
+        let line_index = self.line_index(cx, &source, source_content);
 
         let mut suggested_lines: IndexMap<usize, Vec<Suggestion>> = IndexMap::new();
 
@@ -1008,9 +2716,12 @@ impl GraphicalRenderer {
                 Suggestion::Insertion { location, .. } => location.offset,
                 Suggestion::Deletion { range } => range.span.0.start,
                 Suggestion::Replacement { range, .. } => range.span.0.start,
+                Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => {
+                    continue
+                }
             };
 
-            let Coord { line, .. } = coords_of_idx(&source_content, start_idx);
+            let line = line_index.line_of(start_idx);
 
             if let Some(group) = suggested_lines.get_mut(&line) {
                 group.push(suggestion.clone());
@@ -1022,11 +2733,11 @@ impl GraphicalRenderer {
         let suggestion_len = suggested_lines.len();
 
         for (index, (line, suggestions)) in suggested_lines.into_iter().enumerate() {
-            self.render_suggestion_line(f, line, suggestions)?;
+            self.render_suggestion_line(f, cx, source_content, &line_index, line, suggestions)?;
 
             // Unless we're at the last suggestion, print a vertical break in the gutter.
             if index < suggestion_len - 1 {
-                self.render_snippet_breakln(f, padding)?;
+                self.render_snippet_breakln(f, cx, padding)?;
             }
         }
 
@@ -1044,6 +2755,9 @@ impl GraphicalRenderer {
     fn render_suggestion_line(
         &self,
         f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        source_content: &str,
+        index: &LineIndex,
         line_num: usize,
         mut suggestions: Vec<Suggestion>,
     ) -> std::fmt::Result {
@@ -1059,12 +2773,31 @@ impl GraphicalRenderer {
         // has no effect on previous suggestions on the same line.
         suggestions.reverse();
 
-        let first_suggestion = suggestions.first().unwrap();
+        // File-level operations have no source lines, so they're rendered elsewhere.
+        suggestions.retain(|s| !s.is_file_operation());
+
+        let Some(first_suggestion) = suggestions.first() else {
+            return Ok(());
+        };
+
+        let Some(first_span) = first_suggestion.span() else {
+            return Ok(());
+        };
+
+        let (source_line, _) = index.extract_with_context_offset(source_content, first_span, 0);
+        let line_start = source_line.as_ptr() as usize - source_content.as_ptr() as usize;
+        let source_line = self.filter_content(source_line).into_owned();
+        let padding = self.gutter_size_of(cx, index.line_count());
 
-        let source = first_suggestion.source();
-        let source_content = source.content();
-        let source_line = extract_with_context(&source_content, first_suggestion.span(), 0);
-        let padding = self.gutter_size_of(&source_content);
+        let has_insertions = suggestions
+            .iter()
+            .any(|s| matches!(s, Suggestion::Insertion { .. } | Suggestion::Replacement { .. }));
+
+        if self.suggestion_style == SuggestionStyle::Diff && has_insertions {
+            suggestions.reverse();
+
+            return self.render_suggestion_diff(f, cx, padding, &source_line, line_start, &suggestions, line_num);
+        }
 
         // Render the suggestion itself.
         //
@@ -1074,19 +2807,20 @@ impl GraphicalRenderer {
         let mut styled_line = Box::new(source_line) as Box<dyn std::fmt::Display>;
 
         for suggestion in &suggestions {
-            let span = coords_of_span(&source_content, suggestion.span());
+            let Some(span) = suggestion.span() else { continue };
+            let span = span.start.saturating_sub(line_start)..span.end.saturating_sub(line_start);
 
             styled_line = self.style_suggestion_line(suggestion, styled_line, span);
         }
 
-        self.render_snippet_line(f, padding, styled_line, line_num + 1)?;
+        self.render_snippet_line(f, cx, padding, styled_line, line_num + 1)?;
 
         // Render the arrows below the suggestions
         //
         //     |                ^     ^
         //
 
-        self.render_snippet_gutter(f, padding, "", self.theme.arrows.vertical)?;
+        self.render_snippet_gutter(f, cx, padding, "", self.theme.arrows.vertical)?;
 
         // Un-reverse the suggestions again, so we can draw the arrows
         // below the marked sections of the suggestions.
@@ -1094,24 +2828,26 @@ impl GraphicalRenderer {
 
         let mut offset = 0;
         for suggestion in &suggestions {
-            let span = suggestion.span();
-            let Span { start, end } = coords_of_span(&source_content, span);
+            let Some(span) = suggestion.span() else { continue };
+            let Span { start, end } = self.display_coords_of_span(index, source_content, span);
 
             // Write the padding between the arrows.
-            let spacing = start.column.checked_sub(offset).unwrap_or_default();
+            let spacing = start.column.saturating_sub(offset);
 
             write!(f, "{}", " ".repeat(spacing))?;
 
             let style = match suggestion {
                 Suggestion::Insertion { .. } => self.theme.style.insertion,
                 Suggestion::Replacement { .. } => self.theme.style.insertion,
-                Suggestion::Deletion { .. } => self.theme.style.deletion,
+                Suggestion::Deletion { .. } | Suggestion::DeleteFile { .. } => self.theme.style.deletion,
+                Suggestion::CreateFile { .. } | Suggestion::RenameFile { .. } => self.theme.style.insertion,
             };
 
             let arrow_count = match suggestion {
-                Suggestion::Insertion { value, .. } => value.len(),
-                Suggestion::Replacement { replacement, .. } => replacement.len(),
-                Suggestion::Deletion { range } => range.span.0.len(),
+                Suggestion::Insertion { value, .. } => display_width(value, self.column_policy),
+                Suggestion::Replacement { replacement, .. } => display_width(replacement, self.column_policy),
+                Suggestion::Deletion { .. } => end.column.saturating_sub(start.column),
+                Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => 0,
             };
 
             for _ in 0..arrow_count {
@@ -1124,20 +2860,93 @@ impl GraphicalRenderer {
         writeln!(f)
     }
 
+    /// Renders a single line where one-or-more suggestions are defined, followed
+    /// by the line after applying the suggestions on it's own line.
+    ///
+    /// The given suggestions must be sorted in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```text
+    ///    help: a type with a similar name exists
+    ///  12 │     let a = new Tesitng();
+    ///   + │     let a = new Testing();
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn render_suggestion_diff(
+        &self,
+        f: &mut impl std::fmt::Write,
+        cx: &RenderContext,
+        padding: usize,
+        source_line: &str,
+        line_start: usize,
+        suggestions: &[Suggestion],
+        line_num: usize,
+    ) -> std::fmt::Result {
+        self.render_snippet_line(f, cx, padding, source_line, line_num + 1)?;
+
+        let mut fixed_line = String::new();
+        let mut cursor = 0;
+
+        // Converts an offset within the source into an offset within the line,
+        // so spans which continue past the line are cut off at the end of the line.
+        let local_offset = |offset: usize, cursor: usize| {
+            let offset = offset.saturating_sub(line_start).max(cursor).min(source_line.len());
+
+            normalize_span(source_line, offset..offset).start
+        };
+
+        for suggestion in suggestions {
+            let Some(span) = suggestion.span() else { continue };
+            let start = local_offset(span.start, cursor);
+
+            fixed_line.push_str(&source_line[cursor..start]);
+
+            cursor = match suggestion {
+                Suggestion::Insertion { value, .. } => {
+                    fixed_line.push_str(&self.style(value, self.theme.style.insertion).to_string());
+
+                    start
+                }
+                Suggestion::Replacement { replacement, .. } => {
+                    fixed_line.push_str(&self.style(replacement, self.theme.style.insertion).to_string());
+
+                    local_offset(span.end, start)
+                }
+                Suggestion::Deletion { .. } => local_offset(span.end, start),
+                Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => start,
+            };
+        }
+
+        fixed_line.push_str(&source_line[cursor..]);
+
+        // Without any line numbers, the gutter is collapsed, so the marker replaces the bar instead.
+        if self.show_line_numbers {
+            self.render_snippet_gutter(
+                f,
+                cx,
+                padding,
+                self.style(&"+", self.theme.style.insertion),
+                self.theme.arrows.vertical,
+            )?;
+        } else {
+            self.render_snippet_gutter(f, cx, padding, "", self.style(&"+", self.theme.style.insertion))?;
+        }
+
+        writeln!(f, "{fixed_line}")
+    }
+
     /// Styles a single suggestion into a "fixed" line.
     fn style_suggestion_line<'a>(
         &self,
         suggestion: &Suggestion,
         line: Box<dyn std::fmt::Display + 'a>,
-        span: Span,
+        span: Range<usize>,
     ) -> Box<dyn std::fmt::Display + 'a> {
         let line = line.to_string();
 
-        let span: Range<usize> = if span.is_multiline() {
-            span.start.column..line.len()
-        } else {
-            span.start.column..span.end.column
-        };
+        // Spans which continue past the end of the line are cut off at the end of it.
+        let span = span.start..span.end.min(line.len());
 
         let formatted = match suggestion {
             Suggestion::Deletion { .. } => {
@@ -1167,159 +2976,107 @@ impl GraphicalRenderer {
                     after
                 )
             }
+            Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => line,
         };
 
         Box::new(formatted) as Box<dyn std::fmt::Display>
     }
-
-    /// Groups a list of [`Label`]s into a tree of [`Label`]s, where each parent
-    /// label overlaps with all it's direct child nodes.
-    fn group_overlapping_labels(
-        &self,
-        diag_source: Option<Arc<dyn Source>>,
-        labels: impl Iterator<Item = Label>,
-    ) -> Vec<LabelContext> {
-        let mut labels = labels.into_iter().enumerate().collect::<Vec<(usize, Label)>>();
-        labels.sort_unstable_by_key(|(_, l)| l.range().0.start);
-
-        let mut contexts = Vec::with_capacity(labels.len());
-        let mut visited = HashSet::new();
-
-        for (idx, (pos, parent)) in labels.iter().cloned().enumerate() {
-            // If no source code is attached to the label itself, see if
-            // a source is attached to the parent diagnostic.
-            //
-            // If no source is found on either, skip over the label entirely.
-            let Some(parent_source) = parent.source.clone().or(diag_source.clone()) else {
-                continue;
-            };
-
-            if !visited.insert(idx) {
-                continue;
-            }
-
-            let parent_span = parent.range.0.clone();
-            let mut context = LabelContext {
-                pos,
-                parent,
-                children: Vec::new(),
-                source: parent_source.clone(),
-            };
-
-            // If the parent label only spans a single line, it cannot contain any children.
-            if !coords_of_span(parent_source.content().as_ref(), parent_span.clone()).is_multiline() {
-                contexts.push(context);
-
-                continue;
-            }
-
-            for (idx, (pos, child)) in labels.iter().enumerate().skip(idx + 1) {
-                let Some(child_source) = child.source.clone().or(diag_source.clone()) else {
-                    continue;
-                };
-
-                // Group the labels into groups where all elements have the same source file.
-                // This helps prevent multiple label headers in a row from defining the same file path.
-                if child_source.name() != parent_source.name() {
-                    continue;
-                }
-
-                if parent_span.contains(&child.range.0.start) && visited.insert(idx) {
-                    context.children.push((*pos, child.clone()));
-                }
-            }
-
-            contexts.push(context);
-        }
-
-        // Sort the labels back to their original ordering.
-        contexts.sort_unstable_by_key(|c| c.pos);
-
-        for context in &mut contexts {
-            context.children.sort_unstable_by_key(|(pos, _)| *pos);
-        }
-
-        contexts
-    }
 }
 
-#[derive(Debug)]
-struct LabelContext {
-    /// Used for reordering and sorting.
-    pub pos: usize,
-
-    /// Defines the root label within the context.
-    pub parent: Label,
-
-    /// Defines all child labels, which are contained within the parent.
-    pub children: Vec<(usize, Label)>,
+struct LabelGroup {
+    /// Defines all the labels in the group
+    pub labels: Vec<Label>,
 
-    /// Defines the common source for the labels.
+    /// Defines the common source for the labels
     pub source: Arc<dyn Source>,
 }
 
-impl LabelContext {
-    /// Gets the span which contains all labels within the context, including the parent.
-    pub fn max_span(&self) -> SpanRange {
-        let start = self.parent.range.0.start;
-        let end = self.children.iter().map(|(_, c)| c.range.0.end).max().unwrap_or(0);
+/// Defines a single cell of a [`StyledText`], which is a single character along with it's style.
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    c: char,
 
-        SpanRange(start..end.max(self.parent.range().0.end))
-    }
-}
+    /// Defines the display width of the character, in columns.
+    width: usize,
 
-struct LabelGroup {
-    /// Defines all the labels in the group
-    pub labels: Vec<Label>,
+    style: Style,
+}
 
-    /// Defines the common source for the labels
-    pub source: Arc<dyn Source>,
+impl Cell {
+    fn new(c: char, style: Style) -> Self {
+        Self {
+            c,
+            width: c.width().unwrap_or_default(),
+            style,
+        }
+    }
 }
 
 /// Defines a text span, where each character can be individually styled.
+///
+/// The text is stored as a list of cells, which are indexed by character, so placing
+/// and styling characters is **O(1)**, regardless of the characters before them.
 #[derive(Debug, Clone)]
 struct StyledText {
-    str: String,
-    chars: Vec<Style>,
+    cells: Vec<Cell>,
 }
 
 impl StyledText {
-    pub fn new(str: String) -> Self {
+    /// Creates a new [`StyledText`] from the given string, without any specific styling.
+    pub fn new(str: &str) -> Self {
+        Self {
+            cells: str.chars().map(|c| Cell::new(c, Style::new())).collect(),
+        }
+    }
+
+    /// Creates a new [`StyledText`] with the given amount of spaces.
+    pub fn blank(len: usize) -> Self {
         Self {
-            chars: vec![Style::new(); str.len()],
-            str,
+            cells: vec![Cell::new(' ', Style::new()); len],
         }
     }
 
-    /// Appends the given string, without any specific styling.
+    /// Appends the given string, using the given style.
     pub fn append(&mut self, str: &str, style: Style) {
-        self.chars.extend(vec![style; str.len()]);
-        self.str.push_str(str);
+        self.cells.extend(str.chars().map(|c| Cell::new(c, style)));
+    }
+
+    /// Changes the character at the given character offset, while keeping it's style.
+    ///
+    /// If the offset is outside of the text, the text is left unchanged.
+    pub fn set_char(&mut self, offset: usize, c: char) {
+        if let Some(cell) = self.cells.get_mut(offset) {
+            *cell = Cell::new(c, cell.style);
+        }
     }
 
     /// Applies a style to a span of characters.
     pub fn style_span(&mut self, span: Range<usize>, style: Style) {
-        for idx in span {
-            let Some(s) = self.chars.get_mut(idx) else {
-                break;
-            };
+        let end = span.end.min(self.cells.len());
 
-            *s = style;
+        for cell in self.cells.get_mut(span.start..end).into_iter().flatten() {
+            cell.style = style;
         }
     }
 
-    pub fn render(self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
-        for (c, style) in self.str.chars().zip(self.chars.into_iter()) {
-            write!(f, "{}", c.style(style))?;
-        }
+    /// Gets the display width of the text, in columns.
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(|cell| cell.width).sum()
+    }
 
-        Ok(())
+    /// Gets the text without any styling.
+    pub fn plain(&self) -> String {
+        self.cells.iter().map(|cell| cell.c).collect()
     }
 }
 
 impl Display for StyledText {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.clone().render(f)
+        for cell in &self.cells {
+            write!(f, "{}", cell.c.style(cell.style))?;
+        }
+
+        Ok(())
     }
 }
 
@@ -1330,340 +3087,179 @@ impl Display for StyledText {
 ///
 /// If the `termsize` is not enabled, the default terminal width is returned (defaults to `80`).
 fn terminal_width() -> usize {
-    #[cfg(feature = "termsize")]
+    #[cfg(all(feature = "termsize", not(target_family = "wasm")))]
     if let Some((terminal_size::Width(w), _)) = terminal_size::terminal_size() {
         w as usize
     } else {
         DEFAULT_TERM_WIDTH
     }
 
-    #[cfg(not(feature = "termsize"))]
+    #[cfg(not(all(feature = "termsize", not(target_family = "wasm"))))]
     DEFAULT_TERM_WIDTH
 }
 
-/// Changes a single character inside the given [`String`], at the offset `offset`.
-///
-/// The offset defines a character offset, not a byte offset. The function supports
-/// UTF-8, but it does come at the cost of having a time-complexity of **O(n)**, where
-/// `n` is the given offset.
-fn str_set_char(str: &mut String, offset: usize, c: char) -> bool {
-    let Some(char_range) = str.char_indices().nth(offset).map(|(i, c)| (i..i + c.len_utf8())) else {
-        return false;
-    };
-
-    str.replace_range(char_range, &c.to_string());
-
-    true
-}
-
-/// Splits the given string into `N` slices, where each index defines
-/// where the source string should be split.
-fn split_str_at<const N: usize>(str: &str, mut indices: Vec<usize>) -> [&str; N] {
-    indices.sort();
-    indices.reverse();
-
-    let mut current = str;
-    let mut slices = [""; N];
-
-    for (i, index) in indices.iter().enumerate() {
-        let (before, after) = current.split_at(*index);
-
-        current = before;
-        slices[N - i - 1] = after;
-    }
-
-    slices[0] = current;
-
-    slices
-}
-
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
-struct Coord {
-    pub line: usize,
-    pub column: usize,
-}
-
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
-struct Span {
-    pub start: Coord,
-    pub end: Coord,
+/// Defines the level of color support of a terminal.
+#[cfg(feature = "terminal")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorSupport {
+    None,
+    Ansi,
+    TrueColor,
 }
 
-impl Span {
-    pub fn columns(self) -> Range<usize> {
-        debug_assert_eq!(self.start.line, self.end.line);
-
-        if self.start.column > self.end.column {
-            return self.start.column..self.start.column + 1;
-        }
-
-        self.start.column..self.end.column
-    }
-
-    pub fn is_multiline(self) -> bool {
-        self.start.line != self.end.line
+/// Detects the level of color support of a terminal, from the given environment variables
+/// and whether the output stream is a terminal.
+#[cfg(feature = "terminal")]
+fn detect_color_support(var: impl Fn(&str) -> Option<String>, is_terminal: bool) -> ColorSupport {
+    if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return ColorSupport::None;
     }
-}
-
-/// Gets the line number and column indices which contains the given span.
-fn coords_of_span(str: &str, span: impl Into<Range<usize>>) -> Span {
-    let range: Range<usize> = span.into();
-
-    let start = coords_of_idx(str, range.start);
-    let end = coords_of_idx(str, range.end);
-
-    Span { start, end }
-}
 
-/// Gets the line number and column number which contains the character at the given index.
-fn coords_of_idx(str: &str, index: usize) -> Coord {
-    if index > str.len() {
-        let line_cnt = str.lines().count();
+    let truecolor =
+        || var("COLORTERM").is_some_and(|v| matches!(v.to_ascii_lowercase().as_str(), "truecolor" | "24bit"));
 
-        return Coord {
-            line: line_cnt.saturating_sub(1),
-            column: str.lines().last().map(|l| l.len()).unwrap_or_default(),
-        };
+    match var("FORCE_COLOR").as_deref() {
+        Some("0") | Some("false") => return ColorSupport::None,
+        Some("3") => return ColorSupport::TrueColor,
+        Some(_) if truecolor() => return ColorSupport::TrueColor,
+        Some(_) => return ColorSupport::Ansi,
+        None => {}
     }
 
-    let mut line = 0;
-    let mut column = 0;
-
-    for (i, c) in str.chars().peekable().enumerate() {
-        if i == index {
-            return Coord { line, column };
-        }
-
-        if c == '\n' {
-            line += 1;
-            column = 0;
-        } else {
-            column += 1;
-        }
+    if !is_terminal || var("TERM").as_deref() == Some("dumb") {
+        return ColorSupport::None;
     }
 
-    if index == str.len() {
-        return Coord { line, column };
+    if truecolor() {
+        ColorSupport::TrueColor
+    } else {
+        ColorSupport::Ansi
     }
-
-    Coord::default()
 }
 
-#[cfg(test)]
-mod coords_of_idx_tests {
-    use super::{coords_of_idx, Coord};
-
-    #[test]
-    fn test_index_out_of_range() {
-        let source = "let a = 1;";
-        let Coord { line, column } = coords_of_idx(source, 12);
-
-        assert_eq!(line, 0);
-        assert_eq!(column, 10);
-    }
-
-    #[test]
-    fn test_index_at_end_boundary() {
-        let source = "let a = 1;";
-        let Coord { line, column } = coords_of_idx(source, 10);
-
-        assert_eq!(line, 0);
-        assert_eq!(column, 10);
-    }
-
-    #[test]
-    fn test_multiline() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let Coord { line, column } = coords_of_idx(source, 26);
-
-        assert_eq!(line, 2);
-        assert_eq!(column, 4);
-    }
-
-    #[test]
-    fn test_multiline_line_boundary_start() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let Coord { line, column } = coords_of_idx(source, 22);
-
-        assert_eq!(line, 2);
-        assert_eq!(column, 0);
-    }
-
-    #[test]
-    fn test_multiline_line_boundary_end() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let Coord { line, column } = coords_of_idx(source, 36);
-
-        assert_eq!(line, 2);
-        assert_eq!(column, 14);
+/// Detects the background color of a terminal from the `COLORFGBG` environment variable.
+///
+/// The background is the last field of the variable, since some terminals insert
+/// a field between the foreground and background, such as `15;default;0`.
+#[cfg(feature = "detect-background")]
+fn detect_background(var: impl Fn(&str) -> Option<String>) -> Option<Background> {
+    let value = var("COLORFGBG")?;
+    let background = value.rsplit(';').next()?.trim().parse::<u8>().ok()?;
+
+    match background {
+        7 | 9..=15 => Some(Background::Light),
+        0..=6 | 8 => Some(Background::Dark),
+        _ => None,
     }
 }
 
-/// Extracts a slice of the given string, which contains the lines where
-/// `span` is contained, along with the `context_lines` amount of surrounding lines.
-///
-/// # Example
-///
-/// ```
-/// use error_snippet::render::graphical::extract_with_context;
-///
-/// let source = r#"let a = 1;
-/// let b = 2;
-/// let c = a + b;
-/// let d = c * 2;
-/// let e = (d + 3) * 2;"#;
-///
-/// // indexes "a + b" on line 3
-/// let span = 30..35;
-///
-/// let snipped = extract_with_context(source, span, 1);
-///
-/// assert_eq!(snipped, r#"let b = 2;
-/// let c = a + b;
-/// let d = c * 2;"#);
-/// ```
-pub fn extract_with_context(input: &str, range: impl Into<Range<usize>>, context_lines: usize) -> &str {
-    let (slice, _) = extract_with_context_offset(input, range, context_lines);
+/// Converts the given byte range within the line into a range of character indices.
+fn char_range_of_bytes(line: &str, range: Range<usize>) -> Range<usize> {
+    let range = normalize_span(line, range);
 
-    slice
+    line[..range.start].chars().count()..line[..range.end].chars().count()
 }
 
-/// Extracts a slice of the given string, which contains the lines where
-/// `span` is contained, along with the `context_lines` amount of surrounding lines.
-///
-/// The function also returns the line number where the "center" of the context starts.
-///
-/// # Example
-///
-/// ```
-/// use error_snippet::render::graphical::extract_with_context;
-///
-/// let source = r#"let a = 1;
-/// let b = 2;
-/// let c = a + b;
-/// let d = c * 2;
-/// let e = (d + 3) * 2;"#;
-///
-/// // indexes "a + b" on line 3
-/// let span = 30..35;
-///
-/// let snipped = extract_with_context(source, span, 1);
+/// Normalizes the span of the given suggestion against the content of it's source,
+/// so invalid spans are degraded gracefully instead of panicking.
 ///
-/// assert_eq!(snipped, r#"let b = 2;
-/// let c = a + b;
-/// let d = c * 2;"#);
-/// ```
-pub fn extract_with_context_offset(input: &str, range: impl Into<Range<usize>>, context_lines: usize) -> (&str, usize) {
-    let range: Range<usize> = range.into();
-
-    let mut line_start = 0;
-    let mut line_spans = Vec::new();
-
-    for line in input.lines() {
-        let line_len = line.len();
-        let span = line_start..(line_start + line_len);
-
-        line_spans.push(span);
-
-        // +1 for '\n' (assuming UNIX-style newlines)
-        line_start += line_len + 1;
-    }
-
-    // Determine the lines that intersect with the byte range
-    let mut matching_lines = Vec::new();
-    for (i, span) in line_spans.iter().enumerate() {
-        if span.end > range.start && span.start < range.end {
-            matching_lines.push(i);
+/// See [`normalize_span`] for more information.
+fn normalize_suggestion(suggestion: &Suggestion, content: &str) -> Suggestion {
+    match suggestion {
+        Suggestion::Deletion { range } => Suggestion::Deletion {
+            range: SourceRange::new(range.source.clone(), normalize_span(content, range.span.0.clone())),
+        },
+        Suggestion::Insertion { location, value } => Suggestion::Insertion {
+            location: SourceLocation::new(
+                location.source.clone(),
+                normalize_span(content, location.offset..location.offset).start,
+            ),
+            value: value.clone(),
+        },
+        Suggestion::Replacement { range, replacement } => Suggestion::Replacement {
+            range: SourceRange::new(range.source.clone(), normalize_span(content, range.span.0.clone())),
+            replacement: replacement.clone(),
+        },
+        Suggestion::CreateFile { .. } | Suggestion::DeleteFile { .. } | Suggestion::RenameFile { .. } => {
+            suggestion.clone()
         }
     }
-
-    // If the range is outside the span of the input string,
-    // we return the first context window of the string as a fallback.
-    if matching_lines.is_empty() {
-        // Get the end of the context window, if possible.
-        // Otherwise, just return the entire string.
-        let last_line_span = line_spans.get(context_lines * 2 + 1).or_else(|| line_spans.last());
-
-        let last_line_idx = last_line_span.map(|s| s.end).unwrap_or_default();
-
-        return (&input[0..last_line_idx], context_lines);
-    }
-
-    let first_matching_line = *matching_lines.first().unwrap();
-
-    let first_match = first_matching_line.saturating_sub(context_lines);
-    let last_match = (matching_lines.last().unwrap() + context_lines).min(line_spans.len() - 1);
-
-    let start_byte = line_spans[first_match].start;
-    let end_byte = line_spans[last_match].end;
-
-    (&input[start_byte..end_byte], first_matching_line)
 }
 
-#[cfg(test)]
-mod extract_with_context_offset_tests {
-    use super::extract_with_context_offset;
-
-    #[test]
-    fn test_extract_with_context() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 30..35, 1);
+/// Splits the given string into `N` slices, where each index defines
+/// where the source string should be split.
+fn split_str_at<const N: usize>(str: &str, mut indices: Vec<usize>) -> [&str; N] {
+    indices.sort();
+    indices.reverse();
 
-        assert_eq!(snipped, "let b = 2;\nlet c = a + b;\nlet d = c * 2;");
-        assert_eq!(offset, 2);
-    }
+    let mut current = str;
+    let mut slices = [""; N];
 
-    #[test]
-    fn test_extract_without_context() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 30..35, 0);
+    for (i, index) in indices.iter().enumerate() {
+        let (before, after) = current.split_at(normalize_span(current, *index..*index).start);
 
-        assert_eq!(snipped, "let c = a + b;");
-        assert_eq!(offset, 2);
+        current = before;
+        slices[N - i - 1] = after;
     }
 
-    #[test]
-    fn test_extract_at_beginning_boundary() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 0..10, 2);
-
-        assert_eq!(snipped, "let a = 1;\nlet b = 2;\nlet c = a + b;");
-        assert_eq!(offset, 0);
-    }
+    slices[0] = current;
 
-    #[test]
-    fn test_extract_at_ending_boundary() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 60..71, 2);
+    slices
+}
 
-        assert_eq!(snipped, "let c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;");
-        assert_eq!(offset, 4);
-    }
+#[cfg(all(test, any(feature = "terminal", feature = "detect-background")))]
+mod graphical_tests {
+    use super::*;
 
-    #[test]
-    fn test_extract_at_line_start() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 22..36, 1);
+    #[cfg(feature = "terminal")]
+    fn detect(vars: &[(&str, &str)], is_terminal: bool) -> ColorSupport {
+        let var = |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string());
 
-        assert_eq!(snipped, "let b = 2;\nlet c = a + b;\nlet d = c * 2;");
-        assert_eq!(offset, 2);
+        detect_color_support(var, is_terminal)
     }
 
     #[test]
-    fn test_extract_first_line() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 4..9, 1);
-
-        assert_eq!(snipped, "let a = 1;\nlet b = 2;");
-        assert_eq!(offset, 0);
+    #[cfg(feature = "terminal")]
+    fn detect_color_support_env() {
+        assert_eq!(detect(&[], true), ColorSupport::Ansi);
+        assert_eq!(detect(&[], false), ColorSupport::None);
+        assert_eq!(detect(&[("COLORTERM", "truecolor")], true), ColorSupport::TrueColor);
+        assert_eq!(detect(&[("COLORTERM", "24bit")], false), ColorSupport::None);
+        assert_eq!(detect(&[("TERM", "dumb")], true), ColorSupport::None);
+
+        assert_eq!(detect(&[("NO_COLOR", "1")], true), ColorSupport::None);
+        assert_eq!(detect(&[("NO_COLOR", "")], true), ColorSupport::Ansi);
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("FORCE_COLOR", "1")], true),
+            ColorSupport::None
+        );
+
+        assert_eq!(detect(&[("FORCE_COLOR", "1")], false), ColorSupport::Ansi);
+        assert_eq!(detect(&[("FORCE_COLOR", "3")], false), ColorSupport::TrueColor);
+        assert_eq!(
+            detect(&[("FORCE_COLOR", "1"), ("COLORTERM", "truecolor")], false),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(detect(&[("FORCE_COLOR", "0")], true), ColorSupport::None);
+        assert_eq!(
+            detect(&[("FORCE_COLOR", "1"), ("TERM", "dumb")], false),
+            ColorSupport::Ansi
+        );
     }
 
     #[test]
-    fn test_extract_last_line() {
-        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
-        let (snipped, offset) = extract_with_context_offset(source, 64..75, 1);
-
-        assert_eq!(snipped, "let d = c * 2;\nlet e = (d + 3) * 2;");
-        assert_eq!(offset, 4);
+    #[cfg(feature = "detect-background")]
+    fn detect_background_env() {
+        let detect = |value: Option<&str>| detect_background(|_| value.map(String::from));
+
+        assert_eq!(detect(None), None);
+        assert_eq!(detect(Some("15;0")), Some(Background::Dark));
+        assert_eq!(detect(Some("0;15")), Some(Background::Light));
+        assert_eq!(detect(Some("0;7")), Some(Background::Light));
+        assert_eq!(detect(Some("15;8")), Some(Background::Dark));
+        assert_eq!(detect(Some("15;default;0")), Some(Background::Dark));
+        assert_eq!(detect(Some("0;default;15")), Some(Background::Light));
+        assert_eq!(detect(Some("15;default")), None);
+        assert_eq!(detect(Some("")), None);
     }
 }
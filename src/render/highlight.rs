@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+use owo_colors::Style;
+
+/// Defines a hook for applying syntax highlighting to the source lines of snippets,
+/// so rendered snippets are displayed with the syntax colors of their language.
+///
+/// The highlighter is invoked once per displayed line of source code, without the trailing
+/// newline and after any content filter has been applied. Styles of labels are applied on
+/// top of the syntax highlighting, so labelled spans are still highlighted by severity.
+///
+/// Any function or closure of `Fn(Option<&str>, &str) -> Vec<(Range<usize>, Style)>`
+/// implements [`SyntaxHighlighter`].
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{GraphicalRenderer, Label, NamedSource, Renderer, SimpleDiagnostic, SyntaxHighlighter};
+/// use owo_colors::{OwoColorize, Style};
+///
+/// // Highlights all `let` keywords in magenta.
+/// let highlighter = |_name: Option<&str>, line: &str| {
+///     line.match_indices("let")
+///         .map(|(idx, keyword)| (idx..idx + keyword.len(), Style::new().magenta()))
+///         .collect::<Vec<_>>()
+/// };
+///
+/// assert_eq!(highlighter.highlight_line(None, "let a = 1;"), vec![(0..3, Style::new().magenta())]);
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.syntax_highlighter = Some(Arc::new(highlighter));
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::error(Some(source), 8..9, "not found in this scope"));
+///
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.contains(&"l".style(Style::new().magenta()).to_string()));
+/// ```
+pub trait SyntaxHighlighter: Send + Sync {
+    /// Highlights the given line of source code, which is taken from the source with the given name.
+    ///
+    /// Returns the styles to apply on the line, along with the byte range within the line which
+    /// they apply to. Parts of the line which aren't covered by any range are left unstyled.
+    fn highlight_line(&self, source_name: Option<&str>, line: &str) -> Vec<(Range<usize>, Style)>;
+}
+
+impl<F> SyntaxHighlighter for F
+where
+    F: Fn(Option<&str>, &str) -> Vec<(Range<usize>, Style)> + Send + Sync,
+{
+    fn highlight_line(&self, source_name: Option<&str>, line: &str) -> Vec<(Range<usize>, Style)> {
+        self(source_name, line)
+    }
+}
+
+impl std::fmt::Debug for dyn SyntaxHighlighter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SyntaxHighlighter")
+    }
+}
+
+/// Syntax highlighter which uses [`syntect`] to highlight source lines.
+///
+/// The syntax of each line is determined from the file extension of the source name. If the
+/// source has no name, or no syntax is defined for the extension, the line is left unstyled.
+///
+/// Since each line is highlighted independently, constructs spanning multiple lines,
+/// such as block comments, might not be highlighted correctly.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{SyntaxHighlighter, SyntectHighlighter};
+///
+/// let highlighter = SyntectHighlighter::new();
+///
+/// assert!(!highlighter.highlight_line(Some("src/main.rs"), "let a = 1;").is_empty());
+/// assert!(highlighter.highlight_line(Some("src/main.unknown"), "let a = 1;").is_empty());
+/// ```
+#[cfg(feature = "syntect")]
+#[derive(Debug, Clone)]
+pub struct SyntectHighlighter {
+    syntaxes: syntect::parsing::SyntaxSet,
+    theme: syntect::highlighting::Theme,
+}
+
+#[cfg(feature = "syntect")]
+impl SyntectHighlighter {
+    /// Creates a new [`SyntectHighlighter`], which uses the default syntaxes
+    /// of [`syntect`] along with the `base16-ocean.dark` theme.
+    pub fn new() -> Self {
+        let mut themes = syntect::highlighting::ThemeSet::load_defaults();
+
+        Self {
+            syntaxes: syntect::parsing::SyntaxSet::load_defaults_nonewlines(),
+            theme: themes.themes.remove("base16-ocean.dark").unwrap_or_default(),
+        }
+    }
+
+    /// Sets the syntaxes to use when highlighting lines.
+    ///
+    /// Since lines are highlighted without their trailing newline, the syntaxes
+    /// should be loaded without newlines, such as using [`syntect::parsing::SyntaxSet::load_defaults_nonewlines`].
+    pub fn with_syntaxes(mut self, syntaxes: syntect::parsing::SyntaxSet) -> Self {
+        self.syntaxes = syntaxes;
+        self
+    }
+
+    /// Sets the theme to use when highlighting lines.
+    pub fn with_theme(mut self, theme: syntect::highlighting::Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Converts the given [`syntect`] style into a terminal style, ignoring the background color.
+    fn convert_style(style: syntect::highlighting::Style) -> Style {
+        use syntect::highlighting::FontStyle;
+
+        let syntect::highlighting::Color { r, g, b, .. } = style.foreground;
+        let mut converted = Style::new().truecolor(r, g, b);
+
+        if style.font_style.contains(FontStyle::BOLD) {
+            converted = converted.bold();
+        }
+
+        if style.font_style.contains(FontStyle::ITALIC) {
+            converted = converted.italic();
+        }
+
+        if style.font_style.contains(FontStyle::UNDERLINE) {
+            converted = converted.underline();
+        }
+
+        converted
+    }
+}
+
+#[cfg(feature = "syntect")]
+impl Default for SyntectHighlighter {
+    fn default() -> Self {
+        SyntectHighlighter::new()
+    }
+}
+
+#[cfg(feature = "syntect")]
+impl SyntaxHighlighter for SyntectHighlighter {
+    fn highlight_line(&self, source_name: Option<&str>, line: &str) -> Vec<(Range<usize>, Style)> {
+        let Some(extension) = source_name.and_then(|name| std::path::Path::new(name).extension()) else {
+            return Vec::new();
+        };
+
+        let Some(syntax) = self.syntaxes.find_syntax_by_extension(&extension.to_string_lossy()) else {
+            return Vec::new();
+        };
+
+        let mut highlighter = syntect::easy::HighlightLines::new(syntax, &self.theme);
+
+        let Ok(regions) = highlighter.highlight_line(line, &self.syntaxes) else {
+            return Vec::new();
+        };
+
+        let mut offset = 0;
+
+        regions
+            .into_iter()
+            .map(|(style, text)| {
+                let range = offset..offset + text.len();
+                offset = range.end;
+
+                (range, Self::convert_style(style))
+            })
+            .collect()
+    }
+}
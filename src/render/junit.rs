@@ -0,0 +1,269 @@
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+
+use super::short::{line_and_column, primary_location};
+use super::{Renderer, ShortRenderer};
+use crate::{Diagnostic, Severity};
+
+/// Name of the test suite which contains all diagnostics without any location.
+const UNLOCATED_SUITE: &str = "<unknown>";
+
+/// Exporter which collects diagnostics into a [JUnit XML](https://github.com/testmoapp/junitxml) report,
+/// so CI servers, such as Jenkins or GitLab, can display the diagnostics natively.
+///
+/// Diagnostics are grouped into a `<testsuite>` per source file, based on the first label of each
+/// diagnostic, where each diagnostic becomes a `<testcase>`. Errors, fatal errors and bugs are reported
+/// as failures, warnings are reported as skipped, while all other diagnostics are reported as passing.
+/// The rendered diagnostic is attached to the test case as well, which uses [`ShortRenderer`] by default.
+///
+/// Test suites are ordered by the first appearance of their source file.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{Diagnostic, JUnitReport, Label, NamedSource, Severity, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = b;\n}"));
+///
+/// let error = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::error(Some(source.clone()), 24..25, "not found in this scope"));
+///
+/// let warning = SimpleDiagnostic::new("unused variable `a`")
+///     .with_severity(Severity::Warning)
+///     .with_label(Label::warning(Some(source), 20..21, "unused"));
+///
+/// let report = JUnitReport::from_diagnostics([&error as &dyn Diagnostic, &warning as &dyn Diagnostic]);
+/// let xml = report.to_xml();
+///
+/// assert!(xml.contains(r#"<testsuite name="src/main.lm" tests="2" failures="1" skipped="1">"#));
+/// ```
+pub struct JUnitReport {
+    /// Defines the name of the report, which is used as the name of the `<testsuites>` element.
+    name: String,
+
+    /// Defines the renderer to use when rendering the body of each test case.
+    renderer: Box<dyn Renderer + Send + Sync>,
+
+    /// Stores all test cases, grouped by the name of their test suite.
+    suites: IndexMap<String, Vec<TestCase>>,
+}
+
+/// A single diagnostic within a [`JUnitReport`].
+#[derive(Debug, Clone)]
+struct TestCase {
+    message: String,
+    code: Option<String>,
+    severity: Severity,
+    file: Option<String>,
+    line: Option<usize>,
+    body: String,
+}
+
+impl TestCase {
+    /// Gets the name of the test case, which is prefixed by the code of the diagnostic, if any.
+    fn name(&self) -> String {
+        let message = self.message.lines().collect::<Vec<_>>().join(" ");
+
+        match &self.code {
+            Some(code) => format!("{code}: {message}"),
+            None => message,
+        }
+    }
+
+    fn is_failure(&self) -> bool {
+        self.severity.is_at_least(Severity::Error)
+    }
+
+    fn is_skipped(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+}
+
+impl JUnitReport {
+    /// Creates a new, empty [`JUnitReport`].
+    pub fn new() -> Self {
+        JUnitReport {
+            name: String::from("diagnostics"),
+            renderer: Box::new(ShortRenderer::new()),
+            suites: IndexMap::new(),
+        }
+    }
+
+    /// Creates a new [`JUnitReport`] with all the given diagnostics.
+    pub fn from_diagnostics<'a>(diagnostics: impl IntoIterator<Item = &'a dyn Diagnostic>) -> Self {
+        let mut report = JUnitReport::new();
+
+        for diagnostic in diagnostics {
+            report.add_diagnostic(diagnostic);
+        }
+
+        report
+    }
+
+    /// Sets the name of the report, which is used as the name of the `<testsuites>` element.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the renderer to use when rendering the body of each test case.
+    ///
+    /// Only diagnostics which are added after setting the renderer are affected. Since the body is
+    /// embedded in XML, the renderer should not use any colors.
+    pub fn with_renderer(mut self, renderer: Box<dyn Renderer + Send + Sync>) -> Self {
+        self.renderer = renderer;
+        self
+    }
+
+    /// Adds the given diagnostic to the report, as a test case in the test suite of it's source file.
+    ///
+    /// If the diagnostic fails to render, the message of the diagnostic is used as the body instead.
+    pub fn add_diagnostic(&mut self, diagnostic: &dyn Diagnostic) {
+        let location = primary_location(diagnostic, None);
+
+        let file = location
+            .as_ref()
+            .and_then(|(source, _)| source.name().map(String::from));
+
+        let line = location.as_ref().and_then(|(source, offset)| {
+            let content = source.try_text()?;
+
+            Some(line_and_column(content, *offset).0)
+        });
+
+        let case = TestCase {
+            message: diagnostic.message(),
+            code: diagnostic.code().map(|code| code.to_string()),
+            severity: diagnostic.severity(),
+            file: file.clone(),
+            line,
+            body: self
+                .renderer
+                .render(diagnostic)
+                .unwrap_or_else(|_| diagnostic.message()),
+        };
+
+        let suite = file.unwrap_or_else(|| String::from(UNLOCATED_SUITE));
+
+        self.suites.entry(suite).or_default().push(case);
+    }
+
+    /// Determines whether the report contains any diagnostics.
+    pub fn is_empty(&self) -> bool {
+        self.suites.is_empty()
+    }
+
+    /// Formats the report as a JUnit XML document.
+    pub fn to_xml(&self) -> String {
+        let cases = self.suites.values().flatten();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            xml_escape(&self.name),
+            cases.clone().count(),
+            cases.clone().filter(|c| c.is_failure()).count(),
+            cases.filter(|c| c.is_skipped()).count(),
+        );
+
+        for (name, cases) in &self.suites {
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+                xml_escape(name),
+                cases.len(),
+                cases.iter().filter(|c| c.is_failure()).count(),
+                cases.iter().filter(|c| c.is_skipped()).count(),
+            );
+
+            for case in cases {
+                self.write_case(&mut xml, name, case);
+            }
+
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// Writes a single test case into the given XML document.
+    fn write_case(&self, xml: &mut String, suite: &str, case: &TestCase) {
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\"",
+            xml_escape(&case.name()),
+            xml_escape(suite)
+        );
+
+        if let Some(file) = &case.file {
+            let _ = write!(xml, " file=\"{}\"", xml_escape(file));
+        }
+
+        if let Some(line) = case.line {
+            let _ = write!(xml, " line=\"{line}\"");
+        }
+
+        xml.push_str(">\n");
+
+        let message = xml_escape(&case.message);
+        let body = xml_escape(case.body.trim_end());
+
+        if case.is_failure() {
+            let _ = writeln!(
+                xml,
+                "      <failure message=\"{message}\" type=\"{}\">{body}</failure>",
+                case.severity
+            );
+        } else {
+            if case.is_skipped() {
+                let _ = writeln!(xml, "      <skipped message=\"{message}\"/>");
+            }
+
+            let _ = writeln!(xml, "      <system-out>{body}</system-out>");
+        }
+
+        xml.push_str("    </testcase>\n");
+    }
+}
+
+impl Default for JUnitReport {
+    fn default() -> Self {
+        JUnitReport::new()
+    }
+}
+
+impl std::fmt::Debug for JUnitReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JUnitReport")
+            .field("name", &self.name)
+            .field("suites", &self.suites)
+            .finish()
+    }
+}
+
+/// Escapes the given string, so it can be used within XML attributes and text.
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+
+            // Control characters aren't allowed in XML 1.0 at all, so they're dropped.
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,848 @@
+//! Layout primitives for rendering diagnostics, independent of any formatting.
+//!
+//! This module exposes the computations used by [`crate::GraphicalRenderer`] to place labels
+//! within source snippets, so custom renderers (such as TUI or GUI renderers) can reuse them
+//! and produce the same layout.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::column::{display_column, display_width, expand_tabs, visualize_invisible, ColumnPolicy};
+use crate::{Label, Source, SpanRange};
+
+/// Defines a single label, along with all the labels which are contained within it.
+#[derive(Debug)]
+pub struct LabelContext {
+    /// Used for reordering and sorting.
+    pub pos: usize,
+
+    /// Defines the root label within the context.
+    pub parent: Label,
+
+    /// Defines all child labels, which are contained within the parent.
+    pub children: Vec<(usize, Label)>,
+
+    /// Defines the common source for the labels.
+    pub source: Arc<dyn Source>,
+}
+
+impl LabelContext {
+    /// Gets the span which contains all labels within the context, including the parent.
+    pub fn max_span(&self) -> SpanRange {
+        self.children
+            .iter()
+            .fold(self.parent.range.clone(), |span, (_, child)| span.join(child.range()))
+    }
+}
+
+/// Groups a list of [`Label`]s into a tree of [`Label`]s, where each parent
+/// label overlaps with all it's direct child nodes.
+///
+/// Labels without any source attached use `diag_source` instead. Labels where
+/// no source is available at all are skipped. The returned contexts retain the
+/// original ordering of the labels.
+pub fn group_overlapping_labels(
+    diag_source: Option<Arc<dyn Source>>,
+    labels: impl Iterator<Item = Label>,
+) -> Vec<LabelContext> {
+    let mut labels = labels.into_iter().enumerate().collect::<Vec<(usize, Label)>>();
+    labels.sort_unstable_by_key(|(_, l)| l.range().0.start);
+
+    let mut contexts = Vec::with_capacity(labels.len());
+    let mut visited = HashSet::new();
+
+    for (idx, (pos, parent)) in labels.iter().cloned().enumerate() {
+        // If no source code is attached to the label itself, see if
+        // a source is attached to the parent diagnostic.
+        //
+        // If no source is found on either, skip over the label entirely.
+        let Some(parent_source) = parent.source.clone().or(diag_source.clone()) else {
+            continue;
+        };
+
+        if !visited.insert(idx) {
+            continue;
+        }
+
+        let parent_span = parent.range.0.clone();
+        let mut context = LabelContext {
+            pos,
+            parent,
+            children: Vec::new(),
+            source: parent_source.clone(),
+        };
+
+        // If the parent label only spans a single line, it cannot contain any children.
+        if !display_coords_of_span(parent_source.text(), parent_span.clone(), ColumnPolicy::default()).is_multiline() {
+            contexts.push(context);
+
+            continue;
+        }
+
+        for (idx, (pos, child)) in labels.iter().enumerate().skip(idx + 1) {
+            let Some(child_source) = child.source.clone().or(diag_source.clone()) else {
+                continue;
+            };
+
+            // Group the labels into groups where all elements have the same source file.
+            // This helps prevent multiple label headers in a row from defining the same file path.
+            if child_source.name() != parent_source.name() {
+                continue;
+            }
+
+            if parent_span.contains(&child.range.0.start) && visited.insert(idx) {
+                context.children.push((*pos, child.clone()));
+            }
+        }
+
+        contexts.push(context);
+    }
+
+    // Sort the labels back to their original ordering.
+    contexts.sort_unstable_by_key(|c| c.pos);
+
+    for context in &mut contexts {
+        context.children.sort_unstable_by_key(|(pos, _)| *pos);
+    }
+
+    contexts
+}
+
+/// Merges the contexts of nearby single-line labels into a single context, so they're rendered as
+/// one continuous snippet, instead of separate snippets with a break in between.
+///
+/// Contexts are merged if their snippets, including `context_lines` lines of context on either side,
+/// would overlap or be separated by at most `distance` lines. Only contexts within the same source
+/// are merged, while contexts with a multi-line parent label are never merged, since the parent is drawn
+/// as a bracket around all it's children. The label which appeared first becomes the parent of the
+/// merged context. The returned contexts retain the original ordering of the labels.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::layout::{group_overlapping_labels, merge_nearby_contexts};
+/// use error_snippet::{Label, Source};
+///
+/// let source: Arc<dyn Source> = Arc::new("let a = 1;\nlet b = 2;\nlet c = 3;\nlet d = a + b;");
+/// let labels = vec![Label::error(None, 4..5, "first"), Label::error(None, 37..38, "second")];
+///
+/// let contexts = group_overlapping_labels(Some(source), labels.into_iter());
+/// assert_eq!(contexts.len(), 2);
+///
+/// assert_eq!(merge_nearby_contexts(contexts, 2, 0).len(), 1);
+/// ```
+pub fn merge_nearby_contexts(contexts: Vec<LabelContext>, distance: usize, context_lines: usize) -> Vec<LabelContext> {
+    let mut contexts = contexts
+        .into_iter()
+        .map(|context| {
+            let content = context.source.text();
+            let policy = ColumnPolicy::default();
+            let lines = display_coords_of_span(content, context.max_span().0, policy);
+
+            let mergeable = !display_coords_of_span(content, context.parent.range.0.clone(), policy).is_multiline()
+                && context
+                    .children
+                    .iter()
+                    .all(|(_, child)| !display_coords_of_span(content, child.range.0.clone(), policy).is_multiline());
+
+            (lines.start.line, lines.end.line, mergeable, context)
+        })
+        .collect::<Vec<_>>();
+
+    contexts.sort_by_key(|(start, ..)| *start);
+
+    let mut merged: Vec<(usize, bool, LabelContext)> = Vec::with_capacity(contexts.len());
+
+    for (start, end, mergeable, mut context) in contexts {
+        if let Some((last_end, last_mergeable, last)) = merged.last_mut() {
+            let is_nearby = start <= *last_end + context_lines * 2 + distance + 1;
+
+            if mergeable && *last_mergeable && is_nearby && last.source.name() == context.source.name() {
+                // The label which appeared first is kept as the parent, so the severity of the
+                // context doesn't depend on the position of the labels within the source.
+                if context.pos < last.pos {
+                    std::mem::swap(&mut last.pos, &mut context.pos);
+                    std::mem::swap(&mut last.parent, &mut context.parent);
+                }
+
+                last.children.push((context.pos, context.parent));
+                last.children.append(&mut context.children);
+                *last_end = (*last_end).max(end);
+
+                continue;
+            }
+        }
+
+        merged.push((end, mergeable, context));
+    }
+
+    let mut merged = merged.into_iter().map(|(.., context)| context).collect::<Vec<_>>();
+
+    // Sort the labels back to their original ordering.
+    merged.sort_unstable_by_key(|c| c.pos);
+
+    for context in &mut merged {
+        context.children.sort_unstable_by_key(|(pos, _)| *pos);
+    }
+
+    merged
+}
+
+/// Defines a zero-indexed line and column within a source file.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coord {
+    /// Defines the zero-indexed line number.
+    pub line: usize,
+
+    /// Defines the zero-indexed column within the line.
+    pub column: usize,
+}
+
+/// Defines a span between two coordinates within a source file.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Defines the coordinate where the span starts.
+    pub start: Coord,
+
+    /// Defines the coordinate where the span ends.
+    pub end: Coord,
+}
+
+impl Span {
+    /// Gets the columns which the span covers, where empty spans cover a single column.
+    ///
+    /// This is only valid for spans which start and end on the same line.
+    pub fn columns(self) -> Range<usize> {
+        debug_assert_eq!(self.start.line, self.end.line);
+
+        // Empty spans, such as insertion points, cover the column they point at.
+        if self.start.column >= self.end.column {
+            return self.start.column..self.start.column + 1;
+        }
+
+        self.start.column..self.end.column
+    }
+
+    /// Determines whether the span covers more than a single line.
+    pub fn is_multiline(self) -> bool {
+        self.start.line != self.end.line
+    }
+}
+
+/// Defines how a label is marked on a single line of a snippet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    /// The label starts and ends on the line.
+    Single,
+
+    /// The label starts on the line, but continues onto the following lines.
+    Start,
+
+    /// The label covers the entire line, connecting the start and end of the label.
+    Connector,
+
+    /// The label started on a previous line, and ends on the line.
+    End,
+}
+
+/// Defines a marker of a single label on a single line of a snippet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    /// Defines the index of the label which the marker belongs to.
+    pub label: usize,
+
+    /// Defines the display columns which the marker covers on the line.
+    ///
+    /// For [`MarkerKind::Connector`] markers, this covers the entire line.
+    pub columns: Range<usize>,
+
+    /// Defines how the label is marked on the line.
+    pub kind: MarkerKind,
+}
+
+/// Defines a single line of a snippet, along with all the markers on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutLine {
+    /// Defines the one-indexed line number within the source.
+    pub number: usize,
+
+    /// Defines the content of the line, where tabs are expanded into spaces.
+    pub text: String,
+
+    /// Defines the markers of all labels which touch the line.
+    pub markers: Vec<Marker>,
+}
+
+/// Defines the layout of a snippet of source code, containing one-or-more labels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetLayout {
+    /// Defines all lines within the snippet, including the surrounding context lines.
+    pub lines: Vec<LayoutLine>,
+}
+
+/// Computes the layout of a snippet containing the given labels, along with
+/// `context_lines` amount of lines surrounding them.
+///
+/// The labels are all expected to refer to the given source, so the sources attached to them
+/// are ignored. Markers refer to labels using their index within `labels`.
+///
+/// # Examples
+/// ```
+/// use error_snippet::layout::{layout_snippet, MarkerKind};
+/// use error_snippet::{ColumnPolicy, Label};
+///
+/// let source = "let a = 1;\nlet b = a + true;\nlet c = b;";
+/// let labels = [Label::new(None, 19..27, "mismatched types")];
+///
+/// let layout = layout_snippet(source, &labels, 1, ColumnPolicy::default());
+///
+/// assert_eq!(layout.lines.len(), 3);
+/// assert_eq!(layout.lines[1].number, 2);
+/// assert_eq!(layout.lines[1].markers[0].columns, 8..16);
+/// assert_eq!(layout.lines[1].markers[0].kind, MarkerKind::Single);
+/// ```
+pub fn layout_snippet(source: &str, labels: &[Label], context_lines: usize, policy: ColumnPolicy) -> SnippetLayout {
+    let ranges = labels
+        .iter()
+        .map(|l| SpanRange(clamp_span_to_lines(source, normalize_span(source, l.range().0.clone()))))
+        .collect::<Vec<_>>();
+
+    let Some(span) = ranges.iter().cloned().reduce(|a, b| a.join(&b)) else {
+        return SnippetLayout { lines: Vec::new() };
+    };
+
+    let label_spans = ranges
+        .iter()
+        .map(|range| display_coords_of_span(source, range.0.clone(), policy))
+        .collect::<Vec<_>>();
+
+    let (snippet, _) = extract_with_context_offset(source, span.0, context_lines);
+
+    // Since the snippet is a sub-slice of the source, we can get the first line number from the offset.
+    let snippet_offset = snippet.as_ptr() as usize - source.as_ptr() as usize;
+    let first_line = source[..snippet_offset].matches('\n').count();
+
+    let lines = snippet
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let line_idx = first_line + idx;
+            let text = visualize_invisible(&expand_tabs(line, policy), policy).into_owned();
+            let width = display_width(&text, policy);
+
+            let markers = label_spans
+                .iter()
+                .enumerate()
+                .filter_map(|(label, span)| {
+                    let (columns, kind) = if !span.is_multiline() && span.start.line == line_idx {
+                        (span.columns(), MarkerKind::Single)
+                    } else if span.start.line == line_idx {
+                        (span.start.column..width.max(span.start.column + 1), MarkerKind::Start)
+                    } else if span.end.line == line_idx && span.is_multiline() {
+                        (0..span.end.column, MarkerKind::End)
+                    } else if span.start.line < line_idx && line_idx < span.end.line {
+                        (0..width, MarkerKind::Connector)
+                    } else {
+                        return None;
+                    };
+
+                    Some(Marker { label, columns, kind })
+                })
+                .collect();
+
+            LayoutLine {
+                number: line_idx + 1,
+                text,
+                markers,
+            }
+        })
+        .collect();
+
+    SnippetLayout { lines }
+}
+
+/// Normalizes the given span, so it's guaranteed to be a valid range within the source text.
+///
+/// Spans often originate from user input or other tools, so they might not match the source
+/// they refer to. Instead of panicking, all invalid spans are degraded gracefully:
+/// - reversed spans, where the start is past the end, are swapped around,
+/// - offsets past the end of the source are clamped to the end of the source,
+/// - offsets within a multi-byte character are widened to the boundaries of the character,
+///   while empty spans are moved to the start of the character.
+///
+/// # Example
+///
+/// ```
+/// use error_snippet::layout::normalize_span;
+///
+/// assert_eq!(normalize_span("let a = 1;", 4..5), 4..5);
+/// assert_eq!(normalize_span("let a = 1;", 5..4), 4..5);
+/// assert_eq!(normalize_span("let a = 1;", 8..100), 8..10);
+/// assert_eq!(normalize_span("let a = 1;", 50..100), 10..10);
+/// assert_eq!(normalize_span("a = \"ü\"", 6..6), 5..5);
+/// assert_eq!(normalize_span("a = \"ü\"", 5..6), 5..7);
+/// ```
+pub fn normalize_span(source: &str, span: impl Into<Range<usize>>) -> Range<usize> {
+    let range: Range<usize> = span.into();
+
+    let mut start = range.start.min(range.end).min(source.len());
+    let mut end = range.start.max(range.end).min(source.len());
+
+    while !source.is_char_boundary(start) {
+        start -= 1;
+    }
+
+    while !source.is_char_boundary(end) {
+        end += 1;
+    }
+
+    // Empty spans, such as insertion points, are kept empty.
+    if range.start == range.end {
+        end = start;
+    }
+
+    start..end
+}
+
+/// Adjusts the given normalized span, so it's displayed on the lines it visually belongs to.
+///
+/// Line breaks aren't displayed within snippets, so a span which ends with a line break would appear
+/// to extend onto the next line. Likewise, the empty line after the final line break of a source
+/// isn't displayed at all. Instead:
+/// - spans which end with a line break exclude it, so spans of only a line break point at the end of the line,
+/// - empty spans at the very end of a source, after the final line break, point at the end of the last line.
+///
+/// # Example
+///
+/// ```
+/// use error_snippet::layout::clamp_span_to_lines;
+///
+/// assert_eq!(clamp_span_to_lines("let a = 1\nlet b = 2\n", 4..5), 4..5);
+/// assert_eq!(clamp_span_to_lines("let a = 1\nlet b = 2\n", 0..10), 0..9);
+/// assert_eq!(clamp_span_to_lines("let a = 1\nlet b = 2\n", 9..10), 9..9);
+/// assert_eq!(clamp_span_to_lines("let a = 1\nlet b = 2\n", 20..20), 19..19);
+/// assert_eq!(clamp_span_to_lines("let a = 1\r\n", 9..11), 9..9);
+/// ```
+pub fn clamp_span_to_lines(source: &str, span: Range<usize>) -> Range<usize> {
+    let Range { mut start, mut end } = span;
+
+    let line_break_len = |offset: usize| {
+        if source[..offset].ends_with("\r\n") {
+            2
+        } else if source[..offset].ends_with('\n') {
+            1
+        } else {
+            0
+        }
+    };
+
+    if start == source.len() {
+        start -= line_break_len(start);
+        end = start;
+    }
+
+    if end > start {
+        end -= line_break_len(end).min(end - start);
+    }
+
+    start..end
+}
+
+/// Gets the line number and display column indices which contains the given span.
+pub fn display_coords_of_span(str: &str, span: impl Into<Range<usize>>, policy: ColumnPolicy) -> Span {
+    let range: Range<usize> = span.into();
+
+    let start = display_coords_of_idx(str, range.start, policy);
+    let end = display_coords_of_idx(str, range.end, policy);
+
+    Span { start, end }
+}
+
+/// Gets the line number and display column which contains the given byte index.
+pub fn display_coords_of_idx(str: &str, index: usize, policy: ColumnPolicy) -> Coord {
+    if index > str.len() {
+        let line_cnt = str.lines().count();
+
+        return Coord {
+            line: line_cnt.saturating_sub(1),
+            column: str.lines().last().map(|l| display_width(l, policy)).unwrap_or_default(),
+        };
+    }
+
+    let line = str.as_bytes()[..index].iter().filter(|b| **b == b'\n').count();
+    let column = display_column(str, index, policy);
+
+    Coord { line, column }
+}
+
+/// Gets the line number and column indices which contains the given span.
+#[deprecated(
+    since = "0.2.1",
+    note = "use `display_coords_of_span` instead, which takes a `ColumnPolicy`"
+)]
+pub fn coords_of_span(str: &str, span: impl Into<Range<usize>>) -> Span {
+    display_coords_of_span(str, span, ColumnPolicy::default())
+}
+
+/// Gets the line number and column number which contains the given byte index.
+#[deprecated(
+    since = "0.2.1",
+    note = "use `display_coords_of_idx` instead, which takes a `ColumnPolicy`"
+)]
+pub fn coords_of_idx(str: &str, index: usize) -> Coord {
+    display_coords_of_idx(str, index, ColumnPolicy::default())
+}
+
+#[cfg(test)]
+mod display_coords_of_idx_tests {
+    use super::{display_coords_of_idx, ColumnPolicy, Coord};
+
+    fn coords_of_idx(source: &str, index: usize) -> Coord {
+        display_coords_of_idx(source, index, ColumnPolicy::default())
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let source = "let a = 1;";
+        let Coord { line, column } = coords_of_idx(source, 12);
+
+        assert_eq!(line, 0);
+        assert_eq!(column, 10);
+    }
+
+    #[test]
+    fn test_index_at_end_boundary() {
+        let source = "let a = 1;";
+        let Coord { line, column } = coords_of_idx(source, 10);
+
+        assert_eq!(line, 0);
+        assert_eq!(column, 10);
+    }
+
+    #[test]
+    fn test_multiline() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let Coord { line, column } = coords_of_idx(source, 26);
+
+        assert_eq!(line, 2);
+        assert_eq!(column, 4);
+    }
+
+    #[test]
+    fn test_multiline_line_boundary_start() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let Coord { line, column } = coords_of_idx(source, 22);
+
+        assert_eq!(line, 2);
+        assert_eq!(column, 0);
+    }
+
+    #[test]
+    fn test_multiline_line_boundary_end() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let Coord { line, column } = coords_of_idx(source, 36);
+
+        assert_eq!(line, 2);
+        assert_eq!(column, 14);
+    }
+
+    #[test]
+    fn test_non_ascii_byte_index() {
+        let source = "let név = föo(bar);";
+        let Coord { line, column } = coords_of_idx(source, 15);
+
+        assert_eq!(line, 0);
+        assert_eq!(column, 13);
+    }
+}
+
+/// Extracts a slice of the given string, which contains the lines where
+/// `span` is contained, along with the `context_lines` amount of surrounding lines.
+///
+/// # Example
+///
+/// ```
+/// use error_snippet::layout::extract_with_context;
+///
+/// let source = r#"let a = 1;
+/// let b = 2;
+/// let c = a + b;
+/// let d = c * 2;
+/// let e = (d + 3) * 2;"#;
+///
+/// // indexes "a + b" on line 3
+/// let span = 30..35;
+///
+/// let snipped = extract_with_context(source, span, 1);
+///
+/// assert_eq!(snipped, r#"let b = 2;
+/// let c = a + b;
+/// let d = c * 2;"#);
+/// ```
+pub fn extract_with_context(input: &str, range: impl Into<Range<usize>>, context_lines: usize) -> &str {
+    let (slice, _) = extract_with_context_offset(input, range, context_lines);
+
+    slice
+}
+
+/// Extracts a slice of the given string, which contains the lines where
+/// `span` is contained, along with the `context_lines` amount of surrounding lines.
+///
+/// The function also returns the line number where the "center" of the context starts.
+///
+/// # Example
+///
+/// ```
+/// use error_snippet::layout::extract_with_context;
+///
+/// let source = r#"let a = 1;
+/// let b = 2;
+/// let c = a + b;
+/// let d = c * 2;
+/// let e = (d + 3) * 2;"#;
+///
+/// // indexes "a + b" on line 3
+/// let span = 30..35;
+///
+/// let snipped = extract_with_context(source, span, 1);
+///
+/// assert_eq!(snipped, r#"let b = 2;
+/// let c = a + b;
+/// let d = c * 2;"#);
+/// ```
+pub fn extract_with_context_offset(input: &str, range: impl Into<Range<usize>>, context_lines: usize) -> (&str, usize) {
+    LineIndex::new(input).extract_with_context_offset(input, range, context_lines)
+}
+
+/// Index of the lines within some source text, which allows for looking up lines and
+/// extracting snippets without scanning the entire text again.
+///
+/// Building the index requires a single pass over the text, so it's best reused for all
+/// lookups against the same source, such as through a [`crate::RenderSession`].
+///
+/// # Example
+///
+/// ```
+/// use error_snippet::layout::LineIndex;
+///
+/// let source = "let a = 1;\nlet b = 2;\nlet c = a + b;";
+/// let index = LineIndex::new(source);
+///
+/// assert_eq!(index.line_count(), 3);
+/// assert_eq!(index.line_of(26), 2);
+/// assert_eq!(index.extract_with_context_offset(source, 26..31, 0), ("let c = a + b;", 2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Defines the byte span of each line, as returned by [`str::lines`].
+    spans: Vec<Range<usize>>,
+
+    /// Defines the byte offset of each newline character.
+    newlines: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Builds the line index of the given text.
+    pub fn new(input: &str) -> Self {
+        let mut line_start = 0;
+        let mut spans = Vec::new();
+
+        for line in input.lines() {
+            let line_len = line.len();
+            spans.push(line_start..(line_start + line_len));
+
+            // +1 for '\n' (assuming UNIX-style newlines)
+            line_start += line_len + 1;
+        }
+
+        let newlines = input
+            .bytes()
+            .enumerate()
+            .filter_map(|(idx, b)| (b == b'\n').then_some(idx))
+            .collect();
+
+        LineIndex { spans, newlines }
+    }
+
+    /// Gets the amount of lines in the text, as counted by [`str::lines`].
+    pub fn line_count(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Gets the zero-indexed line number which contains the given byte index.
+    pub fn line_of(&self, index: usize) -> usize {
+        self.newlines.partition_point(|&newline| newline < index)
+    }
+
+    /// Gets the line number and display column indices which contains the given span.
+    ///
+    /// The given text must be the same text which the index was built from.
+    pub fn display_coords_of_span(&self, input: &str, span: impl Into<Range<usize>>, policy: ColumnPolicy) -> Span {
+        let range: Range<usize> = span.into();
+
+        let start = self.display_coords_of_idx(input, range.start, policy);
+        let end = self.display_coords_of_idx(input, range.end, policy);
+
+        Span { start, end }
+    }
+
+    /// Gets the line number and display column which contains the given byte index.
+    ///
+    /// The given text must be the same text which the index was built from.
+    pub fn display_coords_of_idx(&self, input: &str, index: usize, policy: ColumnPolicy) -> Coord {
+        if index > input.len() {
+            return display_coords_of_idx(input, index, policy);
+        }
+
+        Coord {
+            line: self.line_of(index),
+            column: display_column(input, index, policy),
+        }
+    }
+
+    /// Extracts a slice of the given text, which contains the lines where `span` is contained,
+    /// along with the `context_lines` amount of surrounding lines.
+    ///
+    /// The given text must be the same text which the index was built from.
+    /// See [`extract_with_context_offset`] for more information.
+    pub fn extract_with_context_offset<'a>(
+        &self,
+        input: &'a str,
+        range: impl Into<Range<usize>>,
+        context_lines: usize,
+    ) -> (&'a str, usize) {
+        let range: Range<usize> = range.into();
+        let line_spans = &self.spans;
+
+        // Determine the lines that intersect with the byte range
+        let mut matching_lines = Vec::new();
+        for (i, span) in line_spans.iter().enumerate() {
+            if span.end > range.start && span.start < range.end {
+                matching_lines.push(i);
+            }
+        }
+
+        // If the range is outside the span of the input string,
+        // we return the first context window of the string as a fallback.
+        if matching_lines.is_empty() {
+            // Get the end of the context window, if possible.
+            // Otherwise, just return the entire string.
+            let last_line_span = line_spans.get(context_lines * 2 + 1).or_else(|| line_spans.last());
+
+            let last_line_idx = last_line_span.map(|s| s.end).unwrap_or_default();
+
+            return (&input[0..last_line_idx], context_lines);
+        }
+
+        let first_matching_line = *matching_lines.first().unwrap();
+
+        let first_match = first_matching_line.saturating_sub(context_lines);
+        let last_match = (matching_lines.last().unwrap() + context_lines).min(line_spans.len() - 1);
+
+        let start_byte = line_spans[first_match].start;
+        let end_byte = line_spans[last_match].end;
+
+        (&input[start_byte..end_byte], first_matching_line)
+    }
+}
+
+#[cfg(test)]
+mod extract_with_context_offset_tests {
+    use super::extract_with_context_offset;
+
+    #[test]
+    fn test_extract_with_context() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 30..35, 1);
+
+        assert_eq!(snipped, "let b = 2;\nlet c = a + b;\nlet d = c * 2;");
+        assert_eq!(offset, 2);
+    }
+
+    #[test]
+    fn test_extract_without_context() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 30..35, 0);
+
+        assert_eq!(snipped, "let c = a + b;");
+        assert_eq!(offset, 2);
+    }
+
+    #[test]
+    fn test_extract_at_beginning_boundary() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 0..10, 2);
+
+        assert_eq!(snipped, "let a = 1;\nlet b = 2;\nlet c = a + b;");
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn test_extract_at_ending_boundary() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 60..71, 2);
+
+        assert_eq!(snipped, "let c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;");
+        assert_eq!(offset, 4);
+    }
+
+    #[test]
+    fn test_extract_at_line_start() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 22..36, 1);
+
+        assert_eq!(snipped, "let b = 2;\nlet c = a + b;\nlet d = c * 2;");
+        assert_eq!(offset, 2);
+    }
+
+    #[test]
+    fn test_extract_first_line() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 4..9, 1);
+
+        assert_eq!(snipped, "let a = 1;\nlet b = 2;");
+        assert_eq!(offset, 0);
+    }
+
+    #[test]
+    fn test_extract_last_line() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;";
+        let (snipped, offset) = extract_with_context_offset(source, 64..75, 1);
+
+        assert_eq!(snipped, "let d = c * 2;\nlet e = (d + 3) * 2;");
+        assert_eq!(offset, 4);
+    }
+}
+
+#[cfg(test)]
+mod layout_snippet_tests {
+    use super::{layout_snippet, MarkerKind};
+    use crate::{ColumnPolicy, Label};
+
+    #[test]
+    fn test_multiline_label() {
+        let source = "fn main() {\n    let a = 1;\n}\nfn foo() {}";
+        let labels = [Label::new(None, 0..28, "function body")];
+
+        let layout = layout_snippet(source, &labels, 0, ColumnPolicy::default());
+        let kinds = layout.lines.iter().map(|l| l.markers[0].kind).collect::<Vec<_>>();
+
+        assert_eq!(kinds, vec![MarkerKind::Start, MarkerKind::Connector, MarkerKind::End]);
+        assert_eq!(layout.lines[2].markers[0].columns, 0..1);
+    }
+
+    #[test]
+    fn test_context_lines() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;";
+        let labels = [Label::new(None, 30..35, "sum")];
+
+        let layout = layout_snippet(source, &labels, 1, ColumnPolicy::default());
+        let numbers = layout.lines.iter().map(|l| l.number).collect::<Vec<_>>();
+
+        assert_eq!(numbers, vec![2, 3, 4]);
+        assert!(layout.lines[0].markers.is_empty());
+        assert_eq!(layout.lines[1].markers[0].columns, 8..13);
+    }
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+
+use super::{BatchOptions, Formatter, RenderMetrics, Renderer, Report};
+use crate::{Diagnostic, Help, Label, MessageArg, MessageKey, RenderHints, Severity, Source, Tag};
+
+/// An implementation of [`Renderer`] which localizes the messages of diagnostics
+/// using a [Fluent](https://projectfluent.org/) bundle, before delegating to an inner renderer.
+///
+/// The message of each diagnostic, as well as all of it's causes and related diagnostics, is looked up in the
+/// bundle using the key returned from [`Diagnostic::message_key`]. If the diagnostic has no message key, the key
+/// is missing from the bundle or the message fails to format, the message from [`Diagnostic::message`] is used.
+///
+/// This renderer is only available with the `i18n` feature.
+///
+/// # Examples
+/// ```
+/// use error_snippet::*;
+/// use fluent_bundle::concurrent::FluentBundle;
+/// use fluent_bundle::FluentResource;
+///
+/// let resource = FluentResource::try_new(String::from("unknown-value = valeur inconnue `{ $name }`")).unwrap();
+///
+/// let mut bundle = FluentBundle::new_concurrent(vec!["fr".parse().unwrap()]);
+/// bundle.set_use_isolating(false);
+/// bundle.add_resource(resource).unwrap();
+///
+/// let mut inner = GraphicalRenderer::new();
+/// inner.use_colors = false;
+///
+/// let renderer = LocalizedRenderer::new(inner, bundle);
+///
+/// let diagnostic = SimpleDiagnostic::new("unknown value `fals`")
+///     .with_message_key(MessageKey::new("unknown-value").with_arg("name", "fals"));
+///
+/// assert_eq!(renderer.render(&diagnostic).unwrap(), "× error: valeur inconnue `fals`\n");
+/// ```
+pub struct LocalizedRenderer<R> {
+    /// Defines the renderer which the localized diagnostics are delegated to.
+    inner: R,
+
+    /// Defines the bundle which messages are looked up in.
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl<R: Renderer> LocalizedRenderer<R> {
+    /// Creates a new [`LocalizedRenderer`], which localizes messages with the given bundle.
+    pub fn new(inner: R, bundle: FluentBundle<FluentResource>) -> Self {
+        Self { inner, bundle }
+    }
+
+    /// Gets the renderer which the localized diagnostics are delegated to.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets the bundle which messages are looked up in.
+    pub fn bundle(&self) -> &FluentBundle<FluentResource> {
+        &self.bundle
+    }
+
+    /// Localizes the message of the given diagnostic, falling back to the original message.
+    fn localize(&self, diagnostic: &(impl Diagnostic + ?Sized)) -> String {
+        diagnostic
+            .message_key()
+            .and_then(|key| self.format_message(&key))
+            .unwrap_or_else(|| diagnostic.message())
+    }
+
+    /// Formats the message with the given key, if it's defined in the bundle and formats without any errors.
+    fn format_message(&self, key: &MessageKey) -> Option<String> {
+        let pattern = self.bundle.get_message(&key.key)?.value()?;
+
+        let mut args = FluentArgs::new();
+
+        for (name, value) in &key.args {
+            let value = match value {
+                MessageArg::String(value) => FluentValue::from(value.as_str()),
+                MessageArg::Number(value) => FluentValue::from(*value),
+            };
+
+            args.set(name.as_str(), value);
+        }
+
+        let mut errors = Vec::new();
+        let message = self.bundle.format_pattern(pattern, Some(&args), &mut errors);
+
+        if !errors.is_empty() {
+            return None;
+        }
+
+        Some(message.into_owned())
+    }
+}
+
+impl<R: Renderer> Renderer for LocalizedRenderer<R> {
+    fn render(&self, diagnostic: &dyn Diagnostic) -> Result<String, std::fmt::Error> {
+        self.inner.render(&Localized::new(self, diagnostic))
+    }
+
+    fn render_report(&self, diagnostic: &dyn Diagnostic) -> Result<Report, std::fmt::Error> {
+        self.inner.render_report(&Localized::new(self, diagnostic))
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_stderr(&self, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        self.inner.render_stderr(&Localized::new(self, diagnostic))
+    }
+
+    fn render_io(&self, writer: &mut dyn std::io::Write, diagnostic: &dyn Diagnostic) -> std::io::Result<()> {
+        self.inner.render_io(writer, &Localized::new(self, diagnostic))
+    }
+
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        self.inner.render_fmt(f, &Localized::new(self, diagnostic))
+    }
+
+    fn measure(&self, diagnostic: &dyn Diagnostic) -> Result<RenderMetrics, std::fmt::Error> {
+        self.inner.measure(&Localized::new(self, diagnostic))
+    }
+
+    fn render_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> Result<String, std::fmt::Error> {
+        self.inner.render_with(&Localized::new(self, diagnostic), options)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_stderr_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> std::fmt::Result {
+        self.inner
+            .render_stderr_with(&Localized::new(self, diagnostic), options)
+    }
+
+    fn render_fmt_with(
+        &self,
+        f: &mut Formatter,
+        diagnostic: &dyn Diagnostic,
+        options: &BatchOptions,
+    ) -> std::fmt::Result {
+        self.inner
+            .render_fmt_with(f, &Localized::new(self, diagnostic), options)
+    }
+}
+
+impl<R: std::fmt::Debug> std::fmt::Debug for LocalizedRenderer<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalizedRenderer")
+            .field("inner", &self.inner)
+            .field("locales", &self.bundle.locales)
+            .finish()
+    }
+}
+
+/// Wraps a diagnostic and overrides the message of it, and all of it's
+/// causes and related diagnostics, with the localized messages.
+#[derive(Debug)]
+struct Localized<'a, D: ?Sized> {
+    diagnostic: &'a D,
+    message: String,
+    causes: Vec<Localized<'a, dyn Diagnostic + Send + Sync + 'a>>,
+    related: Vec<Localized<'a, dyn Diagnostic + Send + Sync + 'a>>,
+}
+
+impl<'a, D: Diagnostic + ?Sized> Localized<'a, D> {
+    fn new<R: Renderer>(renderer: &LocalizedRenderer<R>, diagnostic: &'a D) -> Self {
+        Self {
+            diagnostic,
+            message: renderer.localize(diagnostic),
+            causes: diagnostic.causes().map(|d| Localized::new(renderer, d)).collect(),
+            related: diagnostic.related().map(|d| Localized::new(renderer, d)).collect(),
+        }
+    }
+}
+
+impl<D: Diagnostic + ?Sized> Diagnostic for Localized<'_, D> {
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn message_key(&self) -> Option<MessageKey> {
+        self.diagnostic.message_key()
+    }
+
+    fn severity(&self) -> Severity {
+        self.diagnostic.severity()
+    }
+
+    fn code(&self) -> Option<Box<dyn std::fmt::Display + '_>> {
+        self.diagnostic.code()
+    }
+
+    fn group_key(&self) -> Option<String> {
+        self.diagnostic.group_key()
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        self.diagnostic.tags()
+    }
+
+    fn source_code(&self) -> Option<Arc<dyn Source>> {
+        self.diagnostic.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = Label> + '_>> {
+        self.diagnostic.labels()
+    }
+
+    fn causes(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(self.causes.iter().map(|d| d as &(dyn Diagnostic + Send + Sync)))
+    }
+
+    fn related(&self) -> Box<dyn Iterator<Item = &(dyn Diagnostic + Send + Sync)> + '_> {
+        Box::new(self.related.iter().map(|d| d as &(dyn Diagnostic + Send + Sync)))
+    }
+
+    fn help(&self) -> Option<Box<dyn Iterator<Item = Help> + '_>> {
+        self.diagnostic.help()
+    }
+
+    fn render_hints(&self) -> RenderHints {
+        self.diagnostic.render_hints()
+    }
+}
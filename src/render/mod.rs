@@ -1,24 +1,247 @@
-use crate::Diagnostic;
+use std::sync::Arc;
 
+use crate::{walk, Diagnostic, Source, WalkContext};
+
+pub mod builder;
+pub mod column;
+pub mod filter;
 pub mod graphical;
+pub mod highlight;
+pub mod junit;
+pub mod layout;
+#[cfg(feature = "i18n")]
+pub mod localized;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod report;
+pub mod rustc_json;
+pub mod session;
+pub mod short;
+pub mod tap;
+#[cfg(feature = "theme-config")]
+pub mod theme_config;
+#[cfg(feature = "ratatui")]
+pub mod tui;
+pub mod writer;
 
+pub use builder::*;
+pub use column::*;
+pub use filter::*;
 pub use graphical::*;
+pub use highlight::*;
+pub use junit::*;
+#[cfg(feature = "i18n")]
+pub use localized::*;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
+pub use report::*;
+pub use rustc_json::*;
+pub use session::*;
+pub use short::*;
+pub use tap::*;
+#[cfg(feature = "theme-config")]
+pub use theme_config::*;
+#[cfg(feature = "ratatui")]
+pub use tui::*;
+pub use writer::*;
 
 /// Represents a wrapper around a standard formatter.
+///
+/// To write into a [`std::io::Write`] sink, wrap it in an [`IoWriter`] first. The output can be
+/// further adapted with [`StripAnsi`], [`Crlf`] and [`Tee`], before being passed to the formatter.
 pub struct Formatter<'a> {
     inner: &'a mut dyn std::fmt::Write,
 }
 
+impl<'a> Formatter<'a> {
+    /// Creates a new [`Formatter`], which writes into the given writer.
+    pub fn new(inner: &'a mut dyn std::fmt::Write) -> Self {
+        Formatter { inner }
+    }
+}
+
 impl std::fmt::Write for Formatter<'_> {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
         self.inner.write_str(s)
     }
 }
 
+/// Defines layout options which are shared across a batch of diagnostics,
+/// such as all diagnostics within a single drain of a handler.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{BatchOptions, Diagnostic, Label, NamedSource, SimpleDiagnostic};
+///
+/// let short = Arc::new(NamedSource::new("short.lm", "let a = 1;"));
+/// let long = Arc::new(NamedSource::new("long.lm", "\n".repeat(120)));
+///
+/// let first = SimpleDiagnostic::new("foo").with_label(Label::new(Some(short), 4..5, "here"));
+/// let second = SimpleDiagnostic::new("bar").with_label(Label::new(Some(long), 4..5, "here"));
+///
+/// let options = BatchOptions::aligned([&first as &dyn Diagnostic, &second as &dyn Diagnostic]);
+///
+/// assert_eq!(options.line_number_width, 3);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Defines the minimum amount of digits to reserve for line numbers in gutters.
+    pub line_number_width: usize,
+
+    /// Defines the session which caches layout data of sources across the batch.
+    pub session: RenderSession,
+}
+
+impl BatchOptions {
+    /// Computes the options which align the gutters of all the given diagnostics,
+    /// including their causes, related diagnostics and suggestions.
+    pub fn aligned<'a>(diagnostics: impl IntoIterator<Item = &'a dyn Diagnostic>) -> Self {
+        let mut options = BatchOptions::default();
+
+        for diagnostic in diagnostics {
+            let width = line_number_width_of(&options.session, diagnostic);
+            options.line_number_width = options.line_number_width.max(width);
+        }
+
+        options
+    }
+}
+
+/// Gets the largest amount of digits required to print the line numbers of all sources
+/// referenced by the given diagnostic, including any nested diagnostics.
+fn line_number_width_of(session: &RenderSession, diagnostic: &dyn Diagnostic) -> usize {
+    let width_of = |source: &Arc<dyn Source>| {
+        let line_count = session.line_index(source).map(|index| index.line_count());
+
+        line_count.unwrap_or_default().to_string().len()
+    };
+
+    let mut width = 0;
+
+    walk(diagnostic, &mut |diagnostic: &dyn Diagnostic, _: WalkContext| {
+        if let Some(source) = diagnostic.source_code() {
+            width = width.max(width_of(&source));
+        }
+
+        if let Some(labels) = diagnostic.labels() {
+            for label in labels {
+                if let Some(source) = label.source() {
+                    width = width.max(width_of(&source));
+                }
+            }
+        }
+
+        if let Some(help) = diagnostic.help() {
+            for suggestion in help.flat_map(|h| h.suggestions) {
+                if let Some(source) = suggestion.source() {
+                    width = width.max(width_of(&source));
+                }
+            }
+        }
+    });
+
+    width
+}
+
+/// Defines hints on how a single diagnostic should be rendered, which renderers may honor.
+///
+/// Hints are returned from [`Diagnostic::render_hints`] and only apply to the diagnostic
+/// which returned them, not to any of it's causes or related diagnostics. Renderers for
+/// which a hint doesn't make sense are free to ignore it.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{GraphicalRenderer, Label, NamedSource, RenderHints, Renderer, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+///
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::error(Some(source), 8..9, "not found in this scope"))
+///     .with_render_hints(RenderHints {
+///         suppress_snippets: true,
+///         ..RenderHints::default()
+///     });
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+///
+/// assert_eq!(renderer.render(&diagnostic).unwrap(), "× error: cannot find value `b`\n");
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderHints {
+    /// Defines whether to skip all source snippets of the diagnostic, including
+    /// the snippets of suggestions. Help messages are still rendered.
+    pub suppress_snippets: bool,
+
+    /// Defines whether to render the diagnostic in a compact form, such as a single line
+    /// with the location of the first label.
+    pub compact: bool,
+
+    /// Defines whether to render the diagnostic without any colors,
+    /// even if the renderer would otherwise use them.
+    pub no_color: bool,
+}
+
+/// Defines the size of a rendered diagnostic, as measured by [`Renderer::measure`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderMetrics {
+    /// Defines the amount of lines in the rendered output.
+    pub lines: usize,
+
+    /// Defines the display width of the widest line in the rendered output.
+    pub max_width: usize,
+}
+
+/// Writer which measures the written output, line by line, instead of storing it.
+///
+/// The writer expects output without any escape sequences, so it's wrapped in [`StripAnsi`].
+#[derive(Default)]
+struct MeasuringWriter {
+    metrics: RenderMetrics,
+    line: String,
+}
+
+impl MeasuringWriter {
+    fn end_line(&mut self) {
+        let width = display_width(&self.line, ColumnPolicy::default());
+
+        self.metrics.lines += 1;
+        self.metrics.max_width = self.metrics.max_width.max(width);
+        self.line.clear();
+    }
+
+    fn finish(mut self) -> RenderMetrics {
+        if !self.line.is_empty() {
+            self.end_line();
+        }
+
+        self.metrics
+    }
+}
+
+impl std::fmt::Write for MeasuringWriter {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut lines = s.split('\n');
+
+        if let Some(first) = lines.next() {
+            self.line.push_str(first);
+        }
+
+        for line in lines {
+            self.end_line();
+            self.line.push_str(line);
+        }
+
+        Ok(())
+    }
+}
+
 /// Defines a trait for rendering diagnostics to a formatter.
 pub trait Renderer {
     /// Renders the diagnostic to a string buffer.
-    fn render(&mut self, diagnostic: &dyn Diagnostic) -> Result<String, std::fmt::Error> {
+    fn render(&self, diagnostic: &dyn Diagnostic) -> Result<String, std::fmt::Error> {
         let mut buffer = String::new();
         let mut formatter = Formatter { inner: &mut buffer };
 
@@ -27,13 +250,225 @@ pub trait Renderer {
         Ok(buffer)
     }
 
-    /// Renders the diagnostic to the standard output buffer.
-    fn render_stderr(&mut self, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+    /// Renders the diagnostic into a [`Report`], which contains both the rendered output
+    /// and it's styled segments.
+    ///
+    /// See [`Report`] for more information.
+    fn render_report(&self, diagnostic: &dyn Diagnostic) -> Result<Report, std::fmt::Error> {
+        Ok(Report::new(self.render(diagnostic)?))
+    }
+
+    /// Renders the diagnostic to the standard error stream.
+    #[cfg(feature = "terminal")]
+    fn render_stderr(&self, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
         eprint!("{}", self.render(diagnostic)?);
 
         Ok(())
     }
 
+    /// Renders the diagnostic to the given I/O sink, such as a file or [`std::io::stderr()`].
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Renderer, SimpleDiagnostic};
+    ///
+    /// let mut renderer = GraphicalRenderer::new();
+    /// renderer.use_colors = false;
+    ///
+    /// let mut output = Vec::new();
+    /// renderer.render_io(&mut output, &SimpleDiagnostic::new("mismatched types")).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(output).unwrap(), "× error: mismatched types\n");
+    /// ```
+    fn render_io(&self, writer: &mut dyn std::io::Write, diagnostic: &dyn Diagnostic) -> std::io::Result<()> {
+        let mut writer = IoWriter::new(writer);
+        let result = self.render_fmt(&mut Formatter::new(&mut writer), diagnostic);
+
+        match (writer.take_error(), result) {
+            (Some(err), _) => Err(err),
+            (None, Err(_)) => Err(std::io::Error::other("failed to render diagnostic")),
+            (None, Ok(())) => Ok(()),
+        }
+    }
+
     /// Renders the diagnostic to the given formatter.
-    fn render_fmt(&mut self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result;
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result;
+
+    /// Measures the size of the rendered diagnostic, without keeping the rendered output around.
+    ///
+    /// This can be used to compute the size of scroll regions or panes, before laying out the
+    /// rendered diagnostic. Any ANSI escape sequences in the output are ignored when measuring,
+    /// so the metrics are the same, whether colors are used or not.
+    ///
+    /// Measuring is not a separate layout pass: the default implementation fully renders the
+    /// diagnostic and counts the output as it's written, so it costs as much as a full render,
+    /// minus the allocation of the output. Renderers which can compute their size up-front
+    /// may override it with a cheaper implementation.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::{GraphicalRenderer, Renderer, SimpleDiagnostic};
+    ///
+    /// let diagnostic = SimpleDiagnostic::new("mismatched types").with_help("expected `Int`");
+    /// let metrics = GraphicalRenderer::new().measure(&diagnostic).unwrap();
+    ///
+    /// assert_eq!(metrics.lines, 2);
+    /// assert_eq!(metrics.max_width, 25);
+    /// ```
+    fn measure(&self, diagnostic: &dyn Diagnostic) -> Result<RenderMetrics, std::fmt::Error> {
+        let mut writer = StripAnsi::new(MeasuringWriter::default());
+        let mut formatter = Formatter { inner: &mut writer };
+
+        self.render_fmt(&mut formatter, diagnostic)?;
+
+        Ok(writer.into_inner().finish())
+    }
+
+    /// Renders the diagnostic to a string buffer, as part of a batch with the given options.
+    fn render_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> Result<String, std::fmt::Error> {
+        let mut buffer = String::new();
+        let mut formatter = Formatter { inner: &mut buffer };
+
+        self.render_fmt_with(&mut formatter, diagnostic, options)?;
+
+        Ok(buffer)
+    }
+
+    /// Renders the diagnostic to the standard error buffer, as part of a batch with the given options.
+    #[cfg(feature = "terminal")]
+    fn render_stderr_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> std::fmt::Result {
+        eprint!("{}", self.render_with(diagnostic, options)?);
+
+        Ok(())
+    }
+
+    /// Renders the diagnostic to the given formatter, as part of a batch with the given options.
+    ///
+    /// Renderers which don't support any batch options can rely on the default
+    /// implementation, which ignores the options entirely.
+    fn render_fmt_with(
+        &self,
+        f: &mut Formatter,
+        diagnostic: &dyn Diagnostic,
+        options: &BatchOptions,
+    ) -> std::fmt::Result {
+        let _ = options;
+
+        self.render_fmt(f, diagnostic)
+    }
+}
+
+/// Shares a single renderer, such as between multiple handlers.
+impl<R: Renderer + ?Sized> Renderer for Arc<R> {
+    fn render(&self, diagnostic: &dyn Diagnostic) -> Result<String, std::fmt::Error> {
+        (**self).render(diagnostic)
+    }
+
+    fn render_report(&self, diagnostic: &dyn Diagnostic) -> Result<Report, std::fmt::Error> {
+        (**self).render_report(diagnostic)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_stderr(&self, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        (**self).render_stderr(diagnostic)
+    }
+
+    fn render_io(&self, writer: &mut dyn std::io::Write, diagnostic: &dyn Diagnostic) -> std::io::Result<()> {
+        (**self).render_io(writer, diagnostic)
+    }
+
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        (**self).render_fmt(f, diagnostic)
+    }
+
+    fn measure(&self, diagnostic: &dyn Diagnostic) -> Result<RenderMetrics, std::fmt::Error> {
+        (**self).measure(diagnostic)
+    }
+
+    fn render_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> Result<String, std::fmt::Error> {
+        (**self).render_with(diagnostic, options)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_stderr_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> std::fmt::Result {
+        (**self).render_stderr_with(diagnostic, options)
+    }
+
+    fn render_fmt_with(
+        &self,
+        f: &mut Formatter,
+        diagnostic: &dyn Diagnostic,
+        options: &BatchOptions,
+    ) -> std::fmt::Result {
+        (**self).render_fmt_with(f, diagnostic, options)
+    }
+}
+
+/// Allows the renderer to be reconfigured while it's being shared, such as through
+/// an `Arc<Mutex<GraphicalRenderer>>`, to toggle colors mid-session.
+///
+/// # Examples
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use error_snippet::{BufferedDiagnosticHandler, GraphicalRenderer, Handler, SimpleDiagnostic};
+///
+/// let renderer = Arc::new(Mutex::new(GraphicalRenderer::new()));
+/// let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer.clone()));
+///
+/// renderer.lock().unwrap().use_colors = false;
+///
+/// handler.report(Box::new(SimpleDiagnostic::new("mismatched types")));
+/// handler.drain().unwrap();
+///
+/// assert_eq!(handler.buffer(), "× error: mismatched types\n");
+/// ```
+impl<R: Renderer + ?Sized> Renderer for std::sync::Mutex<R> {
+    fn render(&self, diagnostic: &dyn Diagnostic) -> Result<String, std::fmt::Error> {
+        lock_renderer(self).render(diagnostic)
+    }
+
+    fn render_report(&self, diagnostic: &dyn Diagnostic) -> Result<Report, std::fmt::Error> {
+        lock_renderer(self).render_report(diagnostic)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_stderr(&self, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        lock_renderer(self).render_stderr(diagnostic)
+    }
+
+    fn render_io(&self, writer: &mut dyn std::io::Write, diagnostic: &dyn Diagnostic) -> std::io::Result<()> {
+        lock_renderer(self).render_io(writer, diagnostic)
+    }
+
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        lock_renderer(self).render_fmt(f, diagnostic)
+    }
+
+    fn measure(&self, diagnostic: &dyn Diagnostic) -> Result<RenderMetrics, std::fmt::Error> {
+        lock_renderer(self).measure(diagnostic)
+    }
+
+    fn render_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> Result<String, std::fmt::Error> {
+        lock_renderer(self).render_with(diagnostic, options)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_stderr_with(&self, diagnostic: &dyn Diagnostic, options: &BatchOptions) -> std::fmt::Result {
+        lock_renderer(self).render_stderr_with(diagnostic, options)
+    }
+
+    fn render_fmt_with(
+        &self,
+        f: &mut Formatter,
+        diagnostic: &dyn Diagnostic,
+        options: &BatchOptions,
+    ) -> std::fmt::Result {
+        lock_renderer(self).render_fmt_with(f, diagnostic, options)
+    }
+}
+
+/// Locks the given renderer, even if another thread panicked while holding the lock,
+/// since renderers don't hold any state which could be left inconsistent.
+fn lock_renderer<R: ?Sized>(renderer: &std::sync::Mutex<R>) -> std::sync::MutexGuard<'_, R> {
+    renderer.lock().unwrap_or_else(|err| err.into_inner())
 }
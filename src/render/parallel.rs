@@ -0,0 +1,43 @@
+use rayon::prelude::*;
+
+use super::{BatchOptions, Renderer};
+use crate::Diagnostic;
+
+/// Renders all the given diagnostics concurrently, as part of a batch with the given options,
+/// returning the rendered output of each diagnostic in the same order as they were given.
+///
+/// Rendering is independent for each diagnostic, so large batches, such as a full project check
+/// with hundreds of diagnostics, are rendered on the global [`rayon`] thread pool, while the output
+/// can still be written in order. Since the batch options are shared across all threads, sources
+/// referenced by multiple diagnostics are still only indexed once.
+///
+/// Only diagnostics which are [`Sync`] can be rendered concurrently, so handlers which store
+/// arbitrary diagnostics, such as [`crate::DiagnosticHandler`], render sequentially instead.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{render_parallel, BatchOptions, Diagnostic, GraphicalRenderer, SimpleDiagnostic};
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+///
+/// let diagnostics = (0..100)
+///     .map(|idx| SimpleDiagnostic::new(format!("error #{idx}")))
+///     .collect::<Vec<_>>();
+///
+/// let diagnostics = diagnostics.iter().map(|d| d as &(dyn Diagnostic + Sync)).collect::<Vec<_>>();
+/// let rendered = render_parallel(&renderer, &diagnostics, &BatchOptions::default()).unwrap();
+///
+/// assert_eq!(rendered.len(), 100);
+/// assert_eq!(rendered[42], "× error: error #42\n");
+/// ```
+pub fn render_parallel(
+    renderer: &(dyn Renderer + Send + Sync),
+    diagnostics: &[&(dyn Diagnostic + Sync)],
+    options: &BatchOptions,
+) -> Result<Vec<String>, std::fmt::Error> {
+    diagnostics
+        .par_iter()
+        .map(|diagnostic| renderer.render_with(*diagnostic, options))
+        .collect()
+}
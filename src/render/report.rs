@@ -0,0 +1,293 @@
+use std::fmt::Write;
+
+use super::writer::{AnsiEvent, EscapeParser};
+
+/// Defines the color of a [`StyledSegment`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentColor {
+    /// One of the 16 standard terminal colors, where `0..=7` are the basic colors, such as red or blue,
+    /// and `8..=15` are their bright variants. The actual color is decided by the terminal.
+    Ansi(u8),
+
+    /// One of the 256 colors of the extended terminal palette.
+    Indexed(u8),
+
+    /// A 24-bit color, defined by it's red, green and blue components.
+    Rgb(u8, u8, u8),
+}
+
+/// Defines the style of a [`StyledSegment`], such as it's colors and whether it's bold.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SegmentStyle {
+    /// Defines the foreground color of the segment, if any.
+    pub fg: Option<SegmentColor>,
+
+    /// Defines the background color of the segment, if any.
+    pub bg: Option<SegmentColor>,
+
+    /// Defines whether the segment is bold.
+    pub bold: bool,
+
+    /// Defines whether the segment is dimmed.
+    pub dimmed: bool,
+
+    /// Defines whether the segment is italic.
+    pub italic: bool,
+
+    /// Defines whether the segment is underlined.
+    pub underline: bool,
+
+    /// Defines whether the segment is blinking.
+    pub blink: bool,
+
+    /// Defines whether the foreground and background colors of the segment are swapped.
+    pub reversed: bool,
+
+    /// Defines whether the segment is hidden.
+    pub hidden: bool,
+
+    /// Defines whether the segment is crossed out.
+    pub strikethrough: bool,
+}
+
+impl SegmentStyle {
+    /// Determines whether the style is plain, without any colors or effects.
+    pub fn is_plain(&self) -> bool {
+        *self == SegmentStyle::default()
+    }
+
+    /// Applies the parameters of a single `SGR` escape sequence, such as `1;31`, onto the style.
+    fn apply_sgr(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|code| code.parse::<u8>().unwrap_or_default());
+
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = SegmentStyle::default(),
+                1 => self.bold = true,
+                2 => self.dimmed = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                5 | 6 => self.blink = true,
+                7 => self.reversed = true,
+                8 => self.hidden = true,
+                9 => self.strikethrough = true,
+                22 => {
+                    self.bold = false;
+                    self.dimmed = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                25 => self.blink = false,
+                27 => self.reversed = false,
+                28 => self.hidden = false,
+                29 => self.strikethrough = false,
+                30..=37 => self.fg = Some(SegmentColor::Ansi(code - 30)),
+                38 => self.fg = extended_color(&mut codes).or(self.fg),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(SegmentColor::Ansi(code - 40)),
+                48 => self.bg = extended_color(&mut codes).or(self.bg),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(SegmentColor::Ansi(code - 90 + 8)),
+                100..=107 => self.bg = Some(SegmentColor::Ansi(code - 100 + 8)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parses an extended color, which is either `5;<index>` or `2;<r>;<g>;<b>`.
+fn extended_color(codes: &mut impl Iterator<Item = u8>) -> Option<SegmentColor> {
+    match codes.next()? {
+        5 => Some(SegmentColor::Indexed(codes.next()?)),
+        2 => Some(SegmentColor::Rgb(codes.next()?, codes.next()?, codes.next()?)),
+        _ => None,
+    }
+}
+
+/// Defines a run of text within a [`Report`], which shares a single style.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StyledSegment {
+    /// Defines the text of the segment, without any escape sequences.
+    pub text: String,
+
+    /// Defines the style of the text.
+    pub style: SegmentStyle,
+}
+
+impl StyledSegment {
+    /// Determines whether the segment is a line break.
+    pub fn is_newline(&self) -> bool {
+        self.text == "\n"
+    }
+}
+
+/// Rendered diagnostic, as returned by [`Renderer::render_report()`], which contains both the output
+/// of the renderer and a list of styled segments, so the output can be post-processed without rendering
+/// the diagnostic again or parsing any escape sequences.
+///
+/// Segments never span multiple lines, since each line break is a segment of it's own, so the
+/// report can be split into lines using [`Report::lines()`], such as for pagination. Any escape
+/// sequences other than styles, such as hyperlinks, are omitted from the segments.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{GraphicalRenderer, Renderer, SegmentColor, SimpleDiagnostic};
+///
+/// let report = GraphicalRenderer::new()
+///     .render_report(&SimpleDiagnostic::new("mismatched types"))
+///     .unwrap();
+///
+/// assert_eq!(report.plain(), "× error: mismatched types\n");
+/// assert_eq!(report.lines().count(), 1);
+///
+/// let header = report.segments().iter().find(|segment| segment.text == "error").unwrap();
+/// assert_eq!(header.style.fg, Some(SegmentColor::Rgb(233, 114, 99)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    ansi: String,
+    segments: Vec<StyledSegment>,
+}
+
+impl Report {
+    /// Creates a new [`Report`] from the given output of a renderer, which may contain escape sequences.
+    pub fn new(ansi: impl Into<String>) -> Self {
+        let ansi = ansi.into();
+
+        let mut writer = SegmentWriter::new();
+        let _ = writer.write_str(&ansi);
+
+        Report {
+            ansi,
+            segments: writer.finish(),
+        }
+    }
+
+    /// Creates a new [`Report`] from the output of a renderer and the segments
+    /// which were collected while rendering it.
+    pub(crate) fn from_parts(ansi: String, segments: Vec<StyledSegment>) -> Self {
+        Report { ansi, segments }
+    }
+
+    /// Gets the output of the renderer, including any escape sequences.
+    pub fn ansi(&self) -> &str {
+        &self.ansi
+    }
+
+    /// Consumes the report, returning the output of the renderer.
+    pub fn into_ansi(self) -> String {
+        self.ansi
+    }
+
+    /// Gets all the styled segments of the report, in order.
+    pub fn segments(&self) -> &[StyledSegment] {
+        &self.segments
+    }
+
+    /// Gets an [`Iterator`] over the segments of each line, excluding the line breaks themselves.
+    ///
+    /// A trailing line break doesn't start a new line.
+    pub fn lines(&self) -> impl Iterator<Item = &[StyledSegment]> {
+        let segments = match self.segments.split_last() {
+            Some((last, rest)) if last.is_newline() => rest,
+            _ => &self.segments[..],
+        };
+
+        segments.split(StyledSegment::is_newline)
+    }
+
+    /// Gets the text of the report, without any styles or escape sequences.
+    pub fn plain(&self) -> String {
+        self.segments.iter().map(|segment| segment.text.as_str()).collect()
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.ansi)
+    }
+}
+
+/// Writer which splits the written output into styled segments, as it's being written, where
+/// all `SGR` escape sequences are converted into styles. All other escape sequences, such as
+/// hyperlinks, are removed.
+///
+/// Renderers can write into it directly while rendering, so the segments are collected
+/// in the same pass as the rest of the output.
+#[derive(Debug)]
+pub(crate) struct SegmentWriter {
+    parser: EscapeParser,
+    segments: Vec<StyledSegment>,
+    content: String,
+    style: SegmentStyle,
+}
+
+impl SegmentWriter {
+    /// Creates a new, empty [`SegmentWriter`].
+    pub(crate) fn new() -> Self {
+        SegmentWriter {
+            parser: EscapeParser::new(),
+            segments: Vec::new(),
+            content: String::new(),
+            style: SegmentStyle::default(),
+        }
+    }
+
+    /// Consumes the writer, returning all the segments which were written.
+    pub(crate) fn finish(mut self) -> Vec<StyledSegment> {
+        push_segment(&mut self.segments, &mut self.content, self.style);
+
+        self.segments
+    }
+}
+
+impl std::fmt::Write for SegmentWriter {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let SegmentWriter {
+            parser,
+            segments,
+            content,
+            style,
+        } = self;
+
+        parser.parse(s, |event| {
+            match event {
+                AnsiEvent::Text(text) => {
+                    let mut lines = text.split('\n');
+
+                    if let Some(first) = lines.next() {
+                        content.push_str(first);
+                    }
+
+                    // Styles may span across multiple lines, so the style is kept across line breaks.
+                    for line in lines {
+                        push_segment(segments, content, *style);
+
+                        segments.push(StyledSegment {
+                            text: String::from("\n"),
+                            style: SegmentStyle::default(),
+                        });
+
+                        content.push_str(line);
+                    }
+                }
+                AnsiEvent::Sgr(params) => {
+                    push_segment(segments, content, *style);
+                    style.apply_sgr(params);
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// Pushes the given content as a segment with the given style, if it's not empty.
+fn push_segment(segments: &mut Vec<StyledSegment>, content: &mut String, style: SegmentStyle) {
+    if !content.is_empty() {
+        segments.push(StyledSegment {
+            text: std::mem::take(content),
+            style,
+        });
+    }
+}
@@ -0,0 +1,430 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use super::short::line_and_column;
+use super::{Formatter, GraphicalRenderer, Renderer};
+use crate::{walk, Diagnostic, Help, Label, Severity, Source, SourceResolver, Suggestion, WalkContext};
+
+/// Defines the name of sources without any name, such as inline strings.
+const UNNAMED_SOURCE: &str = "<unknown>";
+
+/// An implementation of [`Renderer`] which emits diagnostics in the same JSON schema as the
+/// `--error-format=json` output of `rustc`, so existing editor integrations and problem matchers
+/// for `rustc` work with any tool built upon this crate.
+///
+/// Each diagnostic is rendered as a single JSON object on it's own line. Labels become `spans`, where the
+/// first label is the primary span, while causes, related diagnostics and help messages become `children`.
+/// Since `rustc` doesn't nest children, nested diagnostics are flattened in the order they're rendered in.
+/// Suggestions are attached to the spans of their help message as `suggested_replacement`. The `rendered`
+/// field contains the output of another renderer, which is [`GraphicalRenderer`] without colors by default.
+///
+/// Since `rustc` has no equivalent of [`Diagnostic::tags()`], the tags of a diagnostic are emitted in
+/// an additional `tags` field, which is omitted for diagnostics without any tags, as consumers of the
+/// `rustc` schema ignore unknown fields.
+///
+/// Line and column numbers are one-indexed, where columns are counted in characters. Spans within
+/// sources whose content is unavailable are omitted, since their lines can't be determined.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{Label, NamedSource, Renderer, RustcJsonRenderer, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source), 8..9, "not found in this scope"));
+///
+/// let rendered = RustcJsonRenderer::new().without_rendered().render(&diagnostic).unwrap();
+///
+/// assert!(rendered.starts_with(r#"{"$message_type":"diagnostic","message":"cannot find value `b`","#));
+/// assert!(rendered.contains(r#""code":{"code":"E0425","explanation":null},"level":"error""#));
+/// assert!(rendered.contains(r#""file_name":"src/main.lm","byte_start":8,"byte_end":9,"line_start":1"#));
+/// ```
+pub struct RustcJsonRenderer {
+    /// Defines the renderer to use for the `rendered` field, if any.
+    renderer: Option<Box<dyn Renderer + Send + Sync>>,
+
+    /// Defines the resolver of source identifiers, for labels which refer to their
+    /// source by a [`crate::SourceId`] instead of holding the source itself.
+    source_resolver: Option<Arc<dyn SourceResolver>>,
+}
+
+impl RustcJsonRenderer {
+    /// Creates a new [`RustcJsonRenderer`], which renders the `rendered` field using
+    /// [`GraphicalRenderer`] without any colors.
+    pub fn new() -> Self {
+        let mut renderer = GraphicalRenderer::new();
+        renderer.use_colors = false;
+
+        RustcJsonRenderer {
+            renderer: Some(Box::new(renderer)),
+            source_resolver: None,
+        }
+    }
+
+    /// Sets the renderer to use for the `rendered` field.
+    pub fn with_renderer(mut self, renderer: Box<dyn Renderer + Send + Sync>) -> Self {
+        self.renderer = Some(renderer);
+        self
+    }
+
+    /// Omits the `rendered` field, by setting it to `null`.
+    pub fn without_rendered(mut self) -> Self {
+        self.renderer = None;
+        self
+    }
+
+    /// Sets the resolver of source identifiers, for labels which refer to their
+    /// source by a [`crate::SourceId`] instead of holding the source itself.
+    ///
+    /// Without a resolver, the spans of such labels are omitted. The resolver only applies to the
+    /// spans and children, so the renderer of the `rendered` field needs a resolver of it's own.
+    pub fn with_source_resolver(mut self, resolver: Arc<dyn SourceResolver>) -> Self {
+        self.source_resolver = Some(resolver);
+        self
+    }
+
+    /// Converts the given diagnostic into a `rustc` diagnostic object, including all it's children.
+    fn diagnostic_object(&self, diagnostic: &dyn Diagnostic) -> Result<Json, std::fmt::Error> {
+        let source = diagnostic.source_code();
+        let resolver = self.source_resolver.as_deref();
+        let mut children = Vec::new();
+
+        // The diagnostic itself is visited first, so it's skipped here. Since `walk()` doesn't keep track of
+        // inherited sources, nested diagnostics without a source of their own use the source of the root.
+        walk(diagnostic, &mut |nested: &dyn Diagnostic, cx: WalkContext| {
+            if cx.depth == 0 {
+                return;
+            }
+
+            let source = nested.source_code().or_else(|| source.clone());
+
+            children.push(child_object(
+                nested.message(),
+                level_of(nested.severity()),
+                label_spans(nested, source.clone(), resolver),
+            ));
+
+            children.extend(suggestion_children(nested, source, resolver));
+        });
+
+        children.extend(suggestion_children(diagnostic, source.clone(), resolver));
+
+        let code = match diagnostic.code() {
+            Some(code) => Json::object([("code", Json::string(code.to_string())), ("explanation", Json::Null)]),
+            None => Json::Null,
+        };
+
+        let rendered = match &self.renderer {
+            Some(renderer) => Json::string(renderer.render(diagnostic)?),
+            None => Json::Null,
+        };
+
+        let mut fields = vec![
+            ("$message_type", Json::string("diagnostic")),
+            ("message", Json::string(diagnostic.message())),
+            ("code", code),
+            ("level", Json::string(level_of(diagnostic.severity()))),
+            ("spans", Json::Array(label_spans(diagnostic, source, resolver))),
+            ("children", Json::Array(children)),
+            ("rendered", rendered),
+        ];
+
+        let tags = diagnostic.tags();
+
+        if !tags.is_empty() {
+            fields.push((
+                "tags",
+                Json::Array(tags.iter().map(|tag| Json::string(tag.to_string())).collect()),
+            ));
+        }
+
+        Ok(Json::Object(fields))
+    }
+}
+
+impl Default for RustcJsonRenderer {
+    fn default() -> Self {
+        RustcJsonRenderer::new()
+    }
+}
+
+impl std::fmt::Debug for RustcJsonRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RustcJsonRenderer")
+            .field("rendered", &self.renderer.is_some())
+            .field("source_resolver", &self.source_resolver)
+            .finish()
+    }
+}
+
+impl Renderer for RustcJsonRenderer {
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        writeln!(f, "{}", self.diagnostic_object(diagnostic)?)
+    }
+}
+
+/// Gets the `rustc` level of the given severity.
+fn level_of(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Bug => "error: internal compiler error",
+        Severity::Fatal | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Note => "note",
+        Severity::Help => "help",
+    }
+}
+
+/// Creates a child diagnostic object, which never has any code, children or rendered output of it's own.
+fn child_object(message: impl Into<String>, level: &str, spans: Vec<Json>) -> Json {
+    Json::object([
+        ("message", Json::string(message)),
+        ("code", Json::Null),
+        ("level", Json::string(level)),
+        ("spans", Json::Array(spans)),
+        ("children", Json::Array(Vec::new())),
+        ("rendered", Json::Null),
+    ])
+}
+
+/// Converts the labels of the given diagnostic into spans, where the first label is the primary span.
+///
+/// Source identifiers of labels are resolved using the given resolver, while labels
+/// without any source of their own use the given source instead.
+fn label_spans(
+    diagnostic: &dyn Diagnostic,
+    source: Option<Arc<dyn Source>>,
+    resolver: Option<&dyn SourceResolver>,
+) -> Vec<Json> {
+    diagnostic
+        .labels()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(idx, label)| {
+            let source = label.resolve_source_or(resolver, source.clone())?;
+            let label_text = match label.message() {
+                "" => Json::Null,
+                message => Json::string(message),
+            };
+
+            span_object(&source, label.range().0.clone(), idx == 0, label_text, None)
+        })
+        .collect()
+}
+
+/// Converts the inline suggestions of labels and the help messages of the given diagnostic into
+/// `help` children, where each suggestion is attached as a span with a suggested replacement.
+fn suggestion_children(
+    diagnostic: &dyn Diagnostic,
+    source: Option<Arc<dyn Source>>,
+    resolver: Option<&dyn SourceResolver>,
+) -> Vec<Json> {
+    let mut children = Vec::new();
+
+    for label in diagnostic.labels().into_iter().flatten() {
+        if let Some(child) = label_suggestion_child(&label, source.clone(), resolver) {
+            children.push(child);
+        }
+    }
+
+    for help in diagnostic.help().into_iter().flatten() {
+        children.push(help_child(&help));
+    }
+
+    children
+}
+
+/// Converts the inline suggestion of the given label, if any, into a `help` child.
+fn label_suggestion_child(
+    label: &Label,
+    source: Option<Arc<dyn Source>>,
+    resolver: Option<&dyn SourceResolver>,
+) -> Option<Json> {
+    let suggestion = label.suggestion()?;
+    let source = label.resolve_source_or(resolver, source)?;
+
+    let replacement = Some((suggestion, "MaybeIncorrect"));
+    let span = span_object(&source, label.range().0.clone(), true, Json::Null, replacement);
+
+    Some(child_object(
+        format!("did you mean `{suggestion}`?"),
+        "help",
+        span.into_iter().collect(),
+    ))
+}
+
+/// Converts the given help message into a `help` child, with a span for each of it's suggestions.
+///
+/// Suggestions which operate on entire files have no span, so they're omitted from the spans.
+fn help_child(help: &Help) -> Json {
+    // Alternatives are mutually exclusive, so they can't all be applied automatically.
+    let applicability = if help.alternative {
+        "MaybeIncorrect"
+    } else {
+        "MachineApplicable"
+    };
+
+    let spans = help
+        .suggestions
+        .iter()
+        .filter_map(|suggestion| {
+            let (source, range, replacement) = match suggestion {
+                Suggestion::Deletion { range } => (range.source(), range.span().0.clone(), ""),
+                Suggestion::Insertion { location, value } => {
+                    (location.source(), location.offset()..location.offset(), value.as_str())
+                }
+                Suggestion::Replacement { range, replacement } => {
+                    (range.source(), range.span().0.clone(), replacement.as_str())
+                }
+                _ => return None,
+            };
+
+            span_object(&source, range, true, Json::Null, Some((replacement, applicability)))
+        })
+        .collect();
+
+    child_object(help.message.clone(), "help", spans)
+}
+
+/// Creates a span object for the given range within the source, along with the text of all
+/// lines which the span covers, or `None` if the content of the source is unavailable.
+fn span_object(
+    source: &Arc<dyn Source>,
+    range: std::ops::Range<usize>,
+    is_primary: bool,
+    label: Json,
+    replacement: Option<(&str, &str)>,
+) -> Option<Json> {
+    let content = source.try_text()?;
+
+    let start = range.start.min(content.len());
+    let end = range.end.clamp(start, content.len());
+
+    let (line_start, column_start) = line_and_column(content, start);
+    let (line_end, column_end) = line_and_column(content, end);
+
+    let lines = content
+        .split('\n')
+        .enumerate()
+        .skip(line_start - 1)
+        .take(line_end - line_start + 1)
+        .map(|(idx, line)| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let highlight_start = if idx + 1 == line_start { column_start } else { 1 };
+            let highlight_end = if idx + 1 == line_end {
+                column_end
+            } else {
+                line.chars().count() + 1
+            };
+
+            Json::object([
+                ("text", Json::string(line)),
+                ("highlight_start", Json::Number(highlight_start)),
+                ("highlight_end", Json::Number(highlight_end)),
+            ])
+        })
+        .collect();
+
+    let (suggested_replacement, suggestion_applicability) = match replacement {
+        Some((replacement, applicability)) => (Json::string(replacement), Json::string(applicability)),
+        None => (Json::Null, Json::Null),
+    };
+
+    Some(Json::object([
+        ("file_name", Json::string(source.name().unwrap_or(UNNAMED_SOURCE))),
+        ("byte_start", Json::Number(start)),
+        ("byte_end", Json::Number(end)),
+        ("line_start", Json::Number(line_start)),
+        ("line_end", Json::Number(line_end)),
+        ("column_start", Json::Number(column_start)),
+        ("column_end", Json::Number(column_end)),
+        ("is_primary", Json::Bool(is_primary)),
+        ("text", Json::Array(lines)),
+        ("label", label),
+        ("suggested_replacement", suggested_replacement),
+        ("suggestion_applicability", suggestion_applicability),
+        ("expansion", Json::Null),
+    ]))
+}
+
+/// Minimal JSON value, which is only used to write the output of [`RustcJsonRenderer`].
+///
+/// Object fields retain their insertion order, so the output matches the field order of `rustc`.
+enum Json {
+    Null,
+    Bool(bool),
+    Number(usize),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn string(value: impl Into<String>) -> Self {
+        Json::String(value.into())
+    }
+
+    fn object<const N: usize>(fields: [(&'static str, Json); N]) -> Self {
+        Json::Object(fields.into())
+    }
+}
+
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{value}"),
+            Json::Number(value) => write!(f, "{value}"),
+            Json::String(value) => write_json_string(f, value),
+            Json::Array(values) => {
+                f.write_char('[')?;
+
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+
+                    write!(f, "{value}")?;
+                }
+
+                f.write_char(']')
+            }
+            Json::Object(fields) => {
+                f.write_char('{')?;
+
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+
+                    write_json_string(f, name)?;
+                    write!(f, ":{value}")?;
+                }
+
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+/// Writes the given value as a quoted JSON string, escaping all characters which JSON doesn't allow within strings.
+fn write_json_string(f: &mut impl Write, value: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::layout::LineIndex;
+use crate::Source;
+
+/// Cache of per-source layout data, which is shared across all diagnostics rendered
+/// within the same session, such as a single drain of a handler.
+///
+/// Rendering a diagnostic requires indexing the lines of each source it references, which
+/// is expensive for large sources. When hundreds of diagnostics point into the same source,
+/// the session ensures the source is only indexed once.
+///
+/// Entries are keyed by the identity of the source, so only diagnostics which share the same
+/// [`Arc<dyn Source>`] benefit from the cache. The session keeps all cached sources alive until
+/// it's dropped. Cloning the session is cheap and the clone shares the same cache.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{RenderSession, NamedSource, Source};
+///
+/// let source: Arc<dyn Source> = Arc::new(NamedSource::new("src/main.lm", "let a = 1;\nlet b = 2;"));
+/// let session = RenderSession::new();
+///
+/// let index = session.line_index(&source).unwrap();
+///
+/// assert_eq!(index.line_count(), 2);
+/// assert!(Arc::ptr_eq(&index, &session.line_index(&source).unwrap()));
+/// ```
+#[derive(Default, Clone)]
+pub struct RenderSession {
+    cache: Arc<Mutex<HashMap<usize, CachedSource>>>,
+}
+
+/// Defines the cached layout data of a single source.
+struct CachedSource {
+    /// Keeps the source alive, so the address used as the key isn't reused for another source.
+    _source: Arc<dyn Source>,
+
+    /// Defines the line index of the content of the source.
+    index: Arc<LineIndex>,
+}
+
+impl RenderSession {
+    /// Creates a new, empty [`RenderSession`].
+    pub fn new() -> Self {
+        RenderSession::default()
+    }
+
+    /// Gets the line index of the given source, building and caching it if it's not already cached.
+    ///
+    /// Returns `None` if the content of the source is unavailable.
+    pub fn line_index(&self, source: &Arc<dyn Source>) -> Option<Arc<LineIndex>> {
+        let key = Arc::as_ptr(source) as *const () as usize;
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+
+        if let Some(cached) = cache.get(&key) {
+            return Some(cached.index.clone());
+        }
+
+        let index = Arc::new(LineIndex::new(source.try_text()?));
+
+        cache.insert(
+            key,
+            CachedSource {
+                _source: source.clone(),
+                index: index.clone(),
+            },
+        );
+
+        Some(index)
+    }
+}
+
+impl PartialEq for RenderSession {
+    /// Sessions are only equal if they share the same cache.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cache, &other.cache)
+    }
+}
+
+impl Eq for RenderSession {}
+
+impl std::fmt::Debug for RenderSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cached = self.cache.lock().map(|cache| cache.len()).unwrap_or_default();
+
+        f.debug_struct("RenderSession")
+            .field("cached_sources", &cached)
+            .finish()
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use super::{Formatter, Renderer};
+use crate::{Diagnostic, Source, SourceResolver};
+
+/// An implementation of [`Renderer`] which displays each diagnostic on a single line,
+/// similar to the `--error-format=short` output of `rustc`.
+///
+/// Each diagnostic is rendered as `file:line:col: severity[code]: message`, where the location is
+/// taken from the first label of the diagnostic. Snippets, labels, help messages and nested
+/// diagnostics are not rendered at all. Line and column numbers are one-indexed, where
+/// columns are counted in characters, so the output can be used in editor quickfix lists.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{Label, NamedSource, Renderer, ShortRenderer, SimpleDiagnostic};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = b;\n}"));
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source), 24..25, "not found in this scope"));
+///
+/// let rendered = ShortRenderer::new().render(&diagnostic).unwrap();
+///
+/// assert_eq!(rendered, "src/main.lm:2:13: error[E0425]: cannot find value `b`\n");
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct ShortRenderer {
+    /// Defines the resolver of source identifiers, for labels which refer to their
+    /// source by a [`crate::SourceId`] instead of holding the source itself.
+    source_resolver: Option<Arc<dyn SourceResolver>>,
+}
+
+impl ShortRenderer {
+    /// Creates a new instance of [`ShortRenderer`].
+    pub fn new() -> Self {
+        ShortRenderer::default()
+    }
+
+    /// Sets the resolver of source identifiers, for labels which refer to their
+    /// source by a [`crate::SourceId`] instead of holding the source itself.
+    ///
+    /// Without a resolver, the location of such labels is omitted.
+    pub fn with_source_resolver(mut self, resolver: Arc<dyn SourceResolver>) -> Self {
+        self.source_resolver = Some(resolver);
+        self
+    }
+
+    /// Writes the location of the first label of the diagnostic, followed by
+    /// a separator. If the diagnostic has no location, nothing is written.
+    ///
+    /// If the content of the source is unavailable, only the name of the source is written.
+    fn write_location(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        let Some((source, offset)) = primary_location(diagnostic, self.source_resolver.as_deref()) else {
+            return Ok(());
+        };
+
+        // Without any content, the line and column can't be determined.
+        let Some(content) = source.try_text() else {
+            return match source.name() {
+                Some(name) => write!(f, "{name}: "),
+                None => Ok(()),
+            };
+        };
+
+        let (line, column) = line_and_column(content, offset);
+
+        if let Some(name) = source.name() {
+            write!(f, "{name}:")?;
+        }
+
+        write!(f, "{line}:{column}: ")
+    }
+}
+
+impl Renderer for ShortRenderer {
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        self.write_location(f, diagnostic)?;
+
+        write!(f, "{}", diagnostic.severity())?;
+
+        if let Some(code) = diagnostic.code() {
+            write!(f, "[{code}]")?;
+        }
+
+        // Multi-line messages are joined, so each diagnostic is kept on a single line.
+        let message = diagnostic.message().lines().collect::<Vec<_>>().join(" ");
+
+        writeln!(f, ": {message}")
+    }
+}
+
+/// Gets the source and starting offset of the first label of the diagnostic, if any.
+///
+/// Source identifiers of labels are resolved using the given resolver, while labels
+/// without any source of their own use the source of the diagnostic instead.
+pub(crate) fn primary_location(
+    diagnostic: &dyn Diagnostic,
+    resolver: Option<&dyn SourceResolver>,
+) -> Option<(Arc<dyn Source>, usize)> {
+    let label = diagnostic.labels()?.next()?;
+    let source = label.resolve_source_or(resolver, diagnostic.source_code())?;
+
+    Some((source, label.range().0.start))
+}
+
+/// Gets the one-indexed line and character column of the given byte offset.
+///
+/// Offsets past the end of the content are clamped to the end of the content.
+pub(crate) fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(content.len());
+
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
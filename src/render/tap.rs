@@ -0,0 +1,153 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::short::{line_and_column, primary_location};
+use super::{Formatter, Renderer};
+use crate::Diagnostic;
+
+/// An implementation of [`Renderer`] which displays each diagnostic as a failing test point
+/// of the [Test Anything Protocol](https://testanything.org/), so lint tools can be integrated
+/// into TAP-based test harnesses.
+///
+/// Each diagnostic is rendered as a `not ok` line, followed by a YAML diagnostic block with the
+/// message, severity and code of the diagnostic, as well as the file, line and column of the first
+/// label. Test points are numbered in the order they're rendered, so the renderer should only be
+/// used for a single stream at a time.
+///
+/// The version header and plan aren't written by the renderer itself, but can be retrieved
+/// from [`TapRenderer::header()`] and [`TapRenderer::plan()`], which should be written before
+/// and after all the diagnostics, respectively.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{Label, NamedSource, Renderer, SimpleDiagnostic, TapRenderer};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = b;\n}"));
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_code("E0425")
+///     .with_label(Label::error(Some(source), 24..25, "not found in this scope"));
+///
+/// let renderer = TapRenderer::new();
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert_eq!(
+///     rendered,
+///     concat!(
+///         "not ok 1 - cannot find value `b`\n",
+///         "  ---\n",
+///         "  message: \"cannot find value `b`\"\n",
+///         "  severity: error\n",
+///         "  code: \"E0425\"\n",
+///         "  file: \"src/main.lm\"\n",
+///         "  line: 2\n",
+///         "  column: 13\n",
+///         "  ...\n",
+///     )
+/// );
+///
+/// assert_eq!(renderer.plan(), "1..1\n");
+/// ```
+#[derive(Default, Debug)]
+pub struct TapRenderer {
+    /// Defines the amount of test points which have been rendered.
+    count: AtomicUsize,
+}
+
+impl TapRenderer {
+    /// Creates a new instance of [`TapRenderer`].
+    pub fn new() -> Self {
+        TapRenderer::default()
+    }
+
+    /// Gets the version header, which should be written before any diagnostics.
+    pub fn header(&self) -> &'static str {
+        "TAP version 14\n"
+    }
+
+    /// Gets the plan for all the diagnostics which have been rendered so far,
+    /// which should be written after all the diagnostics.
+    pub fn plan(&self) -> String {
+        format!("1..{}\n", self.count())
+    }
+
+    /// Gets the amount of diagnostics which have been rendered so far.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Resets the numbering of test points, so the renderer can be used for another stream.
+    pub fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+    }
+
+    /// Writes the location of the first label of the diagnostic into the YAML block.
+    /// If the diagnostic has no location, nothing is written.
+    fn write_location(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        let Some((source, offset)) = primary_location(diagnostic, None) else {
+            return Ok(());
+        };
+
+        if let Some(name) = source.name() {
+            writeln!(f, "  file: {}", yaml_string(name))?;
+        }
+
+        // Without any content, the line and column can't be determined.
+        if let Some(content) = source.try_text() {
+            let (line, column) = line_and_column(content, offset);
+
+            writeln!(f, "  line: {line}")?;
+            writeln!(f, "  column: {column}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Renderer for TapRenderer {
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        let number = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let message = diagnostic.message();
+
+        // Multi-line messages are joined, since the description must be kept on a single line. Hashes
+        // are escaped, so they're not interpreted as the start of a directive, such as `# SKIP`.
+        let description = message.lines().collect::<Vec<_>>().join(" ").replace('#', "\\#");
+
+        writeln!(f, "not ok {number} - {description}")?;
+        writeln!(f, "  ---")?;
+        writeln!(f, "  message: {}", yaml_string(&message))?;
+        writeln!(f, "  severity: {}", diagnostic.severity())?;
+
+        if let Some(code) = diagnostic.code() {
+            writeln!(f, "  code: {}", yaml_string(&code.to_string()))?;
+        }
+
+        self.write_location(f, diagnostic)?;
+
+        writeln!(f, "  ...")
+    }
+}
+
+/// Formats the given string as a double-quoted YAML scalar.
+fn yaml_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,406 @@
+//! Loading of [`Theme`] instances from configuration files.
+//!
+//! This module is only available with the `theme-config` feature. Themes are loaded as a set of
+//! overrides on top of a preset, so configuration files only need to define the values they change.
+//!
+//! # Format
+//!
+//! ```toml
+//! # The preset to start from, either `fancy` (default), `ansi`, `none` or `ascii`.
+//! preset = "fancy"
+//!
+//! [style]
+//! error = "bright-red bold"
+//! warning = "#ebbf83 bold"
+//! note = "114"
+//! gutter = "white on 236"
+//!
+//! [symbols]
+//! error = "✖"
+//!
+//! [arrows]
+//! arrow_up = "~"
+//!
+//! [names]
+//! error = "violation"
+//! ```
+//!
+//! Style values are whitespace-separated lists of colors and effects. Colors are either named ANSI
+//! colors (such as `red` or `bright-blue`), 256-color palette indices (such as `196`) or RGB values
+//! (such as `#e97263`). A color preceded by `on` defines the background color. Supported effects are
+//! `bold`, `dimmed`, `italic`, `underline`, `blink`, `reversed`, `hidden` and `strikethrough`.
+
+use owo_colors::{AnsiColors, DynColors, Effect, Style, XtermColors};
+use serde::Deserialize;
+
+use super::graphical::{ArrowSymbols, Theme, ThemeNames, ThemeStyle, ThemeSymbols};
+
+/// Represents an error which can occur when loading a [`Theme`] from a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThemeError {
+    /// Defines that the configuration file could not be parsed.
+    Parse(String),
+
+    /// Defines that the configuration file refers to an unknown preset.
+    UnknownPreset(String),
+
+    /// Defines that a style value within the configuration file is invalid.
+    InvalidStyle {
+        /// Defines the key of the invalid style, such as `style.error`.
+        key: String,
+
+        /// Defines the invalid value.
+        value: String,
+    },
+}
+
+impl std::error::Error for ThemeError {}
+
+impl std::fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(err) => write!(f, "failed to parse theme: {err}"),
+            Self::UnknownPreset(preset) => write!(f, "unknown theme preset `{preset}`"),
+            Self::InvalidStyle { key, value } => write!(f, "invalid style `{value}` for `{key}`"),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeConfig {
+    preset: Option<String>,
+    style: StyleConfig,
+    symbols: SymbolsConfig,
+    arrows: ArrowsConfig,
+    names: NamesConfig,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleConfig {
+    bug: Option<String>,
+    fatal: Option<String>,
+    error: Option<String>,
+    warning: Option<String>,
+    info: Option<String>,
+    note: Option<String>,
+    help: Option<String>,
+    deletion: Option<String>,
+    insertion: Option<String>,
+    link: Option<String>,
+    gutter: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SymbolsConfig {
+    bug: Option<String>,
+    fatal: Option<String>,
+    error: Option<String>,
+    warning: Option<String>,
+    info: Option<String>,
+    note: Option<String>,
+    help: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ArrowsConfig {
+    hbar: Option<char>,
+    hbot: Option<char>,
+    htop: Option<char>,
+    vertical: Option<char>,
+    vertical_break: Option<char>,
+    top_left: Option<char>,
+    bottom_left: Option<char>,
+    horizontal_right: Option<char>,
+    arrow_up: Option<char>,
+    arrow_down: Option<char>,
+    arrow_right: Option<char>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct NamesConfig {
+    bug: Option<String>,
+    fatal: Option<String>,
+    error: Option<String>,
+    warning: Option<String>,
+    info: Option<String>,
+    note: Option<String>,
+    help: Option<String>,
+}
+
+impl Theme {
+    /// Loads a theme from the given TOML configuration.
+    ///
+    /// See [the module documentation](crate::render::theme_config) for the format of the configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Theme;
+    ///
+    /// let theme = Theme::from_toml(r##"
+    ///     [style]
+    ///     error = "#ff0000 bold"
+    ///
+    ///     [symbols]
+    ///     error = "✖"
+    /// "##).unwrap();
+    ///
+    /// assert_eq!(theme.symbols.error, "✖");
+    /// assert_eq!(theme.symbols.warning, "⚠");
+    /// ```
+    pub fn from_toml(config: &str) -> Result<Self, ThemeError> {
+        let config: ThemeConfig = toml::from_str(config).map_err(|err| ThemeError::Parse(err.message().to_string()))?;
+
+        config.into_theme()
+    }
+
+    /// Loads a theme from the given JSON configuration.
+    ///
+    /// See [the module documentation](crate::render::theme_config) for the format of the configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Theme;
+    ///
+    /// let theme = Theme::from_json(r#"{ "preset": "none", "names": { "error": "violation" } }"#).unwrap();
+    ///
+    /// assert_eq!(theme.names.error, "violation");
+    /// ```
+    pub fn from_json(config: &str) -> Result<Self, ThemeError> {
+        let config: ThemeConfig = serde_json::from_str(config).map_err(|err| ThemeError::Parse(err.to_string()))?;
+
+        config.into_theme()
+    }
+}
+
+impl ThemeConfig {
+    /// Applies the configuration on top of the configured preset.
+    fn into_theme(self) -> Result<Theme, ThemeError> {
+        let mut theme = match self.preset.as_deref() {
+            None | Some("fancy") => Theme::fancy(),
+            Some("ansi") => Theme {
+                style: ThemeStyle::ansi(),
+                ..Theme::fancy()
+            },
+            Some("none") => Theme::none(),
+            Some("ascii") => Theme::ascii(),
+            Some(preset) => return Err(ThemeError::UnknownPreset(preset.to_string())),
+        };
+
+        self.style.apply(&mut theme.style)?;
+        self.symbols.apply(&mut theme.symbols);
+        self.arrows.apply(&mut theme.arrows);
+        self.names.apply(&mut theme.names);
+
+        Ok(theme)
+    }
+}
+
+impl StyleConfig {
+    fn apply(self, style: &mut ThemeStyle) -> Result<(), ThemeError> {
+        let fields = [
+            ("bug", self.bug, &mut style.bug),
+            ("fatal", self.fatal, &mut style.fatal),
+            ("error", self.error, &mut style.error),
+            ("warning", self.warning, &mut style.warning),
+            ("info", self.info, &mut style.info),
+            ("note", self.note, &mut style.note),
+            ("help", self.help, &mut style.help),
+            ("deletion", self.deletion, &mut style.deletion),
+            ("insertion", self.insertion, &mut style.insertion),
+            ("link", self.link, &mut style.link),
+            ("gutter", self.gutter, &mut style.gutter),
+        ];
+
+        for (key, value, target) in fields {
+            let Some(value) = value else { continue };
+
+            *target = parse_style(&value).ok_or_else(|| ThemeError::InvalidStyle {
+                key: format!("style.{key}"),
+                value,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SymbolsConfig {
+    fn apply(self, symbols: &mut ThemeSymbols) {
+        let fields = [
+            (self.bug, &mut symbols.bug),
+            (self.fatal, &mut symbols.fatal),
+            (self.error, &mut symbols.error),
+            (self.warning, &mut symbols.warning),
+            (self.info, &mut symbols.info),
+            (self.note, &mut symbols.note),
+            (self.help, &mut symbols.help),
+        ];
+
+        for (value, target) in fields {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+    }
+}
+
+impl ArrowsConfig {
+    fn apply(self, arrows: &mut ArrowSymbols) {
+        let fields = [
+            (self.hbar, &mut arrows.hbar),
+            (self.hbot, &mut arrows.hbot),
+            (self.htop, &mut arrows.htop),
+            (self.vertical, &mut arrows.vertical),
+            (self.vertical_break, &mut arrows.vertical_break),
+            (self.top_left, &mut arrows.top_left),
+            (self.bottom_left, &mut arrows.bottom_left),
+            (self.horizontal_right, &mut arrows.horizontal_right),
+            (self.arrow_up, &mut arrows.arrow_up),
+            (self.arrow_down, &mut arrows.arrow_down),
+            (self.arrow_right, &mut arrows.arrow_right),
+        ];
+
+        for (value, target) in fields {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+    }
+}
+
+impl NamesConfig {
+    fn apply(self, names: &mut ThemeNames) {
+        let fields = [
+            (self.bug, &mut names.bug),
+            (self.fatal, &mut names.fatal),
+            (self.error, &mut names.error),
+            (self.warning, &mut names.warning),
+            (self.info, &mut names.info),
+            (self.note, &mut names.note),
+            (self.help, &mut names.help),
+        ];
+
+        for (value, target) in fields {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+    }
+}
+
+/// Parses a whitespace-separated list of colors and effects into a [`Style`].
+///
+/// Returns `None` if any of the items in the list are invalid.
+fn parse_style(value: &str) -> Option<Style> {
+    let mut style = Style::new();
+    let mut tokens = value.split_whitespace();
+
+    while let Some(token) = tokens.next() {
+        style = match token {
+            "on" => style.on_color(parse_color(tokens.next()?)?),
+            "bold" => style.effect(Effect::Bold),
+            "dimmed" | "dim" => style.effect(Effect::Dimmed),
+            "italic" => style.effect(Effect::Italic),
+            "underline" => style.effect(Effect::Underline),
+            "blink" => style.effect(Effect::Blink),
+            "reversed" => style.effect(Effect::Reversed),
+            "hidden" => style.effect(Effect::Hidden),
+            "strikethrough" => style.effect(Effect::Strikethrough),
+            color => style.color(parse_color(color)?),
+        };
+    }
+
+    Some(style)
+}
+
+/// Parses a single color, which is either a named ANSI color, an index into
+/// the 256-color palette or an RGB value in the `#rrggbb` format.
+fn parse_color(value: &str) -> Option<DynColors> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+
+        return Some(DynColors::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    if let Ok(index) = value.parse::<u8>() {
+        return Some(DynColors::Xterm(XtermColors::from(index)));
+    }
+
+    let color = match value.replace('_', "-").as_str() {
+        "black" => AnsiColors::Black,
+        "red" => AnsiColors::Red,
+        "green" => AnsiColors::Green,
+        "yellow" => AnsiColors::Yellow,
+        "blue" => AnsiColors::Blue,
+        "magenta" => AnsiColors::Magenta,
+        "cyan" => AnsiColors::Cyan,
+        "white" => AnsiColors::White,
+        "default" => AnsiColors::Default,
+        "bright-black" => AnsiColors::BrightBlack,
+        "bright-red" => AnsiColors::BrightRed,
+        "bright-green" => AnsiColors::BrightGreen,
+        "bright-yellow" => AnsiColors::BrightYellow,
+        "bright-blue" => AnsiColors::BrightBlue,
+        "bright-magenta" => AnsiColors::BrightMagenta,
+        "bright-cyan" => AnsiColors::BrightCyan,
+        "bright-white" => AnsiColors::BrightWhite,
+        _ => return None,
+    };
+
+    Some(DynColors::Ansi(color))
+}
+
+#[cfg(test)]
+mod theme_config_tests {
+    use super::*;
+
+    #[test]
+    fn parse_style_values() {
+        assert_eq!(parse_style("bold"), Some(Style::new().bold()));
+        assert_eq!(parse_style("red"), Some(Style::new().red()));
+        assert_eq!(parse_style("bright_red bold"), Some(Style::new().bright_red().bold()));
+        assert_eq!(parse_style("#e97263"), Some(Style::new().truecolor(233, 114, 99)));
+        assert_eq!(
+            parse_style("196 on 236"),
+            Some(
+                Style::new()
+                    .color(XtermColors::from(196))
+                    .on_color(XtermColors::from(236))
+            )
+        );
+
+        assert_eq!(parse_style("purple"), None);
+        assert_eq!(parse_style("#e9726"), None);
+        assert_eq!(parse_style("red on"), None);
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_eq!(
+            Theme::from_toml("[style]\nerror = \"redd\"").unwrap_err(),
+            ThemeError::InvalidStyle {
+                key: "style.error".into(),
+                value: "redd".into()
+            }
+        );
+
+        assert_eq!(
+            Theme::from_json(r#"{ "preset": "neon" }"#).unwrap_err(),
+            ThemeError::UnknownPreset("neon".into())
+        );
+
+        assert!(matches!(
+            Theme::from_toml("[symbols]\nerrors = \"x\""),
+            Err(ThemeError::Parse(_))
+        ));
+    }
+}
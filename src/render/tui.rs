@@ -0,0 +1,139 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+
+use super::{GraphicalRenderer, Renderer, Report, SegmentColor, SegmentStyle};
+use crate::Diagnostic;
+
+/// Renderer which produces a [`ratatui`] [`Text`] instead of a string, so terminal user
+/// interfaces can embed diagnostics within their own widgets, such as a scrollable `Paragraph`.
+///
+/// Diagnostics are rendered using an inner renderer, which is a [`GraphicalRenderer`] by default. The
+/// styles of the theme are converted into [`Style`] on each [`Span`], so the text can be displayed
+/// without re-parsing any escape sequences. Since the inner renderer decides the layout, it's width
+/// should match the width of the pane, which can be set using [`TuiRenderer::with_renderer()`].
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{Label, NamedSource, SimpleDiagnostic, TuiRenderer};
+///
+/// let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::error(Some(source), 8..9, "not found in this scope"));
+///
+/// let text = TuiRenderer::new().render_text(&diagnostic).unwrap();
+///
+/// assert_eq!(text.lines[0].to_string(), "× error: cannot find value `b`");
+/// assert!(text.lines[0].spans[0].style.fg.is_some());
+/// ```
+pub struct TuiRenderer {
+    /// Defines the renderer which lays out the diagnostics, before they're converted.
+    renderer: Box<dyn Renderer + Send + Sync>,
+}
+
+impl TuiRenderer {
+    /// Creates a new [`TuiRenderer`], which uses a [`GraphicalRenderer`] with default settings.
+    pub fn new() -> Self {
+        TuiRenderer::with_renderer(Box::new(GraphicalRenderer::new()))
+    }
+
+    /// Creates a new [`TuiRenderer`], which uses the given renderer to lay out diagnostics.
+    ///
+    /// The styles of the output are only retained if the renderer has colors enabled, except
+    /// for [`GraphicalRenderer`], which keeps the styles of it's theme either way.
+    pub fn with_renderer(renderer: Box<dyn Renderer + Send + Sync>) -> Self {
+        TuiRenderer { renderer }
+    }
+
+    /// Renders the given diagnostic into a styled [`Text`], with a [`Line`] per line of output.
+    pub fn render_text(&self, diagnostic: &dyn Diagnostic) -> Result<Text<'static>, std::fmt::Error> {
+        let report = self.renderer.render_report(diagnostic)?;
+
+        Ok(report_to_text(&report))
+    }
+}
+
+impl Default for TuiRenderer {
+    fn default() -> Self {
+        TuiRenderer::new()
+    }
+}
+
+impl std::fmt::Debug for TuiRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TuiRenderer").finish_non_exhaustive()
+    }
+}
+
+/// Converts the given report into a styled [`Text`], with a [`Line`] per line of the report.
+fn report_to_text(report: &Report) -> Text<'static> {
+    let lines = report.lines().map(|segments| {
+        let spans = segments
+            .iter()
+            .map(|segment| Span::styled(segment.text.clone(), segment_style(segment.style)))
+            .collect::<Vec<_>>();
+
+        Line::from(spans)
+    });
+
+    Text::from(lines.collect::<Vec<_>>())
+}
+
+/// Converts the given segment style into a [`Style`].
+fn segment_style(style: SegmentStyle) -> Style {
+    let mut converted = Style {
+        fg: style.fg.map(segment_color),
+        bg: style.bg.map(segment_color),
+        ..Style::default()
+    };
+
+    let modifiers = [
+        (style.bold, Modifier::BOLD),
+        (style.dimmed, Modifier::DIM),
+        (style.italic, Modifier::ITALIC),
+        (style.underline, Modifier::UNDERLINED),
+        (style.blink, Modifier::SLOW_BLINK),
+        (style.reversed, Modifier::REVERSED),
+        (style.hidden, Modifier::HIDDEN),
+        (style.strikethrough, Modifier::CROSSED_OUT),
+    ];
+
+    for (enabled, modifier) in modifiers {
+        if enabled {
+            converted.add_modifier.insert(modifier);
+        }
+    }
+
+    converted
+}
+
+/// Converts the given segment color into a [`Color`].
+fn segment_color(color: SegmentColor) -> Color {
+    match color {
+        SegmentColor::Ansi(index) => basic_color(index % 8, index >= 8),
+        SegmentColor::Indexed(index) => Color::Indexed(index),
+        SegmentColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+/// Gets the color of the given index within the 8 basic terminal colors, or their bright variants.
+fn basic_color(index: u8, bright: bool) -> Color {
+    match (index, bright) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (_, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        (_, true) => Color::White,
+    }
+}
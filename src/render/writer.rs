@@ -0,0 +1,315 @@
+use std::fmt::Write;
+
+/// Adapter which allows renderers to write into a [`std::io::Write`] sink, such as
+/// a file or [`std::io::stderr()`], instead of a [`String`] buffer.
+///
+/// Since [`std::fmt::Write`] cannot carry any I/O errors, the first error raised by the
+/// inner writer is kept and can be retrieved afterwards with [`IoWriter::take_error()`].
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Formatter, GraphicalRenderer, IoWriter, Renderer, SimpleDiagnostic};
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+///
+/// let mut writer = IoWriter::new(Vec::new());
+/// renderer.render_fmt(&mut Formatter::new(&mut writer), &SimpleDiagnostic::new("mismatched types")).unwrap();
+///
+/// assert!(writer.take_error().is_none());
+/// assert_eq!(writer.into_inner(), b"\xC3\x97 error: mismatched types\n");
+/// ```
+#[derive(Debug)]
+pub struct IoWriter<W: std::io::Write> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> IoWriter<W> {
+    /// Creates a new [`IoWriter`], which writes into the given I/O sink.
+    pub fn new(inner: W) -> Self {
+        IoWriter { inner, error: None }
+    }
+
+    /// Takes the first I/O error raised by the inner writer, if any.
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    /// Gets the inner writer, which the output is written into.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: std::io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if self.error.is_some() {
+            return Err(std::fmt::Error);
+        }
+
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            std::fmt::Error
+        })
+    }
+}
+
+/// Defines the state of an [`EscapeParser`] in between writes, since escape
+/// sequences might be split across multiple writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    /// Outside of any escape sequence.
+    Text,
+
+    /// Directly after an `ESC` character.
+    Escape,
+
+    /// Inside of a control sequence, such as `ESC [ 31 m`.
+    Control,
+
+    /// Inside of an operating system command, such as `ESC ] 8 ; ; <url> ESC \`.
+    Command,
+
+    /// Directly after an `ESC` character, inside of an operating system command.
+    CommandEscape,
+}
+
+/// Defines a single piece of output, as parsed by an [`EscapeParser`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnsiEvent<'a> {
+    /// A run of text, without any escape sequences.
+    Text(&'a str),
+
+    /// The parameters of a complete `SGR` escape sequence, such as `1;31` in `ESC [ 1;31 m`.
+    Sgr(&'a str),
+}
+
+/// Incremental parser of ANSI escape sequences, which is shared between all
+/// writers which need to look past escape sequences, such as [`StripAnsi`].
+///
+/// Escape sequences may be split across multiple writes, so the parser keeps it's
+/// state in between calls to [`EscapeParser::parse()`]. Control sequences other than `SGR`
+/// and operating system commands, such as hyperlinks, are dropped entirely.
+#[derive(Debug)]
+pub(crate) struct EscapeParser {
+    state: EscapeState,
+    params: String,
+}
+
+impl EscapeParser {
+    /// Creates a new [`EscapeParser`], which starts outside of any escape sequence.
+    pub(crate) fn new() -> Self {
+        EscapeParser {
+            state: EscapeState::Text,
+            params: String::new(),
+        }
+    }
+
+    /// Parses the given chunk of output, passing each text run and style change to the given callback.
+    pub(crate) fn parse(
+        &mut self,
+        s: &str,
+        mut event: impl FnMut(AnsiEvent<'_>) -> std::fmt::Result,
+    ) -> std::fmt::Result {
+        let mut start = 0;
+
+        for (idx, c) in s.char_indices() {
+            match (self.state, c) {
+                (EscapeState::Text, '\x1b') => {
+                    if start < idx {
+                        event(AnsiEvent::Text(&s[start..idx]))?;
+                    }
+
+                    self.state = EscapeState::Escape;
+                }
+                (EscapeState::Text, _) => continue,
+                (EscapeState::Escape, '[') => {
+                    self.params.clear();
+                    self.state = EscapeState::Control;
+                }
+                (EscapeState::Escape, ']') => self.state = EscapeState::Command,
+                (EscapeState::Escape, '\x1b') => {}
+                (EscapeState::Escape, _) => {
+                    // Only control sequences are removed entirely, so other characters are kept.
+                    self.state = EscapeState::Text;
+                    start = idx;
+                    continue;
+                }
+
+                // Control sequences are terminated by a byte in the range `@` to `~`.
+                (EscapeState::Control, '@'..='~') => {
+                    self.state = EscapeState::Text;
+
+                    if c == 'm' {
+                        event(AnsiEvent::Sgr(&self.params))?;
+                    }
+                }
+                (EscapeState::Control, _) => self.params.push(c),
+
+                // Operating system commands are terminated by either `BEL` or `ESC \`.
+                (EscapeState::Command, '\x07') => self.state = EscapeState::Text,
+                (EscapeState::Command, '\x1b') => self.state = EscapeState::CommandEscape,
+                (EscapeState::Command, _) => {}
+                (EscapeState::CommandEscape, '\\') => self.state = EscapeState::Text,
+                (EscapeState::CommandEscape, _) => self.state = EscapeState::Command,
+            }
+
+            start = idx + c.len_utf8();
+        }
+
+        if self.state == EscapeState::Text && start < s.len() {
+            event(AnsiEvent::Text(&s[start..]))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Adapter which removes all ANSI escape sequences, such as color codes and hyperlinks,
+/// before writing into the inner writer.
+///
+/// This allows the output of a renderer with colors enabled to be written
+/// into places where escape sequences aren't supported, such as log files.
+///
+/// # Examples
+/// ```
+/// use error_snippet::StripAnsi;
+/// use std::fmt::Write;
+///
+/// let mut output = String::new();
+/// let mut writer = StripAnsi::new(&mut output);
+///
+/// write!(writer, "\x1b[31merror\x1b[0m: mismatched types").unwrap();
+///
+/// assert_eq!(output, "error: mismatched types");
+/// ```
+#[derive(Debug)]
+pub struct StripAnsi<W: Write> {
+    inner: W,
+    parser: EscapeParser,
+}
+
+impl<W: Write> StripAnsi<W> {
+    /// Creates a new [`StripAnsi`], which writes the stripped output into the given writer.
+    pub fn new(inner: W) -> Self {
+        StripAnsi {
+            inner,
+            parser: EscapeParser::new(),
+        }
+    }
+
+    /// Gets the inner writer, which the output is written into.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let inner = &mut self.inner;
+
+        self.parser.parse(s, |event| match event {
+            AnsiEvent::Text(text) => inner.write_str(text),
+            AnsiEvent::Sgr(_) => Ok(()),
+        })
+    }
+}
+
+/// Adapter which translates all line feeds into carriage return and line feed pairs
+/// (`\r\n`), before writing into the inner writer.
+///
+/// Line feeds which are already preceded by a carriage return are left as-is.
+///
+/// # Examples
+/// ```
+/// use error_snippet::Crlf;
+/// use std::fmt::Write;
+///
+/// let mut output = String::new();
+/// let mut writer = Crlf::new(&mut output);
+///
+/// write!(writer, "first\nsecond\r\n").unwrap();
+///
+/// assert_eq!(output, "first\r\nsecond\r\n");
+/// ```
+#[derive(Debug)]
+pub struct Crlf<W: Write> {
+    inner: W,
+    after_cr: bool,
+}
+
+impl<W: Write> Crlf<W> {
+    /// Creates a new [`Crlf`], which writes the translated output into the given writer.
+    pub fn new(inner: W) -> Self {
+        Crlf { inner, after_cr: false }
+    }
+
+    /// Gets the inner writer, which the output is written into.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for Crlf<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut start = 0;
+
+        for (idx, c) in s.char_indices() {
+            if c == '\n' && !self.after_cr {
+                self.inner.write_str(&s[start..idx])?;
+                self.inner.write_str("\r\n")?;
+                start = idx + 1;
+            }
+
+            self.after_cr = c == '\r';
+        }
+
+        self.inner.write_str(&s[start..])
+    }
+}
+
+/// Adapter which writes the same output into two writers at once.
+///
+/// Combined with [`StripAnsi`], a single render pass can produce both the colored
+/// output for the terminal and a clean copy for log files.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{Formatter, GraphicalRenderer, Renderer, SimpleDiagnostic, StripAnsi, Tee};
+///
+/// let renderer = GraphicalRenderer::new();
+/// let diagnostic = SimpleDiagnostic::new("mismatched types");
+///
+/// let mut terminal = String::new();
+/// let mut log = String::new();
+///
+/// let mut writer = Tee::new(&mut terminal, StripAnsi::new(&mut log));
+/// renderer.render_fmt(&mut Formatter::new(&mut writer), &diagnostic).unwrap();
+///
+/// assert_eq!(log, "× error: mismatched types\n");
+/// ```
+#[derive(Debug)]
+pub struct Tee<A: Write, B: Write> {
+    first: A,
+    second: B,
+}
+
+impl<A: Write, B: Write> Tee<A, B> {
+    /// Creates a new [`Tee`], which writes all output into both of the given writers.
+    pub fn new(first: A, second: B) -> Self {
+        Tee { first, second }
+    }
+
+    /// Gets both of the inner writers, which the output is written into.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Write, B: Write> Write for Tee<A, B> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.first.write_str(s)?;
+        self.second.write_str(s)
+    }
+}
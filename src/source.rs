@@ -1,11 +1,55 @@
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::Result;
+use crate::{Result, SourceRange};
 
 /// Defines a source file, which can be used to provide context for diagnostics.
 ///
 /// This trait represents some sort of source code, which will be reported to the user as
 /// part of the reporting process.
+///
+/// Implementors must implement either [`Source::text`] or the deprecated [`Source::content`],
+/// since each of them is implemented in terms of the other by default.
+///
+/// # Implementations
+///
+/// Most string containers can be used as a source directly, without any name attached:
+/// - [`str`], [`&str`](str), [`String`], [`&String`](String) and [`Cow<str>`](Cow),
+/// - UTF-8 encoded bytes, as [`[u8]`](slice), [`&[u8]`](slice) and [`Vec<u8>`],
+/// - any source within an [`Arc`] or a [`Box`], such as [`Arc<str>`] or [`Box<str>`].
+///
+/// To attach a name to the source, wrap it within a [`NamedSource`], which accepts any
+/// content implementing [`AsRef<str>`]. Sources which aren't valid UTF-8 can be read
+/// using [`BinarySource`] instead.
+///
+/// # Examples
+/// ```
+/// use error_snippet::Source;
+///
+/// #[derive(Debug)]
+/// struct Script {
+///     path: String,
+///     body: String,
+/// }
+///
+/// impl Source for Script {
+///     fn name(&self) -> Option<&str> {
+///         Some(&self.path)
+///     }
+///
+///     fn text(&self) -> &str {
+///         &self.body
+///     }
+/// }
+///
+/// let script = Script { path: "main.lm".into(), body: "let a = 1;".into() };
+///
+/// assert_eq!(script.text(), "let a = 1;");
+/// assert_eq!(script.try_text(), Some("let a = 1;"));
+/// ```
 pub trait Source: Send + Sync + std::fmt::Debug {
     /// Defines the name of the source file.
     fn name(&self) -> Option<&str> {
@@ -13,48 +57,141 @@ pub trait Source: Send + Sync + std::fmt::Debug {
     }
 
     /// Gets the full content of the source file.
-    fn content(&self) -> Box<&str>;
+    fn text(&self) -> &str;
+
+    /// Gets the full content of the source file, if it is available.
+    ///
+    /// Sources which refer to content which can no longer be retrieved, such as generated
+    /// files which have since been removed from disk, should return `None`, in which case
+    /// renderers only display the name of the source, without any snippet.
+    /// [`Source::text`] should then return an empty string.
+    ///
+    /// By default, this returns the result of [`Source::text`].
+    fn try_text(&self) -> Option<&str> {
+        #[allow(deprecated)]
+        self.try_content().map(|content| *content)
+    }
+
+    /// Gets the full content of the source file.
+    ///
+    /// Overriding this method has no effect on renderers, which only use [`Source::text`].
+    #[deprecated(since = "0.2.1", note = "use `Source::text` instead, which doesn't allocate")]
+    fn content(&self) -> Box<&str> {
+        Box::new(self.text())
+    }
+
+    /// Gets the full content of the source file, if it is available.
+    #[deprecated(since = "0.2.1", note = "use `Source::try_text` instead, which doesn't allocate")]
+    fn try_content(&self) -> Option<Box<&str>> {
+        #[allow(deprecated)]
+        Some(self.content())
+    }
+
+    /// Gets the site which the source was expanded from, if the source was
+    /// generated from another source, such as a macro expansion.
+    fn expanded_from(&self) -> Option<&SourceRange> {
+        None
+    }
+
+    /// Gets the version of the content, such as a revision counter or a content hash,
+    /// which must change whenever the content of the source changes.
+    ///
+    /// When defined, [`SourceFingerprint`] uses the version instead of hashing the entire content,
+    /// which is useful for large sources which are kept in memory, such as within a language server.
+    fn version(&self) -> Option<u64> {
+        None
+    }
 }
 
 impl Source for [u8] {
-    fn content(&self) -> Box<&str> {
-        Box::new(std::str::from_utf8(self).unwrap())
+    fn text(&self) -> &str {
+        std::str::from_utf8(self).unwrap()
     }
 }
 
 impl Source for &[u8] {
-    fn content(&self) -> Box<&str> {
-        <[u8] as Source>::content(self)
+    fn text(&self) -> &str {
+        <[u8] as Source>::text(self)
     }
 }
 
 impl Source for Vec<u8> {
-    fn content(&self) -> Box<&str> {
-        <[u8] as Source>::content(self)
+    fn text(&self) -> &str {
+        <[u8] as Source>::text(self)
     }
 }
 
 impl Source for str {
-    fn content(&self) -> Box<&str> {
-        <[u8] as Source>::content(self.as_bytes())
+    fn text(&self) -> &str {
+        self
     }
 }
 
 impl Source for &str {
-    fn content(&self) -> Box<&str> {
-        <str as Source>::content(self)
+    fn text(&self) -> &str {
+        self
     }
 }
 
 impl Source for String {
-    fn content(&self) -> Box<&str> {
-        <str as Source>::content(self)
+    fn text(&self) -> &str {
+        self.as_str()
     }
 }
 
 impl Source for &String {
-    fn content(&self) -> Box<&str> {
-        <String as Source>::content(self)
+    fn text(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Source for Cow<'_, str> {
+    fn text(&self) -> &str {
+        self
+    }
+}
+
+impl<T: Source + ?Sized> Source for Arc<T> {
+    fn name(&self) -> Option<&str> {
+        self.as_ref().name()
+    }
+
+    fn text(&self) -> &str {
+        self.as_ref().text()
+    }
+
+    fn try_text(&self) -> Option<&str> {
+        self.as_ref().try_text()
+    }
+
+    fn expanded_from(&self) -> Option<&SourceRange> {
+        self.as_ref().expanded_from()
+    }
+
+    fn version(&self) -> Option<u64> {
+        self.as_ref().version()
+    }
+}
+
+impl<T: Source + ?Sized> Source for Box<T> {
+    fn name(&self) -> Option<&str> {
+        self.as_ref().name()
+    }
+
+    fn text(&self) -> &str {
+        self.as_ref().text()
+    }
+
+    fn try_text(&self) -> Option<&str> {
+        self.as_ref().try_text()
+    }
+
+    fn expanded_from(&self) -> Option<&SourceRange> {
+        self.as_ref().expanded_from()
+    }
+
+    fn version(&self) -> Option<u64> {
+        self.as_ref().version()
     }
 }
 
@@ -77,8 +214,8 @@ impl Source for StringSource {
         None
     }
 
-    fn content(&self) -> Box<&str> {
-        Box::new(self.content.as_str())
+    fn text(&self) -> &str {
+        self.content.as_str()
     }
 }
 
@@ -86,13 +223,31 @@ impl Source for StringSource {
 ///
 /// This is the default implementation of the [`Source`] trait and is used
 /// internally to create diagnostics using derive-macros.
+///
+/// The content is a [`String`] by default, but any type implementing [`AsRef<str>`]
+/// can be used, such as [`Arc<str>`] or [`Cow<'static, str>`](Cow), to avoid copying content
+/// which is already shared elsewhere.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{NamedSource, Source};
+///
+/// let owned = NamedSource::new("src/main.lm", "let a = 1;");
+///
+/// let content: Arc<str> = Arc::from("let a = 1;");
+/// let shared = NamedSource::from_parts("src/main.lm", content.clone());
+///
+/// assert_eq!(owned.text(), shared.text());
+/// assert_eq!(shared.name(), Some("src/main.lm"));
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct NamedSource {
+pub struct NamedSource<S = String> {
     /// Defines the name of the source file.
     pub name: String,
 
     /// Defines the content of the source file.
-    pub content: String,
+    pub content: S,
 }
 
 impl NamedSource {
@@ -104,8 +259,11 @@ impl NamedSource {
         }
     }
 
-    /// Creates a new [`NamedSource`] instance from an existing file.
-    pub fn from_file(path: PathBuf) -> Result<NamedSource> {
+    /// Creates a new [`NamedSource`] instance from an existing file, where
+    /// the path of the file is used as the name of the source.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<NamedSource> {
+        let path = path.as_ref();
+
         let name = path.to_string_lossy().to_string();
         let content = std::fs::read_to_string(path)?;
 
@@ -113,12 +271,383 @@ impl NamedSource {
     }
 }
 
-impl Source for NamedSource {
+impl<S: AsRef<str>> NamedSource<S> {
+    /// Creates a new [`NamedSource`] from the given name and content of any type.
+    pub fn from_parts(name: impl Into<String>, content: S) -> Self {
+        Self {
+            name: name.into(),
+            content,
+        }
+    }
+}
+
+impl<S: AsRef<str> + Send + Sync + std::fmt::Debug> Source for NamedSource<S> {
     fn name(&self) -> Option<&str> {
         Some(self.name.as_str())
     }
 
-    fn content(&self) -> Box<&str> {
-        Box::new(self.content.as_str())
+    fn text(&self) -> &str {
+        self.content.as_ref()
+    }
+}
+
+/// Represents a source without any file on disk, such as macro-generated
+/// or REPL-evaluated source code.
+///
+/// Virtual sources have a synthetic name, such as `<macro expansion>` or `<repl:3>`, and
+/// can optionally refer to the site which the source was expanded from. When rendered,
+/// the chain of expansion sites is printed below the snippet.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{NamedSource, Source, SourceRange, VirtualSource};
+///
+/// let file = Arc::new(NamedSource::new("src/main.lm", "let a = vec![1, 2, 3];"));
+///
+/// let expansion = VirtualSource::new("<macro expansion>", "Vec::from([1, 2, 3])")
+///     .with_expansion_site(SourceRange::new(file, 8..21));
+///
+/// assert_eq!(expansion.name(), Some("<macro expansion>"));
+/// assert!(expansion.expanded_from().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualSource {
+    /// Defines the synthetic name of the source.
+    pub name: String,
+
+    /// Defines the content of the source.
+    pub content: String,
+
+    /// Defines the site which the source was expanded from, if any.
+    pub expansion_site: Option<SourceRange>,
+}
+
+impl VirtualSource {
+    /// Creates a new [`VirtualSource`] from the given synthetic name and content.
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+            expansion_site: None,
+        }
+    }
+
+    /// Sets the site which the source was expanded from.
+    pub fn with_expansion_site(mut self, site: SourceRange) -> Self {
+        self.expansion_site = Some(site);
+        self
+    }
+}
+
+impl Source for VirtualSource {
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str())
+    }
+
+    fn text(&self) -> &str {
+        self.content.as_str()
+    }
+
+    fn expanded_from(&self) -> Option<&SourceRange> {
+        self.expansion_site.as_ref()
+    }
+}
+
+/// Defines the encoding of the raw bytes of a [`BinarySource`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, where invalid sequences are replaced with `U+FFFD`.
+    #[default]
+    Utf8,
+
+    /// UTF-16 in little-endian byte order, where unpaired surrogates are replaced with `U+FFFD`.
+    Utf16Le,
+
+    /// UTF-16 in big-endian byte order, where unpaired surrogates are replaced with `U+FFFD`.
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Detects the encoding of the given bytes from their byte order mark, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Encoding;
+    ///
+    /// assert_eq!(Encoding::detect(b"\xEF\xBB\xBFlet a = 1;"), Some(Encoding::Utf8));
+    /// assert_eq!(Encoding::detect(b"\xFF\xFEl\0"), Some(Encoding::Utf16Le));
+    /// assert_eq!(Encoding::detect(b"let a = 1;"), None);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => Some(Encoding::Utf8),
+            [0xFF, 0xFE, ..] => Some(Encoding::Utf16Le),
+            [0xFE, 0xFF, ..] => Some(Encoding::Utf16Be),
+            _ => None,
+        }
+    }
+
+    /// Gets the byte order mark of the encoding.
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Encoding::Utf16Le => &[0xFF, 0xFE],
+            Encoding::Utf16Be => &[0xFE, 0xFF],
+        }
+    }
+
+    /// Decodes the given bytes into a string, skipping any leading byte order mark.
+    ///
+    /// Decoding never fails, since invalid sequences are replaced with `U+FFFD`.
+    ///
+    /// # Examples
+    /// ```
+    /// use error_snippet::Encoding;
+    ///
+    /// assert_eq!(Encoding::Utf8.decode(b"\xEF\xBB\xBFna\xEFve"), "na\u{FFFD}ve");
+    /// assert_eq!(Encoding::Utf16Be.decode(b"\0l\0e\0t"), "let");
+    /// ```
+    pub fn decode(self, bytes: &[u8]) -> String {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+
+        let from_bytes = match self {
+            Encoding::Utf8 => return String::from_utf8_lossy(bytes).into_owned(),
+            Encoding::Utf16Le => u16::from_le_bytes,
+            Encoding::Utf16Be => u16::from_be_bytes,
+        };
+
+        let units = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]]));
+
+        let mut decoded = char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect::<String>();
+
+        // A trailing odd byte can't form a complete code unit.
+        if bytes.len() % 2 != 0 {
+            decoded.push(char::REPLACEMENT_CHARACTER);
+        }
+
+        decoded
+    }
+}
+
+/// Represents a source file, which is created from raw bytes in some [`Encoding`].
+///
+/// The bytes are decoded once, when the source is created, so sources which aren't valid
+/// UTF-8 can still be reported against. Since renderers operate on the decoded content, the
+/// ranges of labels must be byte offsets into the decoded content, excluding any byte order mark.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{BinarySource, Encoding, Source};
+///
+/// // Latin-1 encoded `é`, which isn't valid UTF-8.
+/// let source = BinarySource::new(b"let caf\xE9 = 1;").with_name("src/main.lm");
+///
+/// assert_eq!(source.name(), Some("src/main.lm"));
+/// assert_eq!(source.text(), "let caf\u{FFFD} = 1;");
+///
+/// let source = BinarySource::new(b"\xFF\xFEl\0e\0t\0");
+///
+/// assert_eq!(source.encoding(), Encoding::Utf16Le);
+/// assert_eq!(source.text(), "let");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinarySource {
+    name: Option<String>,
+    encoding: Encoding,
+    content: String,
+}
+
+impl BinarySource {
+    /// Creates a new [`BinarySource`] from the given bytes.
+    ///
+    /// The encoding is detected from the byte order mark of the bytes. If there is none,
+    /// the bytes are assumed to be UTF-8.
+    pub fn new(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+
+        Self::with_encoding(bytes, Encoding::detect(bytes).unwrap_or_default())
+    }
+
+    /// Creates a new [`BinarySource`] from the given bytes, which are decoded using the given encoding.
+    pub fn with_encoding(bytes: impl AsRef<[u8]>, encoding: Encoding) -> Self {
+        Self {
+            name: None,
+            encoding,
+            content: encoding.decode(bytes.as_ref()),
+        }
+    }
+
+    /// Creates a new [`BinarySource`] instance from an existing file, detecting it's encoding.
+    ///
+    /// The path of the file is used as the name of the source.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<BinarySource> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+
+        Ok(Self::new(bytes).with_name(path.to_string_lossy()))
+    }
+
+    /// Sets the name of the source file.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Gets the encoding which the source was decoded from.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
+
+impl Source for BinarySource {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn text(&self) -> &str {
+        self.content.as_str()
+    }
+}
+
+/// Lightweight identifier of a source file, which is resolved into the source itself
+/// by a [`SourceResolver`] when rendering.
+///
+/// Labels which refer to their source by identifier, using [`crate::Label::in_source()`], don't
+/// need to hold an [`Arc<dyn Source>`] of their own, so AST nodes can store spans without
+/// any reference counting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceId(pub u32);
+
+impl From<u32> for SourceId {
+    fn from(id: u32) -> SourceId {
+        SourceId(id)
+    }
+}
+
+/// Defines a lookup of sources by their [`SourceId`], which is passed to renderers
+/// so labels created with [`crate::Label::in_source()`] can be rendered.
+///
+/// # Implementations
+///
+/// - any function or closure of `Fn(SourceId) -> Option<Arc<dyn Source>>`,
+/// - [`Vec<Arc<dyn Source>>`], where the identifier is the index of the source,
+/// - [`HashMap<SourceId, Arc<dyn Source>>`].
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use error_snippet::{GraphicalRenderer, Label, NamedSource, Renderer, SimpleDiagnostic, Source, SourceId};
+///
+/// let sources: Vec<Arc<dyn Source>> = vec![Arc::new(NamedSource::new("src/main.lm", "let a = b;"))];
+///
+/// let mut renderer = GraphicalRenderer::new();
+/// renderer.use_colors = false;
+/// renderer.source_resolver = Some(Arc::new(sources));
+///
+/// let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+///     .with_label(Label::in_source(SourceId(0), 8..9, "not found in this scope"));
+///
+/// let rendered = renderer.render(&diagnostic).unwrap();
+///
+/// assert!(rendered.contains("src/main.lm:1:9"));
+/// ```
+pub trait SourceResolver: Send + Sync {
+    /// Resolves the source with the given identifier, if it exists.
+    fn resolve(&self, id: SourceId) -> Option<Arc<dyn Source>>;
+}
+
+impl<F> SourceResolver for F
+where
+    F: Fn(SourceId) -> Option<Arc<dyn Source>> + Send + Sync,
+{
+    fn resolve(&self, id: SourceId) -> Option<Arc<dyn Source>> {
+        self(id)
+    }
+}
+
+impl SourceResolver for Vec<Arc<dyn Source>> {
+    fn resolve(&self, id: SourceId) -> Option<Arc<dyn Source>> {
+        self.get(id.0 as usize).cloned()
+    }
+}
+
+impl SourceResolver for HashMap<SourceId, Arc<dyn Source>> {
+    fn resolve(&self, id: SourceId) -> Option<Arc<dyn Source>> {
+        self.get(&id).cloned()
+    }
+}
+
+impl std::fmt::Debug for dyn SourceResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SourceResolver")
+    }
+}
+
+/// Fingerprint of the content of a source, which is captured when a diagnostic is created, so
+/// renderers can detect when the source has changed before the diagnostic is rendered.
+///
+/// The fingerprint consists of the line count of the source, along with the [`Source::version()`] of
+/// the source, or a hash of the content if the source doesn't define any version. Since the hash is
+/// only stable within a single process, fingerprints shouldn't be persisted.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{NamedSource, SourceFingerprint};
+///
+/// let before = NamedSource::new("src/main.lm", "let a = b;");
+/// let after = NamedSource::new("src/main.lm", "let a = c;");
+///
+/// let fingerprint = SourceFingerprint::of(&before).unwrap();
+///
+/// assert!(fingerprint.matches(&before));
+/// assert!(!fingerprint.matches(&after));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceFingerprint {
+    /// Defines the amount of lines in the source.
+    line_count: usize,
+
+    /// Defines the version of the source, or the hash of it's content.
+    checksum: u64,
+}
+
+impl SourceFingerprint {
+    /// Creates a new [`SourceFingerprint`] from the given line count and checksum.
+    pub fn new(line_count: usize, checksum: u64) -> Self {
+        SourceFingerprint { line_count, checksum }
+    }
+
+    /// Captures the fingerprint of the given source, if it's content is available.
+    pub fn of(source: &dyn Source) -> Option<Self> {
+        let content = source.try_text()?;
+
+        let checksum = source.version().unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            content.hash(&mut hasher);
+            hasher.finish()
+        });
+
+        Some(SourceFingerprint::new(content.lines().count(), checksum))
+    }
+
+    /// Gets the amount of lines in the source, when the fingerprint was captured.
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// Gets the version of the source, or the hash of it's content, when the fingerprint was captured.
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+
+    /// Determines whether the given source still matches the fingerprint.
+    ///
+    /// Sources without any available content are assumed to match, since
+    /// there is no snippet which could be rendered incorrectly.
+    pub fn matches(&self, source: &dyn Source) -> bool {
+        SourceFingerprint::of(source).is_none_or(|current| current == *self)
     }
 }
@@ -0,0 +1,93 @@
+//! Helpers for snapshot testing diagnostics in downstream crates.
+//!
+//! This module is only available with the `testing` feature. It contains a deterministic
+//! renderer configuration, which renders without colors, using ASCII symbols and a fixed
+//! width, so snapshots don't depend on the terminal they were recorded in.
+//!
+//! Snapshots are recorded using [`insta`], so the usual `cargo insta review` workflow applies.
+//!
+//! # Examples
+//! ```no_run
+//! use error_snippet::{assert_diagnostic_snapshot, SimpleDiagnostic};
+//!
+//! #[test]
+//! fn mismatched_types() {
+//!     let diagnostic = SimpleDiagnostic::new("mismatched types").with_code("E0308");
+//!
+//!     assert_diagnostic_snapshot!(diagnostic);
+//! }
+//! ```
+
+use crate::{Diagnostic, GraphicalRenderer, Renderer, Theme};
+
+#[doc(hidden)]
+pub use insta;
+
+/// Defines the width of the deterministic renderer, in columns.
+pub const SNAPSHOT_WIDTH: usize = 100;
+
+/// Creates a [`GraphicalRenderer`] with a deterministic configuration, which renders without
+/// colors, using ASCII symbols and a fixed width of [`SNAPSHOT_WIDTH`] columns.
+///
+/// # Examples
+/// ```
+/// use error_snippet::testing;
+///
+/// let renderer = testing::renderer();
+///
+/// assert!(!renderer.use_colors);
+/// assert_eq!(renderer.width, testing::SNAPSHOT_WIDTH);
+/// ```
+pub fn renderer() -> GraphicalRenderer {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.theme = Theme::ascii();
+    renderer.width = SNAPSHOT_WIDTH;
+    renderer.use_colors = false;
+    renderer.syntax_highlighter = None;
+
+    renderer
+}
+
+/// Renders the given diagnostic using the deterministic [`renderer()`].
+///
+/// # Examples
+/// ```
+/// use error_snippet::{testing, SimpleDiagnostic};
+///
+/// let rendered = testing::render(&SimpleDiagnostic::new("mismatched types"));
+///
+/// assert_eq!(rendered, "x error: mismatched types\n");
+/// ```
+pub fn render(diagnostic: &dyn Diagnostic) -> String {
+    renderer()
+        .render(diagnostic)
+        .expect("diagnostic should render without errors")
+}
+
+/// Asserts that the given diagnostic matches it's snapshot, when rendered using
+/// the deterministic renderer of [`testing::renderer()`](crate::testing::renderer).
+///
+/// Like [`insta::assert_snapshot!`], the snapshot can optionally be given a name.
+///
+/// # Examples
+/// ```no_run
+/// use error_snippet::{assert_diagnostic_snapshot, SimpleDiagnostic};
+///
+/// let diagnostic = SimpleDiagnostic::new("mismatched types");
+///
+/// assert_diagnostic_snapshot!(diagnostic);
+/// assert_diagnostic_snapshot!("mismatched_types", diagnostic);
+/// ```
+#[macro_export]
+macro_rules! assert_diagnostic_snapshot {
+    ($diagnostic:expr $(,)?) => {
+        $crate::testing::insta::assert_snapshot!(
+            None::<String>,
+            $crate::testing::render(&$diagnostic),
+            stringify!($diagnostic)
+        )
+    };
+    ($name:expr, $diagnostic:expr $(,)?) => {
+        $crate::testing::insta::assert_snapshot!($name, $crate::testing::render(&$diagnostic), stringify!($diagnostic))
+    };
+}
@@ -0,0 +1,331 @@
+//! Conformance test-kit for custom [`Renderer`] implementations.
+//!
+//! The test-kit contains a corpus of diagnostic fixtures, which covers the full surface of
+//! the [`Diagnostic`] trait, along with invariant checks for the rendered output of each fixture.
+//! Authors of custom renderers (such as HTML, TUI or JSON renderers) can use it to verify that
+//! their renderer doesn't silently drop any information from the diagnostics.
+//!
+//! The checks are textual, so renderers should be configured to render without any styling,
+//! such as ANSI color codes, which might split up the checked text.
+//!
+//! The test-kit is only available with the `renderer-testkit` feature, which is
+//! usually enabled within `dev-dependencies`.
+//!
+//! # Examples
+//! ```
+//! use error_snippet::testkit;
+//! use error_snippet::GraphicalRenderer;
+//!
+//! let mut renderer = GraphicalRenderer::new();
+//! renderer.use_colors = false;
+//!
+//! testkit::assert_conformance(&renderer);
+//! ```
+
+use std::sync::Arc;
+
+use crate::{
+    walk, Diagnostic, Help, Label, NamedSource, Renderer, Severity, SimpleDiagnostic, SourceLocation, SourceRange,
+    Suggestion, WalkContext, WithSource,
+};
+
+/// A single diagnostic within the test-kit corpus.
+pub struct Fixture {
+    /// Defines the unique name of the fixture, which is used when reporting violations.
+    pub name: &'static str,
+
+    /// Defines the diagnostic of the fixture.
+    pub diagnostic: Box<dyn Diagnostic>,
+}
+
+impl Fixture {
+    /// Creates a new [`Fixture`] with the given name and diagnostic.
+    pub fn new(name: &'static str, diagnostic: impl Diagnostic + 'static) -> Self {
+        Self {
+            name,
+            diagnostic: Box::new(diagnostic),
+        }
+    }
+}
+
+/// A violated invariant in the rendered output of a fixture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Defines the name of the fixture which violated the invariant.
+    pub fixture: &'static str,
+
+    /// Defines a description of the violated invariant.
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.fixture, self.message)
+    }
+}
+
+/// Gets the corpus of diagnostic fixtures.
+///
+/// The corpus covers all severities, codes, labels on single and multiple lines, labels
+/// which rely on the source of their diagnostic, help messages, all kinds of suggestions,
+/// causes and related diagnostics.
+pub fn fixtures() -> Vec<Fixture> {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() -> Int {\n    let a = new Testing();\n    let b = a.invok();\n\n    return (b);\n}",
+    ));
+
+    let mut fixtures = vec![
+        Fixture::new("message", SimpleDiagnostic::new("something went wrong")),
+        Fixture::new("code", SimpleDiagnostic::new("something went wrong").with_code("E0001")),
+    ];
+
+    for (name, severity) in [
+        ("severity-bug", Severity::Bug),
+        ("severity-fatal", Severity::Fatal),
+        ("severity-error", Severity::Error),
+        ("severity-warning", Severity::Warning),
+        ("severity-info", Severity::Info),
+        ("severity-note", Severity::Note),
+        ("severity-help", Severity::Help),
+    ] {
+        fixtures.push(Fixture::new(
+            name,
+            SimpleDiagnostic::new("something happened").with_severity(severity),
+        ));
+    }
+
+    fixtures.extend([
+        Fixture::new(
+            "label",
+            SimpleDiagnostic::new("could not find method").with_label(Label::error(
+                Some(source.clone()),
+                60..65,
+                "could not find method 'invok'",
+            )),
+        ),
+        Fixture::new(
+            "label-diagnostic-source",
+            SimpleDiagnostic::new("could not find method")
+                .with_label(Label::error(None, 60..65, "could not find method 'invok'"))
+                .with_source(source.clone()),
+        ),
+        Fixture::new(
+            "label-multiline",
+            SimpleDiagnostic::new("invalid function").with_label(Label::error(
+                Some(source.clone()),
+                0..66,
+                "function is missing a return value",
+            )),
+        ),
+        Fixture::new(
+            "labels-multiple",
+            SimpleDiagnostic::new("mismatched types")
+                .with_label(Label::note(Some(source.clone()), 13..16, "expected due to this"))
+                .with_label(Label::error(
+                    Some(source.clone()),
+                    81..84,
+                    "expected `Int`, found `Testing`",
+                )),
+        ),
+        Fixture::new(
+            "labels-mixed-severity",
+            SimpleDiagnostic::new("unused variable")
+                .with_severity(Severity::Warning)
+                .with_label(Label::warning(
+                    Some(source.clone()),
+                    27..28,
+                    "variable `a` is never read",
+                ))
+                .with_label(Label::help(
+                    Some(source.clone()),
+                    27..28,
+                    "prefix it with an underscore",
+                )),
+        ),
+        Fixture::new(
+            "help",
+            SimpleDiagnostic::new("could not find method")
+                .with_help("methods are case-sensitive")
+                .with_help("did you mean `invoke`?\nor perhaps `invert`?"),
+        ),
+        Fixture::new(
+            "suggestion-replace",
+            SimpleDiagnostic::new("could not find method").with_help(
+                Help::new("did you mean `invoke`?")
+                    .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 60..65), "invoke")),
+            ),
+        ),
+        Fixture::new(
+            "suggestion-insert",
+            SimpleDiagnostic::new("missing type annotation").with_help(
+                Help::new("add a type annotation")
+                    .with_suggestion(Suggestion::insert(SourceLocation::new(source.clone(), 28), ": Testing")),
+            ),
+        ),
+        Fixture::new(
+            "suggestion-delete",
+            SimpleDiagnostic::new("unnecessary parenthesis").with_help(
+                Help::new("remove the parenthesis")
+                    .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 81..82)))
+                    .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 83..84))),
+            ),
+        ),
+        Fixture::new(
+            "suggestion-files",
+            SimpleDiagnostic::new("module not found").with_help(
+                Help::new("move the module into it's own file")
+                    .with_suggestion(Suggestion::create_file("src/testing.lm", "struct Testing {}"))
+                    .with_suggestion(Suggestion::rename_file("src/test.lm", "src/tests.lm"))
+                    .with_suggestion(Suggestion::delete_file("src/old.lm")),
+            ),
+        ),
+        Fixture::new(
+            "causes",
+            SimpleDiagnostic::new("failed to compile")
+                .add_cause(SimpleDiagnostic::new("failed to type-check `main`"))
+                .add_cause(SimpleDiagnostic::new("could not find method").with_label(Label::error(
+                    Some(source.clone()),
+                    60..65,
+                    "could not find method 'invok'",
+                ))),
+        ),
+        Fixture::new(
+            "related",
+            SimpleDiagnostic::new("duplicate definitions of `main`")
+                .add_related(
+                    SimpleDiagnostic::new("first defined here")
+                        .with_severity(Severity::Note)
+                        .with_label(Label::note(Some(source.clone()), 3..7, "`main` defined here")),
+                )
+                .add_related(SimpleDiagnostic::new("redefined in another module").with_severity(Severity::Note)),
+        ),
+        Fixture::new(
+            "nested",
+            SimpleDiagnostic::new("failed to compile").add_cause(
+                SimpleDiagnostic::new("failed to type-check `main`")
+                    .add_related(SimpleDiagnostic::new("while checking this block").with_severity(Severity::Note)),
+            ),
+        ),
+    ]);
+
+    fixtures
+}
+
+/// Renders all fixtures in the corpus with the given renderer, and returns
+/// all the invariants which were violated by the rendered output.
+pub fn check_renderer(renderer: &dyn Renderer) -> Vec<Violation> {
+    fixtures()
+        .iter()
+        .flat_map(|fixture| check_fixture(renderer, fixture))
+        .collect()
+}
+
+/// Renders the given fixture with the given renderer, and returns all the
+/// invariants which were violated by the rendered output.
+///
+/// The rendered output must contain:
+/// - the message and code of the diagnostic,
+/// - the name of the severity of the diagnostic (case-insensitive),
+/// - the message of all labels,
+/// - every line of all help messages,
+/// - the inserted or replaced text of all suggestions, as well as the paths of file operations,
+/// - all of the above for every cause and related diagnostic, recursively.
+pub fn check_fixture(renderer: &dyn Renderer, fixture: &Fixture) -> Vec<Violation> {
+    let rendered = match renderer.render(fixture.diagnostic.as_ref()) {
+        Ok(rendered) => rendered,
+        Err(err) => {
+            return vec![Violation {
+                fixture: fixture.name,
+                message: format!("failed to render: {err}"),
+            }];
+        }
+    };
+
+    let mut expected = Vec::new();
+
+    walk(
+        fixture.diagnostic.as_ref(),
+        &mut |diagnostic: &dyn Diagnostic, _: WalkContext| expected_fragments(diagnostic, &mut expected),
+    );
+
+    let lowercase = rendered.to_lowercase();
+
+    expected
+        .into_iter()
+        .filter(|(fragment, case_insensitive)| {
+            if *case_insensitive {
+                !lowercase.contains(&fragment.to_lowercase())
+            } else {
+                !rendered.contains(fragment.as_str())
+            }
+        })
+        .map(|(fragment, _)| Violation {
+            fixture: fixture.name,
+            message: format!("expected rendered output to contain {fragment:?}"),
+        })
+        .collect()
+}
+
+/// Asserts that the given renderer upholds all invariants for every fixture in the corpus.
+///
+/// # Panics
+///
+/// Panics if any invariant is violated, listing all the violations.
+pub fn assert_conformance(renderer: &dyn Renderer) {
+    let violations = check_renderer(renderer);
+
+    if !violations.is_empty() {
+        let list = violations
+            .iter()
+            .map(|v| format!("  {v}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        panic!("renderer violated {} invariant(s):\n{list}", violations.len());
+    }
+}
+
+/// Collects all text fragments which must be present in the rendered output of the diagnostic,
+/// excluding any nested diagnostics, along with whether the fragment should be matched case-insensitively.
+fn expected_fragments(diagnostic: &dyn Diagnostic, fragments: &mut Vec<(String, bool)>) {
+    fragments.push((diagnostic.message(), false));
+    fragments.push((diagnostic.severity().to_string(), true));
+
+    if let Some(code) = diagnostic.code() {
+        fragments.push((code.to_string(), false));
+    }
+
+    if let Some(labels) = diagnostic.labels() {
+        for label in labels.filter(|l| !l.message().is_empty()) {
+            fragments.push((label.message().to_string(), false));
+        }
+    }
+
+    if let Some(help) = diagnostic.help() {
+        for help in help {
+            for line in help.message.lines() {
+                fragments.push((line.to_string(), false));
+            }
+
+            if let Some(title) = help.title {
+                fragments.push((title, false));
+            }
+
+            for suggestion in help.suggestions {
+                let fragment = match suggestion {
+                    Suggestion::Insertion { value, .. } => value,
+                    Suggestion::Replacement { replacement, .. } => replacement,
+                    Suggestion::Deletion { .. } => continue,
+                    Suggestion::CreateFile { path, .. } | Suggestion::DeleteFile { path } => path,
+                    Suggestion::RenameFile { from, to } => {
+                        fragments.push((from, false));
+                        to
+                    }
+                };
+
+                fragments.push((fragment, false));
+            }
+        }
+    }
+}
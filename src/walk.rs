@@ -0,0 +1,149 @@
+//! Traversal of diagnostic trees, which consist of a diagnostic along with all it's causes
+//! and related diagnostics, recursively.
+
+use crate::Diagnostic;
+
+/// Defines how a diagnostic within a tree relates to it's parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    /// Defines that the diagnostic is the root of the tree, without any parent.
+    Root,
+
+    /// Defines that the diagnostic is a cause of it's parent.
+    Cause,
+
+    /// Defines that the diagnostic is related to it's parent.
+    Related,
+}
+
+/// Defines the position of a diagnostic within a tree, which is passed to a [`DiagnosticVisitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WalkContext {
+    /// Defines the depth of the diagnostic, where the root has a depth of zero.
+    pub depth: usize,
+
+    /// Defines how the diagnostic relates to it's parent.
+    pub relation: Relation,
+
+    /// Defines the index of the diagnostic within the causes or related diagnostics of it's parent.
+    pub index: usize,
+}
+
+/// Defines how a walk should continue after visiting a diagnostic.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WalkControl {
+    /// Continues with the causes and related diagnostics of the visited diagnostic.
+    #[default]
+    Continue,
+
+    /// Skips the causes and related diagnostics of the visited diagnostic, but continues with it's siblings.
+    SkipChildren,
+
+    /// Stops the walk entirely.
+    Stop,
+}
+
+/// Visitor which is called for every diagnostic within a tree, by [`walk()`].
+///
+/// Any function or closure of `FnMut(&dyn Diagnostic, WalkContext)` implements [`DiagnosticVisitor`],
+/// which visits the entire tree.
+pub trait DiagnosticVisitor {
+    /// Visits the given diagnostic, before any of it's causes or related diagnostics are visited.
+    fn visit(&mut self, diagnostic: &dyn Diagnostic, cx: WalkContext) -> WalkControl;
+
+    /// Leaves the given diagnostic, after all of it's causes and related diagnostics have been visited.
+    ///
+    /// This isn't called for diagnostics whose children were skipped, nor after the walk has been stopped.
+    fn leave(&mut self, _diagnostic: &dyn Diagnostic, _cx: WalkContext) {}
+}
+
+impl<F> DiagnosticVisitor for F
+where
+    F: FnMut(&dyn Diagnostic, WalkContext),
+{
+    fn visit(&mut self, diagnostic: &dyn Diagnostic, cx: WalkContext) -> WalkControl {
+        self(diagnostic, cx);
+
+        WalkControl::Continue
+    }
+}
+
+/// Walks the given diagnostic, along with all it's causes and related diagnostics, recursively.
+///
+/// Diagnostics are visited depth-first, where all causes of a diagnostic are visited before
+/// it's related diagnostics, which is the same order as they're rendered in. Returns
+/// [`WalkControl::Stop`] if the walk was stopped by the visitor.
+///
+/// # Examples
+/// ```
+/// use error_snippet::{walk, Diagnostic, Relation, SimpleDiagnostic, WalkContext};
+///
+/// let diagnostic = SimpleDiagnostic::new("failed to compile")
+///     .add_cause(SimpleDiagnostic::new("failed to read file").add_cause(SimpleDiagnostic::new("permission denied")))
+///     .add_related(SimpleDiagnostic::new("previous definition"));
+///
+/// let mut visited = Vec::new();
+///
+/// walk(&diagnostic, &mut |diagnostic: &dyn Diagnostic, cx: WalkContext| {
+///     visited.push((diagnostic.message(), cx.depth, cx.relation));
+/// });
+///
+/// assert_eq!(
+///     visited,
+///     vec![
+///         (String::from("failed to compile"), 0, Relation::Root),
+///         (String::from("failed to read file"), 1, Relation::Cause),
+///         (String::from("permission denied"), 2, Relation::Cause),
+///         (String::from("previous definition"), 1, Relation::Related),
+///     ]
+/// );
+/// ```
+pub fn walk<V: DiagnosticVisitor + ?Sized>(diagnostic: &dyn Diagnostic, visitor: &mut V) -> WalkControl {
+    let cx = WalkContext {
+        depth: 0,
+        relation: Relation::Root,
+        index: 0,
+    };
+
+    walk_with(diagnostic, visitor, cx)
+}
+
+/// Walks the given diagnostic at the given position within the tree.
+fn walk_with<V: DiagnosticVisitor + ?Sized>(
+    diagnostic: &dyn Diagnostic,
+    visitor: &mut V,
+    cx: WalkContext,
+) -> WalkControl {
+    match visitor.visit(diagnostic, cx) {
+        WalkControl::Continue => {}
+        WalkControl::SkipChildren => return WalkControl::Continue,
+        WalkControl::Stop => return WalkControl::Stop,
+    }
+
+    let children = diagnostic
+        .causes()
+        .enumerate()
+        .map(|(index, nested)| (nested, Relation::Cause, index))
+        .chain(
+            diagnostic
+                .related()
+                .enumerate()
+                .map(|(index, nested)| (nested, Relation::Related, index)),
+        );
+
+    for (nested, relation, index) in children {
+        let nested_cx = WalkContext {
+            depth: cx.depth + 1,
+            relation,
+            index,
+        };
+
+        if walk_with(nested, visitor, nested_cx) == WalkControl::Stop {
+            return WalkControl::Stop;
+        }
+    }
+
+    visitor.leave(diagnostic, cx);
+
+    WalkControl::Continue
+}
@@ -1,7 +1,7 @@
 use std::ops::Range;
 use std::sync::Arc;
 
-use error_snippet::NamedSource;
+use error_snippet::{Diagnostic, NamedSource};
 use error_snippet_derive::Diagnostic;
 use insta::assert_snapshot;
 
@@ -149,3 +149,107 @@ fn multiple_causes_with_source() {
         ]
     }));
 }
+
+#[test]
+fn optional_cause() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "child error")]
+    struct Child {}
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error")]
+    struct Parent {
+        #[cause]
+        pub child: Option<error_snippet::Error>,
+    }
+
+    assert_snapshot!(render(Parent {
+        child: Some(Child {}.into())
+    }));
+
+    assert_eq!(render(Parent { child: None }), "× error: parent error\n");
+}
+
+#[test]
+fn boxed_cause() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "child error")]
+    struct Child {}
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error")]
+    struct Parent {
+        #[cause]
+        pub child: Box<dyn Diagnostic + Send + Sync>,
+    }
+
+    assert_snapshot!(render(Parent {
+        child: Box::new(Child {})
+    }));
+}
+
+#[test]
+fn cause_collection() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "child error")]
+    struct Child {}
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error")]
+    struct Parent {
+        #[cause(collection)]
+        pub children: Vec<error_snippet::Error>,
+    }
+
+    assert_snapshot!(render(Parent {
+        children: vec![Child {}.into(), Child {}.into()]
+    }));
+}
+
+#[test]
+fn generic_cause() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "child error")]
+    struct Child {}
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error with {value}")]
+    struct Parent<T: std::fmt::Debug + std::fmt::Display> {
+        #[cause]
+        pub child: error_snippet::Error,
+
+        pub value: T,
+    }
+
+    assert_snapshot!(render(Parent {
+        child: Child {}.into(),
+        value: 42,
+    }));
+}
+
+#[test]
+fn multiple_cause_fields() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "{name} error")]
+    struct Child {
+        pub name: &'static str,
+    }
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error")]
+    struct Parent {
+        #[cause]
+        pub first: error_snippet::Error,
+
+        #[causes]
+        pub rest: Vec<error_snippet::Error>,
+    }
+
+    let parent = Parent {
+        first: Child { name: "first" }.into(),
+        rest: vec![Child { name: "second" }.into()],
+    };
+
+    let causes = parent.causes().map(|cause| cause.message()).collect::<Vec<_>>();
+    assert_eq!(causes, vec!["first error", "second error"]);
+}
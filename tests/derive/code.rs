@@ -33,3 +33,56 @@ fn complex() {
 
     assert_snapshot!(render(Foo {}));
 }
+
+#[test]
+fn field() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "some error", code = self.code)]
+    struct Foo {
+        pub code: String,
+    }
+
+    assert_snapshot!(render(Foo { code: "E3404".into() }));
+}
+
+#[derive(Debug)]
+enum ErrorCode {
+    UnknownMethod,
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorCode::UnknownMethod => f.write_str("E3404"),
+        }
+    }
+}
+
+#[test]
+fn nested_field() {
+    #[derive(Debug)]
+    struct Kind {
+        code: ErrorCode,
+    }
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "some error", code = self.kind.code)]
+    struct Foo {
+        pub kind: Kind,
+    }
+
+    assert_snapshot!(render(Foo {
+        kind: Kind {
+            code: ErrorCode::UnknownMethod
+        }
+    }));
+}
+
+#[test]
+fn path() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "some error", code = ErrorCode::UnknownMethod)]
+    struct Foo {}
+
+    assert_snapshot!(render(Foo {}));
+}
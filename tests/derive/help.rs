@@ -29,3 +29,54 @@ fn multiple_helps() {
 
     assert_snapshot!(render(Foo {}));
 }
+
+#[test]
+fn field() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "foo")]
+    struct Foo {
+        #[help]
+        pub hint: String,
+    }
+
+    assert_snapshot!(render(Foo {
+        hint: String::from("did you mean `bar`?"),
+    }));
+}
+
+#[test]
+fn field_optional() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "foo", help = "better luck next time!")]
+    struct Foo {
+        #[help]
+        pub hint: Option<String>,
+    }
+
+    assert_snapshot!(
+        "field_optional_some",
+        render(Foo {
+            hint: Some(String::from("did you mean `bar`?")),
+        })
+    );
+
+    assert_snapshot!("field_optional_none", render(Foo { hint: None }));
+}
+
+#[test]
+fn field_collection() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "foo", help = "better luck next time!")]
+    struct Foo {
+        #[help]
+        pub hints: Vec<String>,
+
+        #[help]
+        pub extra: String,
+    }
+
+    assert_snapshot!(render(Foo {
+        hints: vec![String::from("did you mean `bar`?"), String::from("or perhaps `baz`?")],
+        extra: String::from("you'll get there!"),
+    }));
+}
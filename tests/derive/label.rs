@@ -1,7 +1,7 @@
 use std::ops::Range;
 use std::sync::Arc;
 
-use error_snippet::{NamedSource, WithSource};
+use error_snippet::{Diagnostic as _, NamedSource, Severity, SourceLocation, SourceRange, WithSource};
 use error_snippet_derive::Diagnostic;
 use insta::assert_snapshot;
 
@@ -169,3 +169,94 @@ fn label_fmt_debug() {
         name: "void"
     }));
 }
+
+#[test]
+fn tuple_struct_label() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "foo")]
+    struct Foo(#[span] Arc<NamedSource>, #[label("label here")] Range<usize>);
+
+    let source = Arc::new(NamedSource::new(
+        "some_file.lm",
+        r#"fn main() -> void {
+    return 0;
+}
+"#,
+    ));
+
+    assert_snapshot!(render(Foo(source, 13..17)));
+}
+
+#[test]
+fn tuple_struct_multiple_labels() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "foo")]
+    struct Foo(
+        #[label("label 1 here")] Range<usize>,
+        #[label(warning, "label 2 here")] Range<usize>,
+    );
+
+    let source = Arc::new(NamedSource::new(
+        "some_file.lm",
+        r#"fn main() -> void {
+    return 0;
+}
+"#,
+    ));
+
+    assert_snapshot!(render(Foo(13..17, 24..30).with_source(source)));
+}
+
+#[test]
+fn source_range_label() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "foo")]
+    struct Foo {
+        #[label("label here")]
+        pub span: SourceRange,
+
+        #[label(note, "insert here")]
+        pub location: SourceLocation,
+    }
+
+    let source = Arc::new(NamedSource::new(
+        "some_file.lm",
+        r#"fn main() -> void {
+    return 0;
+}
+"#,
+    ));
+
+    assert_snapshot!(render(Foo {
+        span: SourceRange::new(source.clone(), 13..17),
+        location: SourceLocation::new(source, 18),
+    }));
+}
+
+#[test]
+fn note_label_is_secondary() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "duplicate definition of `main`")]
+    struct Foo {
+        #[label("redefined here")]
+        pub span: SourceRange,
+
+        #[label(note, "previous definition here")]
+        pub previous: SourceRange,
+    }
+
+    let source = Arc::new(NamedSource::new("some_file.lm", "fn main() {}\nfn main() {}\n"));
+
+    let diagnostic = Foo {
+        span: SourceRange::new(source.clone(), 16..20),
+        previous: SourceRange::new(source, 3..7),
+    };
+
+    let labels = diagnostic.labels().unwrap().collect::<Vec<_>>();
+
+    assert!(!labels[0].is_secondary());
+    assert!(labels[1].is_secondary());
+    assert_eq!(labels[1].severity(), Some(Severity::Note));
+
+    assert_snapshot!(render(diagnostic));
+}
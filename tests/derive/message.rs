@@ -60,3 +60,35 @@ fn multiple_formatted_message() {
         name2: "bar",
     }));
 }
+
+#[test]
+fn formatted_message_path() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "could not read {path} ({name})")]
+    struct Foo {
+        pub path: std::path::PathBuf,
+
+        pub name: std::ffi::OsString,
+    }
+
+    assert_snapshot!(render(Foo {
+        path: std::path::PathBuf::from("src/main.lm"),
+        name: std::ffi::OsString::from("main.lm"),
+    }));
+}
+
+#[test]
+fn formatted_message_debug_only() {
+    #[derive(Debug)]
+    enum Token {
+        Comma,
+    }
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "unexpected token {token}")]
+    struct Foo {
+        pub token: Token,
+    }
+
+    assert_snapshot!(render(Foo { token: Token::Comma }));
+}
@@ -1,7 +1,7 @@
 use std::ops::Range;
 use std::sync::Arc;
 
-use error_snippet::NamedSource;
+use error_snippet::{NamedSource, SourceRange};
 use error_snippet_derive::Diagnostic;
 use insta::assert_snapshot;
 
@@ -149,3 +149,63 @@ fn multiple_related_with_source() {
         ]
     }));
 }
+
+#[test]
+fn related_inherits_labelled_source() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "child error")]
+    struct Child {
+        #[label("previously defined here")]
+        pub span: Range<usize>,
+    }
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error")]
+    struct Parent {
+        #[related]
+        pub child: error_snippet::Error,
+
+        #[label(source, "duplicate definition")]
+        pub location: SourceRange,
+    }
+
+    let source = Arc::new(NamedSource::new(
+        "some_file.lm",
+        r#"fn main() -> void {}
+fn main() -> void {}
+"#,
+    ));
+
+    assert_snapshot!(render(Parent {
+        child: Child { span: 3..7 }.into(),
+        location: SourceRange::new(source, 24..28),
+    }));
+}
+
+#[test]
+fn multiple_related_fields() {
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "{name} error")]
+    struct Child {
+        pub name: &'static str,
+    }
+
+    #[derive(Debug, Diagnostic)]
+    #[diagnostic(message = "parent error")]
+    struct Parent {
+        #[related]
+        pub first: error_snippet::Error,
+
+        #[related]
+        pub second: Option<error_snippet::Error>,
+
+        #[related]
+        pub rest: Vec<error_snippet::Error>,
+    }
+
+    assert_snapshot!(render(Parent {
+        first: Child { name: "first" }.into(),
+        second: Some(Child { name: "second" }.into()),
+        rest: vec![Child { name: "third" }.into(), Child { name: "fourth" }.into()],
+    }));
+}
@@ -0,0 +1,7 @@
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(mesage = "some error")]
+struct Foo {}
+
+fn main() {}
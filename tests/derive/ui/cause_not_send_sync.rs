@@ -0,0 +1,11 @@
+use error_snippet::Diagnostic;
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = "foo")]
+struct Foo {
+    #[cause]
+    pub cause: Box<dyn Diagnostic>,
+}
+
+fn main() {}
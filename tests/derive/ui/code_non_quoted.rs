@@ -1,7 +1,7 @@
 use error_snippet_derive::Diagnostic;
 
 #[derive(Debug, Diagnostic)]
-#[diagnostic(message = "some help", code = E5132)]
+#[diagnostic(message = "some help", code = 5132)]
 struct Foo {}
 
 fn main() {}
@@ -0,0 +1,11 @@
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = "foo")]
+struct Foo {
+    #[related]
+    #[cause]
+    pub nested: Vec<error_snippet::Error>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = "some help")]
+struct Foo {
+    #[help("did you mean `bar`?")]
+    pub hint: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use std::ops::Range;
+
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = "foo")]
+struct Foo {
+    #[label(warning, 42)]
+    pub span: Range<usize>,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(code = "E0001")]
+struct Foo {}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = some_error)]
+struct Foo {}
+
+fn main() {}
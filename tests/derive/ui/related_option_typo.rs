@@ -0,0 +1,10 @@
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = "foo")]
+struct Foo {
+    #[related(colection)]
+    pub related: Vec<error_snippet::Error>,
+}
+
+fn main() {}
@@ -1,7 +1,7 @@
 use error_snippet_derive::Diagnostic;
 
 #[derive(Debug, Diagnostic)]
-#[diagnostic(message = "some help", severity = Bug)]
+#[diagnostic(message = "some help", severity = Critical)]
 struct Foo {}
 
 fn main() {}
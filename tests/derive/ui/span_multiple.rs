@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use error_snippet::NamedSource;
+use error_snippet_derive::Diagnostic;
+
+#[derive(Debug, Diagnostic)]
+#[diagnostic(message = "foo")]
+struct Foo {
+    #[span]
+    pub first: Arc<NamedSource>,
+
+    #[span]
+    pub second: Arc<NamedSource>,
+}
+
+fn main() {}
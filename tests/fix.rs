@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use error_snippet::{
+    Diagnostic, FixReport, FixedFile, GraphicalRenderer, Help, Label, NamedSource, Renderer, SimpleDiagnostic, Source,
+    SourceLocation, SourceRange, Suggestion, SuggestionConflict, SuggestionSet, WithSource,
+};
+use insta::assert_snapshot;
+
+fn source(name: &str, content: &str) -> Arc<dyn Source> {
+    Arc::new(NamedSource::new(name, content))
+}
+
+#[test]
+fn diff_empty() {
+    let set = SuggestionSet::new();
+
+    assert!(set.is_empty());
+    assert_eq!(set.to_unified_diff(), "");
+}
+
+#[test]
+fn diff_single_replacement() {
+    let source = source("src/main.lm", "fn main() {\n    return fals;\n}\n");
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::replace(SourceRange::new(source.clone(), 23..27), "false"))
+        .unwrap();
+
+    assert_snapshot!(set.to_unified_diff());
+}
+
+#[test]
+fn diff_no_trailing_newline() {
+    let source = source("src/main.lm", "fn main() {\n    return fals;\n}");
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::replace(SourceRange::new(source.clone(), 23..27), "false"))
+        .unwrap();
+    set.add(Suggestion::insert(SourceLocation::new(source.clone(), 30), "\n"))
+        .unwrap();
+
+    assert_snapshot!(set.to_unified_diff());
+}
+
+#[test]
+fn diff_separate_hunks() {
+    let content = (1..=20).map(|i| format!("let a{i} = {i};\n")).collect::<String>();
+    let source = source("src/main.lm", &content);
+
+    let line_start = |line: usize| content.lines().take(line - 1).map(|l| l.len() + 1).sum::<usize>();
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::delete(SourceRange::new(
+        source.clone(),
+        line_start(2)..line_start(3),
+    )))
+    .unwrap();
+    set.add(Suggestion::insert(
+        SourceLocation::new(source.clone(), line_start(15)),
+        "let b = 0;\n",
+    ))
+    .unwrap();
+    set.add(Suggestion::replace(
+        SourceRange::new(source.clone(), line_start(4) + 4..line_start(4) + 6),
+        "b4",
+    ))
+    .unwrap();
+
+    assert_snapshot!(set.to_unified_diff());
+}
+
+#[test]
+fn diff_multiple_files() {
+    let main = source("src/main.lm", "import std::io;\n\nfn main() {}\n");
+    let lib = source("src/lib.rs", "pub fn foo() {}\n");
+
+    let diagnostic = SimpleDiagnostic::new("unused import")
+        .with_help(Help::new("remove the import").with_suggestion(Suggestion::delete(SourceRange::new(main, 0..16))))
+        .add_related(
+            SimpleDiagnostic::new("missing documentation").with_help(
+                Help::new("add documentation")
+                    .with_suggestion(Suggestion::insert(SourceLocation::new(lib, 0), "/// Does foo.\n")),
+            ),
+        );
+
+    let set = SuggestionSet::from_diagnostics([&diagnostic as &dyn Diagnostic]);
+
+    assert_snapshot!(set.to_unified_diff());
+}
+
+#[test]
+fn diff_file_operations() {
+    let diagnostic = SimpleDiagnostic::new("module not found").with_help(
+        Help::new("move the module into it's own file")
+            .with_suggestion(Suggestion::create_file("src/testing.lm", "struct Testing {\n}\n"))
+            .with_suggestion(Suggestion::rename_file("src/test.lm", "src/tests.lm"))
+            .with_suggestion(Suggestion::delete_file("src/old.lm")),
+    );
+
+    let set = SuggestionSet::from_diagnostics([&diagnostic as &dyn Diagnostic]);
+
+    assert_snapshot!(set.to_unified_diff());
+}
+
+#[test]
+fn diff_insert_into_empty_file() {
+    let source = source("src/main.lm", "");
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::insert(
+        SourceLocation::new(source.clone(), 0),
+        "fn main() {}\n",
+    ))
+    .unwrap();
+
+    assert_eq!(
+        set.to_unified_diff(),
+        "--- a/src/main.lm\n+++ b/src/main.lm\n@@ -0,0 +1 @@\n+fn main() {}\n"
+    );
+}
+
+#[test]
+fn label_suggestions_use_diagnostic_source() {
+    let source = source("src/main.lm", "let b = a.invok();\n");
+
+    let diagnostic = SimpleDiagnostic::new("failed to compile")
+        .add_cause(
+            SimpleDiagnostic::new("could not find method")
+                .with_label(Label::error(None, 10..15, "could not find method 'invok'").with_suggestion("invoke")),
+        )
+        .with_source(source.clone());
+
+    let set = SuggestionSet::from_diagnostics([&diagnostic as &dyn Diagnostic]);
+
+    assert_eq!(set.apply(&source).unwrap(), "let b = a.invoke();\n");
+}
+
+#[test]
+fn only_first_alternative_is_applied() {
+    let source = source("src/main.lm", "fn foo() -> Int {\n    return 1.5;\n}\n");
+
+    let diagnostic = SimpleDiagnostic::new("mismatched types")
+        .with_help(
+            Help::new("add a cast to `Int`")
+                .as_alternative()
+                .with_suggestion(Suggestion::insert(SourceLocation::new(source.clone(), 32), " as Int")),
+        )
+        .with_help(
+            Help::new("change the return type to `Float`")
+                .as_alternative()
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 12..15), "Float")),
+        )
+        .with_help(
+            Help::new("remove the semicolon")
+                .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 32..33))),
+        );
+
+    let set = SuggestionSet::from_diagnostics([&diagnostic as &dyn Diagnostic]);
+
+    assert!(set.conflicts().is_empty());
+    assert_eq!(
+        set.apply(&source).unwrap(),
+        "fn foo() -> Int {\n    return 1.5 as Int\n}\n"
+    );
+}
+
+#[test]
+fn highest_priority_alternative_is_applied() {
+    let source = source("src/main.lm", "fn foo() -> Int {\n    return 1.5;\n}\n");
+
+    let diagnostic = SimpleDiagnostic::new("mismatched types")
+        .with_help(
+            Help::new("add a cast to `Int`")
+                .as_alternative()
+                .with_suggestion(Suggestion::insert(SourceLocation::new(source.clone(), 32), " as Int")),
+        )
+        .with_help(
+            Help::new("change the return type to `Float`")
+                .as_alternative()
+                .with_priority(1)
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 12..15), "Float")),
+        );
+
+    let set = SuggestionSet::from_diagnostics([&diagnostic as &dyn Diagnostic]);
+
+    assert_eq!(set.apply(&source).unwrap(), "fn foo() -> Float {\n    return 1.5;\n}\n");
+}
+
+#[test]
+fn duplicate_suggestions_are_merged() {
+    let source = source("src/main.lm", "return (0);");
+
+    let first = SimpleDiagnostic::new("unnecessary parenthesis")
+        .with_help(Help::new("remove it").with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 7..8))));
+
+    let second = SimpleDiagnostic::new("unnecessary parenthesis")
+        .with_help(Help::new("remove it").with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 7..8))));
+
+    let set = SuggestionSet::from_diagnostics([&first as &dyn Diagnostic, &second as &dyn Diagnostic]);
+
+    assert_eq!(set.suggestions().count(), 1);
+    assert!(set.conflicts().is_empty());
+    assert_eq!(set.apply(&source).unwrap(), "return 0);");
+}
+
+#[test]
+fn overlapping_suggestions_conflict() {
+    let source = source("src/main.lm", "return (0);");
+
+    let existing = Suggestion::replace(SourceRange::new(source.clone(), 7..10), "1");
+    let overlapping = Suggestion::delete(SourceRange::new(source.clone(), 9..10));
+    let inside = Suggestion::insert(SourceLocation::new(source.clone(), 8), "2");
+
+    let mut set = SuggestionSet::new();
+    set.add(existing.clone()).unwrap();
+
+    assert_eq!(
+        set.add(overlapping.clone()),
+        Err(SuggestionConflict {
+            suggestion: overlapping,
+            existing: existing.clone(),
+        })
+    );
+
+    assert!(set.add(inside).is_err());
+    assert_eq!(set.conflicts().len(), 2);
+    assert_eq!(
+        set.conflicts()[0].to_string(),
+        "suggestion to delete 9..10 overlaps with suggestion to replace with `1`"
+    );
+
+    assert_eq!(set.apply(&source).unwrap(), "return 1;");
+}
+
+#[test]
+fn insertions_at_same_offset_conflict() {
+    let source = source("src/main.lm", "let a;");
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::insert(SourceLocation::new(source.clone(), 5), ": Int"))
+        .unwrap();
+
+    assert!(set
+        .add(Suggestion::insert(SourceLocation::new(source.clone(), 5), " = 0"))
+        .is_err());
+
+    // Edits which only touch each other can be applied together.
+    set.add(Suggestion::replace(SourceRange::new(source.clone(), 4..5), "b"))
+        .unwrap();
+    set.add(Suggestion::replace(SourceRange::new(source.clone(), 5..6), " = 0;"))
+        .unwrap();
+
+    assert_eq!(set.apply(&source).unwrap(), "let b: Int = 0;");
+}
+
+#[test]
+fn sources_with_same_name_and_content_are_grouped() {
+    let first = source("src/main.lm", "return (0);");
+    let second = source("src/main.lm", "return (0);");
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::delete(SourceRange::new(first.clone(), 7..8)))
+        .unwrap();
+    set.add(Suggestion::delete(SourceRange::new(second.clone(), 9..10)))
+        .unwrap();
+
+    assert_eq!(set.apply(&first).unwrap(), "return 0;");
+    assert_eq!(set.apply(&second).unwrap(), "return 0;");
+}
+
+#[test]
+fn fix_report_summarizes_files() {
+    let main = source("src/main.lm", "return (fals);\n");
+    let lib = source("src/lib.lm", "let a = 1\n");
+
+    let mut set = SuggestionSet::new();
+    set.add(Suggestion::delete(SourceRange::new(main.clone(), 7..8)))
+        .unwrap();
+    set.add(Suggestion::replace(SourceRange::new(main.clone(), 8..12), "false"))
+        .unwrap();
+    set.add(Suggestion::delete(SourceRange::new(main.clone(), 12..13)))
+        .unwrap();
+    set.add(Suggestion::insert(SourceLocation::new(lib.clone(), 9), ";"))
+        .unwrap();
+    set.add(Suggestion::create_file("src/util.lm", "")).unwrap();
+    let _ = set.add(Suggestion::replace(SourceRange::new(main, 7..13), "false"));
+
+    let report = FixReport::new(&set);
+
+    assert_eq!(report.total(), 5);
+    assert_eq!(report.skipped(), 1);
+    assert_eq!(
+        report.files(),
+        [
+            FixedFile {
+                path: String::from("src/main.lm"),
+                fixes: 3
+            },
+            FixedFile {
+                path: String::from("src/lib.lm"),
+                fixes: 1
+            }
+        ]
+    );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    assert_snapshot!(renderer.render(&report).unwrap());
+}
+
+#[test]
+fn fix_report_without_fixes() {
+    let report = FixReport::new(&SuggestionSet::new());
+
+    assert_eq!(report.total(), 0);
+    assert_eq!(report.message(), "no fixes were applied");
+    assert_eq!(report.related().count(), 0);
+}
@@ -1,10 +1,19 @@
-use error_snippet::{DiagnosticHandler, Handler, Renderer, SimpleDiagnostic};
+use std::sync::{Arc, Mutex};
+
+use error_snippet::{
+    channel_handler, BatchOptions, BufferedDiagnosticHandler, CodeAggregation, CollectingHandler, Diagnostic,
+    DiagnosticEvent, DiagnosticHandler, DiagnosticKey, DrainError, DrainResult, DrainSeparator, DrainSummary,
+    ExitCodes, ExitStatus, GraphicalRenderer, Handler, Label, LintLevel, NamedSource, NullHandler, RenderMetrics,
+    Renderer, Severity, SeverityOverrides, SimpleDiagnostic, Tag, WithSource, TRUNCATION_MARKER,
+};
+#[cfg(feature = "terminal")]
+use error_snippet::{WatchHandler, WatchRefresh};
 
 pub struct StubRenderer;
 
 impl Renderer for StubRenderer {
     fn render_fmt(
-        &mut self,
+        &self,
         _f: &mut error_snippet::Formatter,
         _diagnostic: &dyn error_snippet::Diagnostic,
     ) -> std::fmt::Result {
@@ -23,3 +32,1095 @@ fn drain_removes_all() {
     let _ = handler.drain();
     assert_eq!(handler.count(), 0);
 }
+
+#[test]
+fn overrides_allow_discards() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.set_severity_overrides(SeverityOverrides::new().allow("W0001"));
+
+    handler.report(SimpleDiagnostic::new("foo").with_code("W0001").into());
+    handler.report(SimpleDiagnostic::new("bar").with_code("W0002").into());
+
+    assert_eq!(handler.count(), 1);
+}
+
+#[test]
+fn buffered_overrides_apply() {
+    let mut handler = error_snippet::BufferedDiagnosticHandler::with_renderer(0, Box::new(StubRenderer));
+    handler.set_severity_overrides(SeverityOverrides::new().allow("W0001").deny("W0002"));
+
+    handler.report(SimpleDiagnostic::new("foo").with_code("W0001").into());
+    handler.report(
+        SimpleDiagnostic::new("bar")
+            .with_code("W0002")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+
+    let severities = handler.emitted().map(|d| d.severity()).collect::<Vec<_>>();
+
+    assert_eq!(severities, vec![Severity::Error]);
+}
+
+#[test]
+fn overrides_change_severity() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.set_severity_overrides(SeverityOverrides::new().deny("W0001").warn("E0001"));
+
+    handler.report(
+        SimpleDiagnostic::new("foo")
+            .with_code("W0001")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+    handler.report(SimpleDiagnostic::new("bar").with_code("E0001").into());
+    handler.report(SimpleDiagnostic::new("baz").into());
+
+    let severities = handler.emitted().map(|d| d.severity()).collect::<Vec<_>>();
+
+    assert_eq!(severities, vec![Severity::Error, Severity::Warning, Severity::Error]);
+}
+
+#[test]
+fn overrides_deny_keeps_fatal() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.set_severity_overrides(SeverityOverrides::new().deny("E0001"));
+
+    handler.report(
+        SimpleDiagnostic::new("foo")
+            .with_code("E0001")
+            .with_severity(Severity::Fatal)
+            .into(),
+    );
+
+    assert_eq!(handler.emitted().next().unwrap().severity(), Severity::Fatal);
+}
+
+#[test]
+fn report_with_severity_overrides() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.exit_on_error();
+
+    handler.report_with_severity(
+        SimpleDiagnostic::new("foo").with_severity(Severity::Warning).into(),
+        Severity::Error,
+    );
+    handler.report_with_severity(SimpleDiagnostic::new("bar").into(), Severity::Note);
+
+    assert!(matches!(handler.drain(), Err(DrainError::CompoundError(1))));
+}
+
+#[test]
+fn emitted_downcast() {
+    let mut handler = DiagnosticHandler::with_renderer(Box::new(StubRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(Box::new(OwnedDiagnostic {
+        message: "bar",
+        team: "parser",
+    }));
+
+    let emitted = handler.emitted().collect::<Vec<_>>();
+
+    assert!(emitted[0].is::<SimpleDiagnostic>());
+    assert!(emitted[0].downcast_ref::<OwnedDiagnostic>().is_none());
+    assert_eq!(emitted[1].downcast_ref::<OwnedDiagnostic>().unwrap().team, "parser");
+
+    let mut error: error_snippet::Error = SimpleDiagnostic::new("baz").into();
+    error.downcast_mut::<SimpleDiagnostic>().unwrap().message = String::from("qux");
+
+    let error = error.downcast::<SimpleDiagnostic>().unwrap();
+    assert_eq!(error.message, "qux");
+}
+
+#[test]
+fn channel_sends_events() {
+    let (mut handler, receiver) = channel_handler();
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").with_severity(Severity::Warning).into());
+    let _ = handler.drain();
+
+    drop(handler);
+
+    let events = receiver.iter().collect::<Vec<_>>();
+
+    assert_eq!(events.len(), 5);
+    assert_eq!(
+        events[4],
+        DiagnosticEvent::Summary {
+            errors: 1,
+            warnings: 1,
+            total: 2
+        }
+    );
+}
+
+pub struct FailingRenderer;
+
+impl Renderer for FailingRenderer {
+    fn render_fmt(&self, _f: &mut error_snippet::Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        match diagnostic.message().as_str() {
+            "bar" => Err(std::fmt::Error),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn channel_keeps_undrained_on_render_error() {
+    let (mut handler, receiver) = channel_handler();
+    handler.set_renderer(Box::new(FailingRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+    handler.report(SimpleDiagnostic::new("baz").into());
+
+    assert!(handler.drain().is_err());
+    assert_eq!(handler.count(), 2);
+
+    drop(handler);
+
+    let events = receiver.iter().collect::<Vec<_>>();
+
+    assert_eq!(
+        events.last(),
+        Some(&DiagnosticEvent::Summary {
+            errors: 1,
+            warnings: 0,
+            total: 1
+        })
+    );
+}
+
+#[test]
+fn buffered_keeps_undrained_on_render_error() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(FailingRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+    handler.report(SimpleDiagnostic::new("baz").into());
+
+    assert!(handler.drain().is_err());
+
+    let remaining = handler.emitted().map(|d| d.message()).collect::<Vec<_>>();
+    assert_eq!(remaining, vec!["bar", "baz"]);
+}
+
+#[test]
+fn buffered_groups_by_stage() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.group_by_stage();
+
+    handler.report_with_stage(SimpleDiagnostic::new("foo").into(), "parse");
+    handler.report_with_stage(SimpleDiagnostic::new("bar").into(), "typeck");
+    handler.report_with_stage(SimpleDiagnostic::new("baz").into(), "parse");
+    handler.drain().unwrap();
+
+    assert_eq!(
+        handler.buffer(),
+        "parse (2 diagnostics)\nfoo: \nbaz: \ntypeck (1 diagnostic)\nbar: \n"
+    );
+}
+
+#[derive(Debug)]
+struct OwnedDiagnostic {
+    message: &'static str,
+    team: &'static str,
+}
+
+impl Diagnostic for OwnedDiagnostic {
+    fn message(&self) -> String {
+        self.message.to_string()
+    }
+
+    fn group_key(&self) -> Option<String> {
+        Some(self.team.to_string())
+    }
+}
+
+#[test]
+fn drain_groups_by_key() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, renderer);
+    handler.group_by_key();
+
+    handler.report(Box::new(OwnedDiagnostic {
+        message: "foo",
+        team: "parser",
+    }));
+    handler.report(SimpleDiagnostic::new("bar").into());
+    handler.report(Box::new(OwnedDiagnostic {
+        message: "baz",
+        team: "parser",
+    }));
+    let _ = handler.drain();
+
+    assert_eq!(handler.buffer(), "parser (2 diagnostics)\nungrouped (1 diagnostic)\n");
+}
+
+#[test]
+fn report_with_stage_metadata() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+
+    handler.report_with_stage(SimpleDiagnostic::new("foo").into(), "parse");
+    handler.report(SimpleDiagnostic::new("bar").into());
+    let _ = handler.drain();
+
+    handler.report_with_stage(SimpleDiagnostic::new("baz").into(), "typeck");
+
+    let emitted = handler.emitted_with_metadata().collect::<Vec<_>>();
+
+    assert_eq!(emitted.len(), 1);
+    assert_eq!(emitted[0].message(), "baz");
+    assert_eq!(emitted[0].metadata.sequence, 2);
+    assert_eq!(emitted[0].metadata.stage.as_deref(), Some("typeck"));
+}
+
+pub struct GutterRenderer;
+
+impl Renderer for GutterRenderer {
+    fn render_fmt(&self, _f: &mut error_snippet::Formatter, _diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        Ok(())
+    }
+
+    fn render_fmt_with(
+        &self,
+        f: &mut error_snippet::Formatter,
+        _diagnostic: &dyn Diagnostic,
+        options: &BatchOptions,
+    ) -> std::fmt::Result {
+        std::fmt::Write::write_fmt(f, format_args!("{}\n", options.line_number_width))
+    }
+}
+
+#[test]
+fn drain_aligns_gutters() {
+    let short = Arc::new(NamedSource::new("short.lm", "let a = 1;"));
+    let long = Arc::new(NamedSource::new("long.lm", "\n".repeat(1200)));
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(GutterRenderer));
+    handler.report(
+        SimpleDiagnostic::new("foo")
+            .with_label(Label::new(Some(short.clone()), 0..1, ""))
+            .into(),
+    );
+    let _ = handler.drain();
+
+    handler.align_gutters();
+    handler.report(
+        SimpleDiagnostic::new("foo")
+            .with_label(Label::new(Some(short), 0..1, ""))
+            .into(),
+    );
+    handler.report(
+        SimpleDiagnostic::new("bar")
+            .add_related(SimpleDiagnostic::new("baz").with_label(Label::new(Some(long), 0..1, "")))
+            .into(),
+    );
+    let _ = handler.drain();
+
+    assert_eq!(handler.buffer(), "0\n4\n4\n");
+}
+
+#[test]
+fn count_by_severity() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.set_severity_overrides(SeverityOverrides::new().deny("W0001"));
+
+    handler.report(SimpleDiagnostic::new("foo").with_severity(Severity::Warning).into());
+    handler.report(SimpleDiagnostic::new("bar").with_severity(Severity::Warning).into());
+    assert_eq!(handler.error_count(), 0);
+    assert_eq!(handler.warning_count(), 2);
+    assert!(!handler.has_errors());
+
+    handler.report(
+        SimpleDiagnostic::new("baz")
+            .with_code("W0001")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+    assert_eq!(handler.error_count(), 1);
+    assert_eq!(handler.warning_count(), 2);
+    assert_eq!(handler.count_by(Severity::Help), 0);
+    assert!(handler.has_errors());
+
+    let _ = handler.drain();
+    assert!(!handler.has_errors());
+}
+
+#[test]
+fn buffered_exit_on_error() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, renderer);
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    assert!(handler.drain().is_ok());
+
+    handler.exit_on_error();
+    handler.report(SimpleDiagnostic::new("foo").with_severity(Severity::Warning).into());
+    assert!(handler.drain().is_ok());
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+    assert!(matches!(handler.drain(), Err(DrainError::CompoundError(2))));
+}
+
+#[test]
+fn buffered_truncates_and_clears() {
+    let renderer = Box::new(GutterRenderer);
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, renderer);
+    handler.set_max_buffer_size(5);
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+    let _ = handler.drain();
+    assert_eq!(handler.buffer(), "0\n0\n");
+
+    handler.report(SimpleDiagnostic::new("baz").into());
+    handler.report(SimpleDiagnostic::new("qux").into());
+    let _ = handler.drain();
+    assert_eq!(handler.buffer(), format!("0\n0\n0{TRUNCATION_MARKER}"));
+
+    handler.clear();
+    assert_eq!(handler.buffer(), "");
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    let _ = handler.drain();
+    assert_eq!(handler.take_buffer(), "0\n");
+    assert_eq!(handler.buffer(), "");
+}
+
+pub struct LabelRenderer;
+
+impl Renderer for LabelRenderer {
+    fn render_fmt(&self, f: &mut error_snippet::Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        let labels = diagnostic
+            .labels()
+            .map(|labels| labels.map(|l| l.message().to_string()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        std::fmt::Write::write_fmt(f, format_args!("{}: {}\n", diagnostic.message(), labels.join(", ")))
+    }
+}
+
+#[test]
+fn drain_merges_identical_spans() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a: Int = b;"));
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.merge_identical_spans();
+
+    handler.report(
+        SimpleDiagnostic::new("mismatched types")
+            .with_label(Label::error(Some(source.clone()), 13..14, "expected `Int`"))
+            .with_label(Label::note(Some(source.clone()), 7..10, "expected due to this"))
+            .into(),
+    );
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(
+        SimpleDiagnostic::new("cannot infer type of `b`")
+            .with_label(Label::error(Some(source.clone()), 13..14, ""))
+            .into(),
+    );
+    handler.report(
+        SimpleDiagnostic::new("unused variable")
+            .with_severity(Severity::Warning)
+            .with_label(Label::warning(Some(source.clone()), 13..14, "unused"))
+            .into(),
+    );
+    handler.report(SimpleDiagnostic::new("foo").into());
+    let _ = handler.drain();
+
+    assert_eq!(
+        handler.buffer(),
+        "mismatched types: expected `Int`, expected due to this, cannot infer type of `b`\nfoo: \nunused variable: unused\nfoo: \n"
+    );
+}
+
+#[test]
+fn drain_merges_keep_labels_of_different_sources() {
+    let main = Arc::new(NamedSource::new("src/main.lm", "let a: Int = b;"));
+    let first = Arc::new(NamedSource::new("src/first.lm", "fn b() {}"));
+    let second = Arc::new(NamedSource::new("src/second.lm", "fn b() {}"));
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.merge_identical_spans();
+
+    handler.report(
+        SimpleDiagnostic::new("ambiguous name `b`")
+            .with_label(Label::error(Some(main.clone()), 13..14, "ambiguous"))
+            .with_label(Label::note(Some(first.clone()), 3..4, "defined here"))
+            .into(),
+    );
+    handler.report(
+        SimpleDiagnostic::new("ambiguous name `b`")
+            .with_label(Label::error(Some(main.clone()), 13..14, "ambiguous"))
+            .with_label(Label::note(Some(second.clone()), 3..4, "defined here"))
+            .into(),
+    );
+    let _ = handler.drain();
+
+    assert_eq!(
+        handler.buffer(),
+        "ambiguous name `b`: ambiguous, defined here, defined here\n"
+    );
+}
+
+#[test]
+fn drain_iter_streams_grouped() {
+    let renderer = Box::new(LabelRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.group_by_stage();
+
+    handler.report_with_stage(SimpleDiagnostic::new("foo").into(), "parse");
+    handler.report_with_stage(
+        SimpleDiagnostic::new("bar").with_severity(Severity::Warning).into(),
+        "typeck",
+    );
+    handler.report_with_stage(SimpleDiagnostic::new("baz").into(), "parse");
+
+    let drained = handler.drain_iter();
+    assert_eq!(drained.len(), 3);
+
+    let drained = drained.map(Result::unwrap).collect::<Vec<_>>();
+
+    assert_eq!(
+        drained.iter().map(|d| d.rendered.as_str()).collect::<Vec<_>>(),
+        vec![
+            "parse (2 diagnostics)\nfoo: \n",
+            "baz: \n",
+            "typeck (1 diagnostic)\nbar: \n"
+        ]
+    );
+    assert_eq!(
+        drained.iter().map(|d| d.severity).collect::<Vec<_>>(),
+        vec![Severity::Error, Severity::Error, Severity::Warning]
+    );
+    assert_eq!(drained[2].metadata.sequence, 1);
+    assert_eq!(handler.count(), 0);
+}
+
+#[test]
+fn drain_aborts_on_fatal() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").with_severity(Severity::Fatal).into());
+    handler.report(SimpleDiagnostic::new("baz").into());
+
+    assert!(matches!(handler.drain(), Err(DrainError::Fatal)));
+    assert_eq!(handler.buffer(), "foo: \nbar: \n");
+}
+
+#[test]
+fn drain_iter_aborts_on_fatal() {
+    let mut handler = DiagnosticHandler::with_renderer(Box::new(LabelRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").with_severity(Severity::Bug).into());
+    handler.report(SimpleDiagnostic::new("bar").with_severity(Severity::Fatal).into());
+    handler.report(SimpleDiagnostic::new("baz").into());
+
+    assert_eq!(handler.error_count(), 3);
+
+    let drained = handler.drain_iter();
+    assert_eq!(drained.len(), 3);
+
+    let drained = drained.collect::<Vec<_>>();
+
+    assert_eq!(drained.len(), 3);
+    assert_eq!(drained[0].as_ref().unwrap().rendered, "foo: \n");
+    assert_eq!(drained[1].as_ref().unwrap().rendered, "bar: \n");
+    assert!(matches!(drained[2], Err(DrainError::Fatal)));
+}
+
+#[test]
+fn default_source_attached_on_report() {
+    let default = Arc::new(NamedSource::new("<repl>", "let a = b;"));
+    let other = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer)).with_default_source(default);
+
+    handler.report(
+        SimpleDiagnostic::new("foo")
+            .with_label(Label::error(None, 8..9, ""))
+            .into(),
+    );
+    handler.report(
+        SimpleDiagnostic::new("bar")
+            .with_label(Label::error(None, 8..9, ""))
+            .with_source(other)
+            .into(),
+    );
+    handler.drain().unwrap();
+
+    assert!(handler.buffer().contains("[<repl>:1:9]"));
+    assert!(handler.buffer().contains("[src/main.lm:1:9]"));
+}
+
+#[test]
+fn drain_reuses_session_across_diagnostics() {
+    let source: Arc<dyn error_snippet::Source> = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() {\n    let a = b;\n    let c = d;\n    let e = f;\n}",
+    ));
+
+    let diagnostics = [24..25, 39..40, 54..55].map(|range| {
+        SimpleDiagnostic::new("cannot find value").with_label(Label::error(Some(source.clone()), range, "not found"))
+    });
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let expected = diagnostics
+        .iter()
+        .map(|d| renderer.render(d).unwrap())
+        .collect::<String>();
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer));
+
+    for diagnostic in diagnostics {
+        handler.report(diagnostic.into());
+    }
+
+    handler.drain().unwrap();
+
+    assert_eq!(handler.buffer(), expected);
+}
+
+#[test]
+fn buffered_separates_diagnostics() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.set_separator(DrainSeparator::Rule(4));
+    handler.group_by_key();
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").with_code("W0001").into());
+    handler.report(SimpleDiagnostic::new("baz").into());
+    handler.drain().unwrap();
+
+    assert_eq!(
+        handler.buffer(),
+        "ungrouped (2 diagnostics)\nfoo: \n────\nbaz: \n────\nW0001 (1 diagnostic)\nbar: \n"
+    );
+}
+
+#[test]
+fn drain_iter_separates_diagnostics() {
+    let mut handler = DiagnosticHandler::with_renderer(Box::new(LabelRenderer));
+    handler.set_separator(DrainSeparator::BlankLines(2));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+
+    let drained = handler.drain_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+    assert_eq!(
+        drained.iter().map(|d| d.rendered.as_str()).collect::<Vec<_>>(),
+        vec!["foo: \n", "\n\nbar: \n"]
+    );
+}
+
+#[test]
+fn drain_aggregates_by_code() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.aggregate_by_code(CodeAggregation::new(2).with_hint("rerun with --verbose to see all"));
+
+    for idx in 0..5 {
+        let warning = SimpleDiagnostic::new(format!("unused variable `v{idx}`"))
+            .with_code("W0042")
+            .with_severity(Severity::Warning);
+
+        handler.report(warning.into());
+    }
+
+    handler.report(
+        SimpleDiagnostic::new("unused import")
+            .with_code("W0043")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+    handler.report(SimpleDiagnostic::new("mismatched types").with_code("W0042").into());
+    handler.drain().unwrap();
+
+    assert_eq!(
+        handler.buffer(),
+        concat!(
+            "unused variable `v0`: \n",
+            "unused variable `v1`: \n",
+            "unused variable `v0` — 5 occurrences, rerun with --verbose to see all: \n",
+            "unused import: \n",
+            "mismatched types: \n",
+        )
+    );
+}
+
+#[test]
+fn drain_iter_aggregates_by_code() {
+    let mut handler = DiagnosticHandler::with_renderer(Box::new(LabelRenderer));
+    handler.aggregate_by_code(CodeAggregation::new(0));
+
+    handler.report(
+        SimpleDiagnostic::new("foo")
+            .with_code("W0001")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+    handler.report(
+        SimpleDiagnostic::new("bar")
+            .with_code("W0001")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+    handler.report(SimpleDiagnostic::new("baz").with_severity(Severity::Warning).into());
+
+    assert_eq!(handler.count_by_code("W0001"), 2);
+
+    let drained = handler.drain_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+    assert_eq!(
+        drained.iter().map(|d| d.rendered.as_str()).collect::<Vec<_>>(),
+        vec!["foo — 2 occurrences: \n", "baz: \n"]
+    );
+    assert_eq!(drained[0].metadata.sequence, 0);
+}
+
+#[test]
+fn buffered_deduplicates_by_key() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.deduplicate();
+
+    for range in [8..9, 8..9, 4..5] {
+        let diagnostic = SimpleDiagnostic::new("cannot find value")
+            .with_code("E0425")
+            .with_label(Label::error(Some(source.clone()), range, "not found"));
+
+        handler.report(diagnostic.into());
+    }
+
+    handler.report(SimpleDiagnostic::new("cannot find value").with_code("E0425").into());
+    handler.report(
+        SimpleDiagnostic::new("cannot find value")
+            .with_code("E0425")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+    handler.drain().unwrap();
+
+    assert_eq!(
+        handler.buffer(),
+        "cannot find value: not found\ncannot find value: not found\ncannot find value: \ncannot find value: \n"
+    );
+}
+
+#[test]
+fn boxed_diagnostics_compare_by_key() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+
+    let first: error_snippet::Error = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(Some(source.clone()), 8..9, "expected `Int`"))
+        .into();
+
+    let second: error_snippet::Error = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(Some(source.clone()), 4..5, "expected `Int`"))
+        .into();
+
+    let third: error_snippet::Error = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(
+            Some(source.clone()),
+            8..9,
+            "expected `Int`, found `String`",
+        ))
+        .into();
+
+    assert!(first != second);
+    assert!(first == third);
+    assert_eq!(
+        DiagnosticKey::of(first.as_ref()),
+        DiagnosticKey {
+            code: None,
+            message: String::from("mismatched types"),
+            severity: Severity::Error,
+            source: Some(String::from("src/main.lm")),
+            span: Some(8..9),
+        }
+    );
+}
+
+#[test]
+fn collecting_captures_nested() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a: Int = b;"));
+
+    let mut handler = CollectingHandler::new();
+    handler.report(
+        SimpleDiagnostic::new("failed to type-check")
+            .with_severity(Severity::Warning)
+            .with_help("remove the annotation")
+            .add_cause(
+                SimpleDiagnostic::new("mismatched types")
+                    .with_code("E0308")
+                    .with_label(Label::error(None, 13..14, "expected `Int`")),
+            )
+            .with_source(source)
+            .into(),
+    );
+
+    let captured = &handler.diagnostics()[0];
+    assert_eq!(captured.help, vec!["remove the annotation"]);
+    assert_eq!(captured.causes[0].labels[0].file.as_deref(), Some("src/main.lm"));
+
+    handler.assert_has_code("E0308");
+    handler.assert_has_error_code("E0308");
+    handler.assert_label_at("src/main.lm", 13..14);
+    handler.assert_no_errors();
+}
+
+#[test]
+#[should_panic(expected = "expected an error with code `E0308`, found:\n  warning[E0308]: mismatched types")]
+fn collecting_assert_error_code_fails() {
+    let mut handler = CollectingHandler::new();
+    handler.report(
+        SimpleDiagnostic::new("mismatched types")
+            .with_code("E0308")
+            .with_severity(Severity::Warning)
+            .into(),
+    );
+
+    handler.assert_has_error_code("E0308");
+}
+
+#[test]
+#[should_panic(expected = "expected a label at src/main.lm:0..3, found:\n  src/main.lm:4..5: unused")]
+fn collecting_assert_label_fails() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = 1;"));
+
+    let mut handler = CollectingHandler::new();
+    handler.report(
+        SimpleDiagnostic::new("unused variable")
+            .with_label(Label::warning(Some(source), 4..5, "unused"))
+            .into(),
+    );
+
+    handler.assert_label_at("src/main.lm", 0..3);
+}
+
+#[test]
+fn collecting_drain_and_replay() {
+    let mut handler = CollectingHandler::new();
+    handler.exit_on_error();
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    assert!(matches!(handler.drain(), Err(DrainError::CompoundError(1))));
+    assert!(handler.drain().is_ok());
+
+    handler.report(SimpleDiagnostic::new("bar").with_severity(Severity::Warning).into());
+    assert!(handler.drain().is_ok());
+    assert_eq!(handler.count(), 2);
+
+    let mut buffered = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.replay(&mut buffered);
+    buffered.drain().unwrap();
+
+    assert_eq!(handler.count(), 0);
+    assert_eq!(buffered.buffer(), "foo: \nbar: \n");
+}
+
+#[test]
+fn null_handler_discards() {
+    let mut handler = NullHandler::new();
+
+    handler.report(SimpleDiagnostic::new("foo").with_severity(Severity::Fatal).into());
+    handler.report_with_severity(SimpleDiagnostic::new("bar").into(), Severity::Error);
+
+    assert!(handler.drain().is_ok());
+    assert_eq!(handler.count(), 2);
+}
+
+#[test]
+fn boxed_handler_across_threads() {
+    let mut handler: Box<dyn Handler + Send> = Box::new(NullHandler::new());
+
+    let handler = std::thread::spawn(move || {
+        handler.report(SimpleDiagnostic::new("foo").into());
+        handler
+    })
+    .join()
+    .unwrap();
+
+    fn report_twice(mut handler: impl Handler) -> DrainResult {
+        handler.report(SimpleDiagnostic::new("foo").into());
+        handler.report_and_drain(SimpleDiagnostic::new("bar").into())
+    }
+
+    let mut handler = handler;
+    assert!(report_twice(&mut handler).is_ok());
+    assert!(report_twice(handler).is_ok());
+}
+
+#[test]
+fn mutable_reference_handler() {
+    let mut handler = CollectingHandler::new();
+
+    fn report(mut handler: impl Handler) {
+        handler.report_with_severity(SimpleDiagnostic::new("foo").into(), Severity::Warning);
+    }
+
+    report(&mut handler);
+    report(&mut handler as &mut dyn Handler);
+
+    assert_eq!(handler.count(), 2);
+    assert_eq!(handler.diagnostics()[0].severity, Severity::Warning);
+}
+
+#[test]
+fn drain_summary_counts() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(StubRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").with_severity(Severity::Bug).into());
+    handler.report(SimpleDiagnostic::new("baz").with_severity(Severity::Warning).into());
+    handler.report(SimpleDiagnostic::new("qux").with_severity(Severity::Note).into());
+
+    assert_eq!(
+        handler.drain().unwrap(),
+        DrainSummary {
+            errors: 2,
+            warnings: 1,
+            total: 4
+        }
+    );
+    assert_eq!(handler.drain().unwrap(), DrainSummary::default());
+}
+
+#[test]
+fn exit_status_from_drain() {
+    let mut handler = CollectingHandler::new();
+    assert_eq!(ExitStatus::from_drain(&handler.drain()), ExitStatus::Success);
+
+    handler.report(SimpleDiagnostic::new("foo").with_severity(Severity::Warning).into());
+    let status = ExitStatus::from_drain(&handler.drain());
+
+    assert_eq!(status, ExitStatus::Warnings);
+    assert_eq!(status.code(), 0);
+    assert_eq!(
+        status.code_with(&ExitCodes {
+            warnings: 3,
+            ..ExitCodes::default()
+        }),
+        3
+    );
+
+    handler.report(SimpleDiagnostic::new("bar").into());
+    assert_eq!(ExitStatus::from_drain(&handler.drain()).code(), 1);
+
+    handler.exit_on_error();
+    handler.report(SimpleDiagnostic::new("baz").into());
+    assert_eq!(ExitStatus::from_drain(&handler.drain()), ExitStatus::Errors);
+
+    handler.report(SimpleDiagnostic::new("qux").with_severity(Severity::Fatal).into());
+    assert_eq!(ExitStatus::from_drain(&handler.drain()), ExitStatus::Errors);
+
+    assert_eq!(ExitStatus::Usage.code(), 2);
+}
+
+#[test]
+#[cfg(feature = "terminal")]
+fn watch_clears_and_skips_identical() {
+    let mut handler = WatchHandler::with_output(Box::new(LabelRenderer), Vec::new());
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+    assert_eq!(handler.drain().unwrap().errors, 2);
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.report(SimpleDiagnostic::new("bar").into());
+    assert_eq!(handler.drain().unwrap().errors, 2);
+
+    handler.report(SimpleDiagnostic::new("bar").into());
+    handler.drain().unwrap();
+
+    let output = String::from_utf8(handler.into_output()).unwrap();
+    assert_eq!(output, "\x1b[2J\x1b[Hfoo: \nbar: \n\x1b[2J\x1b[Hbar: \n");
+}
+
+#[test]
+#[cfg(feature = "terminal")]
+fn watch_coalesces_drains() {
+    let mut handler = WatchHandler::with_output(Box::new(LabelRenderer), Vec::new());
+    handler.set_refresh(WatchRefresh::Separator(DrainSeparator::BlankLines(1)));
+    handler.set_coalesce_window(std::time::Duration::from_secs(3600));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.drain().unwrap();
+    assert!(!handler.is_pending());
+
+    handler.report(SimpleDiagnostic::new("bar").into());
+    handler.drain().unwrap();
+    assert!(handler.is_pending());
+
+    handler.report(SimpleDiagnostic::new("baz").with_severity(Severity::Warning).into());
+    assert_eq!(handler.drain().unwrap().warnings, 1);
+
+    handler.flush().unwrap();
+    assert!(!handler.is_pending());
+
+    let output = String::from_utf8(handler.into_output()).unwrap();
+    assert_eq!(output, "foo: \n\nbaz: \n");
+}
+
+#[derive(Debug)]
+struct WithFooter(Box<dyn Diagnostic>);
+
+impl Diagnostic for WithFooter {
+    fn message(&self) -> String {
+        format!("{} (see --explain)", self.0.message())
+    }
+
+    fn code(&self) -> Option<Box<dyn std::fmt::Display + '_>> {
+        self.0.code()
+    }
+
+    fn severity(&self) -> Severity {
+        self.0.severity()
+    }
+}
+
+#[test]
+fn transformers_applied_in_order() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(LabelRenderer));
+    handler.add_transformer(|diagnostic| Box::new(WithFooter(diagnostic)));
+    handler.add_transformer(|diagnostic| Box::new(WithFooter(diagnostic)));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.drain().unwrap();
+
+    assert_eq!(handler.buffer(), "foo (see --explain) (see --explain): \n");
+}
+
+#[test]
+fn transformers_applied_before_overrides() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.set_severity_overrides(SeverityOverrides::new().allow("W0001"));
+
+    handler.add_transformer(
+        |diagnostic| match diagnostic.code().map(|code| code.to_string()).as_deref() {
+            Some("W0001") => Box::new(SimpleDiagnostic::new(diagnostic.message()).with_code("W0002")),
+            _ => diagnostic,
+        },
+    );
+
+    handler.report(SimpleDiagnostic::new("foo").with_code("W0001").into());
+
+    assert_eq!(handler.count(), 1);
+    assert_eq!(handler.emitted().next().unwrap().message(), "foo");
+}
+
+#[test]
+fn shared_renderer_reconfigured_between_drains() {
+    let renderer = Arc::new(Mutex::new(GraphicalRenderer::new()));
+
+    let mut first = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer.clone()));
+    let mut second = BufferedDiagnosticHandler::with_renderer(0, Box::new(renderer.clone()));
+
+    first.report(SimpleDiagnostic::new("foo").with_severity(Severity::Warning).into());
+    first.drain().unwrap();
+    assert!(first.buffer().contains('\x1b'));
+
+    renderer.lock().unwrap().use_colors = false;
+
+    second.report(SimpleDiagnostic::new("foo").with_severity(Severity::Warning).into());
+    second.drain().unwrap();
+    assert_eq!(second.buffer(), "⚠ warning: foo\n");
+}
+
+#[test]
+fn shared_renderer_forwards_overrides() {
+    struct FixedRenderer;
+
+    impl Renderer for FixedRenderer {
+        fn render_fmt(&self, _f: &mut error_snippet::Formatter, _diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+            Ok(())
+        }
+
+        fn measure(&self, _diagnostic: &dyn Diagnostic) -> Result<RenderMetrics, std::fmt::Error> {
+            Ok(RenderMetrics {
+                lines: 3,
+                max_width: 40,
+            })
+        }
+    }
+
+    let diagnostic = SimpleDiagnostic::new("foo");
+    let expected = RenderMetrics {
+        lines: 3,
+        max_width: 40,
+    };
+
+    assert_eq!(Arc::new(FixedRenderer).measure(&diagnostic).unwrap(), expected);
+    assert_eq!(Mutex::new(FixedRenderer).measure(&diagnostic).unwrap(), expected);
+    assert_eq!(
+        Arc::new(Mutex::new(FixedRenderer)).measure(&diagnostic).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn set_renderer_replaces_renderer() {
+    let mut handler = BufferedDiagnosticHandler::with_renderer(0, Box::new(StubRenderer));
+    handler.set_renderer(Box::new(LabelRenderer));
+
+    handler.report(SimpleDiagnostic::new("foo").into());
+    handler.drain().unwrap();
+
+    assert_eq!(handler.buffer(), "foo: \n");
+}
+
+#[test]
+fn overrides_by_tag() {
+    let renderer = Box::new(StubRenderer);
+    let mut handler = DiagnosticHandler::with_renderer(renderer);
+    handler.set_severity_overrides(
+        SeverityOverrides::new()
+            .with_tag_level(Tag::Unused, LintLevel::Allow)
+            .with_tag_level(Tag::Security, LintLevel::Deny)
+            .warn("W0001"),
+    );
+
+    handler.report(SimpleDiagnostic::new("foo").with_tag(Tag::Unused).into());
+    handler.report(
+        SimpleDiagnostic::new("bar")
+            .with_severity(Severity::Warning)
+            .with_tag(Tag::Security)
+            .into(),
+    );
+    handler.report(
+        SimpleDiagnostic::new("baz")
+            .with_code("W0001")
+            .with_tag(Tag::Unused)
+            .into(),
+    );
+
+    let severities = handler
+        .emitted()
+        .map(|d| (d.message(), d.severity()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        severities,
+        vec![
+            (String::from("bar"), Severity::Error),
+            (String::from("baz"), Severity::Warning)
+        ]
+    );
+}
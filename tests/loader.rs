@@ -0,0 +1,79 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use error_snippet::{
+    CollectingHandler, Diagnostic, Encoding, Error, FileSource, FsLoader, Handler, MemoryLoader, Source, SourceLoader,
+    SourceMap, SourceResolver,
+};
+
+#[test]
+fn memory_loader_decodes_content() {
+    let loader = MemoryLoader::new().with_file("src/main.lm", b"\xFF\xFEl\0e\0t\0".to_vec());
+
+    let source = FileSource::load_with("src/main.lm", &loader).unwrap();
+
+    assert_eq!(
+        Encoding::detect(&loader.load(Path::new("src/main.lm")).unwrap()),
+        Some(Encoding::Utf16Le)
+    );
+    assert_eq!(source.name(), Some("src/main.lm"));
+    assert_eq!(source.text(), "let");
+}
+
+#[test]
+fn fs_loader_reports_missing_file() {
+    let err = FileSource::load_with("does/not/exist.lm", &FsLoader).unwrap_err();
+
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(err.path(), Path::new("does/not/exist.lm"));
+    assert!(err.message().starts_with("failed to load source `does/not/exist.lm`: "));
+}
+
+#[test]
+fn load_error_is_reportable() {
+    let err: Error = FileSource::load_with("src/main.lm", &MemoryLoader::new())
+        .unwrap_err()
+        .into();
+
+    let mut handler = CollectingHandler::new();
+    handler.report(err);
+
+    assert_eq!(
+        handler.reported().next().unwrap().message(),
+        "failed to load source `src/main.lm`: file not found"
+    );
+}
+
+#[test]
+fn source_map_loads_each_file_once() {
+    let loads = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = loads.clone();
+
+    let loader = move |path: &Path| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        Ok(path.to_string_lossy().into_owned().into_bytes())
+    };
+
+    let sources = SourceMap::with_loader(Arc::new(loader));
+
+    let main = sources.load("src/main.lm").unwrap();
+    let lib = sources.load("src/lib.lm").unwrap();
+
+    assert_ne!(main, lib);
+    assert_eq!(sources.load("src/main.lm").unwrap(), main);
+    assert_eq!(loads.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources.id_of("src/lib.lm"), Some(lib));
+    assert_eq!(sources.resolve(lib).unwrap().text(), "src/lib.lm");
+}
+
+#[test]
+fn source_map_does_not_cache_failures() {
+    let sources = SourceMap::with_loader(Arc::new(MemoryLoader::new()));
+
+    assert!(sources.load("src/main.lm").is_err());
+    assert!(sources.is_empty());
+    assert_eq!(sources.id_of("src/main.lm"), None);
+}
@@ -1,9 +1,16 @@
+use std::borrow::Cow;
 use std::sync::Arc;
 
-use error_snippet::{Help, Label, NamedSource, Severity, SimpleDiagnostic, SourceLocation, SourceRange, Suggestion};
+use error_snippet::{
+    display_width, BinarySource, ColumnPolicy, ExpectedOneOf, GraphicalRenderer, GraphicalRendererBuilder, Help, Label,
+    LabelOrder, LabelPosition, NamedSource, RenderHints, Renderer, RendererConfigError, SegmentColor, Severity,
+    SimpleDiagnostic, Source, SourceFingerprint, SourceId, SourceLocation, SourceRange, Strings, Suggestion,
+    SuggestionStyle, Theme, ThemeNames, VirtualSource, WithSource,
+};
 use insta::assert_snapshot;
+use owo_colors::{OwoColorize, Style};
 
-use crate::render;
+use crate::{render, render_with};
 
 #[test]
 fn only_message() {
@@ -19,6 +26,20 @@ fn with_code() {
     assert_snapshot!(render(message));
 }
 
+#[test]
+fn with_severity_bug() {
+    let message = SimpleDiagnostic::new("unexpected panic in type checker").with_severity(Severity::Bug);
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_severity_fatal() {
+    let message = SimpleDiagnostic::new("could not read `src/main.lm`").with_severity(Severity::Fatal);
+
+    assert_snapshot!(render(message));
+}
+
 #[test]
 fn with_severity_error() {
     let message = SimpleDiagnostic::new("mismatched types").with_severity(Severity::Error);
@@ -83,6 +104,43 @@ fn with_related_nested() {
     assert_snapshot!(render(message));
 }
 
+#[test]
+fn with_related_count() {
+    let message = SimpleDiagnostic::new("could not compile")
+        .add_cause(SimpleDiagnostic::new("invalid syntax"))
+        .add_related(SimpleDiagnostic::new("failed to read file"))
+        .add_related(SimpleDiagnostic::new("permission denied"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.show_related_count = true;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_related_collapsed() {
+    let message = SimpleDiagnostic::new("duplicate definitions")
+        .append_related((0..5).map(|i| SimpleDiagnostic::new(format!("definition {i}")).with_severity(Severity::Note)));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.max_related = Some(2);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_min_nested_severity() {
+    let message = SimpleDiagnostic::new("could not compile")
+        .add_cause(SimpleDiagnostic::new("consider a rewrite").with_severity(Severity::Help))
+        .add_related(SimpleDiagnostic::new("failed to read file").with_severity(Severity::Warning))
+        .add_related(SimpleDiagnostic::new("defined here").with_severity(Severity::Note));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.min_nested_severity = Some(Severity::Info);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
 #[test]
 fn with_related_labelled() {
     let source = Arc::new(NamedSource::new(
@@ -161,6 +219,98 @@ fn with_label_multiple() {
     assert_snapshot!(render(message));
 }
 
+#[test]
+fn with_merged_nearby_labels() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;\nlet f = e;\nlet g = f;\nlet h = g;\nlet i = h + a;\nlet j = i;",
+    ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.merge_distance = Some(1);
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::new(Some(source.clone()), 45..50, "expected `Int`"))
+        .with_label(Label::new(Some(source.clone()), 4..5, "declared as `Boolean` here"))
+        .with_label(Label::new(Some(source.clone()), 114..119, "too far away to be merged"));
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_merged_distant_labels() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;\nlet f = e;\nlet g = f;\nlet h = g;\nlet i = h + a;\nlet j = i;",
+    ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.context_lines = 0;
+    renderer.merge_distance = Some(4);
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::new(Some(source.clone()), 45..50, "expected `Int`"))
+        .with_label(Label::new(Some(source.clone()), 30..35, "found `Boolean`"))
+        .with_label(Label::new(Some(source.clone()), 114..119, "merged across four lines"));
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_label_multiline_message() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types").with_label(Label::new(
+        Some(source),
+        15..20,
+        "expected `Int`\n   found `Boolean`",
+    ));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_label_multiple_multiline_message() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(Some(source.clone()), 0..59, "function body"))
+        .with_label(Label::error(
+            Some(source.clone()),
+            27..28,
+            "expected `Int`\n   found `Testing`",
+        ))
+        .with_label(Label::note(
+            Some(source.clone()),
+            35..42,
+            "expected due to this\nand this",
+        ));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_label_multiline_span_multiline_message() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("unknown type").with_label(Label::error(
+        Some(source),
+        0..42,
+        "could not find type 'int'\nwithin the current scope",
+    ));
+
+    assert_snapshot!(render(message));
+}
+
 #[test]
 fn with_label_different_files() {
     let source1 = Arc::new(NamedSource::new(
@@ -322,6 +472,26 @@ fn with_help_suggestion_insert() {
     assert_snapshot!(render(message));
 }
 
+#[test]
+fn with_help_suggestion_file_operations() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        r#"struct Point {}
+
+fn main() {}"#,
+    ));
+
+    let message = SimpleDiagnostic::new("type defined in wrong module").with_help(
+        Help::new("move `Point` into its own module")
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 0..15)))
+            .with_suggestion(Suggestion::create_file("src/point.lm", "struct Point {}"))
+            .with_suggestion(Suggestion::rename_file("src/test.lm", "src/main.lm"))
+            .with_suggestion(Suggestion::delete_file("src/old_point.lm")),
+    );
+
+    assert_snapshot!(render(message));
+}
+
 #[test]
 fn with_help_suggestion_multiple() {
     let source = Arc::new(NamedSource::new(
@@ -360,3 +530,1258 @@ fn with_help_suggestion_different_lines() {
 
     assert_snapshot!(render(message));
 }
+
+#[test]
+fn with_help_suggestion_diff_replace() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        r#"fn foo() -> Boolean {
+    return fals;
+}"#,
+    ));
+
+    let message = SimpleDiagnostic::new("invalid value").with_help(
+        Help::new("did you mean `false`?")
+            .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 33..37), "false")),
+    );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.suggestion_style = SuggestionStyle::Diff;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_help_suggestion_diff_mixed() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        r#"fn foo() -> Boolean {
+    return (0);
+}"#,
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types").with_help(
+        Help::new("remove the parenthesis and cast the value")
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 33..34)))
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 35..36)))
+            .with_suggestion(Suggestion::insert(
+                SourceLocation::new(source.clone(), 35),
+                " as Boolean",
+            )),
+    );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.suggestion_style = SuggestionStyle::Diff;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_help_suggestion_diff_deletion_only() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        r#"fn foo() -> Boolean {
+    return (false);
+}"#,
+    ));
+
+    let message = SimpleDiagnostic::new("unnecessary parenthesis").with_help(
+        Help::new("remove unnecessary parenthesis here")
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 33..34)))
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 39..40))),
+    );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.suggestion_style = SuggestionStyle::Diff;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_virtual_source_expansion() {
+    let file = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "let a = 1;\nlet b = make_vec!(a);\nlet c = a + b;",
+    ));
+
+    let outer = Arc::new(
+        VirtualSource::new("<macro expansion>", "vec_of!(a, a)").with_expansion_site(SourceRange::new(file, 19..31)),
+    );
+
+    let inner = Arc::new(
+        VirtualSource::new("<macro expansion>", "Vec::from([a, a])")
+            .with_expansion_site(SourceRange::new(outer, 0..13)),
+    );
+
+    let message = SimpleDiagnostic::new("mismatched types").with_label(Label::new(
+        Some(inner),
+        11..15,
+        "expected `Int`, found `Array`",
+    ));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_label_tabs_and_wide_chars() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() {\n\tlet 名前 = \"a\" + 1;\n}",
+    ));
+
+    let message =
+        SimpleDiagnostic::new("mismatched types").with_label(Label::new(Some(source), 26..33, "expected `Int`"));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_label_and_suggestion_non_ascii() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "let név = föo(bar);"));
+
+    let message = SimpleDiagnostic::new("unknown function `föo`")
+        .with_label(Label::new(Some(source.clone()), 11..15, "not found in this scope"))
+        .with_help(
+            Help::new("a function with a similar name exists")
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 11..15), "foo")),
+        );
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn shared_between_threads() {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let renderer = Arc::new(renderer);
+
+    let handles = (0..4)
+        .map(|i| {
+            let renderer = renderer.clone();
+
+            std::thread::spawn(move || renderer.render(&SimpleDiagnostic::new(format!("error {i}"))).unwrap())
+        })
+        .collect::<Vec<_>>();
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(handle.join().unwrap(), format!("× error: error {i}\n"));
+    }
+}
+
+#[test]
+fn with_related_compact() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn foo() {}\nfn foo() {}\nfn foo() {}\nfn foo() {}\nfn foo() {}\nfn foo() {}",
+    ));
+
+    let message = SimpleDiagnostic::new("duplicate definitions of `foo`").append_related((0..6).map(|i| {
+        SimpleDiagnostic::new("also defined here")
+            .with_severity(Severity::Note)
+            .with_label(Label::note(
+                Some(source.clone()),
+                (i * 12 + 3)..(i * 12 + 6),
+                "defined here",
+            ))
+    }));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.max_full_related = Some(1);
+    renderer.max_related = Some(4);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_expected_one_of() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "fn foo(a: Int, b: Int} {}"));
+
+    let message = ExpectedOneOf::new([",", ")", ",", ":"], "}").with_location(Some(source), 21..22);
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_expected_one_of_wrapped() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "let a = }"));
+
+    let expected = [
+        "identifier",
+        "integer literal",
+        "float literal",
+        "string literal",
+        "(",
+        "[",
+        "-",
+        "!",
+        "fn",
+        "if",
+        "match",
+        "true",
+        "false",
+    ];
+
+    let message = ExpectedOneOf::new(expected, "}")
+        .with_location(Some(source), 8..9)
+        .with_max_width(60);
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_content_filter() {
+    let source = Arc::new(NamedSource::new(
+        "config.toml",
+        r#"[registry]
+token = "secret-abc123"
+timeout = "30s""#,
+    ));
+
+    let message = SimpleDiagnostic::new("invalid registry config")
+        .with_label(Label::error(Some(source.clone()), 19..34, "token has expired"))
+        .with_help(
+            Help::new("quote the timeout as a number")
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 45..50), "30")),
+        );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.content_filter = Some(Arc::new(|line: &str| line.replace("secret-abc123", "*************")));
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_custom_severity_names() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn foo() -> Boolean {\n    return fals;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("valeur invalide")
+        .with_code("E0001")
+        .with_label(Label::error(Some(source.clone()), 33..37, "valeur inconnue"))
+        .with_help(
+            Help::new("vouliez-vous dire `false` ?\nles valeurs booléennes sont en minuscules")
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 33..37), "false")),
+        );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.theme.names = ThemeNames {
+        error: "erreur".into(),
+        help: "aide".into(),
+        ..ThemeNames::english()
+    };
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_custom_strings() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn foo() -> Boolean {\n    return fals;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("valeur invalide")
+        .with_label(Label::error(Some(source.clone()), 33..37, "valeur inconnue").with_suggestion("false"))
+        .with_help("les valeurs booléennes sont en minuscules")
+        .add_cause(SimpleDiagnostic::new("échec de la vérification de `foo`"))
+        .add_related(SimpleDiagnostic::new("première valeur").with_severity(Severity::Note))
+        .add_related(SimpleDiagnostic::new("deuxième valeur").with_severity(Severity::Note))
+        .add_related(SimpleDiagnostic::new("troisième valeur").with_severity(Severity::Note));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.show_related_count = true;
+    renderer.max_related = Some(1);
+    renderer.theme.names = ThemeNames {
+        error: "erreur".into(),
+        help: "aide".into(),
+        ..ThemeNames::english()
+    };
+    renderer.strings = Strings {
+        help_prefix: "{help} :".into(),
+        label_suggestion: "{help} : vouliez-vous dire `{suggestion}` ?".into(),
+        cause_count: "+{count} causes".into(),
+        cause_count_singular: "+{count} cause".into(),
+        related_count: "+{count} associés".into(),
+        more_related: "… et {count} de plus".into(),
+        in_expansion_of: "dans l'expansion de".into(),
+        unknown_source: "<inconnu>".into(),
+        lines_omitted: "… {count} lignes omises …".into(),
+        spans_lines: "(… s'étend sur {count} lignes)".into(),
+        help_option: "(option {index} sur {count})".into(),
+        secondary_note: "{note} : {message}".into(),
+        stale_source: "{warning} : la source a changé depuis la création de ce diagnostic".into(),
+    };
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_label_suggestion() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    let b = a.invok();\n\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("could not find method").with_label(
+        Label::error(Some(source.clone()), 60..65, "could not find method 'invok'").with_suggestion("invoke"),
+    );
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_label_suggestion_multiline() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    let b = a.invok();\n\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("unknown type")
+        .with_label(Label::error(Some(source.clone()), 0..42, "could not find type 'int'").with_suggestion("Int"));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_label_above_multiline_message() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types").with_label(Label::new(
+        Some(source),
+        15..20,
+        "expected `Int`\n   found `Boolean`",
+    ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.label_position = LabelPosition::Above;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_label_above_multiple_multiline_message() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(Some(source.clone()), 0..59, "function body"))
+        .with_label(Label::error(
+            Some(source.clone()),
+            27..28,
+            "expected `Int`\n   found `Testing`",
+        ))
+        .with_label(Label::note(
+            Some(source.clone()),
+            35..42,
+            "expected due to this\nand this",
+        ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.label_position = LabelPosition::Above;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_label_above_suggestion() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    let b = a.invok();\n\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("could not find method").with_label(
+        Label::error(Some(source.clone()), 60..65, "could not find method 'invok'").with_suggestion("invoke"),
+    );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    renderer.label_position = LabelPosition::Above;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+/// Source which is unavailable through the deprecated `try_content` method,
+/// to ensure it's still honored by the renderer.
+#[derive(Debug)]
+struct UnavailableSource(&'static str);
+
+impl Source for UnavailableSource {
+    fn name(&self) -> Option<&str> {
+        Some(self.0)
+    }
+
+    fn text(&self) -> &str {
+        ""
+    }
+
+    fn try_content(&self) -> Option<Box<&str>> {
+        None
+    }
+}
+
+#[test]
+fn with_unavailable_source() {
+    let source = Arc::new(UnavailableSource("target/generated.lm"));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(
+            Some(source.clone()),
+            81..84,
+            "expected `Int`, found `Testing`",
+        ))
+        .with_label(Label::note(Some(source.clone()), 13..16, "expected due to this"))
+        .with_help(
+            Help::new("convert the value").with_suggestion(Suggestion::replace(SourceRange::new(source, 81..84), "1")),
+        );
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_hint_suppress_snippets() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::error(Some(source.clone()), 8..9, "not found in this scope"))
+        .with_help(
+            Help::new("did you mean `a`?")
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 8..9), "a")),
+        )
+        .add_related(
+            SimpleDiagnostic::new("similar value defined here")
+                .with_severity(Severity::Note)
+                .with_label(Label::note(Some(source), 4..5, "`a` defined here")),
+        )
+        .with_render_hints(RenderHints {
+            suppress_snippets: true,
+            ..RenderHints::default()
+        });
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_hint_compact() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+
+    let message = SimpleDiagnostic::new("failed to compile").add_related(
+        SimpleDiagnostic::new("cannot find value `b`")
+            .with_label(Label::error(Some(source), 8..9, "not found in this scope"))
+            .with_help("did you mean `a`?")
+            .with_render_hints(RenderHints {
+                compact: true,
+                ..RenderHints::default()
+            }),
+    );
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_message_less_labels() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() {\n    let a = foo(b, c);\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(Some(source.clone()), 28..29, ""))
+        .with_label(Label::note(Some(source), 0..40, ""));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_message_less_child_labels() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() {\n    let a = foo(b, c);\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(Some(source.clone()), 0..40, "in this function"))
+        .with_label(Label::note(Some(source.clone()), 24..27, ""))
+        .with_label(Label::note(Some(source), 28..29, "expected `Int`"));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_highlighted_wide_characters() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let 名前 = ünï;"));
+
+    let message = SimpleDiagnostic::new("cannot find value `ünï`")
+        .with_label(Label::error(Some(source.clone()), 4..10, "défined here"))
+        .with_label(Label::error(Some(source), 13..18, "not found"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = true;
+    renderer.highlight_source = true;
+
+    let error = renderer.theme.style.error;
+    let rendered = renderer.render(&message).unwrap();
+
+    let styled = |text: &str| text.chars().map(|c| c.style(error).to_string()).collect::<String>();
+
+    assert!(rendered.contains(&styled("名前")));
+    assert!(rendered.contains(&styled("ünï")));
+    assert!(rendered.contains(&styled(" défined here")));
+    assert!(!rendered.contains(&styled(" =")));
+}
+
+#[test]
+fn with_secondary_note() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn foo() -> Int {\n    return 1;\n}\n\nfn foo() -> Int {\n    return 2;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("duplicate definition of `foo`")
+        .with_label(Label::error(Some(source.clone()), 37..40, "redefined here"))
+        .with_label(Label::secondary_note(Some(source), 3..6, "previous definition here"));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_secondary_note_dimmed() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "fn foo() {}\nfn foo() {}"));
+
+    let message = SimpleDiagnostic::new("duplicate definition of `foo`")
+        .with_label(Label::error(Some(source.clone()), 15..18, "redefined here"))
+        .with_label(Label::secondary_note(Some(source), 3..6, "previous definition here"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = true;
+
+    let note = renderer.theme.style.note.dimmed();
+    let rendered = renderer.render(&message).unwrap();
+
+    let styled = |text: &str| text.chars().map(|c| c.style(note).to_string()).collect::<String>();
+
+    assert!(rendered.contains(&styled("^^^ note: previous definition here")));
+    assert!(!rendered.contains(&styled("redefined here")));
+}
+
+#[test]
+fn with_code_hyperlink() {
+    let message = SimpleDiagnostic::new("mismatched types").with_code("E0308");
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.code_url_template = Some(String::from("https://errors.lume.dev/{code}"));
+
+    let rendered = renderer.render(&message).unwrap();
+
+    assert!(rendered.contains("\x1b]8;;https://errors.lume.dev/E0308\x1b\\"));
+    assert!(rendered.contains("[E0308]\x1b[0m\x1b]8;;\x1b\\"));
+
+    renderer.use_colors = false;
+
+    assert_eq!(renderer.render(&message).unwrap(), "× error[E0308]: mismatched types\n");
+}
+
+#[test]
+fn with_hint_no_color() {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = true;
+
+    let colored = SimpleDiagnostic::new("cannot find value `b`");
+    let uncolored = SimpleDiagnostic::new("cannot find value `b`").with_render_hints(RenderHints {
+        no_color: true,
+        ..RenderHints::default()
+    });
+
+    assert!(renderer.render(&colored).unwrap().contains('\x1b'));
+    assert_eq!(renderer.render(&uncolored).unwrap(), "× error: cannot find value `b`\n");
+}
+
+#[test]
+fn with_binary_source() {
+    let source = Arc::new(BinarySource::new(b"let caf\xE9 = b;").with_name("src/main.lm"));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(
+        Some(source),
+        13..14,
+        "not found in this scope",
+    ));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_source_id_labels() {
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new(NamedSource::new("src/main.lm", "let a = b;")),
+        Arc::new(NamedSource::new("src/lib.lm", "fn c() {}")),
+    ];
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.source_resolver = Some(Arc::new(sources));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::in_source(SourceId(0), 8..9, "not found in this scope"))
+        .with_label(Label::in_source(
+            SourceId(1),
+            3..4,
+            "a function with a similar name exists",
+        ))
+        .with_label(Label::in_source(SourceId(2), 0..1, "unknown source is skipped"));
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_stale_source() {
+    let fingerprint = SourceFingerprint::of(&NamedSource::new("src/main.lm", "let a = b;")).unwrap();
+
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new(NamedSource::new("src/main.lm", "// moved\nlet a = b;")),
+        Arc::new(NamedSource::new("src/lib.lm", "let a = b;")),
+    ];
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.source_resolver = Some(Arc::new(sources));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::in_source(SourceId(0), 8..9, "not found in this scope").with_fingerprint(fingerprint))
+        .with_label(Label::in_source(SourceId(1), 8..9, "unchanged source is rendered").with_fingerprint(fingerprint));
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_container_sources() {
+    let content = "let a = b;";
+
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new(NamedSource::new("src/main.lm", content)),
+        Arc::new(NamedSource::from_parts("src/main.lm", Arc::<str>::from(content))),
+        Arc::new(NamedSource::from_parts("src/main.lm", Cow::Borrowed(content))),
+        Arc::new(NamedSource::from_parts("src/main.lm", content)),
+    ];
+
+    let rendered = sources
+        .into_iter()
+        .map(|source| {
+            render(SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(
+                Some(source),
+                8..9,
+                "not found in this scope",
+            )))
+        })
+        .collect::<Vec<_>>();
+
+    assert!(rendered.windows(2).all(|pair| pair[0] == pair[1]));
+    assert!(rendered[0].contains("src/main.lm:1:9"));
+}
+
+#[test]
+fn with_unnamed_container_sources() {
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new("let a = b;"),
+        Arc::new(String::from("let a = b;")),
+        Arc::new(Cow::<str>::Borrowed("let a = b;")),
+        Arc::new(Arc::<str>::from("let a = b;")),
+        Arc::new(Box::<str>::from("let a = b;")),
+    ];
+
+    for source in sources {
+        assert_eq!(source.name(), None);
+        assert_eq!(source.text(), "let a = b;");
+    }
+}
+
+#[test]
+fn with_source_from_file() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+
+    let named = NamedSource::from_file(&path).unwrap();
+    let binary = BinarySource::from_file(&path).unwrap();
+
+    assert_eq!(named.name(), Some(path.to_string_lossy().as_ref()));
+    assert_eq!(named.name(), binary.name());
+    assert_eq!(named.text(), binary.text());
+}
+
+#[test]
+fn with_syntax_highlighter() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;"));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(
+        Some(source),
+        8..9,
+        "not found in this scope",
+    ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.highlight_source = true;
+    renderer.syntax_highlighter = Some(Arc::new(|name: Option<&str>, line: &str| {
+        assert_eq!(name, Some("src/main.lm"));
+        assert_eq!(line, "let a = b;");
+
+        vec![(0..3, Style::new().magenta()), (8..9, Style::new().cyan())]
+    }));
+
+    let rendered = renderer.render(&message).unwrap();
+    let error_style = renderer.theme.style.error;
+
+    let keyword = "let"
+        .chars()
+        .map(|c| c.style(Style::new().magenta()).to_string())
+        .collect::<String>();
+
+    assert!(rendered.contains(&format!("{keyword} a = ")));
+    assert!(rendered.contains(&"b".style(error_style).to_string()));
+    assert!(!rendered.contains(&"b".style(Style::new().cyan()).to_string()));
+
+    renderer.use_colors = false;
+
+    assert_eq!(renderer.render(&message).unwrap(), render(message));
+}
+
+#[test]
+fn measure_matches_rendered_output() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let 名前 = b;\nlet c = 名前 + 1;"));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::error(Some(source), 13..14, "not found in this scope"))
+        .with_help("a value with a similar name exists");
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let rendered = renderer.render(&message).unwrap();
+
+    for use_colors in [false, true] {
+        let mut renderer = GraphicalRenderer::new();
+        renderer.use_colors = use_colors;
+
+        let metrics = renderer.measure(&message).unwrap();
+
+        assert_eq!(metrics.lines, rendered.lines().count());
+        assert_eq!(
+            metrics.max_width,
+            rendered
+                .lines()
+                .map(|l| display_width(l, ColumnPolicy::default()))
+                .max()
+                .unwrap()
+        );
+    }
+}
+
+#[test]
+fn with_label_severity_inherited_from_parent() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "fn main() -> int {\n    let a = new Testing();\n    return 0;\n}",
+    ));
+
+    let message = SimpleDiagnostic::new("unused variable")
+        .with_severity(Severity::Warning)
+        .with_label(Label::error(Some(source.clone()), 0..59, "function body"))
+        .with_label(Label::new(Some(source.clone()), 27..28, "never used"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = true;
+
+    let error = renderer.theme.style.error;
+    let warning = renderer.theme.style.warning;
+
+    let rendered = renderer.render(&message).unwrap();
+
+    // Both the arrows of the parent label and the underline of the child label use
+    // the severity of the parent label, instead of the severity of the diagnostic.
+    assert!(rendered.contains(&"╭".style(error).to_string()));
+    assert!(rendered.contains(&"^".style(error).to_string()));
+    assert!(!rendered.contains(&"╭".style(warning).to_string()));
+    assert!(!rendered.contains(&"^".style(warning).to_string()));
+}
+
+#[test]
+fn with_max_label_lines() {
+    let content = format!(
+        "fn main() {{\n{}}}\n",
+        (1..=20).map(|i| format!("    let a{i} = {i};\n")).collect::<String>()
+    );
+
+    let source = Arc::new(NamedSource::new("src/main.lm", content.as_str()));
+
+    let message = SimpleDiagnostic::new("function is too long").with_label(Label::warning(
+        Some(source),
+        0..content.len() - 1,
+        "function spans 22 lines",
+    ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.max_label_lines = Some(6);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_max_label_lines_keeps_labelled_lines() {
+    let content = format!(
+        "fn main() {{\n{}}}\n",
+        (1..=20).map(|i| format!("    let a{i} = {i};\n")).collect::<String>()
+    );
+
+    let start = content.find("a10").unwrap();
+
+    let source = Arc::new(NamedSource::new("src/main.lm", content.as_str()));
+
+    let message = SimpleDiagnostic::new("function is too long")
+        .with_label(Label::warning(
+            Some(source.clone()),
+            0..content.len() - 1,
+            "function spans 22 lines",
+        ))
+        .with_label(Label::error(Some(source), start..start + 3, "unused variable"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.max_label_lines = Some(4);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_folded_label() {
+    let content = format!(
+        "fn main() {{\n{}}}\n",
+        (1..=20).map(|i| format!("    let a{i} = {i};\n")).collect::<String>()
+    );
+
+    let start = content.find("a10").unwrap();
+
+    let source = Arc::new(NamedSource::new("src/main.lm", content.as_str()));
+
+    let message = SimpleDiagnostic::new("function is too long")
+        .with_label(Label::warning(
+            Some(source.clone()),
+            0..content.len() - 1,
+            "function is defined here",
+        ))
+        .with_label(Label::error(Some(source), start..start + 3, "unused variable"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.fold_label_lines = Some(8);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_label_below_fold_limit() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() {\n    let a = 1;\n    let b = 2;\n}\n",
+    ));
+
+    let message = SimpleDiagnostic::new("function is too long").with_label(Label::warning(
+        Some(source),
+        0..42,
+        "function is defined here",
+    ));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.fold_label_lines = Some(8);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_excerpt_without_labels() {
+    let source = Arc::new(NamedSource::new(
+        "lume.toml",
+        "[package]\nname = \"lume\"\nversion = \"0.1.0\"\n\n[dependencies]\nstd = \"*\"\n",
+    ));
+
+    let message = SimpleDiagnostic::new("missing field `edition` in package").with_source(source);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.excerpt_lines = Some(3);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_excerpt_and_labels() {
+    let source = Arc::new(NamedSource::new("lume.toml", "[package]\nname = 1\n"));
+
+    let message = SimpleDiagnostic::new("invalid type")
+        .with_label(Label::error(None, 17..18, "expected a string"))
+        .with_source(source);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.excerpt_lines = Some(3);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_end_of_line_label() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = 1\nlet b = a;\n"));
+
+    let message =
+        SimpleDiagnostic::new("expected `;`").with_label(Label::error(Some(source), 9..9, "missing semicolon here"));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_end_of_file_label() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = 1;\n"));
+
+    let message = SimpleDiagnostic::new("unexpected end of file").with_label(Label::error(
+        Some(source),
+        100..100,
+        "expected `}` at end of file",
+    ));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_line_break_label() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = 1\nlet b = a;\n"));
+
+    let message =
+        SimpleDiagnostic::new("expected `;`").with_label(Label::error(Some(source), 9..10, "found line break"));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_empty_label_beside_other_label() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "call(a b)"));
+
+    let message = SimpleDiagnostic::new("expected `,`")
+        .with_label(Label::error(Some(source.clone()), 6..6, "insert `,` here"))
+        .with_label(Label::note(Some(source), 4..5, "first argument"));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.merge_distance = Some(0);
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn without_line_numbers() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        "let a = 1;\nlet b = 2;\nlet c = a + b;\nlet d = c * 2;\nlet e = (d + 3) * 2;",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::new(Some(source.clone()), 15..20, "labelled message 1"))
+        .with_label(Label::new(Some(source.clone()), 30..35, "labelled message 2"))
+        .with_help(
+            Help::new("replace the variable")
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 34..35), "a")),
+        );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.show_line_numbers = false;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn without_line_numbers_diff() {
+    let source = Arc::new(NamedSource::new(
+        "src/test.lm",
+        r#"fn foo() -> Boolean {
+    return (0);
+}"#,
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types").with_help(
+        Help::new("remove the parenthesis and cast the value")
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 33..34)))
+            .with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 35..36)))
+            .with_suggestion(Suggestion::insert(
+                SourceLocation::new(source.clone(), 35),
+                " as Boolean",
+            )),
+    );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.show_line_numbers = false;
+    renderer.suggestion_style = SuggestionStyle::Diff;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_wrapped_help() {
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_help(
+            "the value is cast implicitly, which loses precision when the integer is too large to be represented",
+        )
+        .with_help("first line fits\nbut this continuation line is wider than the configured width of the renderer")
+        .add_related(
+            SimpleDiagnostic::new("nested")
+                .with_help("nested help messages are wrapped with their indentation taken into account"),
+        );
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.width = 40;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_help_suggestion_multiple_files() {
+    let main = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() {\n    let a = foo::bar();\n}",
+    ));
+    let foo = Arc::new(NamedSource::new("src/foo.lm", "fn bar() -> Int {\n    return 0;\n}"));
+
+    let message = SimpleDiagnostic::new("function `bar` is private")
+        .with_label(Label::new(Some(main.clone()), 29..32, "private function"))
+        .with_help(
+            Help::new("the function must be public to be called from another module")
+                .with_title("make `bar` public")
+                .with_suggestion(Suggestion::insert(SourceLocation::new(foo.clone(), 0), "pub "))
+                .with_suggestion(Suggestion::replace(
+                    SourceRange::new(main.clone(), 24..27),
+                    "crate::foo",
+                )),
+        )
+        .with_help(
+            Help::new("alternatively, inline the function")
+                .with_title("inline `bar`")
+                .with_suggestion(Suggestion::replace(SourceRange::new(main.clone(), 24..34), "0")),
+        );
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_help_alternatives() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "fn foo() -> Int {\n    return 1.5;\n}"));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::new(
+            Some(source.clone()),
+            29..32,
+            "expected `Int`, found `Float`",
+        ))
+        .with_help(
+            Help::new("add a cast to `Int`")
+                .as_alternative()
+                .with_suggestion(Suggestion::insert(SourceLocation::new(source.clone(), 32), " as Int")),
+        )
+        .with_help(
+            Help::new("change the return type to `Float`\nwhich keeps the precision of the value")
+                .as_alternative()
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 12..15), "Float")),
+        )
+        .with_help("floating-point values are never truncated implicitly");
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn builder_sets_options() {
+    let renderer = GraphicalRenderer::builder()
+        .use_colors(false)
+        .context_lines(0)
+        .show_line_numbers(false)
+        .max_related(2)
+        .max_full_related(1)
+        .build()
+        .unwrap();
+
+    assert_eq!(renderer.context_lines, 0);
+    assert!(!renderer.show_line_numbers);
+    assert_eq!(renderer.max_related, Some(2));
+    assert_eq!(renderer.max_full_related, Some(1));
+}
+
+#[test]
+fn builder_rejects_invalid_combinations() {
+    let err = |builder: GraphicalRendererBuilder| builder.build().unwrap_err();
+
+    assert_eq!(
+        err(GraphicalRenderer::builder().use_colors(false).highlight_source(true)),
+        RendererConfigError::RequiresColors("highlight_source")
+    );
+
+    assert_eq!(
+        err(GraphicalRenderer::builder().code_url_template("https://errors.lume.dev/")),
+        RendererConfigError::MissingCodePlaceholder(String::from("https://errors.lume.dev/"))
+    );
+
+    assert_eq!(
+        err(GraphicalRenderer::builder().max_related(1).max_full_related(3)),
+        RendererConfigError::RelatedLimits { full: 3, max: 1 }
+    );
+
+    assert_eq!(
+        err(GraphicalRenderer::builder().width(0)),
+        RendererConfigError::ZeroWidth
+    );
+}
+
+#[test]
+fn with_long_line_in_nested_related() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "let value = compute(first_argument, second_argument, third_argument, fourth_argument);\n",
+    ));
+
+    let related = SimpleDiagnostic::new("value is defined here")
+        .with_label(Label::note(None, 4..9, "defined here"))
+        .with_source(source.clone());
+
+    let message = SimpleDiagnostic::new("could not compile")
+        .add_related(SimpleDiagnostic::new("unused variable").add_related(related));
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.width = 40;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_label_beyond_width() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "let value = compute(first_argument, second_argument, third_argument);\n",
+    ));
+
+    let message = SimpleDiagnostic::new("unknown variable")
+        .with_label(Label::error(None, 36..51, "not found in this scope"))
+        .with_source(source);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.width = 40;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_labels_in_display_order() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;\n\n\n\n\nlet c = d;\n"));
+
+    let message = SimpleDiagnostic::new("unknown variables")
+        .with_label(Label::error(None, 23..24, "not found in this scope"))
+        .with_label(Label::note(None, 8..9, "also not found"))
+        .with_source(source);
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_labels_in_span_order() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let a = b;\n\n\n\n\nlet c = d;\n"));
+
+    let message = SimpleDiagnostic::new("unknown variables")
+        .with_label(Label::error(None, 23..24, "not found in this scope"))
+        .with_label(Label::note(None, 8..9, "also not found"))
+        .with_source(source);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.label_order = LabelOrder::Span;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_help_priority() {
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_help("the return type is declared here")
+        .with_help(Help::new("add a cast to `Int`").with_priority(1).as_alternative())
+        .with_help(Help::new("change the return type to `Float`").as_alternative())
+        .with_help(Help::new("remove the return statement").with_priority(-1));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_invisible_characters() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "let is_admin\u{200B} = false;\nif is_admin { /*\u{202E} } \u{2066}begin admin only */ }\n",
+    ));
+
+    let message = SimpleDiagnostic::new("unused variable `is_admin\u{200B}`")
+        .with_label(Label::warning(None, 4..15, "contains a zero-width space"))
+        .with_label(Label::error(None, 28..36, "not found in this scope"))
+        .with_source(source);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.column_policy.show_invisible = true;
+
+    assert_snapshot!(render_with(renderer, message));
+}
+
+#[test]
+fn with_invisible_characters_hidden() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let is_admin\u{200B} = false;\n"));
+
+    let message = SimpleDiagnostic::new("unused variable")
+        .with_label(Label::warning(None, 4..15, "contains a zero-width space"))
+        .with_source(source);
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn with_invisible_characters_styled() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let is_admin\u{200B} = false;\n"));
+
+    let message = SimpleDiagnostic::new("unused variable")
+        .with_label(Label::error(None, 4..15, "contains a zero-width space"))
+        .with_source(source);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = true;
+    renderer.column_policy.show_invisible = true;
+
+    let report = renderer.render_report(&message).unwrap();
+    let warning = report
+        .segments()
+        .iter()
+        .filter(|s| s.style.fg == Some(SegmentColor::Rgb(235, 191, 131)))
+        .map(|s| s.text.as_str())
+        .collect::<String>();
+
+    assert_eq!(warning, "<U+200B>");
+}
+
+#[test]
+fn with_light_theme() {
+    let message = SimpleDiagnostic::new("unused variable").with_severity(Severity::Warning);
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = true;
+    renderer.theme = Theme::light();
+
+    let report = renderer.render_report(&message).unwrap();
+    let warning = report
+        .segments()
+        .iter()
+        .filter(|s| s.style.fg == Some(SegmentColor::Rgb(154, 103, 0)))
+        .map(|s| s.text.as_str())
+        .collect::<String>();
+
+    assert_eq!(warning, "⚠warning");
+}
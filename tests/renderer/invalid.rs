@@ -32,6 +32,7 @@ fn label_end_outside_range() {
 }
 
 #[test]
+#[allow(clippy::reversed_empty_ranges, reason = "testing invalid ranges")]
 fn label_range_negative_length() {
     let source = Arc::new(NamedSource::new("src/test.lm", "abc"));
 
@@ -43,3 +44,49 @@ fn label_range_negative_length() {
 
     assert_snapshot!(render(message));
 }
+
+#[test]
+fn label_range_inside_character() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "let a = \"ü\";"));
+
+    let message = SimpleDiagnostic::new("failed to read file").with_label(Label::new(
+        Some(source),
+        10..11,
+        "label_range_inside_character",
+    ));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges, reason = "testing invalid ranges")]
+fn suggestion_range_negative_length() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "abc"));
+
+    let message = SimpleDiagnostic::new("failed to read file")
+        .with_help(Help::new("replace it").with_suggestion(Suggestion::replace(SourceRange::new(source, 3..1), "xyz")));
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn suggestion_range_outside_range() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "abc"));
+
+    let message = SimpleDiagnostic::new("failed to read file").with_help(
+        Help::new("remove it").with_suggestion(Suggestion::delete(SourceRange::new(source.clone(), 1..usize::MAX))),
+    );
+
+    assert_snapshot!(render(message));
+}
+
+#[test]
+fn suggestion_location_outside_range() {
+    let source = Arc::new(NamedSource::new("src/test.lm", "abc"));
+
+    let message = SimpleDiagnostic::new("failed to read file").with_help(
+        Help::new("append it").with_suggestion(Suggestion::insert(SourceLocation::new(source, usize::MAX), "def")),
+    );
+
+    assert_snapshot!(render(message));
+}
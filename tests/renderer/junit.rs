@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use error_snippet::{Diagnostic, JUnitReport, Label, NamedSource, Severity, SimpleDiagnostic};
+use insta::assert_snapshot;
+
+#[test]
+fn empty() {
+    let report = JUnitReport::new();
+
+    assert!(report.is_empty());
+    assert_eq!(
+        report.to_xml(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"diagnostics\" tests=\"0\" failures=\"0\" skipped=\"0\">\n</testsuites>\n"
+    );
+}
+
+#[test]
+fn grouped_by_file() {
+    let main = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = b < c;\n}"));
+    let lib = Arc::new(NamedSource::new("src/lib.lm", "import std::io;\n"));
+
+    let diagnostics: Vec<Box<dyn Diagnostic>> = vec![
+        Box::new(
+            SimpleDiagnostic::new("cannot find value `b`")
+                .with_code("E0425")
+                .with_label(Label::error(Some(main.clone()), 24..25, "not found in this scope")),
+        ),
+        Box::new(
+            SimpleDiagnostic::new("unused import")
+                .with_severity(Severity::Warning)
+                .with_label(Label::warning(Some(lib), 7..14, "unused")),
+        ),
+        Box::new(
+            SimpleDiagnostic::new("unused variable `a`")
+                .with_severity(Severity::Warning)
+                .with_label(Label::warning(Some(main), 20..21, "unused")),
+        ),
+        Box::new(SimpleDiagnostic::new("build script emitted \"notes\"").with_severity(Severity::Note)),
+    ];
+
+    let report = JUnitReport::from_diagnostics(diagnostics.iter().map(|d| d.as_ref())).with_name("lumec");
+
+    assert_snapshot!(report.to_xml());
+}
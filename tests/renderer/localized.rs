@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use error_snippet::{
+    GraphicalRenderer, Label, LocalizedRenderer, MessageKey, NamedSource, Renderer, Severity, ShortRenderer,
+    SimpleDiagnostic,
+};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::FluentResource;
+use insta::assert_snapshot;
+
+const CATALOG: &str = r#"
+unknown-method = méthode `{ $name }` introuvable
+failed-to-compile = échec de la compilation
+defined-here = { $count ->
+    [one] défini une fois
+   *[other] défini { $count } fois
+}
+broken = valeur { $missing }
+"#;
+
+fn bundle() -> FluentBundle<FluentResource> {
+    let mut bundle = FluentBundle::new_concurrent(vec!["fr".parse().unwrap()]);
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(FluentResource::try_new(CATALOG.to_string()).unwrap())
+        .unwrap();
+
+    bundle
+}
+
+fn renderer() -> LocalizedRenderer<GraphicalRenderer> {
+    let mut inner = GraphicalRenderer::new();
+    inner.use_colors = false;
+
+    LocalizedRenderer::new(inner, bundle())
+}
+
+#[test]
+fn localizes_nested_diagnostics() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "let b = a.invok();"));
+
+    let message = SimpleDiagnostic::new("failed to compile")
+        .with_message_key(MessageKey::new("failed-to-compile"))
+        .add_cause(
+            SimpleDiagnostic::new("could not find method `invok`")
+                .with_message_key(MessageKey::new("unknown-method").with_arg("name", "invok"))
+                .with_label(Label::error(Some(source), 10..15, "could not find method 'invok'")),
+        )
+        .add_related(
+            SimpleDiagnostic::new("defined 2 times")
+                .with_severity(Severity::Note)
+                .with_message_key(MessageKey::new("defined-here").with_arg("count", 2)),
+        );
+
+    assert_snapshot!(renderer().render(&message).unwrap());
+}
+
+#[test]
+fn selects_plural_forms_from_numbers() {
+    let one =
+        SimpleDiagnostic::new("defined once").with_message_key(MessageKey::new("defined-here").with_arg("count", 1));
+    let many =
+        SimpleDiagnostic::new("defined 3 times").with_message_key(MessageKey::new("defined-here").with_arg("count", 3));
+
+    let renderer = LocalizedRenderer::new(ShortRenderer::new(), bundle());
+
+    assert_eq!(renderer.render(&one).unwrap(), "error: défini une fois\n");
+    assert_eq!(renderer.render(&many).unwrap(), "error: défini 3 fois\n");
+}
+
+#[test]
+fn falls_back_to_message() {
+    let renderer = LocalizedRenderer::new(ShortRenderer::new(), bundle());
+
+    let without_key = SimpleDiagnostic::new("something went wrong");
+    let missing_key = SimpleDiagnostic::new("something went wrong").with_message_key(MessageKey::new("missing"));
+    let missing_arg = SimpleDiagnostic::new("something went wrong").with_message_key(MessageKey::new("broken"));
+
+    assert_eq!(renderer.render(&without_key).unwrap(), "error: something went wrong\n");
+    assert_eq!(renderer.render(&missing_key).unwrap(), "error: something went wrong\n");
+    assert_eq!(renderer.render(&missing_arg).unwrap(), "error: something went wrong\n");
+}
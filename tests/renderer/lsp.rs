@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use error_snippet::{
+    source_uri, to_lsp_diagnostic, to_lsp_diagnostic_with, to_publish_diagnostics, to_workspace_edit, Diagnostic,
+    Label, NamedSource, Severity, SimpleDiagnostic, Source, SourceId, SourceLocation, SourceRange, Suggestion, Tag,
+    WithSource,
+};
+use lsp_types::{
+    DiagnosticSeverity, DiagnosticTag, DocumentChangeOperation, DocumentChanges, NumberOrString, OneOf, Position,
+    Range, ResourceOp, TextEdit, Url,
+};
+
+fn uri(value: &str) -> Url {
+    value.parse().unwrap()
+}
+
+#[test]
+fn lsp_diagnostic_fields() {
+    let source = Arc::new(NamedSource::new("file:///src/main.lm", "let a = b;"));
+
+    let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+        .with_code("E0425")
+        .with_severity(Severity::Warning)
+        .with_help("a local variable with a similar name exists: `a`")
+        .with_label(Label::warning(Some(source.clone()), 8..9, "not found in this scope"))
+        .with_label(Label::note(Some(source), 4..5, ""));
+
+    let (file, converted) = to_lsp_diagnostic(&diagnostic).unwrap();
+
+    assert_eq!(file, uri("file:///src/main.lm"));
+    assert_eq!(converted.range, Range::new(Position::new(0, 8), Position::new(0, 9)));
+    assert_eq!(converted.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(converted.code, Some(NumberOrString::String("E0425".into())));
+    assert_eq!(
+        converted.message,
+        "cannot find value `b`\nhelp: a local variable with a similar name exists: `a`"
+    );
+
+    let related = converted.related_information.unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].message, "not found in this scope");
+}
+
+#[test]
+fn lsp_diagnostic_tags() {
+    let source = Arc::new(NamedSource::new("file:///src/main.lm", "let a = old();"));
+
+    let diagnostic = SimpleDiagnostic::new("unused variable `a`")
+        .with_tag(Tag::Unused)
+        .with_tag(Tag::Deprecated)
+        .with_tag(Tag::Security)
+        .with_tag(Tag::custom("style"))
+        .with_label(Label::warning(Some(source.clone()), 4..5, ""));
+
+    let (_, converted) = to_lsp_diagnostic(&diagnostic).unwrap();
+
+    assert_eq!(
+        converted.tags,
+        Some(vec![DiagnosticTag::UNNECESSARY, DiagnosticTag::DEPRECATED])
+    );
+
+    let diagnostic = SimpleDiagnostic::new("unused variable `a`")
+        .with_tag(Tag::Security)
+        .with_label(Label::warning(Some(source), 4..5, ""));
+
+    assert_eq!(to_lsp_diagnostic(&diagnostic).unwrap().1.tags, None);
+}
+
+#[test]
+fn lsp_position_utf16() {
+    let source = Arc::new(NamedSource::new("file:///src/main.lm", "let s = \"🦀\";\nlet a = b;"));
+
+    let diagnostic =
+        SimpleDiagnostic::new("unterminated string").with_label(Label::error(Some(source.clone()), 13..14, ""));
+    let (_, converted) = to_lsp_diagnostic(&diagnostic).unwrap();
+
+    assert_eq!(converted.range.start, Position::new(0, 11));
+
+    let diagnostic = SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(Some(source), 24..25, ""));
+    let (_, converted) = to_lsp_diagnostic(&diagnostic).unwrap();
+
+    assert_eq!(converted.range, Range::new(Position::new(1, 8), Position::new(1, 9)));
+}
+
+#[test]
+fn lsp_without_location() {
+    let source = Arc::new("let a = b;");
+
+    assert!(to_lsp_diagnostic(&SimpleDiagnostic::new("no labels")).is_none());
+    assert!(
+        to_lsp_diagnostic(&SimpleDiagnostic::new("unnamed").with_label(Label::error(Some(source), 0..1, ""))).is_none()
+    );
+}
+
+#[test]
+fn publish_merges_related() {
+    let main = Arc::new(NamedSource::new("file:///src/main.lm", "fn foo() {}\nfn foo() {}"));
+    let lib = Arc::new(NamedSource::new("file:///src/lib.lm", "let a = b;"));
+
+    let duplicate = SimpleDiagnostic::new("duplicate definition of `foo`")
+        .with_label(Label::error(Some(main.clone()), 15..18, ""))
+        .add_related(
+            SimpleDiagnostic::new("previous definition of `foo`")
+                .with_severity(Severity::Note)
+                .with_label(Label::note(Some(main.clone()), 3..6, "")),
+        );
+
+    let unused = SimpleDiagnostic::new("unused function `foo`")
+        .with_severity(Severity::Warning)
+        .with_label(Label::warning(Some(main), 3..6, ""));
+
+    let missing = SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(Some(lib), 8..9, ""));
+
+    let published = to_publish_diagnostics([
+        &duplicate as &dyn Diagnostic,
+        &unused as &dyn Diagnostic,
+        &missing as &dyn Diagnostic,
+        &SimpleDiagnostic::new("no location") as &dyn Diagnostic,
+    ]);
+
+    assert_eq!(published.len(), 2);
+
+    let main = &published[&uri("file:///src/main.lm")];
+    assert_eq!(main.len(), 2);
+    assert_eq!(main[0].message, "duplicate definition of `foo`");
+    assert_eq!(main[1].message, "unused function `foo`");
+
+    let related = main[0].related_information.as_ref().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].message, "previous definition of `foo`");
+    assert_eq!(related[0].location.range.start, Position::new(0, 3));
+
+    assert_eq!(published[&uri("file:///src/lib.lm")].len(), 1);
+}
+
+#[test]
+fn source_uri_from_path() {
+    let absolute = NamedSource::new("/src/main.lm", "");
+    let relative = NamedSource::new("src/main.lm", "");
+
+    assert_eq!(source_uri(&absolute), Some(uri("file:///src/main.lm")));
+    assert_eq!(
+        source_uri(&relative),
+        Url::from_file_path(std::env::current_dir().unwrap().join("src/main.lm")).ok()
+    );
+    assert_eq!(source_uri(&"let a = b;"), None);
+}
+
+#[test]
+fn workspace_edit_operations() {
+    let source = Arc::new(NamedSource::new("file:///src/main.lm", "struct Point {}\nlet a = b;"));
+
+    let edit = to_workspace_edit(&[
+        Suggestion::delete(SourceRange::new(source.clone(), 0..16)),
+        Suggestion::create_file("file:///src/point.lm", "struct Point {}"),
+        Suggestion::replace(SourceRange::new(source.clone(), 24..25), "a"),
+        Suggestion::rename_file("file:///src/main.lm", "file:///src/lib.lm"),
+        Suggestion::insert(SourceLocation::new(source, 16), "use point::Point;\n"),
+        Suggestion::delete_file("file:///src/old_point.lm"),
+    ]);
+
+    let Some(DocumentChanges::Operations(operations)) = edit.document_changes else {
+        panic!("expected document operations");
+    };
+
+    assert_eq!(operations.len(), 5);
+
+    let DocumentChangeOperation::Edit(main) = &operations[0] else {
+        panic!("expected text edits first");
+    };
+
+    assert_eq!(main.text_document.uri, uri("file:///src/main.lm"));
+    assert_eq!(
+        main.edits,
+        vec![
+            OneOf::Left(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(1, 0)),
+                String::new()
+            )),
+            OneOf::Left(TextEdit::new(
+                Range::new(Position::new(1, 8), Position::new(1, 9)),
+                "a".into()
+            )),
+            OneOf::Left(TextEdit::new(
+                Range::new(Position::new(1, 0), Position::new(1, 0)),
+                "use point::Point;\n".into()
+            )),
+        ]
+    );
+
+    assert!(matches!(
+        &operations[1],
+        DocumentChangeOperation::Op(ResourceOp::Create(create)) if create.uri == uri("file:///src/point.lm")
+    ));
+
+    let DocumentChangeOperation::Edit(point) = &operations[2] else {
+        panic!("expected content of created file");
+    };
+
+    assert_eq!(point.text_document.uri, uri("file:///src/point.lm"));
+    assert_eq!(
+        point.edits,
+        vec![OneOf::Left(TextEdit::new(Range::default(), "struct Point {}".into()))]
+    );
+
+    assert!(matches!(
+        &operations[3],
+        DocumentChangeOperation::Op(ResourceOp::Rename(rename))
+            if rename.old_uri == uri("file:///src/main.lm") && rename.new_uri == uri("file:///src/lib.lm")
+    ));
+    assert!(matches!(
+        &operations[4],
+        DocumentChangeOperation::Op(ResourceOp::Delete(delete)) if delete.uri == uri("file:///src/old_point.lm")
+    ));
+}
+
+#[test]
+fn lsp_diagnostic_source_id_label() {
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new(NamedSource::new("file:///src/main.lm", "let a = b;")),
+        Arc::new(NamedSource::new("file:///src/lib.lm", "fn c() {}\nfn d() {}")),
+    ];
+
+    let diagnostic = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::in_source(
+            SourceId(1),
+            13..14,
+            "a function with a similar name exists",
+        ))
+        .with_source(sources[0].clone());
+
+    let (file, converted) = to_lsp_diagnostic_with(&diagnostic, Some(&sources)).unwrap();
+
+    assert_eq!(file, uri("file:///src/lib.lm"));
+    assert_eq!(converted.range, Range::new(Position::new(1, 3), Position::new(1, 4)));
+
+    assert!(to_lsp_diagnostic(&diagnostic).is_none());
+}
@@ -1,2 +1,20 @@
 mod graphical;
 mod invalid;
+mod junit;
+#[cfg(feature = "i18n")]
+mod localized;
+#[cfg(feature = "lsp")]
+mod lsp;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod report;
+mod rustc_json;
+mod short;
+mod tap;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "renderer-testkit")]
+mod testkit;
+#[cfg(feature = "ratatui")]
+mod tui;
+mod writer;
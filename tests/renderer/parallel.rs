@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use error_snippet::{
+    render_parallel, BatchOptions, Diagnostic, GraphicalRenderer, Label, NamedSource, Renderer, SimpleDiagnostic,
+    Source,
+};
+
+#[test]
+fn renders_in_order() {
+    let source: Arc<dyn Source> = Arc::new(NamedSource::new("src/main.lm", "let a = b;\n".repeat(200)));
+
+    let diagnostics = (0..200)
+        .map(|idx| {
+            SimpleDiagnostic::new(format!("cannot find value `b` (#{idx})")).with_label(Label::error(
+                Some(source.clone()),
+                idx * 11 + 8..idx * 11 + 9,
+                "not found in this scope",
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let options = BatchOptions::aligned(diagnostics.iter().map(|d| d as &dyn Diagnostic));
+    let borrowed = diagnostics
+        .iter()
+        .map(|d| d as &(dyn Diagnostic + Sync))
+        .collect::<Vec<_>>();
+
+    let rendered = render_parallel(&renderer, &borrowed, &options).unwrap();
+
+    let expected = diagnostics
+        .iter()
+        .map(|d| renderer.render_with(d, &options).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(rendered, expected);
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use error_snippet::{GraphicalRenderer, Label, NamedSource, Renderer, Report, SegmentColor, SimpleDiagnostic};
+
+fn diagnostic() -> SimpleDiagnostic {
+    let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = b;\n}"));
+
+    SimpleDiagnostic::new("cannot find value `b`")
+        .with_code("E0425")
+        .with_label(Label::error(Some(source), 24..25, "not found in this scope"))
+}
+
+#[test]
+fn plain_matches_uncolored_output() {
+    let mut plain = GraphicalRenderer::new();
+    plain.use_colors = false;
+
+    let report = GraphicalRenderer::new().render_report(&diagnostic()).unwrap();
+
+    assert_eq!(report.plain(), plain.render(&diagnostic()).unwrap());
+    assert_eq!(report.ansi(), GraphicalRenderer::new().render(&diagnostic()).unwrap());
+}
+
+#[test]
+fn converts_theme_styles() {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.theme.style.error = owo_colors::Style::new().truecolor(10, 20, 30).bold();
+
+    let report = renderer.render_report(&diagnostic()).unwrap();
+    let header = report
+        .segments()
+        .iter()
+        .find(|segment| segment.text == "error")
+        .unwrap();
+
+    assert_eq!(header.style.fg, Some(SegmentColor::Rgb(10, 20, 30)));
+    assert!(header.style.bold);
+}
+
+#[test]
+fn keeps_styles_without_colors() {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    let report = renderer.render_report(&diagnostic()).unwrap();
+    let header = report
+        .segments()
+        .iter()
+        .find(|segment| segment.text == "error")
+        .unwrap();
+
+    assert_eq!(report.ansi(), renderer.render(&diagnostic()).unwrap());
+    assert_eq!(report.plain(), report.ansi());
+    assert_eq!(header.style.fg, Some(SegmentColor::Rgb(233, 114, 99)));
+}
+
+#[test]
+fn splits_lines() {
+    let report = Report::new("\x1b[31mfoo\nbar\x1b[0m\n\nbaz\n");
+
+    let lines = report
+        .lines()
+        .map(|segments| segments.iter().map(|segment| segment.text.as_str()).collect::<String>())
+        .collect::<Vec<_>>();
+
+    assert_eq!(lines, vec!["foo", "bar", "", "baz"]);
+
+    // Styles are kept across line breaks, until they're reset.
+    assert_eq!(report.segments()[2].text, "bar");
+    assert_eq!(report.segments()[2].style.fg, Some(SegmentColor::Ansi(1)));
+    assert!(report.segments().last().unwrap().style.is_plain());
+}
+
+#[test]
+fn omits_hyperlinks() {
+    let report = Report::new("\x1b]8;;file:///src/main.lm\x1b\\src/main.lm\x1b]8;;\x1b\\\n");
+
+    assert_eq!(report.plain(), "src/main.lm\n");
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use error_snippet::{
+    Help, Label, NamedSource, Renderer, RustcJsonRenderer, Severity, ShortRenderer, SimpleDiagnostic, Source, SourceId,
+    SourceLocation, SourceRange, Suggestion, Tag, WithSource,
+};
+use insta::assert_snapshot;
+
+#[test]
+fn without_location() {
+    let message = SimpleDiagnostic::new("failed to compile").with_severity(Severity::Warning);
+
+    assert_eq!(
+        RustcJsonRenderer::new().without_rendered().render(&message).unwrap(),
+        "{\"$message_type\":\"diagnostic\",\"message\":\"failed to compile\",\"code\":null,\"level\":\"warning\",\"spans\":[],\"children\":[],\"rendered\":null}\n"
+    );
+}
+
+#[test]
+fn with_tags() {
+    let message = SimpleDiagnostic::new("use of deprecated function `foo`")
+        .with_severity(Severity::Warning)
+        .with_tag(Tag::Deprecated)
+        .with_tag(Tag::custom("style"));
+
+    assert_eq!(
+        RustcJsonRenderer::new().without_rendered().render(&message).unwrap(),
+        "{\"$message_type\":\"diagnostic\",\"message\":\"use of deprecated function `foo`\",\"code\":null,\"level\":\"warning\",\"spans\":[],\"children\":[],\"rendered\":null,\"tags\":[\"deprecated\",\"style\"]}\n"
+    );
+}
+
+#[test]
+fn with_labels() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let 名前 = b;\n}"));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_code("E0425")
+        .with_label(Label::error(None, 29..30, "not found in this scope").with_suggestion("名前"))
+        .with_label(Label::note(None, 20..26, ""))
+        .with_source(source);
+
+    assert_snapshot!(RustcJsonRenderer::new().without_rendered().render(&message).unwrap());
+}
+
+#[test]
+fn with_multiline_label() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() {\r\n    return \"a\tb\";\r\n}",
+    ));
+
+    let message =
+        SimpleDiagnostic::new("mismatched types").with_label(Label::error(Some(source), 10..34, "in this block"));
+
+    assert_snapshot!(RustcJsonRenderer::new().without_rendered().render(&message).unwrap());
+}
+
+#[test]
+fn with_children() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn foo() -> Int {\n    return 1.5;\n}\n",
+    ));
+
+    let message = SimpleDiagnostic::new("mismatched types")
+        .with_label(Label::error(None, 29..32, "expected `Int`, found `Float`"))
+        .with_help(
+            Help::new("add a cast to `Int`")
+                .as_alternative()
+                .with_suggestion(Suggestion::insert(SourceLocation::new(source.clone(), 32), " as Int")),
+        )
+        .with_help(
+            Help::new("change the return type to `Float`")
+                .as_alternative()
+                .with_suggestion(Suggestion::replace(SourceRange::new(source.clone(), 12..15), "Float")),
+        )
+        .with_help(Help::new("remove the file").with_suggestion(Suggestion::delete_file("src/main.lm")))
+        .add_cause(
+            SimpleDiagnostic::new("return type declared here")
+                .with_severity(Severity::Note)
+                .with_label(Label::note(None, 12..15, "")),
+        )
+        .with_source(source);
+
+    assert_snapshot!(RustcJsonRenderer::new().without_rendered().render(&message).unwrap());
+}
+
+#[test]
+fn with_rendered() {
+    let message = SimpleDiagnostic::new("failed to compile \"main\"").with_code("E0001");
+
+    assert_snapshot!(RustcJsonRenderer::new()
+        .with_renderer(Box::new(ShortRenderer::new()))
+        .render(&message)
+        .unwrap());
+}
+
+#[test]
+fn with_source_id_label() {
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new(NamedSource::new("src/main.lm", "let a = b;")),
+        Arc::new(NamedSource::new("src/lib.lm", "fn c() {}\nfn d() {}")),
+    ];
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::in_source(SourceId(1), 13..14, "").with_suggestion("b"))
+        .with_source(sources[0].clone());
+
+    let rendered = RustcJsonRenderer::new()
+        .without_rendered()
+        .with_source_resolver(Arc::new(sources))
+        .render(&message)
+        .unwrap();
+
+    assert!(rendered.contains(r#""spans":[{"file_name":"src/lib.lm","byte_start":13,"byte_end":14,"line_start":2"#));
+    assert!(rendered.contains(r#""suggested_replacement":"b""#));
+    assert!(!rendered.contains("src/main.lm"));
+
+    let unresolved = RustcJsonRenderer::new().without_rendered().render(&message).unwrap();
+
+    assert!(unresolved.contains(r#""spans":[],"children":[]"#));
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use error_snippet::{
+    Label, NamedSource, Renderer, Severity, ShortRenderer, SimpleDiagnostic, Source, SourceId, StringSource, WithSource,
+};
+
+#[test]
+fn without_location() {
+    let message = SimpleDiagnostic::new("failed to compile").with_severity(Severity::Warning);
+
+    assert_eq!(
+        ShortRenderer::new().render(&message).unwrap(),
+        "warning: failed to compile\n"
+    );
+}
+
+#[test]
+fn with_location() {
+    let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let 名前 = b;\n}"));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`\nin this scope")
+        .with_code("E0425")
+        .with_label(Label::error(None, 29..30, "not found in this scope"))
+        .with_help("did you mean `a`?")
+        .add_related(SimpleDiagnostic::new("defined here"))
+        .with_source(source);
+
+    assert_eq!(
+        ShortRenderer::new().render(&message).unwrap(),
+        "src/main.lm:2:14: error[E0425]: cannot find value `b` in this scope\n"
+    );
+}
+
+#[test]
+fn with_source_id_label() {
+    let sources: Vec<Arc<dyn Source>> = vec![
+        Arc::new(NamedSource::new("src/main.lm", "let a = b;")),
+        Arc::new(NamedSource::new("src/lib.lm", "fn c() {}\nfn d() {}")),
+    ];
+
+    let message = SimpleDiagnostic::new("cannot find value `b`")
+        .with_label(Label::in_source(
+            SourceId(1),
+            13..14,
+            "a function with a similar name exists",
+        ))
+        .with_source(sources[0].clone());
+
+    assert_eq!(
+        ShortRenderer::new()
+            .with_source_resolver(Arc::new(sources))
+            .render(&message)
+            .unwrap(),
+        "src/lib.lm:2:4: error: cannot find value `b`\n"
+    );
+
+    assert_eq!(
+        ShortRenderer::new().render(&message).unwrap(),
+        "error: cannot find value `b`\n"
+    );
+}
+
+#[test]
+fn with_unnamed_source() {
+    let source = Arc::new(StringSource::new("let a = b;".into()));
+
+    let message = SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(Some(source), 8..9, ""));
+
+    assert_eq!(
+        ShortRenderer::new().render(&message).unwrap(),
+        "1:9: error: cannot find value `b`\n"
+    );
+}
+
+#[derive(Debug)]
+struct UnavailableSource;
+
+impl Source for UnavailableSource {
+    fn name(&self) -> Option<&str> {
+        Some("target/generated.lm")
+    }
+
+    fn text(&self) -> &str {
+        ""
+    }
+
+    fn try_text(&self) -> Option<&str> {
+        None
+    }
+}
+
+#[test]
+fn with_unavailable_source() {
+    let message = SimpleDiagnostic::new("cannot find value `b`").with_label(Label::error(
+        Some(Arc::new(UnavailableSource)),
+        8..9,
+        "",
+    ));
+
+    assert_eq!(
+        ShortRenderer::new().render(&message).unwrap(),
+        "target/generated.lm: error: cannot find value `b`\n"
+    );
+}
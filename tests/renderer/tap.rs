@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use error_snippet::{Label, NamedSource, Renderer, Severity, SimpleDiagnostic, TapRenderer, WithSource};
+
+#[test]
+fn stream() {
+    let source = Arc::new(NamedSource::new("src/\"main\".lm", "fn main() {\n    let a = b;\n}"));
+
+    let first = SimpleDiagnostic::new("unused variable `a`\n#[warn(unused)] is on by default")
+        .with_severity(Severity::Warning)
+        .with_label(Label::warning(None, 20..21, "unused"))
+        .with_source(source);
+
+    let second = SimpleDiagnostic::new("failed to compile");
+
+    let renderer = TapRenderer::new();
+
+    let mut output = String::from(renderer.header());
+    output.push_str(&renderer.render(&first).unwrap());
+    output.push_str(&renderer.render(&second).unwrap());
+    output.push_str(&renderer.plan());
+
+    assert_eq!(
+        output,
+        concat!(
+            "TAP version 14\n",
+            "not ok 1 - unused variable `a` \\#[warn(unused)] is on by default\n",
+            "  ---\n",
+            "  message: \"unused variable `a`\\n#[warn(unused)] is on by default\"\n",
+            "  severity: warning\n",
+            "  file: \"src/\\\"main\\\".lm\"\n",
+            "  line: 2\n",
+            "  column: 9\n",
+            "  ...\n",
+            "not ok 2 - failed to compile\n",
+            "  ---\n",
+            "  message: \"failed to compile\"\n",
+            "  severity: error\n",
+            "  ...\n",
+            "1..2\n",
+        )
+    );
+
+    renderer.reset();
+    assert_eq!(renderer.plan(), "1..0\n");
+}
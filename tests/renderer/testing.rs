@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use error_snippet::{assert_diagnostic_snapshot, Help, Label, NamedSource, SimpleDiagnostic};
+
+#[test]
+fn diagnostic_snapshot() {
+    let source = Arc::new(NamedSource::new(
+        "src/main.lm",
+        "fn main() -> Int {\n    let a = new Testing();\n    return a;\n}",
+    ));
+
+    let diagnostic = SimpleDiagnostic::new("mismatched types")
+        .with_code("E0308")
+        .with_label(Label::error(Some(source.clone()), 0..62, "in this function"))
+        .with_label(Label::error(Some(source), 57..58, "expected `Int`, found `Testing`"))
+        .with_help(Help::new("consider converting the value to `Int`"));
+
+    assert_diagnostic_snapshot!(diagnostic);
+}
@@ -0,0 +1,28 @@
+use error_snippet::{testkit, Diagnostic, Formatter, GraphicalRenderer, Renderer};
+
+pub struct MessageRenderer;
+
+impl Renderer for MessageRenderer {
+    fn render_fmt(&self, f: &mut Formatter, diagnostic: &dyn Diagnostic) -> std::fmt::Result {
+        std::fmt::Write::write_fmt(f, format_args!("{}: {}\n", diagnostic.severity(), diagnostic.message()))
+    }
+}
+
+#[test]
+fn graphical_conformance() {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+
+    testkit::assert_conformance(&renderer);
+}
+
+#[test]
+fn reports_violations() {
+    let violations = testkit::check_renderer(&MessageRenderer);
+
+    assert!(violations.iter().all(|v| v.fixture != "message"));
+    assert!(violations.iter().any(|v| v.fixture == "code"));
+    assert!(violations.iter().any(|v| v.fixture == "label"));
+    assert!(violations.iter().any(|v| v.fixture == "suggestion-files"));
+    assert!(violations.iter().any(|v| v.fixture == "causes"));
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use error_snippet::{GraphicalRenderer, Label, NamedSource, Renderer, SimpleDiagnostic, TuiRenderer};
+use ratatui::style::{Color, Modifier};
+
+fn diagnostic() -> SimpleDiagnostic {
+    let source = Arc::new(NamedSource::new("src/main.lm", "fn main() {\n    let a = b;\n}"));
+
+    SimpleDiagnostic::new("cannot find value `b`")
+        .with_code("E0425")
+        .with_label(Label::error(Some(source), 24..25, "not found in this scope"))
+}
+
+#[test]
+fn text_matches_plain_output() {
+    let mut plain = GraphicalRenderer::new();
+    plain.use_colors = false;
+
+    let text = TuiRenderer::with_renderer(Box::new(GraphicalRenderer::new()))
+        .render_text(&diagnostic())
+        .unwrap();
+
+    let lines = text.lines.iter().map(|line| format!("{line}\n")).collect::<String>();
+
+    assert_eq!(lines, plain.render(&diagnostic()).unwrap());
+}
+
+#[test]
+fn converts_theme_styles() {
+    let mut renderer = GraphicalRenderer::new();
+    renderer.theme.style.error = owo_colors::Style::new().truecolor(10, 20, 30).bold();
+
+    let text = TuiRenderer::with_renderer(Box::new(renderer))
+        .render_text(&diagnostic())
+        .unwrap();
+
+    let header = &text.lines[0].spans[2];
+
+    assert_eq!(header.content, "error");
+    assert_eq!(header.style.fg, Some(Color::Rgb(10, 20, 30)));
+    assert!(header.style.add_modifier.contains(Modifier::BOLD));
+}
@@ -0,0 +1,82 @@
+use std::fmt::Write;
+
+use error_snippet::{Crlf, Formatter, GraphicalRenderer, Renderer, SimpleDiagnostic, StripAnsi, Tee};
+
+#[test]
+fn strip_ansi_across_writes() {
+    let mut output = String::new();
+    let mut writer = StripAnsi::new(&mut output);
+
+    writer.write_str("\x1b[3").unwrap();
+    writer.write_str("1merr").unwrap();
+    writer.write_str("or\x1b").unwrap();
+    writer.write_str("[0m: ×").unwrap();
+
+    assert_eq!(output, "error: ×");
+}
+
+#[test]
+fn strip_hyperlinks_across_writes() {
+    let mut output = String::new();
+    let mut writer = StripAnsi::new(&mut output);
+
+    writer.write_str("\x1b]8;;https://errors.lume.dev/E0308\x1b").unwrap();
+    writer.write_str("\\[E0308]\x1b]8").unwrap();
+    writer.write_str(";;\x07: mismatched types").unwrap();
+
+    assert_eq!(output, "[E0308]: mismatched types");
+}
+
+#[test]
+fn strip_repeated_escapes() {
+    let mut output = String::new();
+    let mut writer = StripAnsi::new(&mut output);
+
+    writer.write_str("\x1b\x1b[31merror\x1b").unwrap();
+    writer.write_str("\x1b[0m: ×").unwrap();
+
+    assert_eq!(output, "error: ×");
+}
+
+#[test]
+fn render_colored_and_stripped() {
+    let diagnostic = SimpleDiagnostic::new("mismatched types").with_help("expected `Int`");
+
+    let mut renderer = GraphicalRenderer::new();
+    renderer.use_colors = false;
+    let expected = renderer.render(&diagnostic).unwrap().replace('\n', "\r\n");
+
+    renderer.use_colors = true;
+
+    let mut colored = String::new();
+    let mut log = String::new();
+
+    let mut writer = Tee::new(&mut colored, Crlf::new(StripAnsi::new(&mut log)));
+    renderer
+        .render_fmt(&mut Formatter::new(&mut writer), &diagnostic)
+        .unwrap();
+
+    assert!(colored.contains('\x1b'));
+    assert_eq!(log, expected);
+}
+
+#[test]
+fn render_io_reports_errors() {
+    struct FailingWriter;
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let err = GraphicalRenderer::new()
+        .render_io(&mut FailingWriter, &SimpleDiagnostic::new("mismatched types"))
+        .unwrap_err();
+
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
@@ -10,3 +10,10 @@ fn render(diagnostic: impl Diagnostic) -> String {
     owo_colors::set_override(false);
     renderer.render(&diagnostic).unwrap().to_string()
 }
+
+fn render_with(mut renderer: GraphicalRenderer, diagnostic: impl Diagnostic) -> String {
+    renderer.use_colors = false;
+
+    owo_colors::set_override(false);
+    renderer.render(&diagnostic).unwrap().to_string()
+}
@@ -0,0 +1,101 @@
+use error_snippet::{walk, Diagnostic, DiagnosticVisitor, Relation, SimpleDiagnostic, WalkContext, WalkControl};
+
+fn tree() -> SimpleDiagnostic {
+    SimpleDiagnostic::new("root")
+        .add_cause(SimpleDiagnostic::new("cause 0").add_cause(SimpleDiagnostic::new("nested cause")))
+        .add_cause(SimpleDiagnostic::new("cause 1"))
+        .add_related(SimpleDiagnostic::new("related 0").add_related(SimpleDiagnostic::new("nested related")))
+}
+
+/// Records every visit and leave, while skipping or stopping at the given messages.
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    skip: Option<&'static str>,
+    stop: Option<&'static str>,
+}
+
+impl DiagnosticVisitor for Recorder {
+    fn visit(&mut self, diagnostic: &dyn Diagnostic, cx: WalkContext) -> WalkControl {
+        let message = diagnostic.message();
+        self.events.push(format!(
+            "visit {message} @ {} ({:?} #{})",
+            cx.depth, cx.relation, cx.index
+        ));
+
+        if self.stop == Some(message.as_str()) {
+            WalkControl::Stop
+        } else if self.skip == Some(message.as_str()) {
+            WalkControl::SkipChildren
+        } else {
+            WalkControl::Continue
+        }
+    }
+
+    fn leave(&mut self, diagnostic: &dyn Diagnostic, _cx: WalkContext) {
+        self.events.push(format!("leave {}", diagnostic.message()));
+    }
+}
+
+#[test]
+fn walk_visits_in_order() {
+    let mut recorder = Recorder::default();
+
+    assert_eq!(walk(&tree(), &mut recorder), WalkControl::Continue);
+    assert_eq!(
+        recorder.events,
+        vec![
+            "visit root @ 0 (Root #0)",
+            "visit cause 0 @ 1 (Cause #0)",
+            "visit nested cause @ 2 (Cause #0)",
+            "leave nested cause",
+            "leave cause 0",
+            "visit cause 1 @ 1 (Cause #1)",
+            "leave cause 1",
+            "visit related 0 @ 1 (Related #0)",
+            "visit nested related @ 2 (Related #0)",
+            "leave nested related",
+            "leave related 0",
+            "leave root",
+        ]
+    );
+}
+
+#[test]
+fn walk_skips_children() {
+    let mut recorder = Recorder {
+        skip: Some("cause 0"),
+        ..Recorder::default()
+    };
+
+    walk(&tree(), &mut recorder);
+
+    assert!(recorder.events.contains(&String::from("visit cause 1 @ 1 (Cause #1)")));
+    assert!(!recorder.events.iter().any(|event| event.contains("nested cause")));
+    assert!(!recorder.events.contains(&String::from("leave cause 0")));
+}
+
+#[test]
+fn walk_stops() {
+    let mut recorder = Recorder {
+        stop: Some("nested cause"),
+        ..Recorder::default()
+    };
+
+    assert_eq!(walk(&tree(), &mut recorder), WalkControl::Stop);
+    assert_eq!(recorder.events.last().unwrap(), "visit nested cause @ 2 (Cause #0)");
+    assert!(!recorder.events.iter().any(|event| event.starts_with("leave")));
+}
+
+#[test]
+fn walk_with_closure() {
+    let mut related = 0;
+
+    walk(&tree(), &mut |_: &dyn Diagnostic, cx: WalkContext| {
+        if cx.relation == Relation::Related {
+            related += 1;
+        }
+    });
+
+    assert_eq!(related, 2);
+}